@@ -24,6 +24,7 @@ extern crate env_logger;
 extern crate atty;
 extern crate ansi_term;
 extern crate regex;
+extern crate rustc_hex;
 extern crate time;
 extern crate fdlimit;
 extern crate futures;
@@ -209,13 +210,20 @@ where
 		.to_string_lossy()
 		.into();
 
+	if let Some(path) = matches.value_of("password_filename") {
+		let mut password = String::new();
+		File::open(path)
+			.and_then(|mut file| file.read_to_string(&mut password))
+			.map_err(|e| error::ErrorKind::Input(format!("Unable to read the keystore password file: {}", e)))?;
+		config.keystore_password = password.trim_right_matches(|c| c == '\r' || c == '\n').to_owned();
+	}
+
 	config.database_path = db_path(&base_path, config.chain_spec.id()).to_string_lossy().into();
 
 	config.pruning = match matches.value_of("pruning") {
-		Some("archive") => PruningMode::ArchiveAll,
 		None => PruningMode::default(),
-		Some(s) => PruningMode::keep_blocks(s.parse()
-			.map_err(|_| error::ErrorKind::Input("Invalid pruning mode specified".to_owned()))?),
+		Some(s) => parse_pruning(s)
+			.ok_or_else(|| error::ErrorKind::Input("Invalid pruning mode specified".to_owned()))?,
 	};
 
 	let role =
@@ -240,6 +248,28 @@ where
 	}
 
 	config.roles = role;
+	{
+		let pool = &mut config.transaction_pool;
+		if let Some(count) = matches.value_of("pool_ready_limit") {
+			pool.ready.count = count.parse().map_err(|_| "Invalid pool-ready-limit value specified.")?;
+		}
+		if let Some(kbytes) = matches.value_of("pool_ready_kbytes") {
+			pool.ready.total_bytes = kbytes.parse::<usize>().ok()
+				.and_then(|kbytes| kbytes.checked_mul(1024))
+				.ok_or("Invalid pool-ready-kbytes value specified.")?;
+		}
+		if let Some(count) = matches.value_of("pool_future_limit") {
+			pool.future.count = count.parse().map_err(|_| "Invalid pool-future-limit value specified.")?;
+		}
+		if let Some(kbytes) = matches.value_of("pool_future_kbytes") {
+			pool.future.total_bytes = kbytes.parse::<usize>().ok()
+				.and_then(|kbytes| kbytes.checked_mul(1024))
+				.ok_or("Invalid pool-future-kbytes value specified.")?;
+		}
+	}
+	config.transaction_pool_journal_path = Some(
+		transaction_pool_journal_path(&base_path, config.chain_spec.id()).to_string_lossy().into()
+	);
 	{
 		config.network.boot_nodes.extend(matches
 			.values_of("bootnodes")
@@ -484,6 +514,29 @@ fn parse_address(default: &str, port_param: &str, matches: &clap::ArgMatches) ->
 	Ok(address)
 }
 
+/// Parse the pruning mode: 'archive', a number of blocks to keep, or a number of blocks followed by
+/// a colon and a comma-separated list of hex-encoded key prefixes whose history is kept.
+fn parse_pruning(s: &str) -> Option<PruningMode> {
+	use rustc_hex::FromHex;
+
+	if s == "archive" {
+		return Some(PruningMode::ArchiveAll);
+	}
+
+	let mut parts = s.splitn(2, ':');
+	let blocks = parts.next()?.parse().ok()?;
+	match parts.next() {
+		None => Some(PruningMode::keep_blocks(blocks)),
+		Some(prefixes) => {
+			let prefixes = prefixes.split(',')
+				.map(|prefix| prefix.trim().trim_left_matches("0x").from_hex().ok()
+					.filter(|prefix: &Vec<u8>| !prefix.is_empty()))
+				.collect::<Option<Vec<Vec<u8>>>>()?;
+			Some(PruningMode::keep_blocks_and_prefixes(blocks, prefixes))
+		},
+	}
+}
+
 fn keystore_path(base_path: &Path, chain_id: &str) -> PathBuf {
 	let mut path = base_path.to_owned();
 	path.push("chains");
@@ -500,6 +553,14 @@ fn db_path(base_path: &Path, chain_id: &str) -> PathBuf {
 	path
 }
 
+fn transaction_pool_journal_path(base_path: &Path, chain_id: &str) -> PathBuf {
+	let mut path = base_path.to_owned();
+	path.push("chains");
+	path.push(chain_id);
+	path.push("txpool");
+	path
+}
+
 fn network_path(base_path: &Path, chain_id: &str) -> PathBuf {
 	let mut path = base_path.to_owned();
 	path.push("chains");
@@ -575,6 +636,26 @@ fn kill_color(s: &str) -> String {
 mod tests {
 	use super::*;
 
+	#[test]
+	fn parses_pruning_modes() {
+		match parse_pruning("archive") {
+			Some(PruningMode::ArchiveAll) => (),
+			mode => panic!("Unexpected pruning mode: {:?}", mode),
+		}
+		match parse_pruning("256") {
+			Some(PruningMode::Constrained(ref constraints)) if constraints.max_blocks == Some(256) => (),
+			mode => panic!("Unexpected pruning mode: {:?}", mode),
+		}
+		assert!(parse_pruning("foo").is_none());
+		assert!(parse_pruning("256:").is_none());
+		assert!(parse_pruning("256:0xzz").is_none());
+		assert!(parse_pruning("256:0x").is_none());
+
+		let mode = parse_pruning("256:0x1234,5678").unwrap();
+		assert!(!mode.is_archive());
+		assert_eq!(mode.archived_prefixes(), &[vec![0x12, 0x34], vec![0x56, 0x78]][..]);
+	}
+
 	#[test]
 	fn tests_node_name_good() {
 		assert!(is_node_name_valid("short name").is_ok());
@@ -33,6 +33,11 @@ pub struct CoreParams {
     #[structopt(long = "keystore-path", value_name = "PATH", parse(from_os_str))]
     keystore_path: Option<PathBuf>,
 
+    /// Specify a file containing the password the keystore keys are encrypted with. Without it
+    /// the keys are stored with an empty password
+    #[structopt(long = "password-filename", value_name = "PATH", parse(from_os_str))]
+    password_filename: Option<PathBuf>,
+
     /// Specify additional key seed
     #[structopt(long = "key", value_name = "STRING")]
     key: Option<String>,
@@ -61,11 +66,13 @@ pub struct CoreParams {
     #[structopt(long = "port", value_name = "PORT")]
     port: Option<u32>,
   
-    /// Listen to all RPC interfaces (default is local)
+    /// Listen to all RPC interfaces (default is local). The operator-only methods are only
+    /// available on the local interface
     #[structopt(long = "rpc-external")]
     rpc_external: bool,
   
-    /// Listen to all Websocket interfaces (default is local)
+    /// Listen to all Websocket interfaces (default is local). The operator-only methods are only
+    /// available on the local interface
     #[structopt(long = "ws-external")]
     ws_external: bool,
   
@@ -98,8 +105,26 @@ pub struct CoreParams {
     chain: Option<String>,
   
     /// Specify the pruning mode, a number of blocks to keep or 'archive'. Default is 256.
+    /// The whole history of some storage key prefixes can be kept by appending them as
+    /// comma-separated hex strings to the number of blocks, e.g. '256:0x1234,0x5678'.
     #[structopt(long = "pruning", value_name = "PRUNING_MODE")]
-    pruning: Option<u32>,
+    pruning: Option<String>,
+  
+    /// Maximum number of transactions in the ready queue of the transaction pool. Default is 512.
+    #[structopt(long = "pool-ready-limit", value_name = "COUNT")]
+    pool_ready_limit: Option<usize>,
+  
+    /// Maximum total size of all transactions in the ready queue of the transaction pool, in kilobytes. Default is 10240.
+    #[structopt(long = "pool-ready-kbytes", value_name = "COUNT")]
+    pool_ready_kbytes: Option<usize>,
+  
+    /// Maximum number of transactions in the future queue of the transaction pool. Default is 128.
+    #[structopt(long = "pool-future-limit", value_name = "COUNT")]
+    pool_future_limit: Option<usize>,
+  
+    /// Maximum total size of all transactions in the future queue of the transaction pool, in kilobytes. Default is 1024.
+    #[structopt(long = "pool-future-kbytes", value_name = "COUNT")]
+    pool_future_kbytes: Option<usize>,
   
     /// The human-readable name for this node, as reported to the telemetry server, if enabled
     #[structopt(long = "name", value_name = "NAME")]
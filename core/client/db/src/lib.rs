@@ -54,6 +54,7 @@ pub mod light;
 mod cache;
 mod utils;
 
+use std::collections::HashSet;
 use std::sync::Arc;
 use std::path::PathBuf;
 use std::io;
@@ -65,6 +66,7 @@ use kvdb::{KeyValueDB, DBTransaction};
 use trie::MemoryDB;
 use parking_lot::RwLock;
 use primitives::{H256, AuthorityId, Blake2Hasher, ChangesTrieConfiguration, convert_hash};
+use primitives::hexdisplay::HexDisplay;
 use primitives::storage::well_known_keys;
 use runtime_primitives::{generic::BlockId, Justification, StorageMap, ChildrenStorageMap};
 use runtime_primitives::traits::{Block as BlockT, Header as HeaderT, As, NumberFor, Zero, Digest, DigestItem};
@@ -80,8 +82,134 @@ pub use state_db::PruningMode;
 const CANONICALIZATION_DELAY: u64 = 256;
 const MIN_BLOCKS_TO_KEEP_CHANGES_TRIES_FOR: u64 = 32768;
 
+type DbTrieState = state_machine::TrieBackend<Arc<state_machine::Storage<Blake2Hasher>>, Blake2Hasher>;
+
 /// DB-backed patricia trie state, transaction type is an overlay of changes to commit.
-pub type DbState = state_machine::TrieBackend<Arc<state_machine::Storage<Blake2Hasher>>, Blake2Hasher>;
+///
+/// The state of a block pruned in the hybrid pruning mode only serves the keys under the archived
+/// prefixes. Reading anything else fails instead of returning partial data, and iterating over
+/// keys only visits the archived ones.
+pub struct DbState {
+	state: DbTrieState,
+	pruned: Option<Arc<Vec<Vec<u8>>>>,
+}
+
+impl DbState {
+	/// Create a new state with the given storage and root.
+	pub fn new(storage: Arc<state_machine::Storage<Blake2Hasher>>, root: H256) -> Self {
+		DbState {
+			state: DbTrieState::new(storage, root),
+			pruned: None,
+		}
+	}
+
+	fn new_pruned(storage: Arc<state_machine::Storage<Blake2Hasher>>, root: H256, archived_prefixes: Arc<Vec<Vec<u8>>>) -> Self {
+		DbState {
+			state: DbTrieState::new(storage, root),
+			pruned: Some(archived_prefixes),
+		}
+	}
+
+	/// Returns an error if `key` (or every key starting with it) is not kept in this state.
+	fn check_available(&self, key: &[u8]) -> Result<(), String> {
+		match self.pruned {
+			Some(ref archived_prefixes) if !archived_prefixes.iter().any(|prefix| key.starts_with(prefix)) =>
+				Err(format!("State is pruned; only the keys under the archived prefixes are available, not {}", HexDisplay::from(&key))),
+			_ => Ok(()),
+		}
+	}
+
+	/// Calls `f` for every key starting with `prefix` that is kept in this state.
+	fn for_available_keys_with_prefix<F: FnMut(&[u8])>(&self, prefix: &[u8], mut f: F) {
+		let archived_prefixes = match self.pruned {
+			Some(ref archived_prefixes) if !archived_prefixes.iter().any(|archived| prefix.starts_with(archived)) =>
+				archived_prefixes,
+			_ => return self.state.for_keys_with_prefix(prefix, f),
+		};
+
+		// only the archived prefixes under `prefix` are kept, the nested ones are visited with
+		// the ones they are nested in.
+		for archived in archived_prefixes.iter().filter(|archived| archived.starts_with(prefix)) {
+			let nested = archived_prefixes.iter()
+				.any(|other| other.len() < archived.len() && archived.starts_with(other));
+			if !nested {
+				self.state.for_keys_with_prefix(archived, &mut f);
+			}
+		}
+	}
+}
+
+impl StateBackend<Blake2Hasher> for DbState {
+	type Error = String;
+	type Transaction = MemoryDB<Blake2Hasher>;
+	type TrieBackendStorage = Arc<state_machine::Storage<Blake2Hasher>>;
+
+	fn storage(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+		self.check_available(key)?;
+		self.state.storage(key)
+	}
+
+	fn child_storage(&self, storage_key: &[u8], key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+		if self.pruned.is_some() {
+			return Err("State is pruned; child storage is not available".into());
+		}
+		self.state.child_storage(storage_key, key)
+	}
+
+	fn for_keys_in_child_storage<F: FnMut(&[u8])>(&self, storage_key: &[u8], f: F) {
+		// child storage is not kept in a pruned state.
+		if self.pruned.is_none() {
+			self.state.for_keys_in_child_storage(storage_key, f)
+		}
+	}
+
+	fn for_keys_with_prefix<F: FnMut(&[u8])>(&self, prefix: &[u8], f: F) {
+		self.for_available_keys_with_prefix(prefix, f)
+	}
+
+	fn keys(&self, prefix: &[u8]) -> Result<Vec<Vec<u8>>, Self::Error> {
+		self.check_available(prefix)?;
+		self.state.keys(prefix)
+	}
+
+	fn keys_paged(&self, prefix: &[u8], start_key: Option<&[u8]>, count: usize) -> Result<Vec<Vec<u8>>, Self::Error> {
+		self.check_available(prefix)?;
+		self.state.keys_paged(prefix, start_key, count)
+	}
+
+	fn storage_root<I>(&self, delta: I) -> (H256, Self::Transaction)
+		where I: IntoIterator<Item=(Vec<u8>, Option<Vec<u8>>)>
+	{
+		self.state.storage_root(delta)
+	}
+
+	fn child_storage_root<I>(&self, storage_key: &[u8], delta: I) -> (Vec<u8>, bool, Self::Transaction)
+		where I: IntoIterator<Item=(Vec<u8>, Option<Vec<u8>>)>
+	{
+		self.state.child_storage_root(storage_key, delta)
+	}
+
+	fn pairs(&self) -> Vec<(Vec<u8>, Vec<u8>)> {
+		if self.pruned.is_none() {
+			return self.state.pairs();
+		}
+
+		let mut pairs = Vec::new();
+		self.for_available_keys_with_prefix(&[], |key| {
+			if let Ok(Some(value)) = self.state.storage(key) {
+				pairs.push((key.to_vec(), value));
+			}
+		});
+		pairs
+	}
+
+	fn try_into_trie_backend(self) -> Option<state_machine::TrieBackend<Self::TrieBackendStorage, Blake2Hasher>> {
+		match self.pruned {
+			Some(_) => None,
+			None => Some(self.state),
+		}
+	}
+}
 
 /// Database settings.
 pub struct DatabaseSettings {
@@ -503,6 +631,7 @@ pub struct Backend<Block: BlockT> {
 	changes_tries_storage: DbChangesTrieStorage<Block>,
 	blockchain: BlockchainDb<Block>,
 	canonicalization_delay: u64,
+	archived_prefixes: Arc<Vec<Vec<u8>>>,
 }
 
 impl<Block: BlockT> Backend<Block> {
@@ -530,6 +659,7 @@ impl<Block: BlockT> Backend<Block> {
 
 	fn from_kvdb(db: Arc<KeyValueDB>, pruning: PruningMode, canonicalization_delay: u64) -> Result<Self, client::error::Error> {
 		let is_archive_pruning = pruning.is_archive();
+		let archived_prefixes = Arc::new(pruning.archived_prefixes().to_vec());
 		let blockchain = BlockchainDb::new(db.clone())?;
 		let meta = blockchain.meta.clone();
 		let map_e = |e: state_db::Error<io::Error>| ::client::error::Error::from(format!("State database error: {:?}", e));
@@ -550,6 +680,7 @@ impl<Block: BlockT> Backend<Block> {
 			changes_tries_storage,
 			blockchain,
 			canonicalization_delay,
+			archived_prefixes,
 		})
 	}
 
@@ -749,8 +880,19 @@ impl<Block> client::backend::Backend<Block, Blake2Hasher> for Backend<Block> whe
 					changeset.deleted.push(key);
 				}
 			}
+			// the removed nodes of the archived prefixes are never deleted from the database.
+			let archived: HashSet<H256> = if !self.archived_prefixes.is_empty() && !changeset.deleted.is_empty() {
+				let deleted: HashSet<H256> = changeset.deleted.iter().cloned().collect();
+				operation.old_state.state.essence()
+					.prefixed_nodes(&self.archived_prefixes, |hash| deleted.contains(hash))
+					.map_err(client::error::Error::from)?
+					.into_iter()
+					.collect()
+			} else {
+				HashSet::new()
+			};
 			let number_u64 = number.as_();
-			let commit = self.storage.state_db.insert_block(&hash, number_u64, &pending_block.header.parent_hash(), changeset)
+			let commit = self.storage.state_db.insert_block(&hash, number_u64, &pending_block.header.parent_hash(), changeset, &archived)
 				.map_err(|e: state_db::Error<io::Error>| client::error::Error::from(format!("State database error: {:?}", e)))?;
 			apply_state_commit(&mut transaction, commit);
 			self.changes_tries_storage.commit(&mut transaction, operation.changes_trie_updates);
@@ -797,7 +939,9 @@ impl<Block> client::backend::Backend<Block, Blake2Hasher> for Backend<Block> whe
 		Ok(())
 	}
 
-	fn finalize_block(&self, block: BlockId<Block>) -> Result<(), client::error::Error> {
+	fn finalize_block(&self, block: BlockId<Block>, justification: Option<Justification>)
+		-> Result<(), client::error::Error>
+	{
 		use runtime_primitives::traits::Header;
 
 		if let Some(header) = ::client::blockchain::HeaderBackend::header(&self.blockchain, block)? {
@@ -805,6 +949,10 @@ impl<Block> client::backend::Backend<Block, Blake2Hasher> for Backend<Block> whe
 			// TODO: ensure best chain contains this block.
 			let hash = header.hash();
 			self.note_finalized(&mut transaction, &header, hash.clone())?;
+			if let Some(justification) = justification {
+				let lookup_key = ::utils::number_and_hash_to_lookup_key(header.number().clone(), hash.clone());
+				transaction.put(columns::JUSTIFICATION, &lookup_key, &justification.encode());
+			}
 			self.storage.db.write(transaction).map_err(db_err)?;
 			self.blockchain.update_meta(hash, header.number().clone(), false, true);
 			Ok(())
@@ -869,6 +1017,11 @@ impl<Block> client::backend::Backend<Block, Blake2Hasher> for Backend<Block> whe
 				let root = H256::from_slice(hdr.state_root().as_ref());
 				Ok(DbState::new(self.storage.clone(), root))
 			},
+			// only the archived part of the hybrid-pruned states is left.
+			Ok(Some(ref hdr)) if !self.archived_prefixes.is_empty() => {
+				let root = H256::from_slice(hdr.state_root().as_ref());
+				Ok(DbState::new_pruned(self.storage.clone(), root, self.archived_prefixes.clone()))
+			},
 			Err(e) => Err(e),
 			_ => Err(client::error::ErrorKind::UnknownBlock(format!("{:?}", block)).into()),
 		}
@@ -1196,11 +1349,99 @@ mod tests {
 			assert!(backend.storage.db.get(::columns::STATE, key.as_bytes()).unwrap().is_none());
 		}
 
-		backend.finalize_block(BlockId::Number(1)).unwrap();
-		backend.finalize_block(BlockId::Number(2)).unwrap();
+		backend.finalize_block(BlockId::Number(1), None).unwrap();
+		backend.finalize_block(BlockId::Number(2), None).unwrap();
 		assert!(backend.storage.db.get(::columns::STATE, key.as_bytes()).unwrap().is_none());
 	}
 
+	/// A backend in the hybrid pruning mode archiving `bal`, with the state of block 0 pruned.
+	fn hybrid_pruned_backend() -> Backend<Block> {
+		use utils::NUM_COLUMNS;
+
+		let backend = Backend::<Block>::from_kvdb(
+			Arc::new(::kvdb_memorydb::create(NUM_COLUMNS)) as Arc<_>,
+			PruningMode::keep_blocks_and_prefixes(0, vec![b"bal".to_vec()]),
+			0,
+		).unwrap();
+
+		let mut header = Header {
+			number: 0,
+			parent_hash: Default::default(),
+			state_root: Default::default(),
+			digest: Default::default(),
+			extrinsics_root: Default::default(),
+		};
+		let hash = {
+			let mut op = backend.begin_operation(BlockId::Hash(Default::default())).unwrap();
+			let storage = vec![
+				(b"balance1".to_vec(), vec![1; 64]),
+				(b"other1".to_vec(), vec![1; 64]),
+			];
+			header.state_root = op.old_state.storage_root(storage
+				.iter()
+				.cloned()
+				.map(|(x, y)| (x, Some(y)))
+			).0.into();
+			op.reset_storage(storage.iter().cloned().collect(), Default::default()).unwrap();
+			op.set_block_data(header.clone(), Some(vec![]), None, NewBlockState::Final).unwrap();
+			backend.commit_operation(op).unwrap();
+			header.hash()
+		};
+
+		{
+			let mut op = backend.begin_operation(BlockId::Number(0)).unwrap();
+			header.number = 1;
+			header.parent_hash = hash;
+			let storage = vec![
+				(b"balance1".to_vec(), Some(vec![2; 64])),
+				(b"other1".to_vec(), Some(vec![2; 64])),
+			];
+			let (root, overlay) = op.old_state.storage_root(storage.iter().cloned());
+			op.update_storage(overlay).unwrap();
+			header.state_root = root.into();
+			op.set_block_data(header, Some(vec![]), None, NewBlockState::Final).unwrap();
+			backend.commit_operation(op).unwrap();
+		}
+
+		backend
+	}
+
+	#[test]
+	fn hybrid_pruning_keeps_archived_prefixes() {
+		let backend = hybrid_pruned_backend();
+
+		let state = backend.state_at(BlockId::Number(0)).unwrap();
+		assert_eq!(state.storage(b"balance1").unwrap(), Some(vec![1; 64]));
+		assert_eq!(state.keys(b"bal").unwrap(), vec![b"balance1".to_vec()]);
+		assert!(state.storage(b"other1").unwrap_err().contains("State is pruned"));
+		assert!(state.keys(b"").is_err());
+
+		let state = backend.state_at(BlockId::Number(1)).unwrap();
+		assert_eq!(state.storage(b"balance1").unwrap(), Some(vec![2; 64]));
+		assert_eq!(state.storage(b"other1").unwrap(), Some(vec![2; 64]));
+	}
+
+	#[test]
+	fn hybrid_pruned_state_iterates_archived_keys_only() {
+		let backend = hybrid_pruned_backend();
+		let keys_with_prefix = |state: &DbState, prefix: &[u8]| {
+			let mut keys = Vec::new();
+			state.for_keys_with_prefix(prefix, |key| keys.push(key.to_vec()));
+			keys
+		};
+
+		let state = backend.state_at(BlockId::Number(0)).unwrap();
+		assert_eq!(keys_with_prefix(&state, b""), vec![b"balance1".to_vec()]);
+		assert_eq!(keys_with_prefix(&state, b"b"), vec![b"balance1".to_vec()]);
+		assert_eq!(keys_with_prefix(&state, b"balance"), vec![b"balance1".to_vec()]);
+		assert!(keys_with_prefix(&state, b"other").is_empty());
+		assert_eq!(state.pairs(), vec![(b"balance1".to_vec(), vec![1; 64])]);
+
+		let state = backend.state_at(BlockId::Number(1)).unwrap();
+		assert_eq!(keys_with_prefix(&state, b""), vec![b"balance1".to_vec(), b"other1".to_vec()]);
+		assert_eq!(state.pairs().len(), 2);
+	}
+
 	#[test]
 	fn changes_trie_storage_works() {
 		let backend = Backend::<Block>::new_test(1000, 100);
@@ -1490,6 +1731,20 @@ mod tests {
 		test_client::trait_tests::test_blockchain_query_by_number_gets_canonical(backend);
 	}
 
+	#[test]
+	fn finalize_block_stores_justification() {
+		use client::blockchain::Backend as BlockchainBackend;
+
+		let backend = Backend::<Block>::new_test(10, 10);
+		let block0 = insert_header(&backend, 0, Default::default(), Vec::new(), Default::default());
+		let block1 = insert_header(&backend, 1, block0, Vec::new(), Default::default());
+		assert_eq!(backend.blockchain().justification(BlockId::Hash(block1)).unwrap(), None);
+
+		let justification = Some(vec![1, 2, 3]);
+		backend.finalize_block(BlockId::Number(1), justification.clone()).unwrap();
+		assert_eq!(backend.blockchain().justification(BlockId::Hash(block1)).unwrap(), justification);
+	}
+
 	#[test]
 	fn test_aux() {
 		let backend: Backend<test_client::runtime::Block> = Backend::new_test(0, 0);
@@ -105,9 +105,9 @@ pub trait Backend<Block, H>: Send + Sync where
 	fn begin_operation(&self, block: BlockId<Block>) -> error::Result<Self::BlockImportOperation>;
 	/// Commit block insertion.
 	fn commit_operation(&self, transaction: Self::BlockImportOperation) -> error::Result<()>;
-	/// Finalize block with given Id. This should only be called if the parent of the given
-	/// block has been finalized.
-	fn finalize_block(&self, block: BlockId<Block>) -> error::Result<()>;
+	/// Finalize block with given Id, storing the justification of its finality if given.
+	/// This should only be called if the parent of the given block has been finalized.
+	fn finalize_block(&self, block: BlockId<Block>, justification: Option<Justification>) -> error::Result<()>;
 	/// Returns reference to blockchain backend.
 	fn blockchain(&self) -> &Self::Blockchain;
 	/// Returns reference to changes trie storage.
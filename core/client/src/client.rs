@@ -39,7 +39,7 @@ use primitives::storage::well_known_keys;
 use codec::Decode;
 use state_machine::{
 	DBValue, Backend as StateBackend, CodeExecutor, ChangesTrieAnchorBlockId,
-	ExecutionStrategy, ExecutionManager, prove_read,
+	ExecutionStrategy, ExecutionManager, prove_read, prove_read_keys,
 	ChangesTrieRootsStorage, ChangesTrieStorage,
 	key_changes, key_changes_proof, OverlayedChanges
 };
@@ -142,6 +142,12 @@ pub struct BlockImportNotification<Block: BlockT> {
 	pub is_new_best: bool,
 	/// Tags provided by transactions imported in that block.
 	pub tags: Vec<TransactionTag>,
+	/// Blocks that are no longer part of the best chain, if the import switched the best chain
+	/// to another fork. In reverse order, i.e. starting with the previous best block.
+	pub retracted: Vec<Block::Hash>,
+	/// Blocks of the new best chain, apart from the imported one, that weren't part of the best
+	/// chain before the import. In order, i.e. ending with the parent of the imported block.
+	pub enacted: Vec<Block::Hash>,
 }
 
 /// Summary of a finalized block.
@@ -278,6 +284,34 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 			.map(StorageData))
 	}
 
+	/// Return the keys of the storage entries that start with the given prefix in state in a block of given hash.
+	pub fn storage_keys(&self, id: &BlockId<Block>, key_prefix: &StorageKey) -> error::Result<Vec<StorageKey>> {
+		let keys = self.state_at(id)?
+			.keys(&key_prefix.0).map_err(|e| error::Error::from_state(Box::new(e)))?
+			.into_iter()
+			.map(StorageKey)
+			.collect();
+		Ok(keys)
+	}
+
+	/// Return at most `count` keys of the storage entries that start with the given prefix and
+	/// follow `start_key` in state in a block of given hash.
+	pub fn storage_keys_paged(
+		&self,
+		id: &BlockId<Block>,
+		key_prefix: &StorageKey,
+		start_key: Option<&StorageKey>,
+		count: usize,
+	) -> error::Result<Vec<StorageKey>> {
+		let keys = self.state_at(id)?
+			.keys_paged(&key_prefix.0, start_key.map(|key| &key.0[..]), count)
+			.map_err(|e| error::Error::from_state(Box::new(e)))?
+			.into_iter()
+			.map(StorageKey)
+			.collect();
+		Ok(keys)
+	}
+
 	/// Get the code at a given block.
 	pub fn code_at(&self, id: &BlockId<Block>) -> error::Result<Vec<u8>> {
 		Ok(self.storage(id, &StorageKey(well_known_keys::CODE.to_vec()))?
@@ -313,6 +347,13 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 				.map_err(Into::into))
 	}
 
+	/// Reads storage values at a given block + keys, returning a single read proof for all of them.
+	pub fn read_proof_keys(&self, id: &BlockId<Block>, keys: &[Vec<u8>]) -> error::Result<Vec<Vec<u8>>> {
+		self.state_at(id)
+			.and_then(|state| prove_read_keys(state, keys)
+				.map_err(Into::into))
+	}
+
 	/// Execute a call to a contract on top of state in a block of given hash
 	/// AND returning execution proof.
 	///
@@ -624,7 +665,7 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 		// ensure parent block is finalized to maintain invariant that
 		// finality is called sequentially.
 		if finalized {
-			self.apply_finality(parent_hash, last_best, make_notifications)?;
+			self.apply_finality(parent_hash, None, last_best, make_notifications)?;
 		}
 
 		let tags = self.transaction_tags(parent_hash, &body)?;
@@ -675,6 +716,21 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 
 		trace!("Imported {}, (#{}), best={}, origin={:?}", hash, import_headers.post().number(), is_new_best, origin);
 
+		// the best chain switches to another fork if the new best block doesn't extend the old one.
+		let (retracted, enacted) = if make_notifications && is_new_best && parent_hash != last_best {
+			let route = ::blockchain::tree_route(
+				self.backend.blockchain(),
+				BlockId::Hash(last_best),
+				BlockId::Hash(parent_hash),
+			)?;
+			(
+				route.retracted().iter().map(|entry| entry.hash).collect(),
+				route.enacted().iter().map(|entry| entry.hash).collect(),
+			)
+		} else {
+			(Vec::new(), Vec::new())
+		};
+
 		transaction.set_block_data(
 			import_headers.post().clone(),
 			body,
@@ -718,6 +774,8 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 				header: import_headers.into_post(),
 				is_new_best,
 				tags,
+				retracted,
+				enacted,
 			};
 
 			self.import_notification_sinks.lock()
@@ -727,12 +785,26 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 		Ok(ImportResult::Queued)
 	}
 
-	/// Finalizes all blocks up to given.
-	fn apply_finality(&self, block: Block::Hash, best_block: Block::Hash, notify: bool) -> error::Result<()> {
+	/// Finalizes all blocks up to given. If a justification is provided it is
+	/// stored with the given block.
+	fn apply_finality(
+		&self,
+		block: Block::Hash,
+		justification: Option<Justification>,
+		best_block: Block::Hash,
+		notify: bool,
+	) -> error::Result<()> {
 		// find tree route from last finalized to given block.
 		let last_finalized = self.backend.blockchain().last_finalized()?;
 
-		if block == last_finalized { return Ok(()) }
+		if block == last_finalized {
+			// the block is final already, but a justification for it is still worth keeping.
+			if justification.is_some() {
+				self.backend.finalize_block(BlockId::Hash(block), justification)?;
+			}
+			return Ok(())
+		}
+
 		let route_from_finalized = ::blockchain::tree_route(
 			self.backend.blockchain(),
 			BlockId::Hash(last_finalized),
@@ -759,9 +831,13 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 			// `block`.
 		}
 
-		for finalize_new in route_from_finalized.enacted() {
-			self.backend.finalize_block(BlockId::Hash(finalize_new.hash))?;
+		// `block` is last in the route, which is non-empty since it is neither the last
+		// finalized block nor retracted.
+		let enacted = route_from_finalized.enacted();
+		for finalize_new in &enacted[..enacted.len() - 1] {
+			self.backend.finalize_block(BlockId::Hash(finalize_new.hash), None)?;
 		}
+		self.backend.finalize_block(BlockId::Hash(block), justification)?;
 
 		if notify {
 			// sometimes when syncing, tons of blocks can be finalized at once.
@@ -788,10 +864,12 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 	/// Finalize a block. This will implicitly finalize all blocks up to it and
 	/// fire finality notifications.
 	///
+	/// The justification of the block's finality, if given, is stored with the block.
+	///
 	/// Pass a flag to indicate whether finality notifications should be propagated.
 	/// This is usually tied to some synchronization state, where we don't send notifications
 	/// while performing major synchronization work.
-	pub fn finalize_block(&self, id: BlockId<Block>, notify: bool) -> error::Result<()> {
+	pub fn finalize_block(&self, id: BlockId<Block>, justification: Option<Justification>, notify: bool) -> error::Result<()> {
 		let last_best = self.backend.blockchain().info()?.best_hash;
 		let to_finalize_hash = match id {
 			BlockId::Hash(h) => h,
@@ -799,7 +877,7 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 				.ok_or_else(|| error::ErrorKind::UnknownBlock(format!("No block with number {:?}", n)))?,
 		};
 
-		self.apply_finality(to_finalize_hash, last_best, notify)
+		self.apply_finality(to_finalize_hash, justification, last_best, notify)
 	}
 
 	/// Attempts to revert the chain by `n` blocks. Returns the number of blocks that were
@@ -1671,6 +1749,64 @@ pub(crate) mod tests {
 		assert_eq!(None, client.best_containing(d2.hash().clone(), Some(0)).unwrap());
 	}
 
+	#[test]
+	fn finalizing_last_finalized_block_stores_justification() {
+		let client = test_client::new();
+
+		let a1 = client.new_block().unwrap().bake().unwrap();
+		client.justify_and_import(BlockOrigin::Own, a1.clone()).unwrap();
+		client.finalize_block(BlockId::Hash(a1.hash()), None, true).unwrap();
+
+		let justification = vec![1, 2, 3];
+		assert_ne!(client.justification(&BlockId::Hash(a1.hash())).unwrap(), Some(justification.clone()));
+		client.finalize_block(BlockId::Hash(a1.hash()), Some(justification.clone()), true).unwrap();
+		assert_eq!(client.info().unwrap().chain.finalized_hash, a1.hash());
+		assert_eq!(client.justification(&BlockId::Hash(a1.hash())).unwrap(), Some(justification));
+	}
+
+	#[test]
+	fn import_notification_contains_retracted_and_enacted_blocks() {
+		use futures::Stream;
+
+		// block tree:
+		// G -> A1 -> A2
+		//      A1 -> B2 -> B3
+		let client = test_client::new();
+
+		// G -> A1
+		let a1 = client.new_block().unwrap().bake().unwrap();
+		client.justify_and_import(BlockOrigin::Own, a1.clone()).unwrap();
+
+		// A1 -> A2
+		let a2 = client.new_block_at(&BlockId::Hash(a1.hash())).unwrap().bake().unwrap();
+		client.justify_and_import(BlockOrigin::Own, a2.clone()).unwrap();
+
+		// A1 -> B2
+		let mut builder = client.new_block_at(&BlockId::Hash(a1.hash())).unwrap();
+		// this push is required as otherwise B2 has the same hash as A2 and won't get imported
+		builder.push_transfer(Transfer {
+			from: Keyring::Alice.to_raw_public().into(),
+			to: Keyring::Ferdie.to_raw_public().into(),
+			amount: 41,
+			nonce: 0,
+		}).unwrap();
+		let b2 = builder.bake().unwrap();
+		client.justify_and_import(BlockOrigin::Own, b2.clone()).unwrap();
+
+		let mut notifications = client.import_notification_stream().wait();
+
+		// B2 -> B3, switches the best chain from A2 to B3
+		let b3 = client.new_block_at(&BlockId::Hash(b2.hash())).unwrap().bake().unwrap();
+		client.justify_and_import(BlockOrigin::Own, b3.clone()).unwrap();
+
+		let notification = notifications.next().unwrap().unwrap();
+		assert_eq!(notification.hash, b3.hash());
+		assert!(notification.is_new_best);
+		assert_eq!(notification.retracted, vec![a2.hash()]);
+		assert_eq!(notification.enacted, vec![b2.hash()]);
+		assert_eq!(client.info().unwrap().chain.best_hash, b3.hash());
+	}
+
 	#[test]
 	fn key_changes_works() {
 		let (client, _, test_cases) = prepare_client_with_key_changes();
@@ -23,6 +23,8 @@
 #[cfg(feature = "std")]
 extern crate substrate_trie as trie;
 extern crate parity_codec as codec;
+#[macro_use]
+extern crate parity_codec_derive;
 extern crate substrate_primitives as primitives;
 extern crate sr_primitives as runtime_primitives;
 #[cfg(feature = "std")]
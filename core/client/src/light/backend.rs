@@ -102,7 +102,7 @@ impl<S, F, Block, H> ClientBackend<Block, H> for Backend<S, F> where
 		)
 	}
 
-	fn finalize_block(&self, block: BlockId<Block>) -> ClientResult<()> {
+	fn finalize_block(&self, block: BlockId<Block>, _justification: Option<Justification>) -> ClientResult<()> {
 		self.blockchain.storage().finalize_header(block)
 	}
 
@@ -244,6 +244,10 @@ where
 		// whole state is not available on light node
 	}
 
+	fn keys(&self, _prefix: &[u8]) -> ClientResult<Vec<Vec<u8>>> {
+		Err(ClientErrorKind::NotAvailableOnLightClient.into())
+	}
+
 	fn for_keys_in_child_storage<A: FnMut(&[u8])>(&self, _storage_key: &[u8], _action: A) {
 		// whole state is not available on light node
 	}
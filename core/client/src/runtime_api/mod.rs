@@ -111,6 +111,9 @@ pub mod id {
 
 	/// ApiId for the Metadata trait.
 	pub const METADATA: ApiId = *b"metadata";
+
+	/// ApiId for the ContractsApi trait.
+	pub const CONTRACTS: ApiId = *b"contract";
 }
 
 pub use self::core::*;
@@ -19,6 +19,29 @@ use runtime_primitives::{
 	traits::{Block as BlockT},
 	transaction_validity::TransactionValidity
 };
+use rstd::vec::Vec;
+
+/// The result of executing a contract call without committing its effects.
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum ContractExecResult {
+	/// The call succeeded.
+	Success {
+		/// The data returned by the contract.
+		output: Vec<u8>,
+		/// The amount of gas consumed by the call.
+		gas_consumed: u64,
+		/// The encoded runtime events that the call would emit.
+		events: Vec<Vec<u8>>,
+	},
+	/// The call failed. The events it would emit are discarded along with its other effects.
+	Error {
+		/// The reason of the failure.
+		reason: Vec<u8>,
+		/// The amount of gas consumed by the call.
+		gas_consumed: u64,
+	},
+}
 
 decl_runtime_apis! {
 	/// The `Metadata` api trait that returns metadata for the runtime.
@@ -32,4 +55,18 @@ decl_runtime_apis! {
 		/// Validate the given transaction.
 		fn validate_transaction(tx: <Block as BlockT>::Extrinsic) -> TransactionValidity;
 	}
+
+	/// The `ContractsApi` api trait for executing contract calls against the state of a block
+	/// without committing them.
+	pub trait ContractsApi {
+		/// Perform a call from the `origin` to the contract at `dest`, transferring `value`
+		/// and supplying at most `gas_limit` gas.
+		fn contracts_call<AccountId, Balance>(
+			origin: AccountId,
+			dest: AccountId,
+			value: Balance,
+			gas_limit: u64,
+			input_data: Vec<u8>
+		) -> ContractExecResult;
+	}
 }
@@ -53,19 +53,23 @@ extern crate log;
 
 extern crate futures;
 
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use codec::Encode;
-use consensus_common::{Authorities, BlockImport, Environment, Proposer};
+use consensus_common::{Authorities, BlockImport, Environment, LocalKey, Proposer};
 use client::ChainHead;
 use consensus_common::{ImportBlock, BlockOrigin};
 use runtime_primitives::{generic, generic::BlockId};
-use runtime_primitives::traits::{Block, Header, Digest, DigestItemFor};
+use runtime_primitives::traits::{Block, Header, Digest, DigestItem, DigestItemFor};
 use network::import_queue::{Verifier, BasicQueue};
 use primitives::{AuthorityId, ed25519};
 
 use futures::{Stream, Future, IntoFuture, future::{self, Either}};
+use futures::sync::mpsc;
+use parking_lot::Mutex;
 use tokio::timer::Interval;
 
 pub use consensus_common::SyncOracle;
@@ -86,7 +90,9 @@ pub trait Network: Clone {
 #[derive(Clone)]
 pub struct Config {
 	/// The local authority keypair. Can be none if this is just an observer.
-	pub local_key: Option<Arc<ed25519::Pair>>,
+	///
+	/// It is looked up anew every slot, so a rotated key is authored with from the next slot on.
+	pub local_key: Option<Arc<dyn LocalKey>>,
 	/// The slot duration in seconds.
 	pub slot_duration: u64
 }
@@ -170,7 +176,7 @@ pub fn start_aura<B, C, E, I, SO, Error>(
 		let env = env.clone();
 		let sync_oracle = sync_oracle.clone();
 
-		let local_keys = config.local_key.map(|pair| (pair.public(), pair));
+		let local_key = config.local_key;
 		let slot_duration = config.slot_duration;
 		let mut last_authored_slot = 0;
 		let next_slot_start = duration_now().map(|now| {
@@ -181,7 +187,10 @@ pub fn start_aura<B, C, E, I, SO, Error>(
 
 		Interval::new(next_slot_start, Duration::from_secs(slot_duration))
 			.filter(move |_| !sync_oracle.is_major_syncing()) // only propose when we are not syncing.
-			.filter_map(move |_| local_keys.clone()) // skip if not authoring.
+			.filter_map(move |_| local_key.as_ref() // skip if not authoring.
+				.and_then(|local_key| local_key.local_key())
+				.map(|pair| (pair.public(), pair))
+			)
 			.map_err(|e|  debug!(target: "aura", "Faulty timer: {:?}", e))
 			.for_each(move |(public_key, key)| {
 				use futures::future;
@@ -330,6 +339,84 @@ fn check_header<B: Block>(slot_now: u64, mut header: B::Header, hash: B::Hash, a
 	}
 }
 
+/// Number of slots before the latest one for which the sealed headers are kept
+/// to detect equivocations.
+const MAX_SLOT_HEADERS_AGE: u64 = 128;
+
+/// A proof that an authority sealed two different headers in the same slot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EquivocationProof<H> {
+	/// The slot both headers were sealed in.
+	pub slot: u64,
+	/// The authority who sealed both headers.
+	pub author: AuthorityId,
+	/// The header seen first, including its seal.
+	pub first: H,
+	/// The conflicting header, including its seal.
+	pub second: H,
+}
+
+impl<H: Header> EquivocationProof<H> where <H::Digest as Digest>::Item: CompatibleDigestItem {
+	/// The hashes of both headers without their seals, each along with the signature
+	/// of its seal.
+	pub fn seals(&self) -> Option<((H::Hash, ed25519::Signature), (H::Hash, ed25519::Signature))> {
+		fn unseal<H: Header>(header: &H) -> Option<(H::Hash, ed25519::Signature)>
+			where <H::Digest as Digest>::Item: CompatibleDigestItem
+		{
+			let mut pre_header = header.clone();
+			let seal = pre_header.digest_mut().pop()?;
+			let signature = *seal.as_aura_seal()?.1;
+			Some((pre_header.hash(), signature))
+		}
+
+		Some((unseal(&self.first)?, unseal(&self.second)?))
+	}
+}
+
+/// A stream of equivocations found while verifying blocks.
+pub type EquivocationStream<H> = mpsc::UnboundedReceiver<EquivocationProof<H>>;
+
+/// The headers sealed in recent slots, by slot and author.
+struct SlotHeaders<H> {
+	headers: HashMap<(u64, AuthorityId), H>,
+	latest_slot: u64,
+}
+
+impl<H: Header> SlotHeaders<H> {
+	fn new() -> Self {
+		SlotHeaders {
+			headers: HashMap::new(),
+			latest_slot: 0,
+		}
+	}
+
+	/// Note a header sealed by `author` in `slot`. Returns the header the author sealed
+	/// in that slot before, if it is a different one.
+	fn note(&mut self, slot: u64, author: AuthorityId, header: H) -> Option<H> {
+		if slot.saturating_add(MAX_SLOT_HEADERS_AGE) < self.latest_slot {
+			return None;
+		}
+
+		if slot > self.latest_slot {
+			self.latest_slot = slot;
+			let oldest_slot = slot.saturating_sub(MAX_SLOT_HEADERS_AGE);
+			self.headers.retain(|&(slot, _), _| slot >= oldest_slot);
+		}
+
+		match self.headers.entry((slot, author)) {
+			Entry::Occupied(entry) => if entry.get().hash() != header.hash() {
+				Some(entry.get().clone())
+			} else {
+				None
+			},
+			Entry::Vacant(entry) => {
+				entry.insert(header);
+				None
+			},
+		}
+	}
+}
+
 /// Extra verification for Aura blocks.
 pub trait ExtraVerification<B: Block>: Send + Sync {
 	/// Future that resolves when the block is verified or fails with error if not.
@@ -351,22 +438,63 @@ impl<B: Block> ExtraVerification<B> for NothingExtra {
 	}
 }
 /// A verifier for Aura blocks.
-pub struct AuraVerifier<C, E> {
+///
+/// It keeps the headers sealed in recent slots, and notifies of authorities which
+/// sealed more than one header in a slot via `equivocation_stream`.
+pub struct AuraVerifier<B: Block, C, E> {
 	config: Config,
 	client: Arc<C>,
 	extra: E,
+	slot_headers: Mutex<SlotHeaders<B::Header>>,
+	equivocation_sinks: Mutex<Vec<mpsc::UnboundedSender<EquivocationProof<B::Header>>>>,
+}
+
+impl<B: Block, C, E> AuraVerifier<B, C, E> {
+	/// Create a new verifier.
+	pub fn new(config: Config, client: Arc<C>, extra: E) -> Self {
+		AuraVerifier {
+			config,
+			client,
+			extra,
+			slot_headers: Mutex::new(SlotHeaders::new()),
+			equivocation_sinks: Mutex::new(Vec::new()),
+		}
+	}
+
+	/// Get a stream of the equivocations found while verifying blocks.
+	pub fn equivocation_stream(&self) -> EquivocationStream<B::Header> {
+		let (sink, stream) = mpsc::unbounded();
+		self.equivocation_sinks.lock().push(sink);
+		stream
+	}
+
+	/// Note a checked header sealed by `author` in `slot_num`, notifying of an
+	/// equivocation if the author sealed a different header in that slot before.
+	fn note_sealed_header(&self, slot_num: u64, author: AuthorityId, header: B::Header) {
+		let first = match self.slot_headers.lock().note(slot_num, author, header.clone()) {
+			Some(first) => first,
+			None => return,
+		};
+
+		warn!(target: "aura", "Authority {:?} sealed {:?} and {:?} in slot {}",
+			author, first.hash(), header.hash(), slot_num);
+
+		let proof = EquivocationProof { slot: slot_num, author, first, second: header };
+		self.equivocation_sinks.lock()
+			.retain(|sink| sink.unbounded_send(proof.clone()).is_ok());
+	}
 }
 
-impl<B: Block, C, E> Verifier<B> for AuraVerifier<C, E> where
+impl<B: Block, C, E> Verifier<B> for AuraVerifier<B, C, E> where
 	C: Authorities<B> + BlockImport<B> + Send + Sync,
-	DigestItemFor<B>: CompatibleDigestItem,
+	DigestItemFor<B>: CompatibleDigestItem + DigestItem<AuthorityId=AuthorityId>,
 	E: ExtraVerification<B>,
 {
 	fn verify(
 		&self,
 		origin: BlockOrigin,
 		header: B::Header,
-		_justification: Vec<u8>,
+		justification: Vec<u8>,
 		body: Option<Vec<B::Extrinsic>>
 	) -> Result<(ImportBlock<B>, Option<Vec<AuthorityId>>), String> {
 		let slot_now = slot_now(self.config.slot_duration)
@@ -378,10 +506,9 @@ impl<B: Block, C, E> Verifier<B> for AuraVerifier<C, E> where
 
 		let extra_verification = self.extra.verify(&header, body.as_ref().map(|x| &x[..]));
 
-		// we add one to allow for some small drift.
-		// FIXME: in the future, alter this queue to allow deferring of headers
-		// https://github.com/paritytech/substrate/issues/1019
-		let checked_header = check_header::<B>(slot_now + 1, header, hash, &authorities[..])?;
+		// headers from future slots are held back by the import queue until their
+		// slot starts, see `verification_delay`.
+		let checked_header = check_header::<B>(slot_now, header, hash, &authorities[..])?;
 		match checked_header {
 			CheckedHeader::Checked(pre_header, slot_num, sig) => {
 				let item = <DigestItemFor<B>>::aura_seal(slot_num, sig);
@@ -389,18 +516,34 @@ impl<B: Block, C, E> Verifier<B> for AuraVerifier<C, E> where
 				debug!(target: "aura", "Checked {:?}; importing.", pre_header);
 
 				extra_verification.into_future().wait()?;
+
+				let author = slot_author(slot_num, &authorities)
+					.expect("the seal was checked against the slot author; qed");
+				let mut header = pre_header.clone();
+				header.digest_mut().push(item.clone());
+				self.note_sealed_header(slot_num, author, header);
+
+				// the runtime signals authority set changes in the digest. passing them on
+				// lets the client cache the authorities of the imported block.
+				let new_authorities = pre_header.digest().logs().iter()
+					.filter_map(DigestItem::as_authorities_change)
+					.next()
+					.map(|authorities| authorities.to_vec());
+				if let Some(ref authorities) = new_authorities {
+					debug!(target: "aura", "Authorities changed at {:?}: {:?}", hash, authorities);
+				}
+
 				let import_block = ImportBlock {
 					origin,
 					header: pre_header,
-					justification: Vec::new(),
+					justification,
 					post_digests: vec![item],
 					body,
 					finalized: false,
 					auxiliary: Vec::new(),
 				};
 
-				// FIXME: extract authorities - https://github.com/paritytech/substrate/issues/1019
-				Ok((import_block, None))
+				Ok((import_block, new_authorities))
 			}
 			CheckedHeader::Deferred(a, b) => {
 				debug!(target: "aura", "Checking {:?} failed; {:?}, {:?}.", hash, a, b);
@@ -408,20 +551,45 @@ impl<B: Block, C, E> Verifier<B> for AuraVerifier<C, E> where
 			}
 		}
 	}
+
+	fn verification_delay(&self, header: &B::Header) -> Option<Duration> {
+		let slot_num = header.digest().logs().last()
+			.and_then(|item| item.as_aura_seal())
+			.map(|(slot_num, _)| slot_num)?;
+		time_until_slot(slot_num, self.config.slot_duration, duration_now()?)
+	}
+}
+
+/// The time from `now` until the given slot starts, if it starts in the future.
+fn time_until_slot(slot_num: u64, slot_duration: u64, now: Duration) -> Option<Duration> {
+	let slot_start = Duration::from_secs(slot_num.saturating_mul(slot_duration));
+	if slot_start > now {
+		Some(slot_start - now)
+	} else {
+		None
+	}
 }
 
 /// The Aura import queue type.
-pub type AuraImportQueue<B, C, E> = BasicQueue<B, AuraVerifier<C, E>>;
+pub type AuraImportQueue<B, C, E> = BasicQueue<B, AuraVerifier<B, C, E>>;
 
 /// Start an import queue for the Aura consensus algorithm.
-pub fn import_queue<B, C, E>(config: Config, client: Arc<C>, extra: E) -> AuraImportQueue<B, C, E> where
+///
+/// Blocks from slots up to `max_drift` in the future are held back until their slot starts,
+/// blocks further ahead fail verification.
+pub fn import_queue<B, C, E>(
+	config: Config,
+	client: Arc<C>,
+	extra: E,
+	max_drift: Duration,
+) -> AuraImportQueue<B, C, E> where
 	B: Block,
 	C: Authorities<B> + BlockImport<B,Error=client::error::Error> + Send + Sync,
-	DigestItemFor<B>: CompatibleDigestItem,
+	DigestItemFor<B>: CompatibleDigestItem + DigestItem<AuthorityId=AuthorityId>,
 	E: ExtraVerification<B>,
 {
-	let verifier = Arc::new(AuraVerifier { config, client: client.clone(), extra, });
-	BasicQueue::new(verifier, client)
+	let verifier = Arc::new(AuraVerifier::new(config, client.clone(), extra));
+	BasicQueue::with_max_drift(verifier, client, max_drift)
 }
 
 #[cfg(test)]
@@ -469,12 +637,12 @@ mod tests {
 	const TEST_ROUTING_INTERVAL: Duration = Duration::from_millis(50);
 
 	pub struct AuraTestNet {
-		peers: Vec<Arc<Peer<AuraVerifier<PeersClient, NothingExtra>, ()>>>,
+		peers: Vec<Arc<Peer<AuraVerifier<TestBlock, PeersClient, NothingExtra>, ()>>>,
 		started: bool
 	}
 
 	impl TestNetFactory for AuraTestNet {
-		type Verifier = AuraVerifier<PeersClient, NothingExtra>;
+		type Verifier = AuraVerifier<TestBlock, PeersClient, NothingExtra>;
 		type PeerData = ();
 
 		/// Create new test network with peers and given config.
@@ -489,7 +657,7 @@ mod tests {
 			-> Arc<Self::Verifier>
 		{
 			let config = Config { local_key: None, slot_duration: SLOT_DURATION };
-			Arc::new(AuraVerifier { client, config, extra: NothingExtra })
+			Arc::new(AuraVerifier::new(config, client, NothingExtra))
 		}
 
 		fn peer(&self, i: usize) -> &Peer<Self::Verifier, ()> {
@@ -540,9 +708,10 @@ mod tests {
 					})
 					.for_each(move |_| Ok(()))
 			);
+			let key: Arc<ed25519::Pair> = Arc::new(key.clone().into());
 			let aura = start_aura(
 				Config {
-					local_key: Some(Arc::new(key.clone().into())),
+					local_key: Some(Arc::new(key)),
 					slot_duration: SLOT_DURATION
 				},
 				client.clone(),
@@ -570,4 +739,151 @@ mod tests {
 
 		runtime.block_on(wait_for.select(drive_to_completion).map_err(|_| ())).unwrap();
 	}
+
+	struct RotatingKey(Mutex<Arc<ed25519::Pair>>);
+
+	impl LocalKey for RotatingKey {
+		fn local_key(&self) -> Option<Arc<ed25519::Pair>> {
+			Some(self.0.lock().clone())
+		}
+	}
+
+	#[test]
+	fn authoring_uses_rotated_key_without_restart() {
+		let client = Arc::new(test_client::new());
+		let environ = Arc::new(DummyFactory(client.clone()));
+
+		// Dave is no authority, so nothing is authored until the key is rotated to Alice's.
+		let key = Arc::new(RotatingKey(Mutex::new(Arc::new(Keyring::Dave.into()))));
+		let aura = start_aura(
+			Config {
+				local_key: Some(key.clone()),
+				slot_duration: SLOT_DURATION
+			},
+			client.clone(),
+			client.clone(),
+			environ,
+			DummyOracle,
+		);
+
+		let mut runtime = current_thread::Runtime::new().unwrap();
+		runtime.spawn(aura);
+
+		// rotate the key once the worker has run for a while.
+		let rotated_key = key.clone();
+		let rotate_at = Instant::now() + Duration::from_secs(SLOT_DURATION) * 3 / 2;
+		runtime.spawn(::tokio::timer::Delay::new(rotate_at)
+			.map(move |_| *rotated_key.0.lock() = Arc::new(Keyring::Alice.into()))
+			.map_err(|_| ())
+		);
+		let authored = client.import_notification_stream()
+			.filter(|n| n.origin == BlockOrigin::Own)
+			.into_future()
+			.map_err(|_| ());
+		let header = runtime.block_on(authored).unwrap().0.unwrap().header;
+
+		let mut pre_header = header.clone();
+		let seal = pre_header.digest_mut().pop().unwrap();
+		let (slot_num, signature) = seal.as_aura_seal().unwrap();
+		let to_sign = (slot_num, pre_header.hash()).encode();
+		assert!(ed25519::verify_strong(signature, &to_sign[..], ed25519::Public::from(Keyring::Alice)));
+	}
+
+	#[test]
+	fn verifier_extracts_authorities_change() {
+		let client = Arc::new(test_client::new());
+		let slot_num = slot_now(SLOT_DURATION).unwrap();
+
+		let new_authorities: Vec<AuthorityId> = vec![Keyring::Alice.to_raw_public().into()];
+		let header = sealed_header(&client, slot_num, generic::DigestItem::AuthoritiesChange(new_authorities.clone()));
+
+		let verifier = AuraVerifier::new(
+			Config { local_key: None, slot_duration: SLOT_DURATION },
+			client,
+			NothingExtra,
+		);
+		let (_, authorities_change) = verifier.verify(BlockOrigin::NetworkBroadcast, header, Vec::new(), None).unwrap();
+		assert_eq!(authorities_change, Some(new_authorities));
+	}
+
+	#[test]
+	fn verifier_detects_equivocations() {
+		let client = Arc::new(test_client::new());
+		let authorities = client.authorities(&BlockId::Number(0)).unwrap();
+		let slot_num = slot_now(SLOT_DURATION).unwrap();
+		let author = slot_author(slot_num, &authorities).unwrap();
+
+		let first = sealed_header(&client, slot_num, generic::DigestItem::Other(vec![1]));
+		let second = sealed_header(&client, slot_num, generic::DigestItem::Other(vec![2]));
+
+		let verifier = AuraVerifier::new(
+			Config { local_key: None, slot_duration: SLOT_DURATION },
+			client,
+			NothingExtra,
+		);
+		let equivocations = verifier.equivocation_stream();
+
+		for header in vec![first.clone(), first.clone(), second.clone()] {
+			verifier.verify(BlockOrigin::NetworkBroadcast, header, Vec::new(), None).unwrap();
+		}
+		drop(verifier);
+
+		let proofs: Vec<_> = equivocations.collect().wait().unwrap();
+		assert_eq!(proofs, vec![EquivocationProof { slot: slot_num, author, first: first.clone(), second: second.clone() }]);
+
+		let (first_seal, second_seal) = proofs[0].seals().unwrap();
+		let mut first_pre_header = first;
+		let mut second_pre_header = second;
+		first_pre_header.digest_mut().pop();
+		second_pre_header.digest_mut().pop();
+		assert_eq!(first_seal.0, first_pre_header.hash());
+		assert_eq!(second_seal.0, second_pre_header.hash());
+	}
+
+	#[test]
+	fn slot_headers_are_pruned() {
+		let client = test_client::new();
+		let author: AuthorityId = Keyring::Alice.to_raw_public().into();
+		let header = |n: u8| {
+			let mut header = client.new_block().unwrap().bake().unwrap().deconstruct().0;
+			header.digest_mut().push(generic::DigestItem::Other(vec![n]));
+			header
+		};
+
+		let mut slot_headers = SlotHeaders::new();
+		assert_eq!(slot_headers.note(1, author, header(1)), None);
+		assert_eq!(slot_headers.note(1, author, header(1)), None);
+		assert_eq!(slot_headers.note(1, author, header(2)), Some(header(1)));
+
+		assert_eq!(slot_headers.note(1 + MAX_SLOT_HEADERS_AGE, author, header(3)), None);
+		assert_eq!(slot_headers.note(1, author, header(2)), Some(header(1)));
+
+		assert_eq!(slot_headers.note(2 + MAX_SLOT_HEADERS_AGE, author, header(4)), None);
+		assert_eq!(slot_headers.note(1, author, header(2)), None);
+		assert_eq!(slot_headers.headers.len(), 2);
+	}
+
+	#[test]
+	fn blocks_are_held_back_until_their_slot_starts() {
+		let now = Duration::from_secs(10 * SLOT_DURATION) + Duration::from_millis(300);
+		assert_eq!(time_until_slot(9, SLOT_DURATION, now), None);
+		assert_eq!(time_until_slot(10, SLOT_DURATION, now), None);
+		assert_eq!(
+			time_until_slot(12, SLOT_DURATION, now),
+			Some(Duration::from_secs(2 * SLOT_DURATION) - Duration::from_millis(300)),
+		);
+	}
+
+	/// Build a block on genesis with the given extra digest item, sealed by the author of `slot_num`.
+	fn sealed_header(client: &TestClient, slot_num: u64, item: DigestItemFor<TestBlock>) -> <TestBlock as BlockT>::Header {
+		let authorities = client.authorities(&BlockId::Number(0)).unwrap();
+		let author = slot_author(slot_num, &authorities).unwrap();
+		let key = Keyring::from_raw_public(author.0).unwrap();
+
+		let mut header = client.new_block().unwrap().bake().unwrap().deconstruct().0;
+		header.digest_mut().push(item);
+		let signature = key.sign(&(slot_num, header.hash()).encode()[..]);
+		header.digest_mut().push(CompatibleDigestItem::aura_seal(slot_num, signature));
+		header
+	}
 }
@@ -73,6 +73,21 @@ pub trait Environment<B: Block> {
 		-> Result<Self::Proposer, Self::Error>;
 }
 
+/// A handle to the key the local node signs consensus messages with.
+///
+/// The key can change while the node is running, e.g. when it is rotated, so it is looked up
+/// whenever something is about to be signed rather than once at startup.
+pub trait LocalKey: Send + Sync {
+	/// The current key, if there is any.
+	fn local_key(&self) -> Option<Arc<ed25519::Pair>>;
+}
+
+impl LocalKey for Arc<ed25519::Pair> {
+	fn local_key(&self) -> Option<Arc<ed25519::Pair>> {
+		Some(self.clone())
+	}
+}
+
 /// Logic for a proposer.
 ///
 /// This will encapsulate creation and evaluation of proposals at a specific
@@ -36,6 +36,7 @@ extern crate sr_std as rstd;
 
 use substrate_primitives::AuthorityId;
 use sr_primitives::traits::{Block as BlockT, DigestFor, NumberFor};
+use parity_codec::Encode;
 use rstd::vec::Vec;
 
 /// A scheduled change of authority set.
@@ -48,6 +49,60 @@ pub struct ScheduledChange<N> {
 	pub delay: N,
 }
 
+/// The stage of a round a GRANDPA vote was cast in.
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub enum VoteStage {
+	/// A prevote.
+	Prevote,
+	/// A precommit.
+	Precommit,
+}
+
+/// A vote for a block along with the voter's signature on it.
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+pub struct SignedVote<H, N, S> {
+	/// The hash of the block voted for.
+	pub target_hash: H,
+	/// The number of the block voted for.
+	pub target_number: N,
+	/// The signature on the localized vote, see `localized_vote_payload`.
+	pub signature: S,
+}
+
+/// Proof that a voter cast two different votes in the same stage of a round.
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+pub struct EquivocationProof<H, N, Id, S> {
+	/// The id of the authority set the votes were cast in.
+	pub set_id: u64,
+	/// The round the votes were cast in.
+	pub round: u64,
+	/// The stage of the round the votes were cast in.
+	pub stage: VoteStage,
+	/// The equivocating voter.
+	pub identity: Id,
+	/// The first vote.
+	pub first: SignedVote<H, N, S>,
+	/// The second, conflicting vote.
+	pub second: SignedVote<H, N, S>,
+}
+
+/// The payload a voter signs for a vote in the given round and authority set.
+///
+/// This is the same encoding as the one of a `(message, round, set_id)` triple used by the
+/// finality gadget, so that gossiped signatures can be checked on-chain.
+pub fn localized_vote_payload<H: Encode, N: Encode>(
+	stage: VoteStage,
+	target_hash: &H,
+	target_number: &N,
+	round: u64,
+	set_id: u64,
+) -> Vec<u8> {
+	(stage, target_hash, target_number, round, set_id).encode()
+}
+
 /// WASM function call to check for pending changes.
 pub const PENDING_CHANGE_CALL: &str = "grandpa_pending_change";
 /// WASM function call to get current GRANDPA authorities.
@@ -175,7 +175,23 @@ pub(crate) fn outgoing_messages<Block: BlockT, N: Network>(
 	(rx, outgoing)
 }
 
-fn check_compact_commit<Block: BlockT>(
+/// Convert a commit into its compact form, as sent over the network.
+pub(crate) fn compact_commit<Block: BlockT>(commit: Commit<Block>) -> CompactCommit<Block> {
+	let (precommits, auth_data) = commit.precommits.into_iter()
+		.map(|signed| (signed.precommit, (signed.signature, signed.id)))
+		.unzip();
+
+	CompactCommit::<Block> {
+		target_hash: commit.target_hash,
+		target_number: commit.target_number,
+		precommits,
+		auth_data
+	}
+}
+
+/// Check the signatures on all precommits of a compact commit, and that they are
+/// made by the given voters.
+pub(crate) fn check_compact_commit<Block: BlockT>(
 	msg: CompactCommit<Block>,
 	voters: &HashMap<AuthorityId, u64>,
 	round: u64,
@@ -260,16 +276,7 @@ impl<Block: BlockT, N: Network> Sink for CommitsOut<Block, N> {
 
 	fn start_send(&mut self, input: (u64, Commit<Block>)) -> StartSend<Self::SinkItem, Error> {
 		let (round, commit) = input;
-		let (precommits, auth_data) = commit.precommits.into_iter()
-			.map(|signed| (signed.precommit, (signed.signature, signed.id)))
-			.unzip();
-
-		let compact_commit = CompactCommit::<Block> {
-			target_hash: commit.target_hash,
-			target_number: commit.target_number,
-			precommits,
-			auth_data
-		};
+		let compact_commit = compact_commit::<Block>(commit);
 
 		self.network.send_commit(self.set_id, Encode::encode(&(round, compact_commit)));
 
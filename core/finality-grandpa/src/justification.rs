@@ -0,0 +1,146 @@
+// Copyright 2018 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Justifications of block finality, made from the commit messages of the rounds
+//! that finalized the blocks.
+
+use std::collections::{HashMap, HashSet};
+
+use client::{CallExecutor, Client};
+use client::backend::Backend;
+use client::error::{Error as ClientError, ErrorKind as ClientErrorKind};
+use codec::Decode;
+use runtime_primitives::generic::BlockId;
+use runtime_primitives::traits::{Block as BlockT, Header as HeaderT, NumberFor};
+use substrate_primitives::{H256, AuthorityId, Blake2Hasher};
+
+use {Commit, CompactCommit};
+use communication::{check_compact_commit, compact_commit};
+
+/// A GRANDPA justification for block finality. It contains the commit of the round
+/// that finalized the block, along with the headers linking the targets of all
+/// precommits to the committed block, so that it can be checked without access to
+/// the chain.
+#[derive(Encode, Decode)]
+pub struct GrandpaJustification<Block: BlockT> {
+	/// The round the block was finalized in.
+	pub round: u64,
+	/// The commit of that round.
+	pub commit: CompactCommit<Block>,
+	/// The headers between the targets of the precommits and the committed block.
+	pub votes_ancestries: Vec<Block::Header>,
+}
+
+impl<Block: BlockT<Hash=H256>> GrandpaJustification<Block> {
+	/// Create a justification from the commit of the given round, fetching the headers
+	/// between the precommit targets and the committed block from the client.
+	pub(crate) fn from_commit<B, E, RA>(
+		client: &Client<B, E, Block, RA>,
+		round: u64,
+		commit: Commit<Block>,
+	) -> Result<GrandpaJustification<Block>, ClientError> where
+		B: Backend<Block, Blake2Hasher>,
+		E: CallExecutor<Block, Blake2Hasher> + Send + Sync,
+		RA: Send + Sync,
+	{
+		let mut votes_ancestries_hashes = HashSet::new();
+		let mut votes_ancestries = Vec::new();
+
+		for signed in &commit.precommits {
+			let mut current_hash = signed.precommit.target_hash;
+			while current_hash != commit.target_hash {
+				let header = client.header(&BlockId::Hash(current_hash))?
+					.ok_or_else(|| ClientErrorKind::UnknownBlock(format!("{}", current_hash)))?;
+
+				if *header.number() <= commit.target_number {
+					return Err(ClientErrorKind::BadJustification(
+						"precommit target is not a descendant of the commit target".into()
+					).into());
+				}
+
+				current_hash = *header.parent_hash();
+				if votes_ancestries_hashes.insert(header.hash()) {
+					votes_ancestries.push(header);
+				}
+			}
+		}
+
+		Ok(GrandpaJustification {
+			round,
+			commit: compact_commit::<Block>(commit),
+			votes_ancestries,
+		})
+	}
+
+	/// Decode a justification and check it against the given authority set.
+	///
+	/// The justification is valid if all precommits are correctly signed by distinct
+	/// voters of the set, and the precommits for descendants of the committed block
+	/// carry at least a supermajority of the voters' weight.
+	pub(crate) fn decode_and_verify(
+		encoded: &[u8],
+		set_id: u64,
+		voters: &HashMap<AuthorityId, u64>,
+	) -> Result<GrandpaJustification<Block>, ClientError> {
+		let bad_justification = |msg: &str| -> ClientError {
+			ClientErrorKind::BadJustification(msg.into()).into()
+		};
+
+		let justification = GrandpaJustification::<Block>::decode(&mut &*encoded)
+			.ok_or_else(|| bad_justification("failed to decode"))?;
+
+		let commit = check_compact_commit::<Block>(justification.commit.clone(), voters, justification.round, set_id)
+			.ok_or_else(|| bad_justification("invalid commit"))?;
+
+		let ancestry: HashMap<_, _> = justification.votes_ancestries.iter()
+			.map(|header| (header.hash(), *header.parent_hash()))
+			.collect();
+
+		let mut signers = HashSet::new();
+		let mut weight = 0u64;
+		for (precommit, &(_, ref id)) in commit.precommits.iter().zip(&commit.auth_data) {
+			if !signers.insert(id) {
+				return Err(bad_justification("duplicate precommit"));
+			}
+
+			// each step moves to a different ancestry entry, so the walk is bounded.
+			let mut current_hash = precommit.target_hash;
+			let mut steps = 0;
+			while current_hash != commit.target_hash {
+				match ancestry.get(&current_hash) {
+					Some(parent_hash) if steps < ancestry.len() => current_hash = *parent_hash,
+					_ => return Err(bad_justification("precommit not for a descendant of the commit target")),
+				}
+				steps += 1;
+			}
+
+			weight += voters.get(id).cloned().unwrap_or(0);
+		}
+
+		let total_weight: u64 = voters.values().sum();
+		let faulty = total_weight.saturating_sub(1) / 3;
+		if weight < total_weight - faulty {
+			return Err(bad_justification("not enough weight in precommits"));
+		}
+
+		Ok(justification)
+	}
+
+	/// The hash and number of the block this justification is for.
+	pub(crate) fn target(&self) -> (Block::Hash, NumberFor<Block>) {
+		(self.commit.target_hash, self.commit.target_number)
+	}
+}
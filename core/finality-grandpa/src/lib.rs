@@ -89,12 +89,13 @@ use client::{
 use client::blockchain::HeaderBackend;
 use client::runtime_api::TaggedTransactionQueue;
 use codec::{Encode, Decode};
-use consensus_common::{BlockImport, ImportBlock, ImportResult, Authorities};
+use consensus_common::{BlockImport, ImportBlock, ImportResult, Authorities, LocalKey};
 use runtime_primitives::traits::{
 	NumberFor, Block as BlockT, Header as HeaderT, DigestFor, ProvideRuntimeApi, Hash as HashT,
 };
-use fg_primitives::GrandpaApi;
+use fg_primitives::{GrandpaApi, SignedVote, VoteStage};
 use runtime_primitives::generic::BlockId;
+use runtime_primitives::Justification;
 use substrate_primitives::{ed25519, H256, AuthorityId, Blake2Hasher};
 use tokio::timer::Delay;
 
@@ -112,9 +113,11 @@ use authorities::SharedAuthoritySet;
 use until_imported::{UntilCommitBlocksImported, UntilVoteTargetImported};
 
 pub use fg_primitives::ScheduledChange;
+pub use justification::GrandpaJustification;
 
 mod authorities;
 mod communication;
+mod justification;
 mod until_imported;
 
 #[cfg(feature="service-integration")]
@@ -159,13 +162,23 @@ pub type CompactCommit<Block> = grandpa::CompactCommit<
 	AuthorityId
 >;
 
+/// A proof of a voter equivocating, as checked by the runtime.
+pub type EquivocationProof<Block> = fg_primitives::EquivocationProof<
+	<Block as BlockT>::Hash,
+	NumberFor<Block>,
+	AuthorityId,
+	ed25519::Signature,
+>;
+
 /// Configuration for the GRANDPA service.
 #[derive(Clone)]
 pub struct Config {
 	/// The expected duration for a message to be gossiped across the network.
 	pub gossip_duration: Duration,
 	/// The local signing key.
-	pub local_key: Option<Arc<ed25519::Pair>>,
+	///
+	/// It is looked up anew every round, so a rotated key is voted with from the next round on.
+	pub local_key: Option<Arc<dyn LocalKey>>,
 	/// Some local identifier of the voter.
 	pub name: Option<String>,
 }
@@ -197,6 +210,18 @@ impl From<GrandpaError> for Error {
 	}
 }
 
+/// Something which reports equivocations of voters, generally by submitting the
+/// proof to the chain so that the offender is slashed.
+pub trait EquivocationReporter<Block: BlockT>: Send + Sync {
+	/// Report a proven equivocation.
+	fn report_equivocation(&self, proof: EquivocationProof<Block>);
+}
+
+/// Equivocations are only logged.
+impl<Block: BlockT> EquivocationReporter<Block> for () {
+	fn report_equivocation(&self, _proof: EquivocationProof<Block>) { }
+}
+
 /// A handle to the network. This is generally implemented by providing some
 /// handle to a gossip service or similar.
 ///
@@ -305,6 +330,7 @@ struct Environment<B, E, Block: BlockT, N: Network, RA> {
 	authority_set: SharedAuthoritySet<Block::Hash, NumberFor<Block>>,
 	network: N,
 	set_id: u64,
+	equivocation_reporter: Arc<dyn EquivocationReporter<Block>>,
 }
 
 impl<Block: BlockT<Hash=H256>, B, E, N, RA> grandpa::Chain<Block::Hash, NumberFor<Block>> for Environment<B, E, Block, N, RA> where
@@ -460,7 +486,7 @@ impl<B, E, Block: BlockT<Hash=H256>, N, RA> voter::Environment<Block::Hash, Numb
 		let (out_rx, outgoing) = ::communication::outgoing_messages::<Block, _>(
 			round,
 			self.set_id,
-			self.config.local_key.clone(),
+			self.config.local_key.as_ref().and_then(|local_key| local_key.local_key()),
 			self.voters.clone(),
 			self.network.clone(),
 		);
@@ -497,6 +523,23 @@ impl<B, E, Block: BlockT<Hash=H256>, N, RA> voter::Environment<Block::Hash, Numb
 			state.finalized.as_ref().map(|e| e.1),
 		);
 
+		// the authority set may have changed by importing a justified block, in which
+		// case the voter restarts on top of the last finalized block.
+		let (set_id, authorities) = {
+			let authority_set = self.authority_set.inner().read();
+			let (set_id, authorities) = authority_set.current();
+			(set_id, authorities.to_vec())
+		};
+		if set_id != self.set_id {
+			let info = self.inner.info()?;
+			return Err(ExitOrError::AuthoritiesChanged(NewAuthoritySet {
+				canon_hash: info.chain.finalized_hash,
+				canon_number: info.chain.finalized_number,
+				set_id,
+				authorities,
+			}));
+		}
+
 		let encoded_state = (round, state).encode();
 		if let Err(e) = self.inner.backend()
 			.insert_aux(&[(LAST_COMPLETED_KEY, &encoded_state[..])], &[])
@@ -508,77 +551,26 @@ impl<B, E, Block: BlockT<Hash=H256>, N, RA> voter::Environment<Block::Hash, Numb
 		}
 	}
 
-	fn finalize_block(&self, hash: Block::Hash, number: NumberFor<Block>, _commit: Commit<Block>) -> Result<(), Self::Error> {
-		// ideally some handle to a synchronization oracle would be used
-		// to avoid unconditionally notifying.
-		if let Err(e) = self.inner.finalize_block(BlockId::Hash(hash), true) {
-			warn!(target: "afg", "Error applying finality to block {:?}: {:?}", (hash, number), e);
-
-			// we return without error because not being able to finalize (temporarily) is
-			// non-fatal.
-			return Ok(());
-		}
-
-		debug!(target: "afg", "Finalizing blocks up to ({:?}, {})", number, hash);
-
-		// lock must be held through writing to DB to avoid race
-		let mut authority_set = self.authority_set.inner().write();
-		let client = &self.inner;
-		let status = authority_set.apply_changes(number, |canon_number| {
-			client.block_hash_from_id(&BlockId::number(canon_number))
-				.map(|h| h.expect("given number always less than newly-finalized number; \
-					thus there is a block with that number finalized already; qed"))
-		})?;
-
-		if status.changed {
-			// write new authority set state to disk.
-			let encoded_set = authority_set.encode();
-
-			let write_result = if let Some((ref canon_hash, ref canon_number)) = status.new_set_block {
-				// we also overwrite the "last completed round" entry with a blank slate
-				// because from the perspective of the finality gadget, the chain has
-				// reset.
-				let round_state = RoundState::genesis((*canon_hash, *canon_number));
-				let last_completed: LastCompleted<_, _> = (0, round_state);
-				let encoded = last_completed.encode();
-
-				client.backend().insert_aux(
-					&[
-						(AUTHORITY_SET_KEY, &encoded_set[..]),
-						(LAST_COMPLETED_KEY, &encoded[..]),
-					],
-					&[]
-				)
-			} else {
-				client.backend().insert_aux(&[(AUTHORITY_SET_KEY, &encoded_set[..])], &[])
-			};
-
-			if let Err(e) = write_result {
-				warn!(target: "finality", "Failed to write updated authority set to disk. Bailing.");
-				warn!(target: "finality", "Node is in a potentially inconsistent state.");
-
-				return Err(e.into());
-			}
-		}
-
-		if let Some((canon_hash, canon_number)) = status.new_set_block {
-			// the authority set has changed.
-			let (new_id, set_ref) = authority_set.current();
-
-			if set_ref.len() > 16 {
-				info!("Applying GRANDPA set change to new set with {} authorities", set_ref.len());
-			} else {
-				info!("Applying GRANDPA set change to new set {:?}", set_ref);
+	fn finalize_block(
+		&self,
+		hash: Block::Hash,
+		number: NumberFor<Block>,
+		round: u64,
+		commit: Commit<Block>,
+	) -> Result<(), Self::Error> {
+		// not being able to build a justification is non-fatal; the block is finalized
+		// without one.
+		let justification = match GrandpaJustification::from_commit(&self.inner, round, commit) {
+			Ok(justification) => Some(justification.encode()),
+			Err(e) => {
+				debug!(target: "afg", "Error creating justification for block {:?}: {:?}", (hash, number), e);
+				None
 			}
+		};
 
-			Err(ExitOrError::AuthoritiesChanged(NewAuthoritySet {
-				canon_hash,
-				canon_number,
-				set_id: new_id,
-				authorities: set_ref.to_vec(),
-			}))
-		} else {
-			Ok(())
+		match finalize_block(&*self.inner, &self.authority_set, hash, number, justification)? {
+			Some(new_set) => Err(ExitOrError::AuthoritiesChanged(new_set)),
+			None => Ok(()),
 		}
 	}
 
@@ -598,7 +590,20 @@ impl<B, E, Block: BlockT<Hash=H256>, N, RA> voter::Environment<Block::Hash, Numb
 		equivocation: ::grandpa::Equivocation<Self::Id, Prevote<Block>, Self::Signature>
 	) {
 		warn!(target: "afg", "Detected prevote equivocation in the finality worker: {:?}", equivocation);
-		// nothing yet; this could craft misbehavior reports of some kind.
+
+		let vote = |(prevote, signature): (Prevote<Block>, ed25519::Signature)| SignedVote {
+			target_hash: prevote.target_hash,
+			target_number: prevote.target_number,
+			signature,
+		};
+		self.equivocation_reporter.report_equivocation(EquivocationProof::<Block> {
+			set_id: self.set_id,
+			round: equivocation.round_number,
+			stage: VoteStage::Prevote,
+			identity: equivocation.identity,
+			first: vote(equivocation.first),
+			second: vote(equivocation.second),
+		});
 	}
 
 	fn precommit_equivocation(
@@ -607,13 +612,115 @@ impl<B, E, Block: BlockT<Hash=H256>, N, RA> voter::Environment<Block::Hash, Numb
 		equivocation: Equivocation<Self::Id, Precommit<Block>, Self::Signature>
 	) {
 		warn!(target: "afg", "Detected precommit equivocation in the finality worker: {:?}", equivocation);
-		// nothing yet
+
+		let vote = |(precommit, signature): (Precommit<Block>, ed25519::Signature)| SignedVote {
+			target_hash: precommit.target_hash,
+			target_number: precommit.target_number,
+			signature,
+		};
+		self.equivocation_reporter.report_equivocation(EquivocationProof::<Block> {
+			set_id: self.set_id,
+			round: equivocation.round_number,
+			stage: VoteStage::Precommit,
+			identity: equivocation.identity,
+			first: vote(equivocation.first),
+			second: vote(equivocation.second),
+		});
+	}
+}
+
+/// Finalize the given block and apply any authority set changes enacted by its
+/// finality, storing the justification with the block if given.
+///
+/// Returns the new authority set if it changed.
+fn finalize_block<B, Block: BlockT<Hash=H256>, E, RA>(
+	client: &Client<B, E, Block, RA>,
+	authority_set: &SharedAuthoritySet<Block::Hash, NumberFor<Block>>,
+	hash: Block::Hash,
+	number: NumberFor<Block>,
+	justification: Option<Justification>,
+) -> Result<Option<NewAuthoritySet<Block::Hash, NumberFor<Block>>>, ClientError> where
+	B: Backend<Block, Blake2Hasher>,
+	E: CallExecutor<Block, Blake2Hasher> + Send + Sync,
+	RA: Send + Sync,
+{
+	// ideally some handle to a synchronization oracle would be used
+	// to avoid unconditionally notifying.
+	if let Err(e) = client.finalize_block(BlockId::Hash(hash), justification, true) {
+		warn!(target: "afg", "Error applying finality to block {:?}: {:?}", (hash, number), e);
+
+		// we return without error because not being able to finalize (temporarily) is
+		// non-fatal.
+		return Ok(None);
+	}
+
+	debug!(target: "afg", "Finalizing blocks up to ({:?}, {})", number, hash);
+
+	// lock must be held through writing to DB to avoid race
+	let mut authority_set = authority_set.inner().write();
+	let status = authority_set.apply_changes(number, |canon_number| {
+		client.block_hash_from_id(&BlockId::number(canon_number))
+			.map(|h| h.expect("given number always less than newly-finalized number; \
+				thus there is a block with that number finalized already; qed"))
+	})?;
+
+	if status.changed {
+		// write new authority set state to disk.
+		let encoded_set = authority_set.encode();
+
+		let write_result = if let Some((ref canon_hash, ref canon_number)) = status.new_set_block {
+			// we also overwrite the "last completed round" entry with a blank slate
+			// because from the perspective of the finality gadget, the chain has
+			// reset.
+			let round_state = RoundState::genesis((*canon_hash, *canon_number));
+			let last_completed: LastCompleted<_, _> = (0, round_state);
+			let encoded = last_completed.encode();
+
+			client.backend().insert_aux(
+				&[
+					(AUTHORITY_SET_KEY, &encoded_set[..]),
+					(LAST_COMPLETED_KEY, &encoded[..]),
+				],
+				&[]
+			)
+		} else {
+			client.backend().insert_aux(&[(AUTHORITY_SET_KEY, &encoded_set[..])], &[])
+		};
+
+		if let Err(e) = write_result {
+			warn!(target: "finality", "Failed to write updated authority set to disk. Bailing.");
+			warn!(target: "finality", "Node is in a potentially inconsistent state.");
+
+			return Err(e);
+		}
+	}
+
+	if let Some((canon_hash, canon_number)) = status.new_set_block {
+		// the authority set has changed.
+		let (new_id, set_ref) = authority_set.current();
+
+		if set_ref.len() > 16 {
+			info!("Applying GRANDPA set change to new set with {} authorities", set_ref.len());
+		} else {
+			info!("Applying GRANDPA set change to new set {:?}", set_ref);
+		}
+
+		Ok(Some(NewAuthoritySet {
+			canon_hash,
+			canon_number,
+			set_id: new_id,
+			authorities: set_ref.to_vec(),
+		}))
+	} else {
+		Ok(None)
 	}
 }
 
 /// A block-import handler for GRANDPA.
 ///
 /// This scans each imported block for signals of changing authority set.
+/// Blocks imported with a justification are checked against the current authority
+/// set and finalized right away.
 /// When using GRANDPA, the block import worker should be using this block import
 /// object.
 pub struct GrandpaBlockImport<B, E, Block: BlockT<Hash=H256>, RA, PRA> {
@@ -627,7 +734,7 @@ impl<B, E, Block: BlockT<Hash=H256>, RA, PRA> BlockImport<Block>
 		B: Backend<Block, Blake2Hasher> + 'static,
 		E: CallExecutor<Block, Blake2Hasher> + 'static + Clone + Send + Sync,
 		DigestFor<Block>: Encode,
-		RA: TaggedTransactionQueue<Block>,
+		RA: TaggedTransactionQueue<Block> + Send + Sync,
 		PRA: ProvideRuntimeApi,
 		PRA::Api: GrandpaApi<Block>
 {
@@ -638,6 +745,32 @@ impl<B, E, Block: BlockT<Hash=H256>, RA, PRA> BlockImport<Block>
 	{
 		use authorities::PendingChange;
 
+		let hash = block.post_header().hash();
+		let number = block.header.number().clone();
+
+		// a block with a valid justification is imported as finalized.
+		let justified = !block.justification.is_empty();
+		if justified {
+			let (set_id, voters) = {
+				let authorities = self.authority_set.inner().read();
+				let (set_id, voters) = authorities.current();
+				(set_id, voters.iter().cloned().collect::<HashMap<_, _>>())
+			};
+
+			let justification = GrandpaJustification::<Block>::decode_and_verify(
+				&block.justification,
+				set_id,
+				&voters,
+			)?;
+			if justification.target() != (hash, number) {
+				return Err(::client::error::ErrorKind::BadJustification(
+					"justification is for another block".into()
+				).into());
+			}
+
+			block.finalized = true;
+		}
+
 		let maybe_change = self.api.runtime_api().grandpa_pending_change(
 			&BlockId::hash(*block.header.parent_hash()),
 			&block.header.digest().clone(),
@@ -647,9 +780,6 @@ impl<B, E, Block: BlockT<Hash=H256>, RA, PRA> BlockImport<Block>
 		// until the block is written to prevent a race if we need to restore
 		// the old authority set on error.
 		let just_in_case = maybe_change.map(|change| {
-			let hash = block.post_header().hash();
-			let number = block.header.number().clone();
-
 			let mut authorities = self.authority_set.inner().write();
 			let old_set = authorities.clone();
 			authorities.add_pending_change(PendingChange {
@@ -663,15 +793,33 @@ impl<B, E, Block: BlockT<Hash=H256>, RA, PRA> BlockImport<Block>
 			(old_set, authorities)
 		});
 
-		let result = self.inner.import_block(block, new_authorities);
-		if let Err(ref e) = result {
-			if let Some((old_set, mut authorities)) = just_in_case {
-				debug!(target: "afg", "Restoring old set after block import error: {:?}", e);
-				*authorities = old_set;
+		let import_result = match self.inner.import_block(block, new_authorities) {
+			Ok(import_result) => import_result,
+			Err(e) => {
+				if let Some((old_set, mut authorities)) = just_in_case {
+					debug!(target: "afg", "Restoring old set after block import error: {:?}", e);
+					*authorities = old_set;
+				}
+				return Err(e);
+			}
+		};
+
+		// the lock must be released before applying finality to the authority set.
+		drop(just_in_case);
+
+		match import_result {
+			ImportResult::Queued if justified => {
+				// the block is already finalized by the client, but the authority set changes
+				// its finality enacts are not applied yet. a running voter picks up a new set
+				// when completing its current round.
+				if let Some(new_set) = finalize_block(&*self.inner, &self.authority_set, hash, number, None)? {
+					debug!(target: "afg", "Justification for block {:?} enacted new authority set {}", hash, new_set.set_id);
+				}
 			}
+			_ => {}
 		}
 
-		result
+		Ok(import_result)
 	}
 }
 
@@ -804,10 +952,13 @@ fn committer_communication<Block: BlockT<Hash=H256>, B, E, N, RA>(
 
 /// Run a GRANDPA voter as a task. Provide configuration and a link to a
 /// block import worker that has already been instantiated with `block_import`.
+/// Equivocations of other voters observed by the voter are passed to
+/// `equivocation_reporter`.
 pub fn run_grandpa<B, E, Block: BlockT<Hash=H256>, N, RA>(
 	config: Config,
 	link: LinkHalf<B, E, Block, RA>,
 	network: N,
+	equivocation_reporter: Arc<dyn EquivocationReporter<Block>>,
 ) -> ::client::error::Result<impl Future<Item=(),Error=()> + Send + 'static> where
 	Block::Hash: Ord,
 	B: Backend<Block, Blake2Hasher> + 'static,
@@ -844,6 +995,7 @@ pub fn run_grandpa<B, E, Block: BlockT<Hash=H256>, N, RA>(
 		network: network.clone(),
 		set_id: authority_set.set_id(),
 		authority_set: authority_set.clone(),
+		equivocation_reporter: equivocation_reporter.clone(),
 	});
 
 	let work = future::loop_fn((initial_environment, last_round_number, last_state), move |params| {
@@ -881,6 +1033,7 @@ pub fn run_grandpa<B, E, Block: BlockT<Hash=H256>, N, RA>(
 		let config = config.clone();
 		let network = network.clone();
 		let authority_set = authority_set.clone();
+		let equivocation_reporter = equivocation_reporter.clone();
 		future::Either::A(voter.then(move |res| match res {
 			// voters don't conclude naturally; this could reasonably be an error.
 			Ok(()) => Ok(FutureLoop::Break(())),
@@ -893,6 +1046,7 @@ pub fn run_grandpa<B, E, Block: BlockT<Hash=H256>, N, RA>(
 					set_id: new.set_id,
 					network,
 					authority_set,
+					equivocation_reporter,
 				});
 
 				// start the new authority set using the block where the
@@ -328,6 +328,11 @@ fn make_ids(keys: &[Keyring]) -> Vec<(AuthorityId, u64)> {
 		.collect()
 }
 
+fn make_local_key(key: Keyring) -> Option<Arc<dyn LocalKey>> {
+	let pair: Arc<ed25519::Pair> = Arc::new(key.into());
+	Some(Arc::new(pair))
+}
+
 #[test]
 fn finalize_3_voters_no_observers() {
 	let peers = &[Keyring::Alice, Keyring::Bob, Keyring::Charlie];
@@ -367,11 +372,12 @@ fn finalize_3_voters_no_observers() {
 		let voter = run_grandpa(
 			Config {
 				gossip_duration: TEST_GOSSIP_DURATION,
-				local_key: Some(Arc::new(key.clone().into())),
+				local_key: make_local_key(key.clone()),
 				name: Some(format!("peer#{}", peer_id)),
 			},
 			link,
 			MessageRouting::new(net.clone(), peer_id),
+			Arc::new(()),
 		).expect("all in order with client and network");
 
 		assert_send(&voter);
@@ -384,12 +390,23 @@ fn finalize_3_voters_no_observers() {
 		.map(|_| ())
 		.map_err(|_| ());
 
+	let net_for_routing = net.clone();
 	let drive_to_completion = ::tokio::timer::Interval::new_interval(TEST_ROUTING_INTERVAL)
-		.for_each(move |_| { net.lock().route_until_complete(); Ok(()) })
+		.for_each(move |_| { net_for_routing.lock().route_until_complete(); Ok(()) })
 		.map(|_| ())
 		.map_err(|_| ());
 
 	runtime.block_on(wait_for.select(drive_to_completion).map_err(|_| ())).unwrap();
+
+	// all peers should have stored a valid justification for the finalized block.
+	let voters: HashMap<_, _> = make_ids(peers).into_iter().collect();
+	for i in 0..3 {
+		let justification = net.lock().peer(i).client().justification(&BlockId::Number(20)).unwrap()
+			.expect("finalized block has a justification");
+		let justification = GrandpaJustification::<Block>::decode_and_verify(&justification, 0, &voters)
+			.expect("justification is valid");
+		assert_eq!(justification.target().1, 20);
+	}
 }
 
 #[test]
@@ -407,7 +424,7 @@ fn finalize_3_voters_1_observer() {
 	let mut runtime = current_thread::Runtime::new().unwrap();
 	let all_peers = peers.iter()
 		.cloned()
-		.map(|key| Some(Arc::new(key.into())))
+		.map(make_local_key)
 		.chain(::std::iter::once(None));
 
 	for (peer_id, local_key) in all_peers.enumerate() {
@@ -432,6 +449,7 @@ fn finalize_3_voters_1_observer() {
 			},
 			link,
 			MessageRouting::new(net.clone(), peer_id),
+			Arc::new(()),
 		).expect("all in order with client and network");
 
 		runtime.spawn(voter);
@@ -536,7 +554,7 @@ fn transition_3_voters_twice_1_observer() {
 		.cloned()
 		.collect::<HashSet<_>>() // deduplicate
 		.into_iter()
-		.map(|key| Some(Arc::new(key.into())))
+		.map(make_local_key)
 		.enumerate();
 
 	for (peer_id, local_key) in all_peers {
@@ -568,6 +586,7 @@ fn transition_3_voters_twice_1_observer() {
 			},
 			link,
 			MessageRouting::new(net.clone(), peer_id),
+			Arc::new(()),
 		).expect("all in order with client and network");
 
 		runtime.spawn(voter);
@@ -585,3 +604,81 @@ fn transition_3_voters_twice_1_observer() {
 
 	runtime.block_on(wait_for.select(drive_to_completion).map_err(|_| ())).unwrap();
 }
+
+#[test]
+fn gossiped_votes_are_signed_over_the_localized_vote_payload() {
+	use fg_primitives::localized_vote_payload;
+
+	let peers = &[Keyring::Alice];
+	let voters = make_ids(peers);
+	let net = Arc::new(Mutex::new(GrandpaTestNet::new(TestApi::new(voters.clone()), 1)));
+
+	let (round, set_id) = (5, 2);
+	let pair: Arc<ed25519::Pair> = Arc::new(Keyring::Alice.into());
+	let (signed, mut outgoing) = ::communication::outgoing_messages::<Block, _>(
+		round,
+		set_id,
+		Some(pair.clone()),
+		Arc::new(voters.into_iter().collect()),
+		MessageRouting::new(net, 0),
+	);
+
+	let target_hash = Hash::from([1; 32]);
+	let votes = vec![
+		(VoteStage::Prevote, ::grandpa::Message::Prevote(::grandpa::Prevote { target_hash, target_number: 7 })),
+		(VoteStage::Precommit, ::grandpa::Message::Precommit(::grandpa::Precommit { target_hash, target_number: 7 })),
+	];
+	for &(_, ref message) in &votes {
+		outgoing.start_send(message.clone()).unwrap();
+	}
+
+	let signed: Vec<SignedMessage<Block>> = signed.take(2).collect().wait().unwrap();
+	for ((stage, message), signed) in votes.into_iter().zip(signed) {
+		assert_eq!(signed.message, message);
+		let payload = localized_vote_payload(stage, &target_hash, &7u64, round, set_id);
+		assert!(ed25519::verify_strong(&signed.signature, &payload, pair.public()));
+	}
+}
+
+#[test]
+fn importing_block_with_justification_finalizes_it() {
+	use fg_primitives::localized_vote_payload;
+
+	let peers = &[Keyring::Alice];
+	let voters = make_ids(peers);
+	let client = Arc::new(test_client::new());
+	let (block_import, _link) = block_import(client.clone(), Arc::new(TestApi::new(voters))).unwrap();
+
+	let block = client.new_block().unwrap().bake().unwrap();
+	let (target_hash, target_number) = (block.header.hash(), *block.header.number());
+
+	let round = 1;
+	let precommit = ::grandpa::Precommit { target_hash, target_number };
+	let payload = localized_vote_payload(VoteStage::Precommit, &target_hash, &target_number, round, 0);
+	let pair: ed25519::Pair = Keyring::Alice.into();
+	let justification = GrandpaJustification::<Block> {
+		round,
+		commit: ::grandpa::CompactCommit {
+			target_hash,
+			target_number,
+			precommits: vec![precommit],
+			auth_data: vec![(pair.sign(&payload), AuthorityId(Keyring::Alice.to_raw_public()))],
+		},
+		votes_ancestries: Vec::new(),
+	}.encode();
+
+	let import = ImportBlock {
+		origin: BlockOrigin::NetworkBroadcast,
+		header: block.header,
+		justification: justification.clone(),
+		post_digests: Vec::new(),
+		body: Some(block.extrinsics),
+		finalized: false,
+		auxiliary: Vec::new(),
+	};
+	block_import.import_block(import, None).unwrap();
+
+	let info = client.info().unwrap().chain;
+	assert_eq!((info.finalized_hash, info.finalized_number), (target_hash, target_number));
+	assert_eq!(client.justification(&BlockId::Hash(target_hash)).unwrap(), Some(justification));
+}
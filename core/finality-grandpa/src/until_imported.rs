@@ -433,6 +433,8 @@ mod tests {
 				header,
 				is_new_best: false,
 				tags: Vec::new(),
+				retracted: Vec::new(),
+				enacted: Vec::new(),
 			}).unwrap();
 		}
 	}
@@ -32,12 +32,13 @@ extern crate error_chain;
 #[cfg(test)]
 extern crate tempdir;
 
-use std::collections::HashMap;
+use std::fmt;
 use std::path::PathBuf;
+use std::str::FromStr;
 use std::fs::{self, File};
-use std::io::{self, Write};
+use std::io::{self, Read, Write};
 
-use substrate_primitives::{hashing::blake2_256, ed25519::{Pair, Public, PKCS_LEN}};
+use substrate_primitives::{hashing::blake2_256, crypto::KeyPair, ed25519::{Pair, Public, PKCS_LEN}};
 
 pub use crypto::KEY_ITERATIONS;
 
@@ -56,12 +57,82 @@ error_chain! {
 			description("Invalid PKCS#8 data"),
 			display("Invalid PKCS#8 data"),
 		}
+		InvalidSeed {
+			description("Invalid seed"),
+			display("Invalid seed"),
+		}
+		InvalidSecretUri(e: substrate_primitives::crypto::SecretStringError) {
+			description("Invalid secret URI"),
+			display("Invalid secret URI: {:?}", e),
+		}
+		UnknownKeyType(key_type: String) {
+			description("Unknown key type"),
+			display("Unknown key type: {}", key_type),
+		}
 	}
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct InvalidPassword;
 
+/// The purpose of a key.
+///
+/// Keys of different types are stored separately, so that e.g. rotating session keys
+/// never touches account keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyType {
+	/// Keys used for block authoring.
+	Session,
+	/// Keys controlling accounts.
+	Account,
+	/// Keys used for GRANDPA finality voting.
+	Grandpa,
+}
+
+impl KeyType {
+	/// All key types.
+	pub const ALL: [KeyType; 3] = [KeyType::Session, KeyType::Account, KeyType::Grandpa];
+
+	/// Key types generated when session keys are rotated.
+	pub const SESSION_KEYS: [KeyType; 2] = [KeyType::Session, KeyType::Grandpa];
+
+	/// The name of the key type.
+	pub fn as_str(&self) -> &'static str {
+		match *self {
+			KeyType::Session => "session",
+			KeyType::Account => "account",
+			KeyType::Grandpa => "grandpa",
+		}
+	}
+
+	/// The directory the keys of this type are stored in, relative to the keystore root.
+	///
+	/// Session keys are kept in the root directory, where all keys were stored before keys got typed.
+	fn directory(&self) -> Option<&'static str> {
+		match *self {
+			KeyType::Session => None,
+			_ => Some(self.as_str()),
+		}
+	}
+}
+
+impl FromStr for KeyType {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self> {
+		KeyType::ALL.iter()
+			.find(|key_type| key_type.as_str() == s)
+			.cloned()
+			.ok_or_else(|| ErrorKind::UnknownKeyType(s.into()).into())
+	}
+}
+
+impl fmt::Display for KeyType {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}", self.as_str())
+	}
+}
+
 #[derive(Serialize, Deserialize)]
 struct EncryptedKey {
 	mac: [u8; 32],
@@ -72,7 +143,7 @@ struct EncryptedKey {
 }
 
 impl EncryptedKey {
-	fn encrypt(plain: &[u8; PKCS_LEN], password: &str, iterations: u32) -> Self {
+	fn encrypt(plain: &[u8], password: &str, iterations: u32) -> Self {
 		use rand::{Rng, OsRng};
 
 		let mut rng = OsRng::new().expect("OS Randomness available on all supported platforms; qed");
@@ -86,7 +157,7 @@ impl EncryptedKey {
 
 		// preallocated (on-stack in case of `Secret`) buffer to hold cipher
 		// length = length(plain) as we are using CTR-approach
-		let mut ciphertext = vec![0; PKCS_LEN];
+		let mut ciphertext = vec![0; plain.len()];
 
 		// aes-128-ctr with initial vector of iv
 		crypto::aes::encrypt_128_ctr(&derived_left_bits, &iv, plain, &mut *ciphertext)
@@ -104,7 +175,7 @@ impl EncryptedKey {
 		}
 	}
 
-	fn decrypt(&self, password: &str) -> Result<[u8; PKCS_LEN]> {
+	fn decrypt(&self, password: &str) -> Result<Vec<u8>> {
 		let (derived_left_bits, derived_right_bits) =
 			crypto::derive_key_iterations(password.as_bytes(), &self.salt, self.iterations);
 
@@ -114,7 +185,7 @@ impl EncryptedKey {
 			return Err(ErrorKind::InvalidPassword.into());
 		}
 
-		let mut plain = [0; PKCS_LEN];
+		let mut plain = vec![0; self.ciphertext.len()];
 		crypto::aes::decrypt_128_ctr(&derived_left_bits, &self.iv, &self.ciphertext, &mut plain[..])
 			.expect("input lengths of key and iv are both 16; qed");
 		Ok(plain)
@@ -123,10 +194,13 @@ impl EncryptedKey {
 
 type Seed = [u8; 32];
 
+/// Name of the file recording the current key of a key type.
+const CURRENT_KEY_FILE: &str = "current";
+
 /// Key store.
 pub struct Store {
 	path: PathBuf,
-	additional: HashMap<Public, Seed>,
+	additional: Vec<(Public, Seed)>,
 }
 
 pub fn pad_seed(seed:  &str) -> Seed {
@@ -151,20 +225,48 @@ impl Store {
 	/// Create a new store at the given path.
 	pub fn open(path: PathBuf) -> Result<Self> {
 		fs::create_dir_all(&path)?;
-		Ok(Store { path, additional: HashMap::new() })
+		Ok(Store { path, additional: Vec::new() })
 	}
 
-	/// Generate a new key, placing it into the store.
+	/// Generate a new session key, placing it into the store.
 	pub fn generate(&self, password: &str) -> Result<Pair> {
+		self.generate_by_type(KeyType::Session, password)
+	}
+
+	/// Generate a new key of given type, placing it into the store.
+	///
+	/// The new key becomes the current key of its type.
+	pub fn generate_by_type(&self, key_type: KeyType, password: &str) -> Result<Pair> {
 		let (pair, pkcs_bytes) = Pair::generate_with_pkcs8();
-		let key_file = EncryptedKey::encrypt(&pkcs_bytes, password, KEY_ITERATIONS as u32);
+		self.write_key(key_type, &pair.public(), &pkcs_bytes[..], password)?;
+		Ok(pair)
+	}
+
+	/// Place the key of given secret URI into the store, e.g. `"<phrase>//alice"`.
+	///
+	/// The key becomes the current key of its type.
+	/// See `substrate_primitives::crypto::SecretUri` for the format.
+	pub fn insert_by_type(&self, key_type: KeyType, suri: &str, password: &str) -> Result<Pair> {
+		let pair = Pair::from_string(suri, None).map_err(ErrorKind::InvalidSecretUri)?;
+		let seed = *pair.seed().expect("pairs restored from a secret URI always know their seed; qed");
+		self.write_key(key_type, &pair.public(), &seed[..], password)?;
+		Ok(pair)
+	}
+
+	fn write_key(&self, key_type: KeyType, public: &Public, secret: &[u8], password: &str) -> Result<()> {
+		let key_file = EncryptedKey::encrypt(secret, password, KEY_ITERATIONS as u32);
 
-		let mut file = File::create(self.key_file_path(&pair.public()))?;
+		fs::create_dir_all(self.key_type_path(key_type))?;
+		let mut file = File::create(self.key_file_path(key_type, public))?;
 		::serde_json::to_writer(&file, &key_file)?;
 
 		file.flush()?;
 
-		Ok(pair)
+		let mut current = File::create(self.key_type_path(key_type).join(CURRENT_KEY_FILE))?;
+		current.write_all(hex::encode(public.as_slice()).as_bytes())?;
+		current.flush()?;
+
+		Ok(())
 	}
 
 	/// Create a new key from seed. Do not place it into the store.
@@ -173,29 +275,83 @@ impl Store {
 	pub fn generate_from_seed(&mut self, seed: &str) -> Result<Pair> {
 		let padded_seed = pad_seed(seed);
 		let pair = Pair::from_seed(&padded_seed);
-		self.additional.insert(pair.public(), padded_seed);
+		self.additional.push((pair.public(), padded_seed));
 		Ok(pair)
 	}
 
-	/// Load a key file with given public key.
+	/// Load a session key file with given public key.
 	pub fn load(&self, public: &Public, password: &str) -> Result<Pair> {
-		if let Some(ref seed) = self.additional.get(public) {
-			let pair = Pair::from_seed(seed);
-			return Ok(pair);
+		self.load_by_type(KeyType::Session, public, password)
+	}
+
+	/// Load a key file of given type with given public key.
+	///
+	/// Key files either contain a PKCS#8 document or, for keys inserted from a secret URI, the seed.
+	pub fn load_by_type(&self, key_type: KeyType, public: &Public, password: &str) -> Result<Pair> {
+		if key_type == KeyType::Session {
+			if let Some(&(_, ref seed)) = self.additional.iter().find(|&&(ref p, _)| p == public) {
+				let pair = Pair::from_seed(seed);
+				return Ok(pair);
+			}
 		}
-		let path = self.key_file_path(public);
+		let path = self.key_file_path(key_type, public);
 		let file = File::open(path)?;
 
 		let encrypted_key: EncryptedKey = ::serde_json::from_reader(&file)?;
-		let pkcs_bytes = encrypted_key.decrypt(password)?;
-
-		Pair::from_pkcs8(&pkcs_bytes[..]).map_err(|_| ErrorKind::InvalidPKCS8.into())
+		let secret = encrypted_key.decrypt(password)?;
+
+		match secret.len() {
+			PKCS_LEN => Pair::from_pkcs8(&secret[..]).map_err(|_| ErrorKind::InvalidPKCS8.into()),
+			32 => {
+				let mut seed = [0u8; 32];
+				seed.copy_from_slice(&secret[..]);
+				Ok(Pair::from_seed(&seed))
+			},
+			_ => Err(ErrorKind::InvalidSeed.into()),
+		}
 	}
 
-	/// Get public keys of all stored keys.
+	/// Get public keys of all stored session keys.
 	pub fn contents(&self) -> Result<Vec<Public>> {
-		let mut public_keys: Vec<Public> = self.additional.keys().cloned().collect();
-		for entry in fs::read_dir(&self.path)? {
+		self.contents_by_type(KeyType::Session)
+	}
+
+	/// Get the public key of the current key of given type, if there is any key of that type.
+	///
+	/// The current key is the one generated or inserted last. Session keys given as additional
+	/// seeds take precedence over the stored ones. If the record of the current key is missing,
+	/// the stored key with the lowest public key is used, so that the choice is deterministic.
+	pub fn current_by_type(&self, key_type: KeyType) -> Result<Option<Public>> {
+		if key_type == KeyType::Session {
+			if let Some(&(ref public, _)) = self.additional.first() {
+				return Ok(Some(public.clone()));
+			}
+		}
+
+		let mut current = String::new();
+		match File::open(self.key_type_path(key_type).join(CURRENT_KEY_FILE)) {
+			Ok(mut file) => { file.read_to_string(&mut current)?; },
+			Err(ref e) if e.kind() == io::ErrorKind::NotFound => {},
+			Err(e) => return Err(e.into()),
+		}
+
+		let stored = self.contents_by_type(key_type)?;
+		let current = hex::decode(current.trim()).ok()
+			.and_then(|current| stored.iter().find(|public| public.as_slice() == &current[..]).cloned());
+		Ok(current.or_else(|| stored.into_iter().min_by(|a, b| a.as_slice().cmp(b.as_slice()))))
+	}
+
+	/// Get public keys of all stored keys of given type.
+	pub fn contents_by_type(&self, key_type: KeyType) -> Result<Vec<Public>> {
+		let mut public_keys: Vec<Public> = match key_type {
+			KeyType::Session => self.additional.iter().map(|&(ref public, _)| public.clone()).collect(),
+			_ => Vec::new(),
+		};
+		let path = self.key_type_path(key_type);
+		if !path.exists() {
+			return Ok(public_keys);
+		}
+		for entry in fs::read_dir(&path)? {
 			let entry = entry?;
 			let path = entry.path();
 
@@ -218,8 +374,16 @@ impl Store {
 		Ok(public_keys)
 	}
 
-	fn key_file_path(&self, public: &Public) -> PathBuf {
+	fn key_type_path(&self, key_type: KeyType) -> PathBuf {
 		let mut buf = self.path.clone();
+		if let Some(directory) = key_type.directory() {
+			buf.push(directory);
+		}
+		buf
+	}
+
+	fn key_file_path(&self, key_type: KeyType, public: &Public) -> PathBuf {
+		let mut buf = self.key_type_path(key_type);
 		buf.push(hex::encode(public.as_slice()));
 		buf
 	}
@@ -229,6 +393,7 @@ impl Store {
 mod tests {
 	use super::*;
 	use tempdir::TempDir;
+	use substrate_primitives::crypto::Ss58Codec;
 
 	#[test]
 	fn encrypt_and_decrypt() {
@@ -275,6 +440,68 @@ mod tests {
 		assert_eq!(store.contents().unwrap()[0], key.public());
 	}
 
+	#[test]
+	fn typed_keys_are_stored_separately() {
+		let temp_dir = TempDir::new("keystore").unwrap();
+		let store = Store::open(temp_dir.path().to_owned()).unwrap();
+
+		let session = store.generate_by_type(KeyType::Session, "").unwrap();
+		let grandpa = store.generate_by_type(KeyType::Grandpa, "").unwrap();
+
+		assert_eq!(store.contents_by_type(KeyType::Session).unwrap(), vec![session.public()]);
+		assert_eq!(store.contents_by_type(KeyType::Grandpa).unwrap(), vec![grandpa.public()]);
+		assert!(store.contents_by_type(KeyType::Account).unwrap().is_empty());
+		assert!(store.load_by_type(KeyType::Account, &grandpa.public(), "").is_err());
+		assert_eq!(store.load_by_type(KeyType::Grandpa, &grandpa.public(), "").unwrap().public(), grandpa.public());
+	}
+
+	#[test]
+	fn current_key_is_the_last_one_added() {
+		let temp_dir = TempDir::new("keystore").unwrap();
+		let mut store = Store::open(temp_dir.path().to_owned()).unwrap();
+
+		assert_eq!(store.current_by_type(KeyType::Grandpa).unwrap(), None);
+
+		let first = store.generate_by_type(KeyType::Grandpa, "").unwrap();
+		assert_eq!(store.current_by_type(KeyType::Grandpa).unwrap(), Some(first.public()));
+		let second = store.generate_by_type(KeyType::Grandpa, "").unwrap();
+		assert_eq!(store.current_by_type(KeyType::Grandpa).unwrap(), Some(second.public()));
+		assert_eq!(store.contents_by_type(KeyType::Grandpa).unwrap().len(), 2);
+
+		// without a record of the current key the choice is still deterministic.
+		fs::remove_file(temp_dir.path().join("grandpa").join(CURRENT_KEY_FILE)).unwrap();
+		let lowest = if first.public().as_slice() < second.public().as_slice() { first } else { second };
+		assert_eq!(store.current_by_type(KeyType::Grandpa).unwrap(), Some(lowest.public()));
+
+		// additional seeds take precedence for session keys.
+		let session = store.generate_by_type(KeyType::Session, "").unwrap();
+		assert_eq!(store.current_by_type(KeyType::Session).unwrap(), Some(session.public()));
+		let alice = store.generate_from_seed("Alice").unwrap();
+		assert_eq!(store.current_by_type(KeyType::Session).unwrap(), Some(alice.public()));
+	}
+
+	#[test]
+	fn insert_from_secret_uri() {
+		let temp_dir = TempDir::new("keystore").unwrap();
+		let store = Store::open(temp_dir.path().to_owned()).unwrap();
+		let suri = "bottom drive obey lake curtain smoke basket hold race lonely fit walk//alice";
+
+		let pair = store.insert_by_type(KeyType::Account, suri, "thepassword").unwrap();
+		assert_eq!(pair.public(), Pair::from_string(suri, None).unwrap().public());
+
+		let loaded = store.load_by_type(KeyType::Account, &pair.public(), "thepassword").unwrap();
+		assert_eq!(loaded.public(), pair.public());
+		assert!(store.load_by_type(KeyType::Account, &pair.public(), "notthepassword").is_err());
+
+		assert!(store.insert_by_type(KeyType::Account, "not a phrase", "").is_err());
+	}
+
+	#[test]
+	fn key_type_from_str() {
+		assert_eq!("grandpa".parse::<KeyType>().unwrap(), KeyType::Grandpa);
+		assert!("babe".parse::<KeyType>().is_err());
+	}
+
 	#[test]
 	fn test_generate_from_seed() {
 		let temp_dir = TempDir::new("keystore").unwrap();
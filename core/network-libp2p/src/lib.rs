@@ -43,6 +43,7 @@ extern crate assert_matches;
 
 mod custom_proto;
 mod error;
+mod network_state;
 mod node_handler;
 mod secret;
 mod service_task;
@@ -53,6 +54,7 @@ mod transport;
 
 pub use custom_proto::RegisteredProtocol;
 pub use error::{Error, ErrorKind, DisconnectReason};
+pub use network_state::{NetworkState, ConnectedPeer, PeerEndpoint, KnownPeer, KnownAddress};
 pub use libp2p::{Multiaddr, multiaddr::Protocol, PeerId};
 pub use service_task::{start_service, Service, ServiceEvent};
 pub use traits::{NetworkConfiguration, NodeIndex, NodeId, NonReservedPeerMode};
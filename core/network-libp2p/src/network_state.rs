@@ -0,0 +1,82 @@
+// Copyright 2018 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Information about the networking, for diagnostic purposes.
+//!
+//! **Warning**: These APIs are not stable.
+
+/// Snapshot of the state of the network service.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkState {
+	/// PeerId of the local node, in base58.
+	pub peer_id: String,
+	/// List of addresses the node is currently listening on.
+	pub listened_addresses: Vec<String>,
+	/// Nodes we're currently connected to.
+	pub connected_peers: Vec<ConnectedPeer>,
+	/// Peers known in the topology of the network, along with their addresses.
+	pub known_peers: Vec<KnownPeer>,
+}
+
+/// A node we're currently connected to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectedPeer {
+	/// Index of the node in the network service.
+	pub node_index: usize,
+	/// PeerId of the node, in base58. `None` if not known yet.
+	pub peer_id: Option<String>,
+	/// How we're connected to the node.
+	pub endpoint: PeerEndpoint,
+}
+
+/// How we're connected to a node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PeerEndpoint {
+	/// We dialed the node at the given address.
+	Dialing(String),
+	/// The node dialed us at our given address and can be reached back at the second one.
+	Listening {
+		/// Our address the node connected to.
+		listen_addr: String,
+		/// Address of the node.
+		send_back_addr: String,
+	},
+}
+
+/// A peer known in the topology of the network.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnownPeer {
+	/// PeerId of the node, in base58.
+	pub peer_id: String,
+	/// Known addresses of the node.
+	pub addresses: Vec<KnownAddress>,
+}
+
+/// An address of a peer known in the topology of the network.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnownAddress {
+	/// The multiaddress.
+	pub addr: String,
+	/// Score of the address. The higher, the more likely we are to connect to it.
+	pub score: u32,
+	/// True if we're currently connected through this address.
+	pub connected: bool,
+}
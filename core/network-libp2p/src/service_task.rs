@@ -35,6 +35,7 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 use swarm::{self, Swarm, SwarmEvent};
 use topology::{DisconnectReason, NetTopology};
+use network_state::{NetworkState, ConnectedPeer, PeerEndpoint, KnownPeer, KnownAddress};
 use tokio_timer::{Delay, Interval};
 use {Error, ErrorKind, NetworkConfiguration, NodeIndex, parse_str_addr};
 use {NonReservedPeerMode, ProtocolId};
@@ -355,6 +356,48 @@ impl Service {
 		to_disconnect
 	}
 
+	/// Returns a snapshot of the state of the network, for diagnostic purposes.
+	pub fn state(&self) -> NetworkState {
+		let connected_peers = self.nodes_addresses
+			.iter()
+			.map(|(&node_index, endpoint)| ConnectedPeer {
+				node_index,
+				peer_id: self.peer_id_of_node(node_index).map(|peer_id| peer_id.to_base58()),
+				endpoint: match *endpoint {
+					ConnectedPoint::Dialer { ref address } =>
+						PeerEndpoint::Dialing(address.to_string()),
+					ConnectedPoint::Listener { ref listen_addr, ref send_back_addr } =>
+						PeerEndpoint::Listening {
+							listen_addr: listen_addr.to_string(),
+							send_back_addr: send_back_addr.to_string(),
+						},
+				},
+			})
+			.collect();
+
+		let known_peers = self.topology
+			.known_peers()
+			.into_iter()
+			.map(|(peer_id, addrs)| KnownPeer {
+				peer_id: peer_id.to_base58(),
+				addresses: addrs.into_iter()
+					.map(|(addr, score, connected)| KnownAddress {
+						addr: addr.to_string(),
+						score,
+						connected,
+					})
+					.collect(),
+			})
+			.collect();
+
+		NetworkState {
+			peer_id: self.peer_id().to_base58(),
+			listened_addresses: self.listeners().map(|addr| addr.to_string()).collect(),
+			connected_peers,
+			known_peers,
+		}
+	}
+
 	/// Returns the `PeerId` of a node.
 	#[inline]
 	pub fn peer_id_of_node(&self, node_index: NodeIndex) -> Option<&PeerId> {
@@ -134,6 +134,24 @@ impl NetTopology {
 		});
 	}
 
+	/// Returns all the peers in the topology, along with their addresses, the scores of these
+	/// addresses, and whether we're connected to them.
+	pub fn known_peers(&self) -> Vec<(&PeerId, Vec<(&Multiaddr, u32, bool)>)> {
+		self.store
+			.iter()
+			.map(|(peer, info)| {
+				let addrs = info.addrs
+					.iter()
+					.map(|addr| {
+						let (score, is_connected) = addr.score_and_is_connected();
+						(&addr.addr, score, is_connected)
+					})
+					.collect();
+				(peer, addrs)
+			})
+			.collect()
+	}
+
 	/// Returns the known potential addresses of a peer, ordered by score. Excludes backed-off
 	/// addresses.
 	///
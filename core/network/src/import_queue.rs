@@ -23,10 +23,16 @@
 //! The `ImportQueue` trait allows such verification strategies to be instantiated.
 //! The `BasicQueue` and `BasicVerifier` traits allow serial queues to be
 //! instantiated simply.
+//!
+//! Blocks which the verifier can't check yet, e.g. because they were authored in a
+//! slot that hasn't started according to the local clock, are held back by the
+//! `BasicQueue` and retried once they can be verified, as long as they are within
+//! the maximum drift of the queue.
 
 use std::collections::{HashSet, VecDeque};
 use std::sync::{Arc, Weak};
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 use parking_lot::{Condvar, Mutex, RwLock};
 use network_libp2p::{NodeIndex, Severity};
 use primitives::AuthorityId;
@@ -45,6 +51,12 @@ pub use consensus::{ImportBlock, BlockImport, ImportResult, BlockOrigin};
 /// Shared block import struct used by the queue.
 pub type SharedBlockImport<B> = Arc<dyn BlockImport<B,Error=ClientError> + Send + Sync>;
 
+/// How far in the future blocks may be to be held back by the `BasicQueue` by default.
+pub const DEFAULT_MAX_DRIFT: Duration = Duration::from_secs(30);
+
+/// Maximum number of blocks held back by the `BasicQueue` until they can be verified.
+const MAX_DEFERRED_BLOCKS: usize = 128;
+
 #[cfg(any(test, feature = "test-helpers"))]
 use std::cell::RefCell;
 
@@ -60,6 +72,13 @@ pub trait Verifier<B: BlockT>: Send + Sync + Sized {
 		justification: Vec<u8>,
 		body: Option<Vec<B::Extrinsic>>
 	) -> Result<(ImportBlock<B>, Option<Vec<AuthorityId>>), String>;
+
+	/// How long to wait before the block with the given header can be verified, e.g.
+	/// because it was authored in the future according to the local clock.
+	/// `None` if it can be verified right away.
+	fn verification_delay(&self, _header: &B::Header) -> Option<Duration> {
+		None
+	}
 }
 
 /// Blocks import queue API.
@@ -103,35 +122,83 @@ pub struct BasicQueue<B: BlockT, V: 'static + Verifier<B>> {
 	block_import: SharedBlockImport<B>,
 }
 
-/// Locks order: queue, queue_blocks, best_importing_number
+/// Locks order: queue, deferred, queue_blocks, best_importing_number
 struct AsyncImportQueueData<B: BlockT> {
 	signal: Condvar,
 	queue: Mutex<VecDeque<(BlockOrigin, Vec<BlockData<B>>)>>,
+	/// Blocks held back until they can be verified, along with the time to retry them.
+	deferred: Mutex<Vec<(Instant, BlockOrigin, Vec<BlockData<B>>)>>,
 	queue_blocks: RwLock<HashSet<B::Hash>>,
 	best_importing_number: RwLock<<<B as BlockT>::Header as HeaderT>::Number>,
 	is_stopping: AtomicBool,
+	max_drift: Duration,
 }
 
 impl<B: BlockT, V: Verifier<B>> BasicQueue<B, V> {
 	/// Instantiate a new basic queue, with given verifier.
 	pub fn new(verifier: Arc<V>, block_import: SharedBlockImport<B>) -> Self {
+		Self::with_max_drift(verifier, block_import, DEFAULT_MAX_DRIFT)
+	}
+
+	/// Instantiate a new basic queue, with given verifier, which holds back blocks that
+	/// can be verified within `max_drift` and rejects blocks further in the future.
+	pub fn with_max_drift(verifier: Arc<V>, block_import: SharedBlockImport<B>, max_drift: Duration) -> Self {
 		Self {
 			handle: Mutex::new(None),
-			data: Arc::new(AsyncImportQueueData::new()),
+			data: Arc::new(AsyncImportQueueData::new(max_drift)),
 			verifier,
 			block_import,
 		}
 	}
+
+	/// The verifier blocks are checked with.
+	pub fn verifier(&self) -> &Arc<V> {
+		&self.verifier
+	}
 }
 
 impl<B: BlockT> AsyncImportQueueData<B> {
-	fn new() -> Self {
+	fn new(max_drift: Duration) -> Self {
 		Self {
 			signal: Default::default(),
 			queue: Mutex::new(VecDeque::new()),
+			deferred: Mutex::new(Vec::new()),
 			queue_blocks: RwLock::new(HashSet::new()),
 			best_importing_number: RwLock::new(Zero::zero()),
 			is_stopping: Default::default(),
+			max_drift,
+		}
+	}
+
+	/// The time to retry the given block at, if it is held back.
+	fn deferred_until(&self, hash: &B::Hash) -> Option<Instant> {
+		self.deferred.lock().iter()
+			.find(|&&(_, _, ref blocks)| blocks.iter().any(|b| &b.block.hash == hash))
+			.map(|&(at, _, _)| at)
+	}
+
+	/// Hold back blocks until the given time. Returns false if there are too many
+	/// blocks held back already.
+	fn defer(&self, at: Instant, origin: BlockOrigin, blocks: Vec<BlockData<B>>) -> bool {
+		let mut deferred = self.deferred.lock();
+		let count: usize = deferred.iter().map(|&(_, _, ref blocks)| blocks.len()).sum();
+		if count + blocks.len() > MAX_DEFERRED_BLOCKS {
+			return false;
+		}
+
+		deferred.push((at, origin, blocks));
+		true
+	}
+
+	/// Move the held back blocks which are due at `now` to the front of the queue.
+	fn requeue_deferred(&self, queue: &mut VecDeque<(BlockOrigin, Vec<BlockData<B>>)>, now: Instant) {
+		let mut deferred = self.deferred.lock();
+		let (ready, pending): (Vec<_>, Vec<_>) = deferred.drain(..).partition(|&(at, _, _)| at <= now);
+		*deferred = pending;
+
+		// blocks are deferred after their ancestors, so keep that order.
+		for (_, origin, blocks) in ready.into_iter().rev() {
+			queue.push_front((origin, blocks));
 		}
 	}
 }
@@ -154,9 +221,11 @@ impl<B: BlockT, V: 'static + Verifier<B>> ImportQueue<B> for BasicQueue<B, V> {
 
 	fn clear(&self) {
 		let mut queue = self.data.queue.lock();
+		let mut deferred = self.data.deferred.lock();
 		let mut queue_blocks = self.data.queue_blocks.write();
 		let mut best_importing_number = self.data.best_importing_number.write();
 		queue_blocks.clear();
+		deferred.clear();
 		queue.clear();
 		*best_importing_number = Zero::zero();
 	}
@@ -231,12 +300,18 @@ fn import_thread<B: BlockT, L: Link<B>, V: Verifier<B>>(
 				break;
 			}
 			if queue_lock.is_empty() {
-				qdata.signal.wait(&mut queue_lock);
+				let next_deferred = qdata.deferred.lock().iter().map(|&(at, _, _)| at).min();
+				match next_deferred {
+					Some(at) => { qdata.signal.wait_until(&mut queue_lock, at); },
+					None => qdata.signal.wait(&mut queue_lock),
+				}
 			}
 
+			qdata.requeue_deferred(&mut *queue_lock, Instant::now());
 			match queue_lock.pop_front() {
 				Some(new_blocks) => new_blocks,
-				None => break,
+				None if qdata.deferred.lock().is_empty() => break,
+				None => continue,
 			}
 		};
 
@@ -251,9 +326,15 @@ fn import_thread<B: BlockT, L: Link<B>, V: Verifier<B>>(
 			break;
 		}
 
+		// held back blocks are still being imported.
+		let deferred = qdata.deferred.lock();
 		let mut queue_blocks = qdata.queue_blocks.write();
 		for blocks_hash in blocks_hashes {
-			queue_blocks.remove(&blocks_hash);
+			let is_deferred = deferred.iter()
+				.any(|&(_, _, ref blocks)| blocks.iter().any(|b| b.block.hash == blocks_hash));
+			if !is_deferred {
+				queue_blocks.remove(&blocks_hash);
+			}
 		}
 	}
 
@@ -375,7 +456,23 @@ fn import_many_blocks<'a, B: BlockT, V: Verifier<B>>(
 	trace!(target:"sync", "Starting import of {} blocks {}", count, blocks_range);
 
 	// Blocks in the response/drain should be in ascending order.
-	for block in blocks {
+	let mut blocks = blocks.into_iter();
+	while let Some(block) = blocks.next() {
+		if let Some(qdata) = qdata {
+			if let Some(at) = deferral_time(qdata, &block, &*verifier) {
+				// descendants can't be imported before the block either.
+				let deferred: Vec<_> = ::std::iter::once(block).chain(blocks.by_ref()).collect();
+				trace!(target: "sync", "Deferring import of {} blocks", deferred.len());
+				if !qdata.defer(at, blocks_origin.clone(), deferred) {
+					// the dropped blocks have to be downloaded again.
+					debug!(target: "sync", "Too many blocks deferred, dropping");
+					*qdata.best_importing_number.write() = Zero::zero();
+					link.restart();
+				}
+				break;
+			}
+		}
+
 		let import_result = import_single_block(
 			import_handle,
 			blocks_origin.clone(),
@@ -399,6 +496,26 @@ fn import_many_blocks<'a, B: BlockT, V: Verifier<B>>(
 	true
 }
 
+/// The time to retry the import of a block at, if the verifier can't verify it yet
+/// or its parent is held back. Blocks further in the future than the maximum drift
+/// are not held back, so that they fail verification.
+fn deferral_time<B: BlockT, V: Verifier<B>>(
+	qdata: &AsyncImportQueueData<B>,
+	block: &BlockData<B>,
+	verifier: &V,
+) -> Option<Instant> {
+	let header = block.block.header.as_ref()?;
+	let parent_deferred_until = qdata.deferred_until(header.parent_hash());
+	let verifiable_at = verifier.verification_delay(header)
+		.filter(|delay| *delay <= qdata.max_drift)
+		.map(|delay| Instant::now() + delay);
+
+	match (parent_deferred_until, verifiable_at) {
+		(Some(a), Some(b)) => Some(::std::cmp::max(a, b)),
+		(a, b) => a.or(b),
+	}
+}
+
 /// Single block import function.
 fn import_single_block<B: BlockT, V: Verifier<B>>(
 	import_handle: &BlockImport<B,Error=ClientError>,
@@ -699,6 +816,25 @@ pub mod tests {
 		}
 	}
 
+	/// A verifier which can verify blocks only after the given delay.
+	struct DelayedVerifier(Duration);
+
+	impl Verifier<Block> for DelayedVerifier {
+		fn verify(
+			&self,
+			_origin: BlockOrigin,
+			_header: <Block as BlockT>::Header,
+			_justification: Vec<u8>,
+			_body: Option<Vec<<Block as BlockT>::Extrinsic>>
+		) -> Result<(ImportBlock<Block>, Option<Vec<AuthorityId>>), String> {
+			Err("too far in the future".into())
+		}
+
+		fn verification_delay(&self, _header: &<Block as BlockT>::Header) -> Option<Duration> {
+			Some(self.0)
+		}
+	}
+
 	fn prepare_good_block() -> (client::Client<test_client::Backend, test_client::Executor, Block, test_client::runtime::ClientWithApi>, Hash, u64, BlockData<Block>) {
 		let client = test_client::new();
 		let block = client.new_block().unwrap().bake().unwrap();
@@ -795,7 +931,7 @@ pub mod tests {
 	#[test]
 	fn import_many_blocks_stops_when_stopping() {
 		let (_, _, _, block) = prepare_good_block();
-		let qdata = AsyncImportQueueData::new();
+		let qdata = AsyncImportQueueData::new(DEFAULT_MAX_DRIFT);
 		let verifier = Arc::new(PassThroughVerifier(true));
 		qdata.is_stopping.store(true, Ordering::SeqCst);
 		let client = test_client::new();
@@ -808,6 +944,66 @@ pub mod tests {
 		));
 	}
 
+	#[test]
+	fn import_many_blocks_defers_blocks_until_they_can_be_verified() {
+		let (_, _, _, block) = prepare_good_block();
+		let qdata = AsyncImportQueueData::new(DEFAULT_MAX_DRIFT);
+		let link = TestLink::new();
+		assert!(import_many_blocks(
+			&test_client::new(),
+			&link,
+			Some(&qdata),
+			(BlockOrigin::NetworkBroadcast, vec![block.clone()]),
+			Arc::new(DelayedVerifier(Duration::from_millis(50))),
+		));
+		assert_eq!(link.imported.get(), 0);
+		assert_eq!(link.disconnects.get(), 0);
+		let at = qdata.deferred_until(&block.block.hash).unwrap();
+
+		let mut queue = VecDeque::new();
+		qdata.requeue_deferred(&mut queue, at - Duration::from_millis(1));
+		assert!(queue.is_empty());
+
+		qdata.requeue_deferred(&mut queue, at);
+		assert_eq!(queue.len(), 1);
+		assert!(qdata.deferred_until(&block.block.hash).is_none());
+	}
+
+	#[test]
+	fn import_many_blocks_rejects_blocks_beyond_max_drift() {
+		let (_, _, _, block) = prepare_good_block();
+		let qdata = AsyncImportQueueData::new(Duration::from_secs(1));
+		let link = TestLink::new();
+		assert!(import_many_blocks(
+			&test_client::new(),
+			&link,
+			Some(&qdata),
+			(BlockOrigin::NetworkBroadcast, vec![block.clone()]),
+			Arc::new(DelayedVerifier(Duration::from_secs(2))),
+		));
+		assert_eq!(link.disconnects.get(), 1);
+		assert!(qdata.deferred_until(&block.block.hash).is_none());
+	}
+
+	#[test]
+	fn import_many_blocks_restarts_sync_when_too_many_blocks_are_deferred() {
+		let (_, _, _, block) = prepare_good_block();
+		let qdata = AsyncImportQueueData::new(DEFAULT_MAX_DRIFT);
+		let later = Instant::now() + Duration::from_secs(1);
+		assert!(qdata.defer(later, BlockOrigin::NetworkBroadcast, vec![block.clone(); MAX_DEFERRED_BLOCKS]));
+
+		let link = TestLink::new();
+		assert!(import_many_blocks(
+			&test_client::new(),
+			&link,
+			Some(&qdata),
+			(BlockOrigin::NetworkBroadcast, vec![block]),
+			Arc::new(DelayedVerifier(Duration::from_millis(50))),
+		));
+		assert_eq!(link.imported.get(), 0);
+		assert_eq!(link.restarts.get(), 1);
+	}
+
 	#[test]
 	fn async_import_queue_drops() {
 		// Perform this test multiple times since it exhibits non-deterministic behavior.
@@ -68,7 +68,7 @@ pub use chain::Client as ClientHandle;
 pub use service::{Service, FetchFuture, TransactionPool, ManageNetwork, SyncProvider, ExHashT};
 pub use protocol::{ProtocolStatus, PeerInfo, Context};
 pub use sync::{Status as SyncStatus, SyncState};
-pub use network_libp2p::{NodeIndex, ProtocolId, Severity, Protocol};
+pub use network_libp2p::{NodeIndex, ProtocolId, Severity, Protocol, PeerId, NetworkState};
 pub use message::{generic as generic_message, RequestId, Status as StatusMessage};
 pub use error::Error;
 pub use on_demand::{OnDemand, OnDemandService, RemoteResponse};
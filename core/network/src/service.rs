@@ -22,10 +22,10 @@ use futures::{self, Future, Stream, stream, sync::oneshot};
 use parking_lot::{Mutex, RwLock};
 use network_libp2p::{ProtocolId, PeerId, NetworkConfiguration, ErrorKind};
 use network_libp2p::{start_service, Service as NetworkService, ServiceEvent as NetworkServiceEvent};
-use network_libp2p::{RegisteredProtocol, parse_str_addr, Protocol as Libp2pProtocol};
+use network_libp2p::{RegisteredProtocol, NetworkState, NodeIndex, parse_str_addr, Protocol as Libp2pProtocol};
 use io::NetSyncIo;
 use consensus_gossip::ConsensusGossip;
-use protocol::{self, Protocol, ProtocolContext, Context, ProtocolStatus};
+use protocol::{self, Protocol, ProtocolContext, Context, ProtocolStatus, PeerInfo};
 use config::Params;
 use error::Error;
 use specialization::NetworkSpecialization;
@@ -43,6 +43,10 @@ const PROPAGATE_TIMEOUT: Duration = Duration::from_millis(5000);
 pub trait SyncProvider<B: BlockT>: Send + Sync {
 	/// Get sync status
 	fn status(&self) -> ProtocolStatus<B>;
+	/// Get information about the connected peers.
+	fn peers(&self) -> Vec<(NodeIndex, Option<PeerId>, PeerInfo<B>)>;
+	/// Get a snapshot of the state of the underlying network, for diagnostic purposes.
+	fn network_state(&self) -> NetworkState;
 }
 
 /// Minimum Requirements for a Hash within Networking
@@ -175,6 +179,18 @@ impl<B: BlockT + 'static, S: NetworkSpecialization<B>, H: ExHashT> SyncProvider<
 	fn status(&self) -> ProtocolStatus<B> {
 		self.handler.status()
 	}
+
+	fn peers(&self) -> Vec<(NodeIndex, Option<PeerId>, PeerInfo<B>)> {
+		let peers = self.handler.peers_info();
+		let network = self.network.lock();
+		peers.into_iter()
+			.map(|(who, info)| (who, network.peer_id_of_node(who).cloned(), info))
+			.collect()
+	}
+
+	fn network_state(&self) -> NetworkState {
+		self.network.lock().state()
+	}
 }
 
 /// Trait for managing network
@@ -18,6 +18,8 @@
 #[cfg(feature = "std")]
 use serde::{Serialize, Serializer, Deserialize, Deserializer};
 use H256;
+#[cfg(feature = "std")]
+use crypto::Ss58Codec;
 
 /// An identifier for an authority in the consensus algorithm. The same size as ed25519::Public.
 #[derive(Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
@@ -36,7 +38,7 @@ impl AuthorityId {
 #[cfg(feature = "std")]
 impl ::std::fmt::Display for AuthorityId {
 	fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
-		write!(f, "{}", ::ed25519::Public(self.0).to_ss58check())
+		write!(f, "{}", self.to_ss58check())
 	}
 }
 
@@ -44,7 +46,7 @@ impl ::std::fmt::Display for AuthorityId {
 impl ::std::fmt::Debug for AuthorityId {
 	fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
 		let h = format!("{}", ::hexdisplay::HexDisplay::from(&self.0));
-		write!(f, "{} ({}…{})", ::ed25519::Public(self.0).to_ss58check(), &h[0..8], &h[60..])
+		write!(f, "{} ({}…{})", self.to_ss58check(), &h[0..8], &h[60..])
 	}
 }
 
@@ -67,6 +69,15 @@ impl AsRef<[u8]> for AuthorityId {
 	}
 }
 
+impl AsMut<[u8]> for AuthorityId {
+	fn as_mut(&mut self) -> &mut [u8] {
+		&mut self.0[..]
+	}
+}
+
+#[cfg(feature = "std")]
+impl Ss58Codec for AuthorityId {}
+
 impl Into<[u8; 32]> for AuthorityId {
 	fn into(self) -> [u8; 32] {
 		self.0
@@ -0,0 +1,553 @@
+// Copyright 2018 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+// tag::description[]
+//! Cryptographic utilities shared by the key types: key derivation paths, secret URIs and
+//! SS58 addresses.
+// end::description[]
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use base58::{ToBase58, FromBase58};
+use bip39::{Mnemonic, Language, MnemonicType, Seed as Bip39Seed};
+use blake2_rfc;
+use codec::Encode;
+use hashing::blake2_256;
+
+/// The length of the junction identifier. Note that this is also referred to as the
+/// `CHAIN_CODE_LENGTH` in the context of Schnorrkel.
+pub const JUNCTION_ID_LEN: usize = 32;
+
+/// An error with the interpretation of a secret.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum SecretStringError {
+	/// The overall format was invalid (e.g. the seed phrase contained symbols).
+	InvalidFormat,
+	/// The seed phrase provided is not a valid BIP39 phrase.
+	InvalidPhrase,
+	/// The supplied password was invalid.
+	InvalidPassword,
+	/// The seed is invalid (bad content).
+	InvalidSeed,
+	/// The seed has an invalid length.
+	InvalidSeedLength,
+	/// The derivation path was invalid (e.g. contains soft junctions when they are not supported).
+	InvalidPath,
+}
+
+/// An error with the derivation of a key.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum DeriveError {
+	/// A soft key was found in the path (and is unsupported).
+	SoftKeyInPath,
+	/// The secret seed of the key is not known, e.g. because it was loaded from a PKCS#8 document.
+	SeedUnknown,
+}
+
+/// A single step of a key derivation path.
+///
+/// Hard junctions derive keys that can't be linked to their parent without knowing the
+/// parent's secret. Soft junctions allow deriving the public key without knowing any secret,
+/// but are not supported by every key type.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum DeriveJunction {
+	/// Soft (vanilla) derivation. Public keys have a correspondent derivation.
+	Soft([u8; JUNCTION_ID_LEN]),
+	/// Hard ("hardened") derivation. Public keys do not have a correspondent derivation.
+	Hard([u8; JUNCTION_ID_LEN]),
+}
+
+impl DeriveJunction {
+	/// Consume self to return a soft derive junction with the same chain code.
+	pub fn soften(self) -> Self { DeriveJunction::Soft(self.unwrap_inner()) }
+
+	/// Consume self to return a hard derive junction with the same chain code.
+	pub fn harden(self) -> Self { DeriveJunction::Hard(self.unwrap_inner()) }
+
+	/// Create a new soft (vanilla) junction from an encodable index. If the encoded index
+	/// is longer than `JUNCTION_ID_LEN`, it is hashed with blake2-256.
+	pub fn soft<T: Encode>(index: T) -> Self {
+		let mut cc: [u8; JUNCTION_ID_LEN] = Default::default();
+		index.using_encoded(|data| if data.len() > JUNCTION_ID_LEN {
+			cc.copy_from_slice(&blake2_256(data));
+		} else {
+			cc[0..data.len()].copy_from_slice(data);
+		});
+		DeriveJunction::Soft(cc)
+	}
+
+	/// Create a new hard junction from an encodable index. If the encoded index
+	/// is longer than `JUNCTION_ID_LEN`, it is hashed with blake2-256.
+	pub fn hard<T: Encode>(index: T) -> Self {
+		Self::soft(index).harden()
+	}
+
+	/// Consume self to return the chain code.
+	pub fn unwrap_inner(self) -> [u8; JUNCTION_ID_LEN] {
+		match self {
+			DeriveJunction::Hard(c) | DeriveJunction::Soft(c) => c,
+		}
+	}
+
+	/// Get a reference to the inner chain code.
+	pub fn inner(&self) -> &[u8; JUNCTION_ID_LEN] {
+		match self {
+			DeriveJunction::Hard(ref c) | DeriveJunction::Soft(ref c) => c,
+		}
+	}
+
+	/// Return `true` if the junction is soft.
+	pub fn is_soft(&self) -> bool {
+		match *self {
+			DeriveJunction::Soft(_) => true,
+			_ => false,
+		}
+	}
+
+	/// Return `true` if the junction is hard.
+	pub fn is_hard(&self) -> bool {
+		match *self {
+			DeriveJunction::Hard(_) => true,
+			_ => false,
+		}
+	}
+}
+
+impl<T: AsRef<str>> From<T> for DeriveJunction {
+	/// Create a soft junction from its textual representation, i.e. without the leading slash.
+	///
+	/// Numeric junctions are encoded as `u64`, all others as strings.
+	fn from(j: T) -> DeriveJunction {
+		let j = j.as_ref();
+		if let Ok(n) = str::parse::<u64>(j) {
+			DeriveJunction::soft(n)
+		} else {
+			DeriveJunction::soft(j)
+		}
+	}
+}
+
+/// Parse a derivation path, e.g. `//alice/stash`.
+///
+/// Each junction starts with either `//` (hard junction) or `/` (soft junction).
+/// The empty string is a valid, empty path.
+pub fn parse_derivation_path(path: &str) -> Result<Vec<DeriveJunction>, SecretStringError> {
+	let mut junctions = Vec::new();
+	let mut rest = path;
+	while !rest.is_empty() {
+		let (hard, tail) = if rest.starts_with("//") {
+			(true, &rest[2..])
+		} else if rest.starts_with('/') {
+			(false, &rest[1..])
+		} else {
+			return Err(SecretStringError::InvalidPath);
+		};
+		let end = tail.find('/').unwrap_or(tail.len());
+		let name = &tail[..end];
+		if name.is_empty() {
+			return Err(SecretStringError::InvalidPath);
+		}
+		let junction = DeriveJunction::from(name);
+		junctions.push(if hard { junction.harden() } else { junction });
+		rest = &tail[end..];
+	}
+	Ok(junctions)
+}
+
+/// A parsed secret URI (SURI).
+///
+/// The format is `<phrase or 0x-prefixed hex seed>[//hard][/soft]...[///password]`,
+/// e.g. `"bottom drive obey lake curtain smoke basket hold race lonely fit walk//alice/stash///secret"`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretUri<'a> {
+	/// The BIP39 phrase or the hex encoded seed.
+	pub phrase: &'a str,
+	/// The derivation path.
+	pub path: Vec<DeriveJunction>,
+	/// The password, if any.
+	pub password: Option<&'a str>,
+}
+
+/// Parse a secret URI. See `SecretUri` for the format.
+pub fn parse_suri(suri: &str) -> Result<SecretUri, SecretStringError> {
+	let (rest, password) = match suri.find("///") {
+		Some(pos) => (&suri[..pos], Some(&suri[pos + 3..])),
+		None => (suri, None),
+	};
+	let (phrase, path) = match rest.find('/') {
+		Some(pos) => (&rest[..pos], &rest[pos..]),
+		None => (rest, ""),
+	};
+	if phrase.trim().is_empty() {
+		return Err(SecretStringError::InvalidFormat);
+	}
+	if password.map_or(false, |p| p.is_empty()) {
+		return Err(SecretStringError::InvalidPassword);
+	}
+
+	Ok(SecretUri {
+		phrase: phrase.trim(),
+		path: parse_derivation_path(path)?,
+		password,
+	})
+}
+
+/// A key pair that can be made from a 32 byte seed and derived along a path.
+///
+/// This is all it takes to restore the key pair from a BIP39 phrase or a secret URI, which the
+/// provided methods do the same way for every key type.
+pub trait KeyPair: Sized {
+	/// Make a key pair from a 32 byte seed.
+	fn from_seed(seed: &[u8; 32]) -> Self;
+
+	/// Derive a child key from a series of junctions.
+	fn derive<Iter: Iterator<Item=DeriveJunction>>(&self, path: Iter) -> Result<Self, DeriveError>;
+
+	/// Generate new secure (random) key pair along with the BIP39 phrase it was generated from.
+	///
+	/// The same `password` has to be given to restore the key pair from the phrase.
+	fn generate_with_phrase(password: Option<&str>) -> (Self, String) {
+		let mnemonic = Mnemonic::new(MnemonicType::Words12, Language::English);
+		let phrase = mnemonic.phrase().to_owned();
+		let pair = Self::from_phrase(&phrase, password)
+			.expect("all generated phrases are valid; qed");
+
+		(pair, phrase)
+	}
+
+	/// Restore a key pair from a BIP39 phrase and an optional password.
+	///
+	/// The seed is the first 32 bytes of the BIP39 seed of the phrase.
+	fn from_phrase(phrase: &str, password: Option<&str>) -> Result<Self, SecretStringError> {
+		let mnemonic = Mnemonic::from_phrase(phrase, Language::English)
+			.map_err(|_| SecretStringError::InvalidPhrase)?;
+		let bip39_seed = Bip39Seed::new(&mnemonic, password.unwrap_or(""));
+		let mut seed = [0u8; 32];
+		seed.copy_from_slice(&bip39_seed.as_bytes()[..32]);
+
+		Ok(Self::from_seed(&seed))
+	}
+
+	/// Restore a key pair from a secret URI, e.g. `"<phrase>//alice/stash///password"`.
+	///
+	/// Instead of a phrase, a `0x`-prefixed hex encoded seed can be given. `password_override`
+	/// takes precedence over the password in the URI. See `SecretUri` for the format.
+	fn from_string(suri: &str, password_override: Option<&str>) -> Result<Self, SecretStringError> {
+		let suri = parse_suri(suri)?;
+		let password = password_override.or(suri.password);
+		let root = if suri.phrase.starts_with("0x") {
+			if password.is_some() {
+				return Err(SecretStringError::InvalidPassword);
+			}
+			let bytes: Vec<u8> = ::rustc_hex::FromHex::from_hex(&suri.phrase[2..])
+				.map_err(|_| SecretStringError::InvalidSeed)?;
+			if bytes.len() != 32 {
+				return Err(SecretStringError::InvalidSeedLength);
+			}
+			let mut seed = [0u8; 32];
+			seed.copy_from_slice(&bytes);
+			Self::from_seed(&seed)
+		} else {
+			Self::from_phrase(suri.phrase, password)?
+		};
+
+		root.derive(suri.path.into_iter()).map_err(|_| SecretStringError::InvalidPath)
+	}
+}
+
+/// An error type for SS58 decoding.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum PublicError {
+	/// Bad alphabet.
+	BadBase58,
+	/// Bad length.
+	BadLength,
+	/// Unknown version.
+	UnknownVersion,
+	/// Invalid checksum.
+	InvalidChecksum,
+}
+
+/// The address type prefix of an SS58 address, i.e. the network an address is meant for.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum Ss58AddressFormat {
+	/// Any Substrate network, direct checksum, standard account (*25519).
+	SubstrateAccountDirect,
+	/// Polkadot relay-chain, direct checksum, standard account (*25519).
+	PolkadotAccountDirect,
+	/// Kusama relay-chain, direct checksum, standard account (*25519).
+	KusamaAccountDirect,
+	/// Any other network, identified by its version byte.
+	Custom(u8),
+}
+
+impl Ss58AddressFormat {
+	/// All the networks known by name.
+	pub const KNOWN: [Ss58AddressFormat; 3] = [
+		Ss58AddressFormat::SubstrateAccountDirect,
+		Ss58AddressFormat::PolkadotAccountDirect,
+		Ss58AddressFormat::KusamaAccountDirect,
+	];
+
+	/// The name of the network, or `None` for a custom format.
+	pub fn name(&self) -> Option<&'static str> {
+		match *self {
+			Ss58AddressFormat::SubstrateAccountDirect => Some("substrate"),
+			Ss58AddressFormat::PolkadotAccountDirect => Some("polkadot"),
+			Ss58AddressFormat::KusamaAccountDirect => Some("kusama"),
+			Ss58AddressFormat::Custom(_) => None,
+		}
+	}
+}
+
+impl Default for Ss58AddressFormat {
+	fn default() -> Self {
+		Ss58AddressFormat::SubstrateAccountDirect
+	}
+}
+
+impl From<u8> for Ss58AddressFormat {
+	fn from(x: u8) -> Self {
+		match x {
+			42 => Ss58AddressFormat::SubstrateAccountDirect,
+			0 => Ss58AddressFormat::PolkadotAccountDirect,
+			2 => Ss58AddressFormat::KusamaAccountDirect,
+			x => Ss58AddressFormat::Custom(x),
+		}
+	}
+}
+
+impl From<Ss58AddressFormat> for u8 {
+	fn from(x: Ss58AddressFormat) -> u8 {
+		match x {
+			Ss58AddressFormat::SubstrateAccountDirect => 42,
+			Ss58AddressFormat::PolkadotAccountDirect => 0,
+			Ss58AddressFormat::KusamaAccountDirect => 2,
+			Ss58AddressFormat::Custom(n) => n,
+		}
+	}
+}
+
+impl FromStr for Ss58AddressFormat {
+	type Err = ();
+
+	/// Parse either the name of a known network or a version byte.
+	fn from_str(s: &str) -> Result<Self, ()> {
+		Ss58AddressFormat::KNOWN.iter()
+			.find(|f| f.name() == Some(s))
+			.cloned()
+			.map(Ok)
+			.unwrap_or_else(|| s.parse::<u8>().map(Into::into).map_err(|_| ()))
+	}
+}
+
+impl fmt::Display for Ss58AddressFormat {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self.name() {
+			Some(name) => write!(f, "{}", name),
+			None => write!(f, "{}", u8::from(*self)),
+		}
+	}
+}
+
+static DEFAULT_VERSION: AtomicUsize = AtomicUsize::new(42);
+
+/// Returns the address format used when none is given explicitly.
+pub fn default_ss58_version() -> Ss58AddressFormat {
+	(DEFAULT_VERSION.load(Ordering::Relaxed) as u8).into()
+}
+
+/// Set the address format used when none is given explicitly.
+///
+/// This is process-wide and is meant to be called once on startup, e.g. from a `--network` flag.
+pub fn set_default_ss58_version(version: Ss58AddressFormat) {
+	DEFAULT_VERSION.store(u8::from(version) as usize, Ordering::Relaxed);
+}
+
+const CHECKSUM_LEN: usize = 2;
+const SS58_PREFIX: &[u8] = b"SS58PRE";
+
+/// The legacy checksum: blake2b-512 of the version byte and the key.
+fn legacy_checksum(data: &[u8]) -> blake2_rfc::blake2b::Blake2bResult {
+	blake2_rfc::blake2b::blake2b(64, &[], data)
+}
+
+/// The prefixed checksum: blake2b-512 of `SS58PRE`, the version byte and the key.
+fn prefixed_checksum(data: &[u8]) -> blake2_rfc::blake2b::Blake2bResult {
+	let mut context = blake2_rfc::blake2b::Blake2b::new(64);
+	context.update(SS58_PREFIX);
+	context.update(data);
+	context.finalize()
+}
+
+/// Key that can be encoded to/from SS58.
+///
+/// The encoding is `base58(version ++ key ++ checksum)`, where `version` identifies the network
+/// (see `Ss58AddressFormat`) and `checksum` is the first two bytes of a blake2b-512 hash of
+/// `version ++ key`. Addresses are produced with the legacy unprefixed checksum, which all
+/// existing addresses use; decoding also accepts checksums computed over `SS58PRE ++ version ++ key`.
+pub trait Ss58Codec: Sized + AsRef<[u8]> + AsMut<[u8]> + Default {
+	/// Some if the string is a properly encoded SS58Check address for the default network.
+	fn from_ss58check(s: &str) -> Result<Self, PublicError> {
+		Self::from_ss58check_with_version(s)
+			.and_then(|(r, v)| if v == default_ss58_version() {
+				Ok(r)
+			} else {
+				Err(PublicError::UnknownVersion)
+			})
+	}
+
+	/// Some if the string is a properly encoded SS58Check address, along with its network.
+	fn from_ss58check_with_version(s: &str) -> Result<(Self, Ss58AddressFormat), PublicError> {
+		let mut res = Self::default();
+		let len = res.as_mut().len();
+		let d = s.from_base58().map_err(|_| PublicError::BadBase58)?;	// failure here would be invalid encoding.
+		if d.len() != len + 1 + CHECKSUM_LEN {
+			// Invalid length.
+			return Err(PublicError::BadLength);
+		}
+		let (body, checksum) = d.split_at(len + 1);
+		if checksum != &legacy_checksum(body).as_bytes()[0..CHECKSUM_LEN]
+			&& checksum != &prefixed_checksum(body).as_bytes()[0..CHECKSUM_LEN]
+		{
+			// Invalid checksum.
+			return Err(PublicError::InvalidChecksum);
+		}
+		res.as_mut().copy_from_slice(&body[1..]);
+		Ok((res, body[0].into()))
+	}
+
+	/// Return the ss58-check string for this key, for the given network.
+	fn to_ss58check_with_version(&self, version: Ss58AddressFormat) -> String {
+		let mut v = vec![u8::from(version)];
+		v.extend(self.as_ref());
+		let r = legacy_checksum(&v);
+		v.extend(&r.as_bytes()[0..CHECKSUM_LEN]);
+		v.to_base58()
+	}
+
+	/// Return the ss58-check string for this key, for the default network.
+	fn to_ss58check(&self) -> String {
+		self.to_ss58check_with_version(default_ss58_version())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn junctions_from_strings_work() {
+		assert_eq!(DeriveJunction::from("1"), DeriveJunction::soft(1u64));
+		assert_eq!(DeriveJunction::from("alice"), DeriveJunction::soft("alice"));
+		assert!(DeriveJunction::from("alice").harden().is_hard());
+	}
+
+	#[test]
+	fn long_junctions_are_hashed() {
+		let long = "a".repeat(40);
+		assert_eq!(
+			DeriveJunction::soft(long.as_str()),
+			DeriveJunction::Soft(blake2_256(&long.as_str().encode())),
+		);
+	}
+
+	#[test]
+	fn derivation_path_parsing_works() {
+		assert_eq!(parse_derivation_path(""), Ok(vec![]));
+		assert_eq!(
+			parse_derivation_path("//alice/stash/1"),
+			Ok(vec![
+				DeriveJunction::hard("alice"),
+				DeriveJunction::soft("stash"),
+				DeriveJunction::soft(1u64),
+			]),
+		);
+		assert_eq!(parse_derivation_path("alice"), Err(SecretStringError::InvalidPath));
+		assert_eq!(parse_derivation_path("//alice//"), Err(SecretStringError::InvalidPath));
+		assert_eq!(parse_derivation_path("///alice"), Err(SecretStringError::InvalidPath));
+	}
+
+	#[test]
+	fn suri_parsing_works() {
+		assert_eq!(parse_suri("hello world//alice/1///secret"), Ok(SecretUri {
+			phrase: "hello world",
+			path: vec![DeriveJunction::hard("alice"), DeriveJunction::soft(1u64)],
+			password: Some("secret"),
+		}));
+		assert_eq!(parse_suri("0x1234"), Ok(SecretUri {
+			phrase: "0x1234",
+			path: vec![],
+			password: None,
+		}));
+		assert_eq!(parse_suri("//alice"), Err(SecretStringError::InvalidFormat));
+		assert_eq!(parse_suri("hello world///"), Err(SecretStringError::InvalidPassword));
+	}
+
+	#[derive(Default, Debug, PartialEq)]
+	struct Key([u8; 32]);
+
+	impl AsRef<[u8]> for Key {
+		fn as_ref(&self) -> &[u8] { &self.0[..] }
+	}
+
+	impl AsMut<[u8]> for Key {
+		fn as_mut(&mut self) -> &mut [u8] { &mut self.0[..] }
+	}
+
+	impl Ss58Codec for Key {}
+
+	#[test]
+	fn address_formats_parse() {
+		assert_eq!("substrate".parse(), Ok(Ss58AddressFormat::SubstrateAccountDirect));
+		assert_eq!("polkadot".parse(), Ok(Ss58AddressFormat::PolkadotAccountDirect));
+		assert_eq!("2".parse(), Ok(Ss58AddressFormat::KusamaAccountDirect));
+		assert_eq!("68".parse(), Ok(Ss58AddressFormat::Custom(68)));
+		assert_eq!("unknown".parse::<Ss58AddressFormat>(), Err(()));
+		assert_eq!(u8::from(Ss58AddressFormat::from(68)), 68);
+	}
+
+	#[test]
+	fn ss58_version_roundtrip_works() {
+		let key = Key([7u8; 32]);
+		for version in Ss58AddressFormat::KNOWN.iter().chain(Some(&Ss58AddressFormat::Custom(68))) {
+			let s = key.to_ss58check_with_version(*version);
+			assert_eq!(Key::from_ss58check_with_version(&s), Ok((Key([7u8; 32]), *version)));
+		}
+	}
+
+	#[test]
+	fn ss58_rejects_other_networks() {
+		let key = Key([7u8; 32]);
+		let s = key.to_ss58check_with_version(Ss58AddressFormat::PolkadotAccountDirect);
+		assert_eq!(Key::from_ss58check(&s), Err(PublicError::UnknownVersion));
+		assert_eq!(Key::from_ss58check(&key.to_ss58check()), Ok(Key([7u8; 32])));
+	}
+
+	#[test]
+	fn ss58_accepts_prefixed_checksum() {
+		let mut v = vec![42u8];
+		v.extend(&[7u8; 32]);
+		let r = prefixed_checksum(&v);
+		v.extend(&r.as_bytes()[0..CHECKSUM_LEN]);
+		assert_eq!(Key::from_ss58check(&v.to_base58()), Ok(Key([7u8; 32])));
+
+		let last = v.len() - 1;
+		v[last] ^= 1;
+		assert_eq!(Key::from_ss58check(&v.to_base58()), Err(PublicError::InvalidChecksum));
+	}
+}
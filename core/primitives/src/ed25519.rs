@@ -19,10 +19,12 @@
 // end::description[]
 
 use untrusted;
-use blake2_rfc;
 use ring::{rand, signature};
 use {hash::H512, AuthorityId};
-use base58::{ToBase58, FromBase58};
+use codec::Encode;
+use crypto::{DeriveJunction, DeriveError, KeyPair, Ss58Codec};
+pub use crypto::PublicError;
+use hashing::blake2_256;
 
 /// Alias to 512-bit hash when used in the context of a signature on the relay chain.
 pub type Signature = H512;
@@ -30,6 +32,9 @@ pub type Signature = H512;
 /// Length of the PKCS#8 encoding of the key.
 pub const PKCS_LEN: usize = 85;
 
+/// The secret seed of a key pair.
+pub type Seed = [u8; 32];
+
 /// A localized signature also contains sender information.
 #[derive(PartialEq, Eq, Clone, Debug, Encode, Decode)]
 pub struct LocalizedSignature {
@@ -53,11 +58,13 @@ pub fn verify<P: AsRef<[u8]>>(sig: &[u8], message: &[u8], public: P) -> bool {
 }
 
 /// A public key.
-#[derive(PartialEq, Eq, Clone, Encode, Decode)]
+#[derive(PartialEq, Eq, Clone, Default, Encode, Decode)]
 pub struct Public(pub [u8; 32]);
 
 /// A key pair.
-pub struct Pair(signature::Ed25519KeyPair);
+///
+/// The secret seed is kept along with the key pair when known, so that child keys can be derived.
+pub struct Pair(signature::Ed25519KeyPair, Option<Seed>);
 
 impl ::std::hash::Hash for Public {
 	fn hash<H: ::std::hash::Hasher>(&self, state: &mut H) {
@@ -65,19 +72,6 @@ impl ::std::hash::Hash for Public {
 	}
 }
 
-/// An error type for SS58 decoding.
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
-pub enum PublicError {
-	/// Bad alphabet.
-	BadBase58,
-	/// Bad length.
-	BadLength,
-	/// Unknown version.
-	UnknownVersion,
-	/// Invalid checksum.
-	InvalidChecksum,
-}
-
 impl Public {
 	/// A new instance from the given 32-byte `data`.
 	pub fn from_raw(data: [u8; 32]) -> Self {
@@ -91,24 +85,6 @@ impl Public {
 		Public(r)
 	}
 
-	/// Some if the string is a properly encoded SS58Check address.
-	pub fn from_ss58check(s: &str) -> Result<Self, PublicError> {
-		let d = s.from_base58().map_err(|_| PublicError::BadBase58)?;	// failure here would be invalid encoding.
-		if d.len() != 35 {
-			// Invalid length.
-			return Err(PublicError::BadLength);
-		}
-		if d[0] != 42 {
-			// Invalid version.
-			return Err(PublicError::UnknownVersion);
-		}
-		if d[33..35] != blake2_rfc::blake2b::blake2b(64, &[], &d[0..33]).as_bytes()[0..2] {
-			// Invalid checksum.
-			return Err(PublicError::InvalidChecksum);
-		}
-		Ok(Self::from_slice(&d[1..33]))
-	}
-
 	/// Return a `Vec<u8>` filled with raw data.
 	pub fn to_raw_vec(self) -> Vec<u8> {
 		let r: &[u8; 32] = self.as_ref();
@@ -125,15 +101,6 @@ impl Public {
 	pub fn as_array_ref(&self) -> &[u8; 32] {
 		self.as_ref()
 	}
-
-	/// Return the ss58-check string for this key.
-	pub fn to_ss58check(&self) -> String {
-		let mut v = vec![42u8];
-		v.extend(self.as_slice());
-		let r = blake2_rfc::blake2b::blake2b(64, &[], &v);
-		v.extend(&r.as_bytes()[0..2]);
-		v.to_base58()
-	}
 }
 
 impl AsRef<[u8; 32]> for Public {
@@ -148,6 +115,14 @@ impl AsRef<[u8]> for Public {
 	}
 }
 
+impl AsMut<[u8]> for Public {
+	fn as_mut(&mut self) -> &mut [u8] {
+		&mut self.0[..]
+	}
+}
+
+impl Ss58Codec for Public {}
+
 impl Into<[u8; 32]> for Public {
 	fn into(self) -> [u8; 32] {
 		self.0
@@ -209,16 +184,36 @@ impl Pair {
 
 	/// Generate from pkcs#8 bytes.
 	pub fn from_pkcs8(pkcs8_bytes: &[u8]) -> Result<Self, ::ring::error::Unspecified> {
-		signature::Ed25519KeyPair::from_pkcs8(untrusted::Input::from(&pkcs8_bytes)).map(Pair)
+		signature::Ed25519KeyPair::from_pkcs8(untrusted::Input::from(&pkcs8_bytes)).map(|key| Pair(key, None))
 	}
 
 	/// Make a new key pair from a seed phrase.
 	/// NOTE: prefer pkcs#8 unless security doesn't matter -- this is used primarily for tests.
-	pub fn from_seed(seed: &[u8; 32]) -> Pair {
+	pub fn from_seed(seed: &Seed) -> Pair {
 		let key = signature::Ed25519KeyPair::from_seed_unchecked(untrusted::Input::from(&seed[..]))
 			.expect("seed has valid length; qed");
 
-		Pair(key)
+		Pair(key, Some(*seed))
+	}
+
+	/// Derive a child key from a series of junctions.
+	///
+	/// Only hard junctions are supported by ed25519.
+	pub fn derive<Iter: Iterator<Item=DeriveJunction>>(&self, path: Iter) -> Result<Pair, DeriveError> {
+		let mut seed = self.1.ok_or(DeriveError::SeedUnknown)?;
+		for junction in path {
+			match junction {
+				DeriveJunction::Soft(_) => return Err(DeriveError::SoftKeyInPath),
+				DeriveJunction::Hard(cc) => seed = derive_hard_junction(&seed, &cc),
+			}
+		}
+
+		Ok(Self::from_seed(&seed))
+	}
+
+	/// Get the secret seed of the key pair, if known.
+	pub fn seed(&self) -> Option<&Seed> {
+		self.1.as_ref()
 	}
 
 	/// Sign a message.
@@ -247,6 +242,21 @@ impl Pair {
 	}
 }
 
+/// Derive a single hard junction.
+fn derive_hard_junction(seed: &Seed, cc: &[u8; 32]) -> Seed {
+	("Ed25519HDKD", seed, cc).using_encoded(|data| blake2_256(data))
+}
+
+impl KeyPair for Pair {
+	fn from_seed(seed: &Seed) -> Pair {
+		Pair::from_seed(seed)
+	}
+
+	fn derive<Iter: Iterator<Item=DeriveJunction>>(&self, path: Iter) -> Result<Pair, DeriveError> {
+		Pair::derive(self, path)
+	}
+}
+
 /// Verify a signature on a message. Returns true if the signature is good.
 pub fn verify_strong<P: AsRef<Public>>(sig: &Signature, message: &[u8], pubkey: P) -> bool {
 	let public_key = untrusted::Input::from(&pubkey.as_ref().0[..]);
@@ -281,6 +291,7 @@ impl Verifiable for LocalizedSignature {
 #[cfg(test)]
 mod test {
 	use super::*;
+	use crypto::SecretStringError;
 
 	fn _test_primitives_signature_and_local_the_same() {
 		fn takes_two<T>(_: T, _: T) { }
@@ -334,6 +345,61 @@ mod test {
 		let _pair2 = pair.derive_child_probably_bad(b"session_1234");
 	}
 
+	#[test]
+	fn generated_phrase_should_restore_the_same_pair() {
+		let (pair, phrase) = Pair::generate_with_phrase(Some("password"));
+		let restored = Pair::from_phrase(&phrase, Some("password")).unwrap();
+		assert_eq!(pair.public(), restored.public());
+		let other = Pair::from_phrase(&phrase, None).unwrap();
+		assert!(pair.public() != other.public());
+	}
+
+	#[test]
+	fn invalid_phrase_should_not_work() {
+		assert_eq!(
+			Pair::from_phrase("bottom drive obey lake curtain smoke basket hold race lonely fit fit", None).err(),
+			Some(SecretStringError::InvalidPhrase),
+		);
+	}
+
+	#[test]
+	fn hard_derivation_should_work() {
+		let pair = Pair::from_seed(b"12345678901234567890123456789012");
+		let derived = pair.derive(Some(DeriveJunction::hard("alice")).into_iter()).unwrap();
+		assert!(derived.public() != pair.public());
+		let again = pair.derive(Some(DeriveJunction::hard("alice")).into_iter()).unwrap();
+		assert_eq!(derived.public(), again.public());
+	}
+
+	#[test]
+	fn soft_derivation_should_not_work() {
+		let pair = Pair::from_seed(b"12345678901234567890123456789012");
+		assert_eq!(
+			pair.derive(Some(DeriveJunction::soft(1u64)).into_iter()).err(),
+			Some(DeriveError::SoftKeyInPath),
+		);
+	}
+
+	#[test]
+	fn from_string_should_work() {
+		let phrase = "bottom drive obey lake curtain smoke basket hold race lonely fit walk";
+		let root = Pair::from_phrase(phrase, None).unwrap();
+		let expected = root.derive(vec![DeriveJunction::hard("alice"), DeriveJunction::hard(1u64)].into_iter()).unwrap();
+
+		let pair = Pair::from_string(&format!("{}//alice//1", phrase), None).unwrap();
+		assert_eq!(pair.public(), expected.public());
+
+		let with_password = Pair::from_string(&format!("{}//alice//1///secret", phrase), None).unwrap();
+		let overridden = Pair::from_string(&format!("{}//alice//1", phrase), Some("secret")).unwrap();
+		assert_eq!(with_password.public(), overridden.public());
+		assert!(with_password.public() != pair.public());
+
+		let seed = Pair::from_string("0x3132333435363738393031323334353637383930313233343536373839303132", None).unwrap();
+		assert_eq!(seed.public(), Pair::from_seed(b"12345678901234567890123456789012").public());
+
+		assert_eq!(Pair::from_string(&format!("{}/alice", phrase), None).err(), Some(SecretStringError::InvalidPath));
+	}
+
 	#[test]
 	fn ss58check_roundtrip_works() {
 		let pair = Pair::from_seed(b"12345678901234567890123456789012");
@@ -76,6 +76,11 @@ impl_rest!(H160, 20);
 impl_rest!(H256, 32);
 impl_rest!(H512, 64);
 
+/// 256-bit hashes double as account identifiers (the raw public key), so they can be shown
+/// as SS58 addresses.
+#[cfg(feature = "std")]
+impl ::crypto::Ss58Codec for H256 {}
+
 /// Hash conversion. Used to convert between unbound associated hash types in traits,
 /// implemented by the same hash type.
 /// Panics if used to convert between different hash types.
@@ -47,6 +47,10 @@ extern crate ring;
 extern crate base58;
 #[cfg(feature = "std")]
 extern crate untrusted;
+#[cfg(feature = "std")]
+extern crate bip39;
+#[cfg(feature = "std")]
+extern crate schnorrkel;
 #[cfg(test)]
 #[macro_use]
 extern crate hex_literal;
@@ -92,7 +96,11 @@ pub use hashing::{blake2_256, twox_128, twox_256};
 #[cfg(feature = "std")]
 pub mod hexdisplay;
 #[cfg(feature = "std")]
+pub mod crypto;
+#[cfg(feature = "std")]
 pub mod ed25519;
+#[cfg(feature = "std")]
+pub mod sr25519;
 
 pub mod u32_trait;
 
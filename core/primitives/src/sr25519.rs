@@ -0,0 +1,325 @@
+// Copyright 2018 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+// tag::description[]
+//! Simple sr25519 (Schnorr-Ristretto) API.
+//!
+//! Unlike ed25519, sr25519 keys support soft derivation, i.e. deriving child public keys
+//! without knowing the secret key, and can be used as a verifiable random function (VRF).
+// end::description[]
+
+use ring::rand::{SecureRandom, SystemRandom};
+use schnorrkel::{signing_context, Keypair, SecretKey, MiniSecretKey, PublicKey};
+use schnorrkel::derive::{Derivation, ChainCode};
+use schnorrkel::vrf::{VRFOutput, VRFProof};
+use crypto::{DeriveJunction, DeriveError, KeyPair, Ss58Codec};
+use hash::H512;
+
+/// The context of all sr25519 signatures made by substrate.
+const SIGNING_CTX: &'static [u8] = b"substrate";
+
+/// An sr25519 signature.
+pub type Signature = H512;
+
+/// The mini secret key a key pair is expanded from.
+pub type Seed = [u8; 32];
+
+/// The output of the VRF of a key pair for some input.
+pub type VrfOutput = [u8; 32];
+
+/// A proof that a VRF output was produced by the key pair of some public key.
+pub type VrfProof = [u8; 64];
+
+/// Verify a message without type checking the parameters' types for the right size.
+/// Returns true if the signature is good.
+pub fn verify<P: AsRef<[u8]>>(sig: &[u8], message: &[u8], public: P) -> bool {
+	let signature = match ::schnorrkel::Signature::from_bytes(sig) {
+		Ok(signature) => signature,
+		Err(_) => return false,
+	};
+	match PublicKey::from_bytes(public.as_ref()) {
+		Ok(public) => public.verify(signing_context(SIGNING_CTX).bytes(message), &signature),
+		Err(_) => false,
+	}
+}
+
+/// Verify a signature on a message. Returns true if the signature is good.
+pub fn verify_strong<P: AsRef<Public>>(sig: &Signature, message: &[u8], pubkey: P) -> bool {
+	verify(sig.as_bytes(), message, pubkey.as_ref())
+}
+
+/// Verify that `output` is the VRF output of the key pair of `pubkey` for `message` in `context`.
+/// Returns true if the proof is good.
+pub fn vrf_verify<P: AsRef<Public>>(context: &[u8], message: &[u8], output: &VrfOutput, proof: &VrfProof, pubkey: P) -> bool {
+	let (output, proof) = match (VRFOutput::from_bytes(&output[..]), VRFProof::from_bytes(&proof[..])) {
+		(Ok(output), Ok(proof)) => (output, proof),
+		_ => return false,
+	};
+	match PublicKey::from_bytes(pubkey.as_ref().as_slice()) {
+		Ok(public) => public.vrf_verify(signing_context(context).bytes(message), &output, &proof).is_ok(),
+		Err(_) => false,
+	}
+}
+
+/// A public key.
+#[derive(PartialEq, Eq, Clone, Default, Encode, Decode)]
+pub struct Public(pub [u8; 32]);
+
+/// A key pair.
+pub struct Pair(Keypair);
+
+impl ::std::hash::Hash for Public {
+	fn hash<H: ::std::hash::Hasher>(&self, state: &mut H) {
+		self.0.hash(state);
+	}
+}
+
+impl Public {
+	/// A new instance from the given 32-byte `data`.
+	pub fn from_raw(data: [u8; 32]) -> Self {
+		Public(data)
+	}
+
+	/// A new instance from the given slice that should be 32 bytes long.
+	pub fn from_slice(data: &[u8]) -> Self {
+		let mut r = [0u8; 32];
+		r.copy_from_slice(data);
+		Public(r)
+	}
+
+	/// Return a `Vec<u8>` filled with raw data.
+	pub fn to_raw_vec(self) -> Vec<u8> {
+		self.0.to_vec()
+	}
+
+	/// Return a slice filled with raw data.
+	pub fn as_slice(&self) -> &[u8] {
+		&self.0[..]
+	}
+
+	/// Return a slice filled with raw data.
+	pub fn as_array_ref(&self) -> &[u8; 32] {
+		&self.0
+	}
+
+	/// Derive a child public key from a series of junctions.
+	///
+	/// Returns `None` if the path contains hard junctions, since they require the secret key.
+	pub fn derive<Iter: Iterator<Item=DeriveJunction>>(&self, path: Iter) -> Option<Public> {
+		let mut acc = PublicKey::from_bytes(self.as_slice()).ok()?;
+		for junction in path {
+			match junction {
+				DeriveJunction::Soft(cc) => acc = acc.derived_key_simple(ChainCode(cc), &[]).0,
+				DeriveJunction::Hard(_) => return None,
+			}
+		}
+		Some(Public(acc.to_bytes()))
+	}
+}
+
+impl AsRef<[u8; 32]> for Public {
+	fn as_ref(&self) -> &[u8; 32] {
+		&self.0
+	}
+}
+
+impl AsRef<[u8]> for Public {
+	fn as_ref(&self) -> &[u8] {
+		&self.0[..]
+	}
+}
+
+impl AsMut<[u8]> for Public {
+	fn as_mut(&mut self) -> &mut [u8] {
+		&mut self.0[..]
+	}
+}
+
+impl Ss58Codec for Public {}
+
+impl Into<[u8; 32]> for Public {
+	fn into(self) -> [u8; 32] {
+		self.0
+	}
+}
+
+impl AsRef<Public> for Public {
+	fn as_ref(&self) -> &Public {
+		&self
+	}
+}
+
+impl AsRef<Pair> for Pair {
+	fn as_ref(&self) -> &Pair {
+		&self
+	}
+}
+
+impl ::std::fmt::Display for Public {
+	fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+		write!(f, "{}", self.to_ss58check())
+	}
+}
+
+impl ::std::fmt::Debug for Public {
+	fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+		let s = self.to_ss58check();
+		write!(f, "{} ({}...)", ::hexdisplay::HexDisplay::from(&self.0), &s[0..8])
+	}
+}
+
+impl Pair {
+	/// Generate new secure (random) key pair.
+	pub fn generate() -> Pair {
+		let mut seed = Seed::default();
+		SystemRandom::new().fill(&mut seed[..]).expect("system randomness is available; qed");
+		Self::from_seed(&seed)
+	}
+
+	/// Make a new key pair from a mini secret key.
+	pub fn from_seed(seed: &Seed) -> Pair {
+		let mini_key = MiniSecretKey::from_bytes(&seed[..])
+			.expect("32 bytes can always build a key; qed");
+		Pair(mini_key.expand_to_keypair())
+	}
+
+	/// Derive a child key from a series of junctions.
+	///
+	/// Both hard and soft junctions are supported.
+	pub fn derive<Iter: Iterator<Item=DeriveJunction>>(&self, path: Iter) -> Result<Pair, DeriveError> {
+		let secret = path.fold(self.0.secret.clone(), |acc, junction| match junction {
+			DeriveJunction::Soft(cc) => acc.derived_key_simple(ChainCode(cc), &[]).0,
+			DeriveJunction::Hard(cc) => derive_hard_junction(&acc, cc),
+		});
+
+		Ok(Pair(secret.into()))
+	}
+
+	/// Sign a message.
+	pub fn sign(&self, message: &[u8]) -> Signature {
+		let context = signing_context(SIGNING_CTX);
+		Signature::from(self.0.sign(context.bytes(message)).to_bytes())
+	}
+
+	/// Evaluate the VRF of this key pair on `message` in `context`.
+	///
+	/// Returns the output, which is unpredictable without the secret key, along with a proof
+	/// that can be checked with `vrf_verify`.
+	pub fn vrf_sign(&self, context: &[u8], message: &[u8]) -> (VrfOutput, VrfProof) {
+		let (inout, proof, _) = self.0.vrf_sign(signing_context(context).bytes(message));
+		(inout.to_output().to_bytes(), proof.to_bytes())
+	}
+
+	/// Get the public key.
+	pub fn public(&self) -> Public {
+		Public(self.0.public.to_bytes())
+	}
+}
+
+/// Derive a single hard junction.
+fn derive_hard_junction(secret: &SecretKey, cc: [u8; 32]) -> SecretKey {
+	secret.hard_derive_mini_secret_key(Some(ChainCode(cc)), b"").0.expand()
+}
+
+impl KeyPair for Pair {
+	fn from_seed(seed: &Seed) -> Pair {
+		Pair::from_seed(seed)
+	}
+
+	fn derive<Iter: Iterator<Item=DeriveJunction>>(&self, path: Iter) -> Result<Pair, DeriveError> {
+		Pair::derive(self, path)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn generated_pair_should_work() {
+		let pair = Pair::generate();
+		let public = pair.public();
+		let message = b"Something important";
+		let signature = pair.sign(&message[..]);
+		assert!(verify_strong(&signature, &message[..], &public));
+		assert!(!verify_strong(&signature, b"Something else", &public));
+	}
+
+	#[test]
+	fn vrf_should_work() {
+		let pair = Pair::from_seed(b"12345678901234567890123456789012");
+		let public = pair.public();
+		let (output, proof) = pair.vrf_sign(b"test", b"slot 1");
+		assert!(vrf_verify(b"test", b"slot 1", &output, &proof, &public));
+		assert!(!vrf_verify(b"test", b"slot 2", &output, &proof, &public));
+		assert!(!vrf_verify(b"other", b"slot 1", &output, &proof, &public));
+		assert!(!vrf_verify(b"test", b"slot 1", &[0u8; 32], &proof, &public));
+
+		// the output is deterministic, unlike the proof.
+		assert_eq!(pair.vrf_sign(b"test", b"slot 1").0, output);
+		assert!(pair.vrf_sign(b"test", b"slot 2").0 != output);
+	}
+
+	#[test]
+	fn seeded_pair_should_be_deterministic() {
+		let pair = Pair::from_seed(b"12345678901234567890123456789012");
+		let again = Pair::from_seed(b"12345678901234567890123456789012");
+		assert_eq!(pair.public(), again.public());
+	}
+
+	#[test]
+	fn generated_phrase_should_restore_the_same_pair() {
+		let (pair, phrase) = Pair::generate_with_phrase(None);
+		let restored = Pair::from_phrase(&phrase, None).unwrap();
+		assert_eq!(pair.public(), restored.public());
+	}
+
+	#[test]
+	fn soft_derivation_should_match_public_derivation() {
+		let pair = Pair::from_seed(b"12345678901234567890123456789012");
+		let path = vec![DeriveJunction::soft("stash"), DeriveJunction::soft(1u64)];
+		let derived = pair.derive(path.clone().into_iter()).unwrap();
+		assert_eq!(pair.public().derive(path.into_iter()), Some(derived.public()));
+	}
+
+	#[test]
+	fn hard_derivation_should_not_be_derivable_from_public() {
+		let pair = Pair::from_seed(b"12345678901234567890123456789012");
+		let path = vec![DeriveJunction::hard("alice")];
+		let derived = pair.derive(path.clone().into_iter()).unwrap();
+		assert!(derived.public() != pair.public());
+		assert_eq!(pair.public().derive(path.into_iter()), None);
+	}
+
+	#[test]
+	fn from_string_should_work() {
+		let phrase = "bottom drive obey lake curtain smoke basket hold race lonely fit walk";
+		let root = Pair::from_phrase(phrase, None).unwrap();
+		let expected = root.derive(vec![DeriveJunction::hard("alice"), DeriveJunction::soft("stash")].into_iter()).unwrap();
+
+		let pair = Pair::from_string(&format!("{}//alice/stash", phrase), None).unwrap();
+		assert_eq!(pair.public(), expected.public());
+	}
+
+	#[test]
+	fn ss58check_roundtrip_works() {
+		let pair = Pair::from_seed(b"12345678901234567890123456789012");
+		let public = pair.public();
+		let s = public.to_ss58check();
+		let cmp = Public::from_ss58check(&s).unwrap();
+		assert_eq!(cmp, public);
+	}
+}
@@ -38,11 +38,15 @@ pub type HttpServer = http::Server;
 pub type WsServer = ws::Server;
 
 /// Construct rpc `IoHandler`
-pub fn rpc_handler<Block: BlockT, ExHash, S, C, A, Y>(
+///
+/// The operator-only methods are only added if `author_unsafe` is given.
+pub fn rpc_handler<Block: BlockT, ExHash, S, C, A, U, Y, K>(
 	state: S,
 	chain: C,
 	author: A,
+	author_unsafe: Option<U>,
 	system: Y,
+	contracts: K,
 ) -> RpcHandler where
 	Block: BlockT + 'static,
 	ExHash: Send + Sync + 'static + sr_primitives::Serialize + sr_primitives::DeserializeOwned,
@@ -50,13 +54,19 @@ pub fn rpc_handler<Block: BlockT, ExHash, S, C, A, Y>(
 	S: apis::state::StateApi<Block::Hash, Metadata=Metadata>,
 	C: apis::chain::ChainApi<Block::Hash, Block::Header, NumberFor<Block>, SignedBlock<Block>, Metadata=Metadata>,
 	A: apis::author::AuthorApi<ExHash, Block::Hash, Metadata=Metadata>,
-	Y: apis::system::SystemApi,
+	U: apis::author::AuthorUnsafeApi<ExHash>,
+	Y: apis::system::SystemApi<Block::Hash, NumberFor<Block>>,
+	K: apis::contracts::ContractsApi<Block::Hash>,
 {
 	let mut io = pubsub::PubSubHandler::default();
 	io.extend_with(state.to_delegate());
 	io.extend_with(chain.to_delegate());
 	io.extend_with(author.to_delegate());
+	if let Some(author_unsafe) = author_unsafe {
+		io.extend_with(author_unsafe.to_delegate());
+	}
 	io.extend_with(system.to_delegate());
+	io.extend_with(contracts.to_delegate());
 	io
 }
 
@@ -17,6 +17,7 @@
 //! Authoring RPC module errors.
 
 use client;
+use keystore;
 use transaction_pool::txpool;
 use rpc;
 
@@ -26,6 +27,7 @@ error_chain! {
 	links {
 		Pool(txpool::error::Error, txpool::error::ErrorKind) #[doc = "Pool error"];
 		Client(client::error::Error, client::error::ErrorKind) #[doc = "Client error"];
+		Keystore(keystore::Error, keystore::ErrorKind) #[doc = "Keystore error"];
 	}
 	errors {
 		/// Not implemented yet
@@ -43,6 +45,11 @@ error_chain! {
 			description("extrinsic verification error"),
 			display("Extrinsic verification error: {}", e.description()),
 		}
+		/// The secret URI doesn't correspond to the given public key.
+		KeyMismatch {
+			description("public key mismatch"),
+			display("The secret URI doesn't correspond to the given public key"),
+		}
 	}
 }
 
@@ -62,6 +69,16 @@ impl From<Error> for rpc::Error {
 				message: e.description().into(),
 				data: Some(format!("{:?}", e).into()),
 			},
+			Error(ErrorKind::KeyMismatch, _) => rpc::Error {
+				code: rpc::ErrorCode::ServerError(ERROR + 3),
+				message: "The secret URI doesn't correspond to the given public key.".into(),
+				data: None,
+			},
+			Error(ErrorKind::Keystore(e), _) => rpc::Error {
+				code: rpc::ErrorCode::ServerError(ERROR + 4),
+				message: format!("{}", e),
+				data: None,
+			},
 			e => errors::internal(e),
 		}
 	}
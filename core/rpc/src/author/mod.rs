@@ -20,6 +20,7 @@ use std::sync::Arc;
 
 use client::{self, Client};
 use codec::{Encode, Decode};
+use keystore::{KeyType, Store as Keystore};
 use transaction_pool::{
 	txpool::{
 		ChainApi as PoolChainApi,
@@ -32,7 +33,7 @@ use transaction_pool::{
 };
 use jsonrpc_macros::pubsub;
 use jsonrpc_pubsub::SubscriptionId;
-use primitives::{Bytes, Blake2Hasher, H256};
+use primitives::{Bytes, Blake2Hasher, H256, ed25519, crypto::KeyPair};
 use rpc::futures::{Sink, Stream, Future};
 use runtime_primitives::{generic, traits};
 use subscriptions::Subscriptions;
@@ -44,6 +45,39 @@ mod tests;
 
 use self::error::Result;
 
+/// An extrinsic to be removed from the pool, identified either by its hash or by a tag it provides.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExtrinsicOrTag<Hash> {
+	/// The hash of the extrinsic.
+	Hash(Hash),
+	/// A tag provided by the extrinsic, e.g. SCALE-encoded `(sender, index)` for signed extrinsics
+	/// of the node runtime. All extrinsics providing the tag are removed.
+	Tag(Bytes),
+}
+
+/// Status of the transaction pool.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolStatus<Hash> {
+	/// Number of extrinsics in the ready queue.
+	pub ready: usize,
+	/// Number of extrinsics in the future queue.
+	pub future: usize,
+	/// Extrinsics in the future queue along with the tags they are waiting for.
+	pub future_extrinsics: Vec<FutureExtrinsic<Hash>>,
+}
+
+/// An extrinsic that is not ready to be included in a block yet.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FutureExtrinsic<Hash> {
+	/// The hash of the extrinsic.
+	pub hash: Hash,
+	/// Required tags that are not provided by any ready extrinsic.
+	pub missing_tags: Vec<Bytes>,
+}
+
 build_rpc_trait! {
 	/// Substrate authoring RPC API
 	pub trait AuthorApi<Hash, BlockHash> {
@@ -57,6 +91,10 @@ build_rpc_trait! {
 		#[rpc(name = "author_pendingExtrinsics")]
 		fn pending_extrinsics(&self) -> Result<Vec<Bytes>>;
 
+		/// Returns the number of ready and future extrinsics and what the future ones are waiting for.
+		#[rpc(name = "author_poolStatus")]
+		fn pool_status(&self) -> Result<PoolStatus<Hash>>;
+
 		#[pubsub(name = "author_extrinsicUpdate")] {
 			/// Submit an extrinsic to watch.
 			#[rpc(name = "author_submitAndWatchExtrinsic")]
@@ -70,6 +108,35 @@ build_rpc_trait! {
 	}
 }
 
+build_rpc_trait! {
+	/// Substrate authoring RPC API meant for the node operator only.
+	///
+	/// It is not exposed by the RPC servers listening on non-local interfaces.
+	pub trait AuthorUnsafeApi<Hash> {
+		/// Remove given extrinsics from the pool and temporarily ban them to prevent reimporting.
+		///
+		/// Extrinsics depending on the removed ones are removed as well.
+		/// Returns hashes of all removed extrinsics.
+		#[rpc(name = "author_removeExtrinsic")]
+		fn remove_extrinsic(&self, Vec<ExtrinsicOrTag<Hash>>) -> Result<Vec<Hash>>;
+
+		/// Insert a key of given type (`session`, `account` or `grandpa`) into the keystore.
+		///
+		/// The key is given as a secret URI, e.g. a BIP39 phrase followed by a derivation path,
+		/// and has to correspond to the given public key. It becomes the current key of its type.
+		#[rpc(name = "author_insertKey")]
+		fn insert_key(&self, String, String, Bytes) -> Result<()>;
+
+		/// Generate new session keys in the keystore and return their SCALE-encoded public keys.
+		///
+		/// A key is generated for every session key type, i.e. `session` and `grandpa`, in that order.
+		/// A running authority authors with the new session key from the next slot and votes with
+		/// the new GRANDPA key from the next round on.
+		#[rpc(name = "author_rotateKeys")]
+		fn rotate_keys(&self) -> Result<Bytes>;
+	}
+}
+
 /// Authoring API
 pub struct Author<B, E, P, RA> where P: PoolChainApi + Sync + Send + 'static {
 	/// Substrate client
@@ -78,6 +145,10 @@ pub struct Author<B, E, P, RA> where P: PoolChainApi + Sync + Send + 'static {
 	pool: Arc<Pool<P>>,
 	/// Subscriptions manager
 	subscriptions: Subscriptions,
+	/// The keystore session keys are inserted into and generated in.
+	keystore: Arc<Keystore>,
+	/// The password the keys are stored with.
+	keystore_password: String,
 }
 
 impl<B, E, P, RA> Author<B, E, P, RA> where P: PoolChainApi + Sync + Send + 'static {
@@ -86,11 +157,15 @@ impl<B, E, P, RA> Author<B, E, P, RA> where P: PoolChainApi + Sync + Send + 'sta
 		client: Arc<Client<B, E, <P as PoolChainApi>::Block, RA>>,
 		pool: Arc<Pool<P>>,
 		subscriptions: Subscriptions,
+		keystore: Arc<Keystore>,
+		keystore_password: String,
 	) -> Self {
 		Author {
 			client,
 			pool,
 			subscriptions,
+			keystore,
+			keystore_password,
 		}
 	}
 }
@@ -120,6 +195,23 @@ impl<B, E, P, RA> AuthorApi<ExHash<P>, BlockHash<P>> for Author<B, E, P, RA> whe
 		Ok(self.pool.ready().map(|tx| tx.data.encode().into()).collect())
 	}
 
+	fn pool_status(&self) -> Result<PoolStatus<ExHash<P>>> {
+		let status = self.pool.status();
+		let future_extrinsics = self.pool.futures_missing_tags()
+			.into_iter()
+			.map(|(hash, missing_tags)| FutureExtrinsic {
+				hash,
+				missing_tags: missing_tags.into_iter().map(Bytes).collect(),
+			})
+			.collect();
+
+		Ok(PoolStatus {
+			ready: status.ready,
+			future: status.future,
+			future_extrinsics,
+		})
+	}
+
 	fn watch_extrinsic(&self, _metadata: Self::Metadata, subscriber: pubsub::Subscriber<Status<ExHash<P>, BlockHash<P>>>, xt: Bytes) {
 		let submit = || -> Result<_> {
 			let best_block_hash = self.client.info()?.chain.best_hash;
@@ -153,3 +245,43 @@ impl<B, E, P, RA> AuthorApi<ExHash<P>, BlockHash<P>> for Author<B, E, P, RA> whe
 		Ok(self.subscriptions.cancel(id))
 	}
 }
+
+impl<B, E, P, RA> AuthorUnsafeApi<ExHash<P>> for Author<B, E, P, RA> where
+	B: client::backend::Backend<<P as PoolChainApi>::Block, Blake2Hasher> + Send + Sync + 'static,
+	E: client::CallExecutor<<P as PoolChainApi>::Block, Blake2Hasher> + Send + Sync + 'static,
+	P: PoolChainApi + Sync + Send + 'static,
+	P::Block: traits::Block<Hash=H256>,
+	P::Error: 'static,
+	RA: Send + Sync + 'static
+{
+	fn remove_extrinsic(&self, extrinsics: Vec<ExtrinsicOrTag<ExHash<P>>>) -> Result<Vec<ExHash<P>>> {
+		let hashes = extrinsics.into_iter()
+			.flat_map(|extrinsic| match extrinsic {
+				ExtrinsicOrTag::Hash(hash) => vec![hash],
+				ExtrinsicOrTag::Tag(tag) => self.pool.hashes_providing(&[tag.0]),
+			})
+			.collect::<Vec<_>>();
+
+		Ok(self.pool.remove(&hashes).into_iter().map(|tx| tx.hash.clone()).collect())
+	}
+
+	fn insert_key(&self, key_type: String, suri: String, public: Bytes) -> Result<()> {
+		let key_type: KeyType = key_type.parse()?;
+		let expected = ed25519::Pair::from_string(&suri, None)
+			.map_err(|e| error::ErrorKind::Keystore(::keystore::ErrorKind::InvalidSecretUri(e)))?;
+		if expected.public().as_slice() != &public[..] {
+			return Err(error::ErrorKind::KeyMismatch.into());
+		}
+		self.keystore.insert_by_type(key_type, &suri, &self.keystore_password)?;
+		Ok(())
+	}
+
+	fn rotate_keys(&self) -> Result<Bytes> {
+		let mut keys = Vec::new();
+		for key_type in KeyType::SESSION_KEYS.iter() {
+			let pair = self.keystore.generate_by_type(*key_type, &self.keystore_password)?;
+			pair.public().encode_to(&mut keys);
+		}
+		Ok(keys.into())
+	}
+}
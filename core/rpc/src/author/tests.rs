@@ -27,6 +27,7 @@ use test_client::keyring::Keyring;
 use test_client::runtime::{Extrinsic, Transfer};
 use test_client;
 use tokio::runtime;
+use tempdir::TempDir;
 
 fn uxt(sender: Keyring, nonce: u64) -> Extrinsic {
 	let tx = Transfer {
@@ -39,6 +40,10 @@ fn uxt(sender: Keyring, nonce: u64) -> Extrinsic {
 	Extrinsic { transfer: tx, signature }
 }
 
+fn keystore() -> Arc<Keystore> {
+	Arc::new(Keystore::open(TempDir::new("keystore").unwrap().into_path()).unwrap())
+}
+
 #[test]
 fn submit_transaction_should_not_cause_error() {
 	let runtime = runtime::Runtime::new().unwrap();
@@ -47,6 +52,8 @@ fn submit_transaction_should_not_cause_error() {
 		client: client.clone(),
 		pool: Arc::new(Pool::new(Default::default(), ChainApi::new(client))),
 		subscriptions: Subscriptions::new(runtime.executor()),
+		keystore: keystore(),
+		keystore_password: String::new(),
 	};
 	let h: H256 = hex!("e10ad66bce51ef3e2a1167934ce3740d2d8c703810f9b314e89f2e783f75e826").into();
 
@@ -67,6 +74,8 @@ fn submit_rich_transaction_should_not_cause_error() {
 		client: client.clone(),
 		pool: Arc::new(Pool::new(Default::default(), ChainApi::new(client.clone()))),
 		subscriptions: Subscriptions::new(runtime.executor()),
+		keystore: keystore(),
+		keystore_password: String::new(),
 	};
 	let h: H256 = hex!("fccc48291473c53746cd267cf848449edd7711ee6511fba96919d5f9f4859e4f").into();
 
@@ -89,6 +98,8 @@ fn should_watch_extrinsic() {
 		client,
 		pool: pool.clone(),
 		subscriptions: Subscriptions::new(runtime.executor()),
+		keystore: keystore(),
+		keystore_password: String::new(),
 	};
 	let (subscriber, id_rx, data) = ::jsonrpc_macros::pubsub::Subscriber::new_test("test");
 
@@ -129,6 +140,8 @@ fn should_return_pending_extrinsics() {
 		client,
 		pool: pool.clone(),
 		subscriptions: Subscriptions::new(runtime.executor()),
+		keystore: keystore(),
+		keystore_password: String::new(),
 	};
 	let ex = uxt(Keyring::Alice, 0);
 	AuthorApi::submit_extrinsic(&p, ex.encode().into()).unwrap();
@@ -137,3 +150,135 @@ fn should_return_pending_extrinsics() {
 		Ok(ref expected) if *expected == vec![Bytes(ex.encode())]
 	);
 }
+
+#[test]
+fn should_remove_extrinsics() {
+	let runtime = runtime::Runtime::new().unwrap();
+	let client = Arc::new(test_client::new());
+	let pool = Arc::new(Pool::new(Default::default(), ChainApi::new(client.clone())));
+	let p = Author {
+		client,
+		pool: pool.clone(),
+		subscriptions: Subscriptions::new(runtime.executor()),
+		keystore: keystore(),
+		keystore_password: String::new(),
+	};
+	let hash1 = p.submit_extrinsic(uxt(Keyring::Alice, 0).encode().into()).unwrap();
+	let hash2 = p.submit_extrinsic(uxt(Keyring::Alice, 1).encode().into()).unwrap();
+	let hash3 = p.submit_extrinsic(uxt(Keyring::Bob, 0).encode().into()).unwrap();
+	assert_eq!(pool.status().ready, 3);
+
+	// removing the first extrinsic of Alice removes the one depending on it as well
+	let mut removed = p.remove_extrinsic(vec![ExtrinsicOrTag::Hash(hash1)]).unwrap();
+	removed.sort();
+	let mut expected = vec![hash1, hash2];
+	expected.sort();
+	assert_eq!(removed, expected);
+	assert_eq!(pool.status().ready, 1);
+	// and the removed extrinsic is banned
+	assert!(p.submit_extrinsic(uxt(Keyring::Alice, 0).encode().into()).is_err());
+	assert_eq!(pool.ready().map(|tx| tx.hash).collect::<Vec<_>>(), vec![hash3]);
+}
+
+#[test]
+fn should_report_pool_status_and_remove_by_tag() {
+	let runtime = runtime::Runtime::new().unwrap();
+	let client = Arc::new(test_client::new());
+	let pool = Arc::new(Pool::new(Default::default(), ChainApi::new(client.clone())));
+	let p = Author {
+		client,
+		pool: pool.clone(),
+		subscriptions: Subscriptions::new(runtime.executor()),
+		keystore: keystore(),
+		keystore_password: String::new(),
+	};
+	let hash = p.submit_extrinsic(uxt(Keyring::Alice, 1).encode().into()).unwrap();
+
+	// the extrinsic waits for the one with the previous index
+	let status = p.pool_status().unwrap();
+	assert_eq!(status.ready, 0);
+	assert_eq!(status.future, 1);
+	assert_eq!(status.future_extrinsics.len(), 1);
+	assert_eq!(status.future_extrinsics[0].hash, hash);
+	assert_eq!(status.future_extrinsics[0].missing_tags.len(), 1);
+	let missing_tag = status.future_extrinsics[0].missing_tags[0].clone();
+
+	// the tag is provided by the missing extrinsic
+	let provider = p.submit_extrinsic(uxt(Keyring::Alice, 0).encode().into()).unwrap();
+	let status = p.pool_status().unwrap();
+	assert_eq!((status.ready, status.future), (2, 0));
+	assert!(status.future_extrinsics.is_empty());
+
+	// removing by tag removes the provider and everything depending on it
+	let mut removed = p.remove_extrinsic(vec![ExtrinsicOrTag::Tag(missing_tag)]).unwrap();
+	removed.sort();
+	let mut expected = vec![hash, provider];
+	expected.sort();
+	assert_eq!(removed, expected);
+	assert_eq!((pool.status().ready, pool.status().future), (0, 0));
+}
+
+#[test]
+fn should_deserialize_extrinsic_or_tag() {
+	let hash: ExtrinsicOrTag<H256> = ::serde_json::from_str(
+		r#"{"hash":"0x0000000000000000000000000000000000000000000000000000000000000001"}"#
+	).unwrap();
+	assert_eq!(hash, ExtrinsicOrTag::Hash(H256::from_low_u64_be(1)));
+	let tag: ExtrinsicOrTag<H256> = ::serde_json::from_str(r#"{"tag":"0x0102"}"#).unwrap();
+	assert_eq!(tag, ExtrinsicOrTag::Tag(Bytes(vec![1, 2])));
+}
+
+#[test]
+fn should_insert_key() {
+	let runtime = runtime::Runtime::new().unwrap();
+	let client = Arc::new(test_client::new());
+	let keystore = keystore();
+	let p = Author {
+		client: client.clone(),
+		pool: Arc::new(Pool::new(Default::default(), ChainApi::new(client))),
+		subscriptions: Subscriptions::new(runtime.executor()),
+		keystore: keystore.clone(),
+		keystore_password: String::new(),
+	};
+	let suri = "bottom drive obey lake curtain smoke basket hold race lonely fit walk//alice";
+	let public = ed25519::Pair::from_string(suri, None).unwrap().public();
+
+	// when
+	AuthorUnsafeApi::insert_key(&p, "account".into(), suri.into(), public.as_slice().to_vec().into()).unwrap();
+
+	// then
+	assert_eq!(keystore.contents_by_type(KeyType::Account).unwrap(), vec![public.clone()]);
+	assert!(keystore.contents_by_type(KeyType::Session).unwrap().is_empty());
+	assert_matches!(
+		AuthorUnsafeApi::insert_key(&p, "account".into(), suri.into(), vec![0u8; 32].into()),
+		Err(error::Error(error::ErrorKind::KeyMismatch, _))
+	);
+	assert!(AuthorUnsafeApi::insert_key(&p, "unknown".into(), suri.into(), public.as_slice().to_vec().into()).is_err());
+}
+
+#[test]
+fn should_rotate_keys() {
+	let runtime = runtime::Runtime::new().unwrap();
+	let client = Arc::new(test_client::new());
+	let keystore = keystore();
+	let p = Author {
+		client: client.clone(),
+		pool: Arc::new(Pool::new(Default::default(), ChainApi::new(client))),
+		subscriptions: Subscriptions::new(runtime.executor()),
+		keystore: keystore.clone(),
+		keystore_password: "thepassword".into(),
+	};
+
+	// when
+	let keys = AuthorUnsafeApi::rotate_keys(&p).unwrap();
+
+	// then
+	let session = keystore.contents_by_type(KeyType::Session).unwrap();
+	let grandpa = keystore.contents_by_type(KeyType::Grandpa).unwrap();
+	assert_eq!(session.len(), 1);
+	assert_eq!(grandpa.len(), 1);
+	assert_eq!(keys.0, (session[0].clone(), grandpa[0].clone()).encode());
+	assert_eq!(keystore.current_by_type(KeyType::Grandpa).unwrap(), Some(grandpa[0].clone()));
+	assert!(keystore.load_by_type(KeyType::Grandpa, &grandpa[0], "thepassword").is_ok());
+	assert!(keystore.load_by_type(KeyType::Grandpa, &grandpa[0], "").is_err());
+}
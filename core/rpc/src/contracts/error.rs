@@ -0,0 +1,57 @@
+// Copyright 2018 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Contracts RPC module errors.
+
+use client;
+use rpc;
+
+use errors;
+
+error_chain! {
+	links {
+		Client(client::error::Error, client::error::ErrorKind) #[doc = "Client error"];
+	}
+
+	errors {
+		/// The runtime doesn't support the contracts API.
+		Unsupported {
+			description("contracts API is not supported by the runtime"),
+			display("The runtime doesn't support the contracts API"),
+		}
+		/// The runtime returned a result that couldn't be decoded.
+		InvalidResult {
+			description("invalid call result"),
+			display("The runtime returned an invalid call result"),
+		}
+	}
+}
+
+const ERROR: i64 = 2000;
+
+impl From<Error> for rpc::Error {
+	fn from(e: Error) -> Self {
+		match e {
+			Error(ErrorKind::Unsupported, _) => rpc::Error {
+				code: rpc::ErrorCode::ServerError(ERROR + 1),
+				message: "The runtime doesn't support the contracts API".into(),
+				data: None,
+			},
+			e => errors::internal(e),
+		}
+	}
+}
@@ -0,0 +1,155 @@
+// Copyright 2018 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Substrate contracts API.
+//!
+//! Allows to perform contract calls against the state of a block without committing
+//! them, e.g. to estimate the gas required by a call or to read the state of a contract.
+
+use std::sync::Arc;
+
+use client::{self, Client, CallExecutor, runtime_api::{ContractExecResult, id::CONTRACTS}};
+use codec::{Decode, Encode};
+use jsonrpc_macros::Trailing;
+use primitives::{Blake2Hasher, Bytes, H256};
+use runtime_primitives::generic::BlockId;
+use runtime_primitives::traits::Block as BlockT;
+
+mod error;
+#[cfg(test)]
+mod tests;
+
+use self::error::{ErrorKind, Result};
+
+/// A contract call request.
+///
+/// The account ids and the value are SCALE-encoded, since their types are defined by the runtime.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallRequest {
+	/// The encoded account id of the caller.
+	pub origin: Bytes,
+	/// The encoded account id of the contract to call.
+	pub dest: Bytes,
+	/// The encoded balance to transfer to the contract.
+	pub value: Bytes,
+	/// The maximum amount of gas the call can consume.
+	pub gas_limit: u64,
+	/// The input data passed to the contract.
+	pub input_data: Bytes,
+}
+
+/// The result of a contract call.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CallResult {
+	/// The call succeeded.
+	#[serde(rename_all = "camelCase")]
+	Success {
+		/// The data returned by the contract.
+		output: Bytes,
+		/// The amount of gas consumed by the call.
+		gas_consumed: u64,
+		/// The encoded runtime events that the call would emit.
+		events: Vec<Bytes>,
+	},
+	/// The call failed.
+	#[serde(rename_all = "camelCase")]
+	Error {
+		/// The reason of the failure.
+		reason: String,
+		/// The amount of gas consumed by the call.
+		gas_consumed: u64,
+	},
+}
+
+impl From<ContractExecResult> for CallResult {
+	fn from(result: ContractExecResult) -> Self {
+		match result {
+			ContractExecResult::Success { output, gas_consumed, events } => CallResult::Success {
+				output: output.into(),
+				gas_consumed,
+				events: events.into_iter().map(Into::into).collect(),
+			},
+			ContractExecResult::Error { reason, gas_consumed } => CallResult::Error {
+				reason: String::from_utf8_lossy(&reason).into_owned(),
+				gas_consumed,
+			},
+		}
+	}
+}
+
+build_rpc_trait! {
+	/// Substrate contracts API
+	pub trait ContractsApi<Hash> {
+		/// Executes a call to a contract at a block's state without committing it.
+		///
+		/// Returns the output of the contract, the gas consumed and the events the call would
+		/// emit. No fees are charged and no rent is collected.
+		#[rpc(name = "contracts_call")]
+		fn call(&self, CallRequest, Trailing<Hash>) -> Result<CallResult>;
+	}
+}
+
+/// Contracts API.
+pub struct Contracts<B, E, Block: BlockT, RA> {
+	/// Substrate client.
+	client: Arc<Client<B, E, Block, RA>>,
+}
+
+impl<B, E, Block: BlockT, RA> Contracts<B, E, Block, RA> {
+	/// Create new Contracts API RPC handler.
+	pub fn new(client: Arc<Client<B, E, Block, RA>>) -> Self {
+		Self {
+			client,
+		}
+	}
+}
+
+impl<B, E, Block, RA> ContractsApi<Block::Hash> for Contracts<B, E, Block, RA> where
+	Block: BlockT<Hash=H256> + 'static,
+	B: client::backend::Backend<Block, Blake2Hasher> + Send + Sync + 'static,
+	E: CallExecutor<Block, Blake2Hasher> + Send + Sync + 'static,
+{
+	fn call(&self, request: CallRequest, block: Trailing<Block::Hash>) -> Result<CallResult> {
+		let block = ::helpers::unwrap_or_else(|| Ok(self.client.info()?.chain.best_hash), block)?;
+		let at = BlockId::Hash(block);
+
+		if !self.client.runtime_version_at(&at)?.has_api(CONTRACTS, 1) {
+			bail!(ErrorKind::Unsupported);
+		}
+
+		// The arguments are encoded as a tuple. The account ids and the value are already encoded.
+		let CallRequest { origin, dest, value, gas_limit, input_data } = request;
+		let mut call_data = Vec::new();
+		call_data.extend_from_slice(&origin.0);
+		call_data.extend_from_slice(&dest.0);
+		call_data.extend_from_slice(&value.0);
+		gas_limit.encode_to(&mut call_data);
+		input_data.0.encode_to(&mut call_data);
+
+		trace!(target: "rpc", "Calling contract at {:?}", block);
+		let return_data = self.client
+			.executor()
+			.call(&at, "contracts_call", &call_data)?
+			.return_data;
+
+		ContractExecResult::decode(&mut &return_data[..])
+			.map(Into::into)
+			.ok_or_else(|| ErrorKind::InvalidResult.into())
+	}
+}
@@ -0,0 +1,73 @@
+// Copyright 2018 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+
+use super::*;
+use self::error::{Error, ErrorKind};
+
+use test_client;
+
+#[test]
+fn should_reject_calls_if_runtime_has_no_contracts_api() {
+	let client = Arc::new(test_client::new());
+	let genesis_hash = client.genesis_hash();
+	let api = Contracts::new(client);
+
+	let request = CallRequest {
+		origin: Bytes(vec![1; 32]),
+		dest: Bytes(vec![2; 32]),
+		value: Bytes(vec![0; 16]),
+		gas_limit: 10_000,
+		input_data: Bytes(Vec::new()),
+	};
+
+	assert_matches!(
+		api.call(request, Some(genesis_hash).into()),
+		Err(Error(ErrorKind::Unsupported, _))
+	)
+}
+
+#[test]
+fn call_request_should_deserialize() {
+	let request: CallRequest = ::serde_json::from_str(r#"{
+		"origin": "0x01",
+		"dest": "0x02",
+		"value": "0x03",
+		"gasLimit": 1000,
+		"inputData": "0x0405"
+	}"#).unwrap();
+
+	assert_eq!(request, CallRequest {
+		origin: Bytes(vec![1]),
+		dest: Bytes(vec![2]),
+		value: Bytes(vec![3]),
+		gas_limit: 1000,
+		input_data: Bytes(vec![4, 5]),
+	});
+}
+
+#[test]
+fn call_result_should_serialize() {
+	let result: CallResult = ContractExecResult::Error {
+		reason: b"contract has been evicted".to_vec(),
+		gas_consumed: 135,
+	}.into();
+
+	assert_eq!(
+		::serde_json::to_string(&result).unwrap(),
+		r#"{"error":{"reason":"contract has been evicted","gasConsumed":135}}"#
+	);
+}
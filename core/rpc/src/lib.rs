@@ -23,10 +23,13 @@ extern crate jsonrpc_pubsub;
 extern crate parking_lot;
 extern crate parity_codec as codec;
 extern crate substrate_client as client;
+extern crate substrate_keystore as keystore;
+extern crate substrate_network as network;
 extern crate substrate_transaction_pool as transaction_pool;
 extern crate substrate_primitives as primitives;
 extern crate sr_primitives as runtime_primitives;
 extern crate sr_version as runtime_version;
+extern crate serde_json;
 extern crate tokio;
 
 #[macro_use]
@@ -35,6 +38,8 @@ extern crate error_chain;
 extern crate jsonrpc_macros;
 #[macro_use]
 extern crate log;
+#[macro_use]
+extern crate serde_derive;
 
 #[cfg(test)]
 #[macro_use]
@@ -47,7 +52,11 @@ extern crate substrate_test_client as test_client;
 #[cfg(test)]
 extern crate substrate_consensus_common as consensus;
 #[cfg(test)]
+extern crate substrate_state_machine as state_machine;
+#[cfg(test)]
 extern crate rustc_hex;
+#[cfg(test)]
+extern crate tempdir;
 
 mod errors;
 mod helpers;
@@ -57,6 +66,7 @@ pub use subscriptions::Subscriptions;
 
 pub mod author;
 pub mod chain;
+pub mod contracts;
 pub mod metadata;
 pub mod state;
 pub mod system;
@@ -30,6 +30,11 @@ error_chain! {
 			description("Invalid block range"),
 			display("Cannot resolve a block range ['{:?}' ... '{:?}]. {}", from, to, details),
 		}
+		/// Requested too many storage keys at once.
+		InvalidCount(value: u32, max: u32) {
+			description("Invalid count requested"),
+			display("Requested {} keys, but at most {} can be requested at once", value, max),
+		}
 		/// Not implemented yet
 		Unimplemented {
 			description("not implemented yet"),
@@ -42,6 +47,11 @@ impl From<Error> for rpc::Error {
 	fn from(e: Error) -> Self {
 		match e {
 			Error(ErrorKind::Unimplemented, _) => errors::unimplemented(),
+			Error(ErrorKind::InvalidCount(value, max), _) => rpc::Error {
+				code: rpc::ErrorCode::InvalidParams,
+				message: format!("Requested {} keys, but at most {} can be requested at once", value, max),
+				data: None,
+			},
 			e => errors::internal(e),
 		}
 	}
@@ -42,6 +42,22 @@ mod tests;
 
 use self::error::Result;
 
+/// The maximum number of keys that can be returned by `state_getKeysPaged`.
+const STORAGE_KEYS_PAGED_MAX_COUNT: u32 = 1000;
+
+/// The maximum number of keys a proof can be requested for by `state_getReadProof`.
+const READ_PROOF_MAX_KEYS: u32 = 1000;
+
+/// Read proof of a set of storage entries.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadProof<Hash> {
+	/// Block hash used to generate the proof.
+	pub at: Hash,
+	/// Trie nodes proving the values of the keys (or their absence) against the state root of the block.
+	pub proof: Vec<Bytes>,
+}
+
 build_rpc_trait! {
 	/// Substrate state API
 	pub trait StateApi<Hash> {
@@ -51,6 +67,17 @@ build_rpc_trait! {
 		#[rpc(name = "state_call", alias = ["state_callAt", ])]
 		fn call(&self, String, Bytes, Trailing<Hash>) -> Result<Bytes>;
 
+		/// Returns the keys with prefix, leave empty to get all the keys.
+		#[rpc(name = "state_getKeys")]
+		fn storage_keys(&self, StorageKey, Trailing<Hash>) -> Result<Vec<StorageKey>>;
+
+		/// Returns at most `count` keys with prefix that follow `start_key`, in lexicographic order.
+		///
+		/// The keys are returned from the beginning if `start_key` is not given. To get the next page,
+		/// pass the last key of the previous page as `start_key`.
+		#[rpc(name = "state_getKeysPaged")]
+		fn storage_keys_paged(&self, StorageKey, u32, Option<StorageKey>, Trailing<Hash>) -> Result<Vec<StorageKey>>;
+
 		/// Returns a storage entry at a specific block's state.
 		#[rpc(name = "state_getStorage", alias = ["state_getStorageAt", ])]
 		fn storage(&self, StorageKey, Trailing<Hash>) -> Result<Option<StorageData>>;
@@ -63,6 +90,12 @@ build_rpc_trait! {
 		#[rpc(name = "state_getStorageSize", alias = ["state_getStorageSizeAt", ])]
 		fn storage_size(&self, StorageKey, Trailing<Hash>) -> Result<Option<u64>>;
 
+		/// Returns a proof of the storage entries at a specific block's state.
+		///
+		/// At most 1000 keys can be proven at once.
+		#[rpc(name = "state_getReadProof")]
+		fn read_proof(&self, Vec<StorageKey>, Trailing<Hash>) -> Result<ReadProof<Hash>>;
+
 		/// Returns the runtime metadata as an opaque blob.
 		#[rpc(name = "state_getMetadata")]
 		fn metadata(&self, Trailing<Hash>) -> Result<Bytes>;
@@ -135,6 +168,27 @@ impl<B, E, Block, RA> StateApi<Block::Hash> for State<B, E, Block, RA> where
 		Ok(Bytes(return_data))
 	}
 
+	fn storage_keys(&self, key_prefix: StorageKey, block: Trailing<Block::Hash>) -> Result<Vec<StorageKey>> {
+		let block = self.unwrap_or_best(block)?;
+		trace!(target: "rpc", "Querying storage keys at {:?}", block);
+		Ok(self.client.storage_keys(&BlockId::Hash(block), &key_prefix)?)
+	}
+
+	fn storage_keys_paged(
+		&self,
+		key_prefix: StorageKey,
+		count: u32,
+		start_key: Option<StorageKey>,
+		block: Trailing<Block::Hash>,
+	) -> Result<Vec<StorageKey>> {
+		if count > STORAGE_KEYS_PAGED_MAX_COUNT {
+			bail!(error::ErrorKind::InvalidCount(count, STORAGE_KEYS_PAGED_MAX_COUNT));
+		}
+		let block = self.unwrap_or_best(block)?;
+		trace!(target: "rpc", "Querying storage keys at {:?}", block);
+		Ok(self.client.storage_keys_paged(&BlockId::Hash(block), &key_prefix, start_key.as_ref(), count as usize)?)
+	}
+
 	fn storage(&self, key: StorageKey, block: Trailing<Block::Hash>) -> Result<Option<StorageData>> {
 		let block = self.unwrap_or_best(block)?;
 		trace!(target: "rpc", "Querying storage at {:?} for key {}", block, HexDisplay::from(&key.0));
@@ -150,6 +204,20 @@ impl<B, E, Block, RA> StateApi<Block::Hash> for State<B, E, Block, RA> where
 		Ok(self.storage(key, block)?.map(|x| x.0.len() as u64))
 	}
 
+	fn read_proof(&self, keys: Vec<StorageKey>, block: Trailing<Block::Hash>) -> Result<ReadProof<Block::Hash>> {
+		if keys.len() > READ_PROOF_MAX_KEYS as usize {
+			bail!(error::ErrorKind::InvalidCount(keys.len() as u32, READ_PROOF_MAX_KEYS));
+		}
+		let block = self.unwrap_or_best(block)?;
+		trace!(target: "rpc", "Generating read proof at {:?} for {} keys", block, keys.len());
+		let keys = keys.into_iter().map(|key| key.0).collect::<Vec<_>>();
+		let proof = self.client.read_proof_keys(&BlockId::Hash(block), &keys)?;
+		Ok(ReadProof {
+			at: block,
+			proof: proof.into_iter().map(Bytes).collect(),
+		})
+	}
+
 	fn metadata(&self, block: Trailing<Block::Hash>) -> Result<Bytes> {
 		let block = self.unwrap_or_best(block)?;
 		self.client.runtime_api().metadata(&BlockId::Hash(block)).map(Into::into).map_err(Into::into)
@@ -35,6 +35,64 @@ fn should_return_storage() {
 	)
 }
 
+#[test]
+fn should_return_storage_keys() {
+	let core = ::tokio::runtime::Runtime::new().unwrap();
+	let client = Arc::new(test_client::new());
+	let genesis_hash = client.genesis_hash();
+	let client = State::new(client, Subscriptions::new(core.executor()));
+
+	let keys = client.storage_keys(StorageKey(b":".to_vec()), Some(genesis_hash).into()).unwrap();
+	assert!(keys.contains(&StorageKey(b":code".to_vec())));
+	assert!(keys.iter().all(|key| key.0.starts_with(b":")));
+	let mut sorted_keys = keys.clone();
+	sorted_keys.sort();
+	assert_eq!(keys, sorted_keys);
+
+	let first_page = client.storage_keys_paged(StorageKey(b":".to_vec()), 1, None, Some(genesis_hash).into()).unwrap();
+	assert_eq!(first_page, keys[..1].to_vec());
+	let second_page = client.storage_keys_paged(
+		StorageKey(b":".to_vec()),
+		2,
+		Some(first_page[0].clone()),
+		Some(genesis_hash).into(),
+	).unwrap();
+	assert_eq!(second_page, keys[1..3].to_vec());
+
+	assert_matches!(
+		client.storage_keys_paged(StorageKey(b":".to_vec()), 1001, None, Some(genesis_hash).into()),
+		Err(Error(ErrorKind::InvalidCount(1001, 1000), _))
+	);
+}
+
+#[test]
+fn should_return_read_proof() {
+	let core = ::tokio::runtime::Runtime::new().unwrap();
+	let client = Arc::new(test_client::new());
+	let genesis_hash = client.genesis_hash();
+	let state_root = *client.header(&BlockId::Hash(genesis_hash)).unwrap().unwrap().state_root();
+	let code = client.storage(&BlockId::Hash(genesis_hash), &StorageKey(b":code".to_vec())).unwrap().unwrap();
+	let api = State::new(client, Subscriptions::new(core.executor()));
+
+	let keys = vec![StorageKey(b":code".to_vec()), StorageKey(vec![10])];
+	let read_proof = api.read_proof(keys, Some(genesis_hash).into()).unwrap();
+	assert_eq!(read_proof.at, genesis_hash);
+
+	let proof = read_proof.proof.into_iter().map(|node| node.0).collect();
+	let values = state_machine::read_keys_proof_check::<Blake2Hasher>(
+		state_root,
+		proof,
+		&[b":code".to_vec(), vec![10]],
+	).unwrap();
+	assert_eq!(values, vec![Some(code.0), None]);
+
+	let too_many_keys = vec![StorageKey(vec![10]); 1001];
+	assert_matches!(
+		api.read_proof(too_many_keys, Some(genesis_hash).into()),
+		Err(Error(ErrorKind::InvalidCount(1001, 1000), _))
+	);
+}
+
 #[test]
 fn should_call_contract() {
 	let core = ::tokio::runtime::Runtime::new().unwrap();
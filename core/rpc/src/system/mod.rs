@@ -18,14 +18,22 @@
 
 pub mod error;
 
+mod helpers;
 #[cfg(test)]
 mod tests;
 
+use std::sync::Arc;
+
+use network;
+use runtime_primitives::traits::{self, Header as HeaderT};
+
 use self::error::Result;
 
+pub use self::helpers::{Properties, SystemInfo, Health, PeerInfo};
+
 build_rpc_trait! {
 	/// Substrate system RPC API
-	pub trait SystemApi {
+	pub trait SystemApi<Hash, Number> {
 		/// Get the node's implementation name. Plain old string.
 		#[rpc(name = "system_name")]
 		fn system_name(&self) -> Result<String>;
@@ -40,6 +48,88 @@ build_rpc_trait! {
 
 		/// Get a custom set of properties as a JSON object, defined in the chain spec.
 		#[rpc(name = "system_properties")]
-		fn system_properties(&self) -> Result<serde_json::map::Map<String, serde_json::Value>>;
+		fn system_properties(&self) -> Result<Properties>;
+
+		/// Return health status of the node: the number of connected peers, whether the node is
+		/// performing a major sync and whether it is expected to have peers at all.
+		#[rpc(name = "system_health")]
+		fn system_health(&self) -> Result<Health>;
+
+		/// Returns currently connected peers along with their best blocks.
+		#[rpc(name = "system_peers")]
+		fn system_peers(&self) -> Result<Vec<PeerInfo<Hash, Number>>>;
+
+		/// Returns the state of the network: listened addresses, connected nodes and the scores of
+		/// the addresses known in the topology.
+		///
+		/// **Warning**: This API is not stable.
+		#[rpc(name = "system_networkState")]
+		fn system_network_state(&self) -> Result<network::NetworkState>;
+	}
+}
+
+/// System API implementation
+pub struct System<B: traits::Block> {
+	info: SystemInfo,
+	sync: Arc<network::SyncProvider<B>>,
+	should_have_peers: bool,
+}
+
+impl<B: traits::Block> System<B> {
+	/// Creates new `System` given the `SystemInfo` and the network status provider.
+	///
+	/// `should_have_peers` tells whether the node is expected to be connected to anyone, which is
+	/// not the case e.g. for local development chains.
+	pub fn new(
+		info: SystemInfo,
+		sync: Arc<network::SyncProvider<B>>,
+		should_have_peers: bool,
+	) -> Self {
+		System {
+			info,
+			sync,
+			should_have_peers,
+		}
+	}
+}
+
+impl<B: traits::Block> SystemApi<B::Hash, <B::Header as HeaderT>::Number> for System<B> {
+	fn system_name(&self) -> Result<String> {
+		Ok(self.info.impl_name.clone())
+	}
+
+	fn system_version(&self) -> Result<String> {
+		Ok(self.info.impl_version.clone())
+	}
+
+	fn system_chain(&self) -> Result<String> {
+		Ok(self.info.chain_name.clone())
+	}
+
+	fn system_properties(&self) -> Result<Properties> {
+		Ok(self.info.properties.clone())
+	}
+
+	fn system_health(&self) -> Result<Health> {
+		let status = self.sync.status();
+		Ok(Health {
+			peers: status.num_peers,
+			is_syncing: status.sync.is_major_syncing(),
+			should_have_peers: self.should_have_peers,
+		})
+	}
+
+	fn system_peers(&self) -> Result<Vec<PeerInfo<B::Hash, <B::Header as HeaderT>::Number>>> {
+		Ok(self.sync.peers().into_iter().map(|(_, peer_id, info)| PeerInfo {
+			peer_id: peer_id.map(|peer_id| peer_id.to_base58()),
+			roles: format!("{:?}", info.roles),
+			protocol_version: info.protocol_version,
+			best_hash: info.best_hash,
+			best_number: info.best_number,
+		}).collect())
+	}
+
+	fn system_network_state(&self) -> Result<network::NetworkState> {
+		Ok(self.sync.network_state())
 	}
 }
@@ -15,27 +15,68 @@
 // along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
 
 use super::*;
-use super::error::*;
 
-impl SystemApi for () {
-	fn system_name(&self) -> Result<String> {
-		Ok("testclient".into())
-	}
-	fn system_version(&self) -> Result<String> {
-		Ok("0.2.0".into())
+use network::{self, SyncState, SyncStatus, ProtocolStatus, NodeIndex, PeerId, PeerInfo as NetworkPeerInfo};
+use network::config::Roles;
+use test_client::runtime::Block;
+use primitives::H256;
+
+#[derive(Default)]
+struct Status {
+	pub peers: usize,
+	pub is_syncing: bool,
+}
+
+impl network::SyncProvider<Block> for Status {
+	fn status(&self) -> ProtocolStatus<Block> {
+		ProtocolStatus {
+			sync: SyncStatus {
+				state: if self.is_syncing { SyncState::Downloading } else { SyncState::Idle },
+				best_seen_block: None,
+			},
+			num_peers: self.peers,
+			num_active_peers: 0,
+		}
 	}
-	fn system_chain(&self) -> Result<String> {
-		Ok("testchain".into())
+
+	fn peers(&self) -> Vec<(NodeIndex, Option<PeerId>, NetworkPeerInfo<Block>)> {
+		(0..self.peers).map(|who| (who, None, NetworkPeerInfo {
+			roles: Roles::FULL,
+			protocol_version: 1,
+			best_hash: Default::default(),
+			best_number: who as u64,
+		})).collect()
 	}
-	fn system_properties(&self) -> Result<serde_json::map::Map<String, serde_json::Value>> {
-		Ok(serde_json::map::Map::new())
+
+	fn network_state(&self) -> network::NetworkState {
+		network::NetworkState {
+			peer_id: "QmLocal".into(),
+			listened_addresses: vec!["/ip4/127.0.0.1/tcp/30333".into()],
+			connected_peers: Vec::new(),
+			known_peers: Vec::new(),
+		}
 	}
 }
 
+fn api<T: Into<Option<Status>>>(sync: T) -> System<Block> {
+	let status = sync.into().unwrap_or_default();
+	let should_have_peers = !status.is_syncing;
+	System::new(
+		SystemInfo {
+			impl_name: "testclient".into(),
+			impl_version: "0.2.0".into(),
+			chain_name: "testchain".into(),
+			properties: Default::default(),
+		},
+		Arc::new(status),
+		should_have_peers,
+	)
+}
+
 #[test]
 fn system_name_works() {
 	assert_eq!(
-		SystemApi::system_name(&()).unwrap(),
+		api(None).system_name().unwrap(),
 		"testclient".to_owned()
 	);
 }
@@ -43,7 +84,7 @@ fn system_name_works() {
 #[test]
 fn system_version_works() {
 	assert_eq!(
-		SystemApi::system_version(&()).unwrap(),
+		api(None).system_version().unwrap(),
 		"0.2.0".to_owned()
 	);
 }
@@ -51,7 +92,7 @@ fn system_version_works() {
 #[test]
 fn system_chain_works() {
 	assert_eq!(
-		SystemApi::system_chain(&()).unwrap(),
+		api(None).system_chain().unwrap(),
 		"testchain".to_owned()
 	);
 }
@@ -59,7 +100,67 @@ fn system_chain_works() {
 #[test]
 fn system_properties_works() {
 	assert_eq!(
-		SystemApi::system_properties(&()).unwrap(),
+		api(None).system_properties().unwrap(),
 		serde_json::map::Map::new()
 	);
 }
+
+#[test]
+fn system_health() {
+	assert_eq!(
+		api(None).system_health().unwrap(),
+		Health {
+			peers: 0,
+			is_syncing: false,
+			should_have_peers: true,
+		}
+	);
+
+	assert_eq!(
+		api(Status {
+			peers: 5,
+			is_syncing: true,
+		}).system_health().unwrap(),
+		Health {
+			peers: 5,
+			is_syncing: true,
+			should_have_peers: false,
+		}
+	);
+}
+
+#[test]
+fn system_peers() {
+	assert_eq!(
+		api(Status {
+			peers: 2,
+			is_syncing: false,
+		}).system_peers().unwrap(),
+		vec![
+			PeerInfo {
+				peer_id: None,
+				roles: "FULL".into(),
+				protocol_version: 1,
+				best_hash: H256::default(),
+				best_number: 0,
+			},
+			PeerInfo {
+				peer_id: None,
+				roles: "FULL".into(),
+				protocol_version: 1,
+				best_hash: H256::default(),
+				best_number: 1,
+			},
+		]
+	);
+}
+
+#[test]
+fn system_network_state() {
+	let state = api(None).system_network_state().unwrap();
+	assert_eq!(state.peer_id, "QmLocal");
+	assert_eq!(
+		::serde_json::to_string(&state).unwrap(),
+		r#"{"peerId":"QmLocal","listenedAddresses":["/ip4/127.0.0.1/tcp/30333"],"connectedPeers":[],"knownPeers":[]}"#
+	);
+}
@@ -19,10 +19,11 @@
 use std::{sync::Arc, net::SocketAddr, marker::PhantomData, ops::Deref, ops::DerefMut};
 use serde::{Serialize, de::DeserializeOwned};
 use tokio::runtime::TaskExecutor;
-use chain_spec::{ChainSpec, Properties};
+use chain_spec::ChainSpec;
 use client_db;
 use client::{self, Client, runtime_api::{TaggedTransactionQueue, Metadata}};
-use {error, Service, RpcConfig, maybe_start_server, TransactionPoolAdapter};
+use {error, Service, maybe_start_server, TransactionPoolAdapter};
+use keystore::Store as Keystore;
 use network::{self, OnDemand, import_queue::ImportQueue};
 use substrate_executor::{NativeExecutor, NativeExecutionDispatch};
 use transaction_pool::txpool::{self, Options as TransactionPoolOptions, Pool as TransactionPool};
@@ -120,14 +121,15 @@ impl<T: Serialize + DeserializeOwned + BuildStorage> RuntimeGenesis for T {}
 pub trait StartRPC<C: Components> {
 	fn start_rpc(
 		client: Arc<Client<C::Backend, C::Executor, ComponentBlock<C>, C::RuntimeApi>>,
-		chain_name: String,
-		impl_name: &'static str,
-		impl_version: &'static str,
+		network: Arc<network::SyncProvider<ComponentBlock<C>>>,
+		should_have_peers: bool,
+		system_info: rpc::apis::system::SystemInfo,
 		rpc_http: Option<SocketAddr>,
 		rpc_ws: Option<SocketAddr>,
-		properties: Properties,
 		task_executor: TaskExecutor,
 		transaction_pool: Arc<TransactionPool<C::TransactionPoolApi>>,
+		keystore: Arc<Keystore>,
+		keystore_password: String,
 	) -> Result<(Option<rpc::HttpServer>, Option<rpc::WsServer>), error::Error>;
 }
 
@@ -137,36 +139,49 @@ impl<T: Components> StartRPC<Self> for T where
 {
 	fn start_rpc(
 		client: Arc<Client<T::Backend, T::Executor, ComponentBlock<T>, T::RuntimeApi>>,
-		chain_name: String,
-		impl_name: &'static str,
-		impl_version: &'static str,
+		network: Arc<network::SyncProvider<ComponentBlock<T>>>,
+		should_have_peers: bool,
+		system_info: rpc::apis::system::SystemInfo,
 		rpc_http: Option<SocketAddr>,
 		rpc_ws: Option<SocketAddr>,
-		properties: Properties,
 		task_executor: TaskExecutor,
 		transaction_pool: Arc<TransactionPool<T::TransactionPoolApi>>,
+		keystore: Arc<Keystore>,
+		keystore_password: String,
 	) -> Result<(Option<rpc::HttpServer>, Option<rpc::WsServer>), error::Error> {
-		let rpc_config = RpcConfig { properties, chain_name, impl_name, impl_version };
-
-		let handler = || {
+		// the operator-only methods are only exposed on the local interfaces.
+		let handler = |expose_unsafe: bool| {
 			let client = client.clone();
 			let subscriptions = rpc::apis::Subscriptions::new(task_executor.clone());
 			let chain = rpc::apis::chain::Chain::new(client.clone(), subscriptions.clone());
 			let state = rpc::apis::state::State::new(client.clone(), subscriptions.clone());
+			let contracts = rpc::apis::contracts::Contracts::new(client.clone());
+			let system = rpc::apis::system::System::new(
+				system_info.clone(), network.clone(), should_have_peers
+			);
 			let author = rpc::apis::author::Author::new(
-				client.clone(), transaction_pool.clone(), subscriptions
+				client.clone(), transaction_pool.clone(), subscriptions.clone(), keystore.clone(), keystore_password.clone()
 			);
-			rpc::rpc_handler::<ComponentBlock<T>, ComponentExHash<T>, _, _, _, _>(
+			let author_unsafe = if expose_unsafe {
+				Some(rpc::apis::author::Author::new(
+					client.clone(), transaction_pool.clone(), subscriptions, keystore.clone(), keystore_password.clone()
+				))
+			} else {
+				None
+			};
+			rpc::rpc_handler::<ComponentBlock<T>, ComponentExHash<T>, _, _, _, _, _, _>(
 				state,
 				chain,
 				author,
-				rpc_config.clone(),
+				author_unsafe,
+				system,
+				contracts,
 			)
 		};
 
 		Ok((
-			maybe_start_server(rpc_http, |address| rpc::start_http(address, handler()))?,
-			maybe_start_server(rpc_ws, |address| rpc::start_ws(address, handler()))?,
+			maybe_start_server(rpc_http, |address| rpc::start_http(address, handler(address.ip().is_loopback())))?,
+			maybe_start_server(rpc_ws, |address| rpc::start_ws(address, handler(address.ip().is_loopback())))?,
 		))
 	}
 }
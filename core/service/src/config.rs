@@ -39,10 +39,18 @@ pub struct Configuration<C, G: Serialize + DeserializeOwned + BuildStorage> {
 	pub roles: Roles,
 	/// Extrinsic pool configuration.
 	pub transaction_pool: transaction_pool::txpool::Options,
+	/// Path to the extrinsic pool journal. `None` if the pool is not persisted across restarts.
+	///
+	/// The journal is written every minute and on shutdown, so a crash loses the extrinsics
+	/// imported since the last write.
+	pub transaction_pool_journal_path: Option<String>,
 	/// Network configuration.
 	pub network: NetworkConfiguration,
 	/// Path to key files.
 	pub keystore_path: String,
+	/// Password the keys in the keystore are encrypted with. Empty unless configured, in which case
+	/// the keys are only as safe as the keystore directory.
+	pub keystore_password: String,
 	/// Path to the database.
 	pub database_path: String,
 	/// Pruning settings.
@@ -78,8 +86,10 @@ impl<C: Default, G: Serialize + DeserializeOwned + BuildStorage> Configuration<C
 			name: Default::default(),
 			roles: Roles::FULL,
 			transaction_pool: Default::default(),
+			transaction_pool_journal_path: None,
 			network: Default::default(),
 			keystore_path: Default::default(),
+			keystore_password: Default::default(),
 			database_path: Default::default(),
 			keys: Default::default(),
 			custom: Default::default(),
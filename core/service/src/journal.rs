@@ -0,0 +1,106 @@
+// Copyright 2018 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transaction pool journal.
+//!
+//! Extrinsics held by the transaction pool are written to disk, so that they can be
+//! re-imported after the node is restarted.
+//!
+//! The journal is only written periodically and on shutdown, so the extrinsics imported
+//! since the last write are lost if the node crashes.
+
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use codec::{Decode, Encode};
+use error;
+
+/// Reads extrinsics from the journal at given path.
+///
+/// Returns an empty list if there is no journal yet. A journal that can't be decoded is
+/// moved aside, so that it can be inspected, and an empty list is returned as well.
+pub fn load<Ex: Decode>(path: &Path) -> error::Result<Vec<Ex>> {
+	if !path.exists() {
+		return Ok(Vec::new());
+	}
+
+	let mut data = Vec::new();
+	fs::File::open(path)?.read_to_end(&mut data)?;
+	match Decode::decode(&mut &data[..]) {
+		Some(extrinsics) => Ok(extrinsics),
+		None => {
+			let corrupted_path = path.with_extension("corrupted");
+			warn!("Corrupted transaction pool journal, moving it to {}", corrupted_path.display());
+			fs::rename(path, &corrupted_path)?;
+			Ok(Vec::new())
+		},
+	}
+}
+
+/// Replaces the journal at given path with given extrinsics.
+///
+/// The journal is written to a temporary file first, so that a crash never leaves
+/// a partially written journal behind.
+pub fn store<Ex: Encode>(path: &Path, extrinsics: &[Ex]) -> error::Result<()> {
+	if let Some(parent) = path.parent() {
+		fs::create_dir_all(parent)?;
+	}
+
+	let tmp_path = path.with_extension("tmp");
+	{
+		let mut file = fs::File::create(&tmp_path)?;
+		file.write_all(&extrinsics.encode())?;
+		file.sync_all()?;
+	}
+	fs::rename(&tmp_path, path)?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use tempdir::TempDir;
+
+	#[test]
+	fn should_load_stored_extrinsics() {
+		let dir = TempDir::new("journal").unwrap();
+		let path = dir.path().join("txpool").join("journal");
+
+		store(&path, &[vec![1u8, 2], vec![3]]).unwrap();
+
+		assert_eq!(load::<Vec<u8>>(&path).unwrap(), vec![vec![1u8, 2], vec![3]]);
+		assert!(!path.with_extension("tmp").exists());
+	}
+
+	#[test]
+	fn should_load_nothing_without_journal() {
+		let dir = TempDir::new("journal").unwrap();
+
+		assert!(load::<Vec<u8>>(&dir.path().join("journal")).unwrap().is_empty());
+	}
+
+	#[test]
+	fn should_move_corrupted_journal_aside() {
+		let dir = TempDir::new("journal").unwrap();
+		let path = dir.path().join("journal");
+		fs::write(&path, &[0xff, 0xff]).unwrap();
+
+		assert!(load::<Vec<u8>>(&path).unwrap().is_empty());
+		assert!(!path.exists());
+		assert_eq!(fs::read(path.with_extension("corrupted")).unwrap(), vec![0xff, 0xff]);
+	}
+}
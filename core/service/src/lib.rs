@@ -50,23 +50,29 @@ extern crate log;
 #[macro_use]
 extern crate serde_derive;
 
+#[cfg(test)]
+extern crate tempdir;
+
 mod components;
 mod error;
 mod chain_spec;
 pub mod config;
 pub mod chain_ops;
 pub mod consensus;
+mod journal;
 
 use std::io;
 use std::net::SocketAddr;
 use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
 #[doc(hidden)]
 pub use std::{ops::Deref, result::Result, sync::Arc};
 use futures::prelude::*;
 use parking_lot::{Mutex, RwLock};
-use keystore::Store as Keystore;
+use keystore::{Store as Keystore, KeyType};
 use client::BlockchainEvents;
-use runtime_primitives::traits::{Header, As};
+use runtime_primitives::traits::{Header, As, Block as BlockT, Extrinsic};
 use runtime_primitives::generic::BlockId;
 use exit_future::Signal;
 #[doc(hidden)]
@@ -80,6 +86,7 @@ pub use chain_spec::{ChainSpec, Properties};
 pub use transaction_pool::txpool::{self, Pool as TransactionPool, Options as TransactionPoolOptions, ChainApi, IntoPoolError};
 pub use client::ExecutionStrategy;
 
+use consensus_common::LocalKey;
 use consensus_common::offline_tracker::OfflineTracker;
 pub use consensus::ProposerFactory;
 pub use components::{ServiceFactory, FullBackend, FullExecutor, LightBackend,
@@ -94,13 +101,17 @@ use components::{StartRPC, CreateNetworkParams};
 pub use network::OnDemand;
 
 const DEFAULT_PROTOCOL_ID: &'static str = "sup";
+/// How often the transaction pool journal is written to disk.
+///
+/// It is also written on shutdown, so only a crash loses the extrinsics imported in between.
+const JOURNAL_INTERVAL: Duration = Duration::from_secs(60);
 
 /// Substrate service.
 pub struct Service<Components: components::Components> {
 	client: Arc<ComponentClient<Components>>,
 	network: Option<Arc<components::NetworkService<Components::Factory>>>,
 	transaction_pool: Arc<TransactionPool<Components::TransactionPoolApi>>,
-	keystore: Keystore,
+	keystore: Arc<Keystore>,
 	exit: ::exit_future::Exit,
 	signal: Option<Signal>,
 	/// Configuration of this Service
@@ -149,16 +160,17 @@ impl<Components> Service<Components>
 			keystore.generate_from_seed(seed)?;
 		}
 		// Keep the public key for telemetry
-		let public_key = match keystore.contents()?.get(0) {
-			Some(public_key) => public_key.clone(),
+		let public_key = match keystore.current_by_type(KeyType::Session)? {
+			Some(public_key) => public_key,
 			None => {
-				let key = keystore.generate("")?;
+				let key = keystore.generate(&config.keystore_password)?;
 				let public_key = key.public();
 				info!("Generated a new keypair: {:?}", public_key);
 
 				public_key
 			}
 		};
+		let keystore = Arc::new(keystore);
 
 		let (client, on_demand) = Components::build_client(&config, executor)?;
 		let import_queue = Arc::new(Components::build_import_queue(&mut config, client.clone())?);
@@ -172,6 +184,18 @@ impl<Components> Service<Components>
 		let transaction_pool = Arc::new(
 			Components::build_transaction_pool(config.transaction_pool.clone(), client.clone())?
 		);
+		if let Some(ref path) = config.transaction_pool_journal_path {
+			let extrinsics = journal::load::<ComponentExtrinsic<Components>>(Path::new(path))?;
+			if !extrinsics.is_empty() {
+				let total = extrinsics.len();
+				let imported = transaction_pool.submit_at(&BlockId::hash(best_header.hash()), extrinsics)
+					.map_err(|e| format!("Error re-importing journaled extrinsics: {:?}", e))?
+					.into_iter()
+					.filter(|result| result.is_ok())
+					.count();
+				info!("Re-imported {} of {} extrinsics from the transaction pool journal", imported, total);
+			}
+		}
 		let transaction_pool_adapter = TransactionPoolAdapter::<Components> {
 			imports_external_transactions: !(config.roles == Roles::LIGHT),
 			pool: transaction_pool.clone(),
@@ -209,14 +233,57 @@ impl<Components> Service<Components>
 			// block notifications
 			let network = Arc::downgrade(&network);
 			let txpool = transaction_pool.clone();
+			let client = client.clone();
 
 			let events = client.import_notification_stream()
 				.for_each(move |notification| {
 					if let Some(network) = network.upgrade() {
 						network.on_block_imported(notification.hash, &notification.header);
 					}
-					txpool.prune_tags(&BlockId::hash(notification.hash), notification.tags)
-						.map_err(|e| warn!("Error removing extrinsics: {:?}", e))?;
+					let at = BlockId::hash(notification.hash);
+					// put back transactions from blocks that are no longer part of the best chain.
+					for retracted in &notification.retracted {
+						let extrinsics = match client.block(&BlockId::hash(*retracted)) {
+							Ok(Some(block)) => block.block.deconstruct().1,
+							Ok(None) => continue,
+							Err(e) => {
+								warn!("Error reading retracted block {:?}: {:?}", retracted, e);
+								continue;
+							},
+						};
+						let extrinsics = extrinsics.into_iter()
+							.filter(|xt| xt.is_signed().unwrap_or(false));
+						// errors are only logged, the task also drives the network notifications.
+						if let Err(e) = txpool.submit_at(&at, extrinsics) {
+							warn!("Error re-submitting retracted extrinsics: {:?}", e);
+						}
+					}
+					if let Err(e) = txpool.prune_tags(&at, notification.tags) {
+						warn!("Error removing extrinsics: {:?}", e);
+					}
+					if !notification.retracted.is_empty() || !notification.enacted.is_empty() {
+						if let Err(e) = txpool.revalidate_ready(&at) {
+							warn!("Error re-validating extrinsics: {:?}", e);
+						}
+					}
+					Ok(())
+				})
+				.select(exit.clone())
+				.then(|_| Ok(()));
+			task_executor.spawn(events);
+		}
+
+		if let Some(path) = config.transaction_pool_journal_path.clone() {
+			// periodically persist the transaction pool
+			let txpool = Arc::downgrade(&transaction_pool);
+			let events = tokio::timer::Interval::new(Instant::now() + JOURNAL_INTERVAL, JOURNAL_INTERVAL)
+				.map_err(|e| warn!("Error in the transaction pool journal timer: {:?}", e))
+				.for_each(move |_| {
+					if let Some(txpool) = txpool.upgrade() {
+						if let Err(e) = journal::store(Path::new(&path), &txpool.extrinsics()) {
+							warn!("Error writing the transaction pool journal: {:?}", e);
+						}
+					}
 					Ok(())
 				})
 				.select(exit.clone())
@@ -243,10 +310,18 @@ impl<Components> Service<Components>
 
 
 		// RPC
+		let system_info = substrate_rpc::system::SystemInfo {
+			chain_name: config.chain_spec.name().to_string(),
+			impl_name: config.impl_name.into(),
+			impl_version: config.impl_version.into(),
+			properties: config.chain_spec.properties(),
+		};
+		// a node without any nodes to connect to, e.g. of a local development chain, is fine alone.
+		let should_have_peers = !config.network.boot_nodes.is_empty() || !config.network.reserved_nodes.is_empty();
 		let (rpc_http, rpc_ws) = Components::RPC::start_rpc(
-			client.clone(), config.chain_spec.name().to_string(), config.impl_name,
-			config.impl_version, config.rpc_http, config.rpc_ws, config.chain_spec.properties(),
-			task_executor.clone(), transaction_pool.clone()
+			client.clone(), network.clone(), should_have_peers, system_info, config.rpc_http,
+			config.rpc_ws, task_executor.clone(), transaction_pool.clone(), keystore.clone(),
+			config.keystore_password.clone(),
 		)?;
 
 		let proposer = Arc::new(ProposerFactory {
@@ -299,16 +374,104 @@ impl<Components> Service<Components>
 	}
 
 	/// give the authority key, if we are an authority and have a key
+	///
+	/// This is the current session key of the keystore.
 	pub fn authority_key(&self) -> Option<primitives::ed25519::Pair> {
+		self.current_key(KeyType::Session)
+	}
+
+	/// give the GRANDPA voter key, if we are an authority and have a key
+	///
+	/// This is the current GRANDPA key of the keystore. `None` if there is none, in which case
+	/// the authority key is meant to be used.
+	pub fn grandpa_key(&self) -> Option<primitives::ed25519::Pair> {
+		self.current_key(KeyType::Grandpa)
+	}
+
+	/// A handle to the authority key, if we are an authority.
+	///
+	/// Unlike `authority_key`, it follows the keys inserted into or rotated in the keystore
+	/// while the node is running.
+	pub fn authority_key_handle(&self) -> Option<Arc<dyn LocalKey>> {
+		self.key_handle(vec![KeyType::Session])
+	}
+
+	/// A handle to the GRANDPA voter key, if we are an authority.
+	///
+	/// Unlike `grandpa_key`, it follows the keys inserted into or rotated in the keystore
+	/// while the node is running. The authority key is used while there is no GRANDPA key.
+	pub fn grandpa_key_handle(&self) -> Option<Arc<dyn LocalKey>> {
+		self.key_handle(vec![KeyType::Grandpa, KeyType::Session])
+	}
+
+	fn key_handle(&self, key_types: Vec<KeyType>) -> Option<Arc<dyn LocalKey>> {
+		if self.config.roles != Roles::AUTHORITY { return None }
+		Some(Arc::new(KeystoreKey {
+			keystore: self.keystore.clone(),
+			password: self.config.keystore_password.clone(),
+			key_types,
+			loaded: Mutex::new(None),
+		}))
+	}
+
+	fn current_key(&self, key_type: KeyType) -> Option<primitives::ed25519::Pair> {
 		if self.config.roles != Roles::AUTHORITY { return None }
 		let keystore = &self.keystore;
-		if let Ok(Some(Ok(key))) =  keystore.contents().map(|keys| keys.get(0)
-				.map(|k| keystore.load(k, "")))
-		{
-			Some(key)
-		} else {
-			None
+		let key = match keystore.current_by_type(key_type) {
+			Ok(Some(public)) => keystore.load_by_type(key_type, &public, &self.config.keystore_password).map(Some),
+			Ok(None) => Ok(None),
+			Err(e) => Err(e),
+		};
+		match key {
+			Ok(key) => key,
+			Err(e) => {
+				warn!("Unable to load the {} key: {}", key_type, e);
+				None
+			},
+		}
+	}
+}
+
+/// The current key of the keystore, looked up whenever it is asked for.
+struct KeystoreKey {
+	keystore: Arc<Keystore>,
+	password: String,
+	/// The key types to look for, the first one with a current key is used.
+	key_types: Vec<KeyType>,
+	/// The key loaded last, kept to avoid decrypting it again while it is current.
+	loaded: Mutex<Option<Arc<primitives::ed25519::Pair>>>,
+}
+
+impl LocalKey for KeystoreKey {
+	fn local_key(&self) -> Option<Arc<primitives::ed25519::Pair>> {
+		let mut loaded = self.loaded.lock();
+		for &key_type in &self.key_types {
+			let public = match self.keystore.current_by_type(key_type) {
+				Ok(Some(public)) => public,
+				Ok(None) => continue,
+				Err(e) => {
+					warn!("Unable to look up the current {} key: {}", key_type, e);
+					return None;
+				},
+			};
+			if let Some(ref pair) = *loaded {
+				if pair.public() == public {
+					return Some(pair.clone());
+				}
+			}
+			return match self.keystore.load_by_type(key_type, &public, &self.password) {
+				Ok(pair) => {
+					let pair = Arc::new(pair);
+					*loaded = Some(pair.clone());
+					Some(pair)
+				},
+				Err(e) => {
+					warn!("Unable to load the {} key: {}", key_type, e);
+					None
+				},
+			};
 		}
+		None
 	}
 }
 
@@ -353,6 +516,12 @@ impl<Components> Drop for Service<Components> where Components: components::Comp
 
 		drop(self.network.take());
 
+		if let Some(ref path) = self.config.transaction_pool_journal_path {
+			if let Err(e) = journal::store(Path::new(path), &self.transaction_pool.extrinsics()) {
+				warn!("Error writing the transaction pool journal: {:?}", e);
+			}
+		}
+
 		if let Some(signal) = self.signal.take() {
 			signal.fire();
 		}
@@ -377,32 +546,6 @@ fn maybe_start_server<T, F>(address: Option<SocketAddr>, start: F) -> Result<Opt
 	})
 }
 
-#[derive(Clone)]
-struct RpcConfig {
-	chain_name: String,
-	properties: Properties,
-	impl_name: &'static str,
-	impl_version: &'static str,
-}
-
-impl substrate_rpc::system::SystemApi for RpcConfig {
-	fn system_name(&self) -> substrate_rpc::system::error::Result<String> {
-		Ok(self.impl_name.into())
-	}
-
-	fn system_version(&self) -> substrate_rpc::system::error::Result<String> {
-		Ok(self.impl_version.into())
-	}
-
-	fn system_chain(&self) -> substrate_rpc::system::error::Result<String> {
-		Ok(self.chain_name.clone())
-	}
-
-	fn system_properties(&self) -> substrate_rpc::system::error::Result<Properties> {
-		Ok(self.properties.clone())
-	}
-}
-
 /// Transaction pool adapter.
 pub struct TransactionPoolAdapter<C: Components> {
 	imports_external_transactions: bool,
@@ -122,6 +122,7 @@ fn node_config<F: ServiceFactory> (
 		transaction_pool: Default::default(),
 		network: network_config,
 		keystore_path: root.join("key").to_str().unwrap().into(),
+		keystore_password: Default::default(),
 		database_path: root.join("db").to_str().unwrap().into(),
 		pruning: Default::default(),
 		keys: keys,
@@ -27,7 +27,7 @@ extern crate hash_db;
 #[doc(hidden)]
 pub extern crate parity_codec as codec;
 // re-export hashing functions.
-pub use primitives::{blake2_256, twox_128, twox_256, ed25519};
+pub use primitives::{blake2_256, twox_128, twox_256, ed25519, sr25519};
 
 pub use primitives::{Blake2Hasher};
 // Switch to this after PoC-3
@@ -200,6 +200,11 @@ pub fn ed25519_verify<P: AsRef<[u8]>>(sig: &[u8; 64], msg: &[u8], pubkey: P) ->
 	ed25519::verify(sig, msg, pubkey)
 }
 
+/// Verify an sr25519 signature.
+pub fn sr25519_verify<P: AsRef<[u8]>>(sig: &[u8; 64], msg: &[u8], pubkey: P) -> bool {
+	sr25519::verify(sig, msg, pubkey)
+}
+
 /// Execute the given closure with global function available whose functionality routes into the
 /// externalities `ext`. Forwards the value that the closure returns.
 // NOTE: need a concrete hasher here due to limitations of the `environmental!` macro, otherwise a type param would have been fine I think.
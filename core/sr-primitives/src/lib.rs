@@ -206,6 +206,80 @@ impl From<H512> for Ed25519Signature {
 	}
 }
 
+/// Sr25519 signature verify.
+#[derive(Eq, PartialEq, Clone, Default, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+pub struct Sr25519Signature(pub H512);
+
+impl Verify for Sr25519Signature {
+	type Signer = H256;
+	fn verify<L: Lazy<[u8]>>(&self, mut msg: L, signer: &Self::Signer) -> bool {
+		runtime_io::sr25519_verify((self.0).as_fixed_bytes(), msg.get(), &signer.as_bytes())
+	}
+}
+
+impl From<H512> for Sr25519Signature {
+	fn from(h: H512) -> Sr25519Signature {
+		Sr25519Signature(h)
+	}
+}
+
+/// Signature of any of the supported signature schemes.
+#[derive(Eq, PartialEq, Clone, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+pub enum MultiSignature {
+	/// An Ed25519 signature.
+	Ed25519(Ed25519Signature),
+	/// An Sr25519 signature.
+	Sr25519(Sr25519Signature),
+}
+
+impl Default for MultiSignature {
+	fn default() -> Self {
+		MultiSignature::Ed25519(Default::default())
+	}
+}
+
+impl From<Ed25519Signature> for MultiSignature {
+	fn from(x: Ed25519Signature) -> Self {
+		MultiSignature::Ed25519(x)
+	}
+}
+
+impl From<Sr25519Signature> for MultiSignature {
+	fn from(x: Sr25519Signature) -> Self {
+		MultiSignature::Sr25519(x)
+	}
+}
+
+/// Public key of any of the supported signature schemes.
+#[derive(Eq, PartialEq, Ord, PartialOrd, Clone, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug, Hash, Serialize, Deserialize))]
+pub enum MultiSigner {
+	/// An Ed25519 public key.
+	Ed25519(H256),
+	/// An Sr25519 public key.
+	Sr25519(H256),
+}
+
+impl Default for MultiSigner {
+	fn default() -> Self {
+		MultiSigner::Ed25519(Default::default())
+	}
+}
+
+impl Verify for MultiSignature {
+	type Signer = MultiSigner;
+	/// Verify the signature. A signature of one scheme never verifies against a key of the other.
+	fn verify<L: Lazy<[u8]>>(&self, msg: L, signer: &Self::Signer) -> bool {
+		match (self, signer) {
+			(&MultiSignature::Ed25519(ref sig), &MultiSigner::Ed25519(ref who)) => sig.verify(msg, who),
+			(&MultiSignature::Sr25519(ref sig), &MultiSigner::Sr25519(ref who)) => sig.verify(msg, who),
+			_ => false,
+		}
+	}
+}
+
 #[derive(Eq, PartialEq, Clone, Copy, Decode)]
 #[cfg_attr(feature = "std", derive(Debug, Serialize))]
 #[repr(u8)]
@@ -535,4 +609,24 @@ mod tests {
 		// check that as-style methods are not working with regular items
 		assert!(b1.as_authorities_change().is_none());
 	}
+
+	#[test]
+	fn multi_signature_should_verify_matching_schemes_only() {
+		use substrate_primitives::{ed25519, sr25519};
+		use traits::Verify;
+		use super::{MultiSignature, MultiSigner, Ed25519Signature, Sr25519Signature};
+
+		let msg = &b"test-message"[..];
+		let ed_pair = ed25519::Pair::from_seed(b"12345678901234567890123456789012");
+		let sr_pair = sr25519::Pair::from_seed(b"12345678901234567890123456789012");
+		let ed_signer = MultiSigner::Ed25519(ed_pair.public().0.into());
+		let sr_signer = MultiSigner::Sr25519(sr_pair.public().0.into());
+		let ed_sig = MultiSignature::from(Ed25519Signature(ed_pair.sign(msg)));
+		let sr_sig = MultiSignature::from(Sr25519Signature(sr_pair.sign(msg)));
+
+		assert!(ed_sig.verify(msg, &ed_signer));
+		assert!(sr_sig.verify(msg, &sr_signer));
+		assert!(!ed_sig.verify(msg, &sr_signer));
+		assert!(!sr_sig.verify(msg, &ed_signer));
+	}
 }
@@ -28,6 +28,13 @@
 //! # Pruning.
 //! See `RefWindow` for pruning algorithm details. `StateDb` prunes on each canonicalization until pruning
 //! constraints are satisfied.
+//!
+//! # Hybrid pruning.
+//! `PruningMode::Hybrid` prunes like `PruningMode::Constrained`, except for the history of the trie nodes
+//! under a set of key prefixes. This module only sees node hashes, so the caller passes the hashes of the
+//! archived nodes along with each inserted block. The canonicalization window leaves them out of the block
+//! journal and they are never scheduled for deletion. Pruned states are still reported as pruned, since
+//! only their archived part is kept.
 
 #[macro_use] extern crate log;
 #[macro_use] extern crate parity_codec_derive;
@@ -129,6 +136,9 @@ pub enum PruningMode {
 	ArchiveAll,
 	/// Canonicalization discards non-canonical nodes. All the canonical nodes are kept in the DB.
 	ArchiveCanonical,
+	/// Maintain a pruning window, but keep the history of the trie nodes that lie on the paths
+	/// to the given key prefixes or under them.
+	Hybrid(Constraints, Vec<Vec<u8>>),
 }
 
 impl PruningMode {
@@ -140,14 +150,29 @@ impl PruningMode {
 		})
 	}
 
+	/// Create a mode that keeps given number of blocks and the whole history of the given key prefixes.
+	pub fn keep_blocks_and_prefixes(n: u32, prefixes: Vec<Vec<u8>>) -> PruningMode {
+		PruningMode::Hybrid(Constraints {
+			max_blocks: Some(n),
+			max_mem: None,
+		}, prefixes)
+	}
+
 	/// Is this an archive (either ArchiveAll or ArchiveCanonical) pruning mode?
 	pub fn is_archive(&self) -> bool {
 		match *self {
 			PruningMode::ArchiveAll | PruningMode::ArchiveCanonical => true,
-			PruningMode::Constrained(_) => false
+			PruningMode::Constrained(_) | PruningMode::Hybrid(..) => false
 		}
 	}
 
+	/// Key prefixes whose history is kept. Empty unless this is a hybrid pruning mode.
+	pub fn archived_prefixes(&self) -> &[Vec<u8>] {
+		match *self {
+			PruningMode::Hybrid(_, ref prefixes) => prefixes,
+			_ => &[],
+		}
+	}
 }
 
 impl Default for PruningMode {
@@ -177,8 +202,12 @@ impl<BlockHash: Hash, Key: Hash> StateDbSync<BlockHash, Key> {
 			PruningMode::Constrained(Constraints {
 				max_mem: Some(_),
 				..
-			}) => unimplemented!(),
-			PruningMode::Constrained(_) => Some(RefWindow::new(db)?),
+			}) |
+			PruningMode::Hybrid(Constraints {
+				max_mem: Some(_),
+				..
+			}, _) => unimplemented!(),
+			PruningMode::Constrained(_) | PruningMode::Hybrid(..) => Some(RefWindow::new(db)?),
 			PruningMode::ArchiveAll | PruningMode::ArchiveCanonical => None,
 		};
 		Ok(StateDbSync {
@@ -189,7 +218,7 @@ impl<BlockHash: Hash, Key: Hash> StateDbSync<BlockHash, Key> {
 		})
 	}
 
-	pub fn insert_block<E: fmt::Debug>(&mut self, hash: &BlockHash, number: u64, parent_hash: &BlockHash, mut changeset: ChangeSet<Key>) -> Result<CommitSet<Key>, Error<E>> {
+	pub fn insert_block<E: fmt::Debug>(&mut self, hash: &BlockHash, number: u64, parent_hash: &BlockHash, mut changeset: ChangeSet<Key>, archived: &HashSet<Key>) -> Result<CommitSet<Key>, Error<E>> {
 		if number == 0 {
 			return Ok(CommitSet {
 				data: changeset,
@@ -205,8 +234,8 @@ impl<BlockHash: Hash, Key: Hash> StateDbSync<BlockHash, Key> {
 					meta: Default::default(),
 				})
 			},
-			PruningMode::Constrained(_) | PruningMode::ArchiveCanonical => {
-				self.non_canonical.insert(hash, number, parent_hash, changeset)
+			PruningMode::Constrained(_) | PruningMode::Hybrid(..) | PruningMode::ArchiveCanonical => {
+				self.non_canonical.insert(hash, number, parent_hash, changeset, archived)
 			}
 		}
 	}
@@ -223,7 +252,7 @@ impl<BlockHash: Hash, Key: Hash> StateDbSync<BlockHash, Key> {
 				commit.data.deleted.clear();
 				commit
 			},
-			PruningMode::Constrained(_) => {
+			PruningMode::Constrained(_) | PruningMode::Hybrid(..) => {
 				self.non_canonical.canonicalize(hash)
 			},
 		};
@@ -243,7 +272,11 @@ impl<BlockHash: Hash, Key: Hash> StateDbSync<BlockHash, Key> {
 	}
 
 	fn prune(&mut self, commit: &mut CommitSet<Key>) {
-		if let (&mut Some(ref mut pruning), &PruningMode::Constrained(ref constraints)) = (&mut self.pruning, &self.mode) {
+		let constraints = match self.mode {
+			PruningMode::Constrained(ref constraints) | PruningMode::Hybrid(ref constraints, _) => constraints,
+			PruningMode::ArchiveAll | PruningMode::ArchiveCanonical => return,
+		};
+		if let Some(ref mut pruning) = self.pruning {
 			loop {
 				if pruning.window_size() <= constraints.max_blocks.unwrap_or(0) as u64 {
 					break;
@@ -270,7 +303,7 @@ impl<BlockHash: Hash, Key: Hash> StateDbSync<BlockHash, Key> {
 			PruningMode::ArchiveAll => {
 				Some(CommitSet::default())
 			},
-			PruningMode::ArchiveCanonical | PruningMode::Constrained(_) => {
+			PruningMode::ArchiveCanonical | PruningMode::Constrained(_) | PruningMode::Hybrid(..) => {
 				self.non_canonical.revert_one()
 			},
 		}
@@ -307,8 +340,11 @@ impl<BlockHash: Hash, Key: Hash> StateDb<BlockHash, Key> {
 	}
 
 	/// Add a new non-canonical block.
-	pub fn insert_block<E: fmt::Debug>(&self, hash: &BlockHash, number: u64, parent_hash: &BlockHash, changeset: ChangeSet<Key>) -> Result<CommitSet<Key>, Error<E>> {
-		self.db.write().insert_block(hash, number, parent_hash, changeset)
+	///
+	/// Nodes in `archived` are never deleted, even if `changeset` deletes them. It is only
+	/// relevant to the hybrid pruning mode.
+	pub fn insert_block<E: fmt::Debug>(&self, hash: &BlockHash, number: u64, parent_hash: &BlockHash, changeset: ChangeSet<Key>, archived: &HashSet<Key>) -> Result<CommitSet<Key>, Error<E>> {
+		self.db.write().insert_block(hash, number, parent_hash, changeset, archived)
 	}
 
 	/// Finalize a previously inserted block.
@@ -344,6 +380,8 @@ impl<BlockHash: Hash, Key: Hash> StateDb<BlockHash, Key> {
 	}
 
 	/// Check if block is pruned away.
+	///
+	/// In hybrid pruning mode a pruned block still has the archived part of its state.
 	pub fn is_pruned(&self, number: u64) -> bool {
 		return self.db.read().is_pruned(number)
 	}
@@ -352,11 +390,17 @@ impl<BlockHash: Hash, Key: Hash> StateDb<BlockHash, Key> {
 #[cfg(test)]
 mod tests {
 	use std::io;
+	use std::collections::HashSet;
 	use primitives::H256;
 	use {StateDb, PruningMode, Constraints};
 	use test::{make_db, make_changeset, TestDb};
 
 	fn make_test_db(settings: PruningMode) -> (TestDb, StateDb<H256, H256>) {
+		make_test_db_with_archived(settings, &[])
+	}
+
+	fn make_test_db_with_archived(settings: PruningMode, archived: &[u64]) -> (TestDb, StateDb<H256, H256>) {
+		let archived: HashSet<H256> = archived.iter().map(|v| H256::from_low_u64_be(*v)).collect();
 		let mut db = make_db(&[91, 921, 922, 93, 94]);
 		let state_db = StateDb::new(settings, &db).unwrap();
 
@@ -367,6 +411,7 @@ mod tests {
 					1,
 					&H256::from_low_u64_be(0),
 					make_changeset(&[1], &[91]),
+					&archived,
 				)
 				.unwrap(),
 		);
@@ -377,6 +422,7 @@ mod tests {
 					2,
 					&H256::from_low_u64_be(1),
 					make_changeset(&[21], &[921, 1]),
+					&archived,
 				)
 				.unwrap(),
 		);
@@ -387,6 +433,7 @@ mod tests {
 					2,
 					&H256::from_low_u64_be(1),
 					make_changeset(&[22], &[922]),
+					&archived,
 				)
 				.unwrap(),
 		);
@@ -397,6 +444,7 @@ mod tests {
 					3,
 					&H256::from_low_u64_be(21),
 					make_changeset(&[3], &[93]),
+					&archived,
 				)
 				.unwrap(),
 		);
@@ -408,6 +456,7 @@ mod tests {
 					4,
 					&H256::from_low_u64_be(3),
 					make_changeset(&[4], &[94]),
+					&archived,
 				)
 				.unwrap(),
 		);
@@ -450,6 +499,18 @@ mod tests {
 		assert!(db.data_eq(&make_db(&[21, 3, 922, 93, 94])));
 	}
 
+	#[test]
+	fn hybrid_prunes_like_window_but_keeps_archived_nodes() {
+		let (db, sdb) = make_test_db_with_archived(PruningMode::Hybrid(Constraints {
+			max_blocks: Some(1),
+			max_mem: None,
+		}, vec![b"balances".to_vec()]), &[1, 91]);
+		assert!(sdb.is_pruned(0));
+		assert!(sdb.is_pruned(1));
+		assert!(!sdb.is_pruned(2));
+		assert!(db.data_eq(&make_db(&[1, 21, 3, 91, 922, 93, 94])));
+	}
+
 	#[test]
 	fn prune_window_2() {
 		let (db, sdb) = make_test_db(PruningMode::Constrained(Constraints {
@@ -63,6 +63,21 @@ pub trait Backend<H: Hasher> {
 	/// call `f` for each of those keys.
 	fn for_keys_with_prefix<F: FnMut(&[u8])>(&self, prefix: &[u8], f: F);
 
+	/// Retrieve all the keys that start with the given prefix, in lexicographic order.
+	///
+	/// Fails rather than returning a partial list if the state can't be read.
+	fn keys(&self, prefix: &[u8]) -> Result<Vec<Vec<u8>>, Self::Error>;
+
+	/// Retrieve at most `count` keys that start with the given prefix and follow `start_key`,
+	/// in lexicographic order. The keys from the beginning are returned if `start_key` is `None`.
+	fn keys_paged(&self, prefix: &[u8], start_key: Option<&[u8]>, count: usize) -> Result<Vec<Vec<u8>>, Self::Error> {
+		Ok(self.keys(prefix)?
+			.into_iter()
+			.filter(|key| start_key.map_or(true, |start_key| &key[..] > start_key))
+			.take(count)
+			.collect())
+	}
+
 	/// Calculate the storage root, with given delta over what is already stored in
 	/// the backend, and produce a "transaction" that can be used to commit.
 	fn storage_root<I>(&self, delta: I) -> (H::Out, Self::Transaction)
@@ -226,6 +241,13 @@ impl<H: Hasher> Backend<H> for InMemory<H> where H::Out: HeapSizeOf {
 		self.inner.get(&None).map(|map| map.keys().filter(|key| key.starts_with(prefix)).map(|k| &**k).for_each(f));
 	}
 
+	fn keys(&self, prefix: &[u8]) -> Result<Vec<Vec<u8>>, Self::Error> {
+		let mut keys = Vec::new();
+		self.for_keys_with_prefix(prefix, |key| keys.push(key.to_vec()));
+		keys.sort();
+		Ok(keys)
+	}
+
 	fn for_keys_in_child_storage<F: FnMut(&[u8])>(&self, storage_key: &[u8], mut f: F) {
 		self.inner.get(&Some(storage_key.to_vec())).map(|map| map.keys().for_each(|k| f(&k)));
 	}
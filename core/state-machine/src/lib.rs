@@ -468,6 +468,27 @@ where
 	Ok((result, proving_backend.extract_proof()))
 }
 
+/// Generate a single storage read proof for all the given keys.
+///
+/// The proof can be checked with `read_keys_proof_check` against the root of the state.
+pub fn prove_read_keys<B, H>(
+	backend: B,
+	keys: &[Vec<u8>],
+) -> Result<Vec<Vec<u8>>, Box<Error>>
+where
+	B: Backend<H>,
+	H: Hasher,
+	H::Out: Ord + HeapSizeOf
+{
+	let trie_backend = backend.try_into_trie_backend()
+		.ok_or_else(|| Box::new(ExecutionError::UnableToGenerateProof) as Box<Error>)?;
+	let proving_backend = proving_backend::ProvingBackend::<_, H>::new(&trie_backend);
+	for key in keys {
+		proving_backend.storage(key).map_err(|e| Box::new(e) as Box<Error>)?;
+	}
+	Ok(proving_backend.extract_proof())
+}
+
 /// Check storage read proof, generated by `prove_read` call.
 pub fn read_proof_check<H>(
 	root: H::Out,
@@ -482,6 +503,25 @@ where
 	read_proof_check_on_proving_backend(&proving_backend, key)
 }
 
+/// Check storage read proof for several keys, generated by `prove_read_keys` call.
+///
+/// Returns the values of the keys in the same order, `None` for the keys proven to be absent.
+/// Fails if the proof doesn't contain all the trie nodes needed to read any of the keys.
+pub fn read_keys_proof_check<H>(
+	root: H::Out,
+	proof: Vec<Vec<u8>>,
+	keys: &[Vec<u8>],
+) -> Result<Vec<Option<Vec<u8>>>, Box<Error>>
+where
+	H: Hasher,
+	H::Out: Ord + HeapSizeOf
+{
+	let proving_backend = proving_backend::create_proof_check_backend::<H>(root, proof)?;
+	keys.iter()
+		.map(|key| read_proof_check_on_proving_backend(&proving_backend, key))
+		.collect()
+}
+
 /// Check storage read proof on pre-created proving backend.
 pub fn read_proof_check_on_proving_backend<H>(
 	proving_backend: &TrieBackend<MemoryDB<H>, H>,
@@ -716,6 +756,20 @@ mod tests {
 		assert_eq!(local_result2, false);
 	}
 
+	#[test]
+	fn prove_read_keys_and_proof_check_works() {
+		// fetch read proof from 'remote' full node
+		let remote_backend = trie_backend::tests::test_trie();
+		let remote_root = remote_backend.storage_root(::std::iter::empty()).0;
+		let keys = vec![b"value2".to_vec(), b"key".to_vec(), b"missing".to_vec()];
+		let remote_proof = prove_read_keys(remote_backend, &keys).unwrap();
+		// check proof locally
+		let local_result = read_keys_proof_check::<Blake2Hasher>(remote_root, remote_proof.clone(), &keys).unwrap();
+		assert_eq!(local_result, vec![Some(vec![24]), Some(b"value".to_vec()), None]);
+		// keys that are not covered by the proof can't be checked
+		assert!(read_keys_proof_check::<Blake2Hasher>(remote_root, remote_proof, &[vec![0xff]]).is_err());
+	}
+
 	#[test]
 	fn cannot_change_changes_trie_config() {
 		assert!(execute(
@@ -142,6 +142,10 @@ impl<'a, S, H> Backend<H> for ProvingBackend<'a, S, H>
 		self.backend.for_keys_with_prefix(prefix, f)
 	}
 
+	fn keys(&self, prefix: &[u8]) -> Result<Vec<Vec<u8>>, Self::Error> {
+		self.backend.keys(prefix)
+	}
+
 	fn pairs(&self) -> Vec<(Vec<u8>, Vec<u8>)> {
 		self.backend.pairs()
 	}
@@ -235,6 +239,7 @@ mod tests {
 		let proving_backend = test_proving(&trie_backend);
 		assert_eq!(trie_backend.storage(b"key").unwrap(), proving_backend.storage(b"key").unwrap());
 		assert_eq!(trie_backend.pairs(), proving_backend.pairs());
+		assert_eq!(trie_backend.keys(b"value").unwrap(), proving_backend.keys(b"value").unwrap());
 
 		let (trie_root, mut trie_mdb) = trie_backend.storage_root(::std::iter::empty());
 		let (proving_root, mut proving_mdb) = proving_backend.storage_root(::std::iter::empty());
@@ -262,5 +267,8 @@ mod tests {
 
 		let proof_check = create_proof_check_backend::<Blake2Hasher>(in_memory_root.into(), proof).unwrap();
 		assert_eq!(proof_check.storage(&[42]).unwrap().unwrap(), vec![42]);
+
+		// the proof doesn't cover the other keys, so they can't be listed.
+		assert!(ProvingBackend::new(&proof_check).keys(&[]).is_err());
 	}
 }
@@ -20,15 +20,16 @@ use std::collections::HashMap;
 use std::iter::FromIterator;
 use hash_db::Hasher;
 use heapsize::HeapSizeOf;
-use trie::trie_root;
+use trie::{trie_root, child_trie_root};
 use backend::InMemory;
 use changes_trie::{compute_changes_trie_root, InMemoryStorage as ChangesTrieInMemoryStorage, AnchorBlockId};
-use primitives::storage::well_known_keys::CHANGES_TRIE_CONFIG;
+use primitives::storage::well_known_keys::{CHANGES_TRIE_CONFIG, is_child_storage_key};
 use super::{Externalities, OverlayedChanges};
 
 /// Simple HashMap-based Externalities impl.
 pub struct TestExternalities<H: Hasher> where H::Out: HeapSizeOf {
 	inner: HashMap<Vec<u8>, Vec<u8>>,
+	children: HashMap<Vec<u8>, HashMap<Vec<u8>, Vec<u8>>>,
 	changes_trie_storage: ChangesTrieInMemoryStorage<H>,
 	changes: OverlayedChanges,
 }
@@ -45,6 +46,7 @@ impl<H: Hasher> TestExternalities<H> where H::Out: HeapSizeOf {
 
 		TestExternalities {
 			inner,
+			children: Default::default(),
 			changes_trie_storage: ChangesTrieInMemoryStorage::new(),
 			changes: overlay,
 		}
@@ -92,6 +94,7 @@ impl<H: Hasher> From< HashMap<Vec<u8>, Vec<u8>> > for TestExternalities<H> where
 	fn from(hashmap: HashMap<Vec<u8>, Vec<u8>>) -> Self {
 		TestExternalities {
 			inner: hashmap,
+			children: Default::default(),
 			changes_trie_storage: ChangesTrieInMemoryStorage::new(),
 			changes: Default::default(),
 		}
@@ -103,8 +106,8 @@ impl<H: Hasher> Externalities<H> for TestExternalities<H> where H::Out: Ord + He
 		self.inner.get(key).map(|x| x.to_vec())
 	}
 
-	fn child_storage(&self, _storage_key: &[u8], _key: &[u8]) -> Option<Vec<u8>> {
-		None
+	fn child_storage(&self, storage_key: &[u8], key: &[u8]) -> Option<Vec<u8>> {
+		self.children.get(storage_key).and_then(|child| child.get(key)).cloned()
 	}
 
 	fn place_storage(&mut self, key: Vec<u8>, maybe_value: Option<Vec<u8>>) {
@@ -115,11 +118,23 @@ impl<H: Hasher> Externalities<H> for TestExternalities<H> where H::Out: Ord + He
 		}
 	}
 
-	fn place_child_storage(&mut self, _storage_key: Vec<u8>, _key: Vec<u8>, _value: Option<Vec<u8>>) -> bool {
-		false
+	fn place_child_storage(&mut self, storage_key: Vec<u8>, key: Vec<u8>, value: Option<Vec<u8>>) -> bool {
+		if !is_child_storage_key(&storage_key) {
+			return false;
+		}
+
+		let child = self.children.entry(storage_key).or_default();
+		match value {
+			Some(value) => { child.insert(key, value); }
+			None => { child.remove(&key); }
+		}
+
+		true
 	}
 
-	fn kill_child_storage(&mut self, _storage_key: &[u8]) { }
+	fn kill_child_storage(&mut self, storage_key: &[u8]) {
+		self.children.remove(storage_key);
+	}
 
 	fn clear_prefix(&mut self, prefix: &[u8]) {
 		self.changes.clear_prefix(prefix);
@@ -129,11 +144,21 @@ impl<H: Hasher> Externalities<H> for TestExternalities<H> where H::Out: Ord + He
 	fn chain_id(&self) -> u64 { 42 }
 
 	fn storage_root(&mut self) -> H::Out {
-		trie_root::<H, _, _, _>(self.inner.clone())
+		// Non-empty child tries are committed to the top trie under their storage keys.
+		let mut top = self.inner.clone();
+		for (storage_key, child) in self.children.iter().filter(|(_, child)| !child.is_empty()) {
+			top.insert(storage_key.clone(), child_trie_root::<H, _, _, _>(storage_key, child.clone()));
+		}
+		trie_root::<H, _, _, _>(top)
 	}
 
-	fn child_storage_root(&mut self, _storage_key: &[u8]) -> Option<Vec<u8>> {
-		None
+	fn child_storage_root(&mut self, storage_key: &[u8]) -> Option<Vec<u8>> {
+		if !is_child_storage_key(storage_key) {
+			return None;
+		}
+
+		let child = self.children.get(storage_key).cloned().unwrap_or_default();
+		Some(child_trie_root::<H, _, _, _>(storage_key, child))
 	}
 
 	fn storage_changes_root(&mut self, parent: H::Out, parent_num: u64) -> Option<H::Out> {
@@ -160,4 +185,20 @@ mod tests {
 		const ROOT: [u8; 32] = hex!("0b41e488cccbd67d1f1089592c2c235f5c5399b053f7fe9152dd4b5f279914cd");
 		assert_eq!(ext.storage_root(), H256::from(ROOT));
 	}
+
+	#[test]
+	fn child_storage_should_work() {
+		let mut ext = TestExternalities::<Blake2Hasher>::default();
+		let child = b":child_storage:default:test".to_vec();
+		let empty_root = ext.child_storage_root(&child).unwrap();
+
+		assert!(ext.set_child_storage(child.clone(), b"dog".to_vec(), b"puppy".to_vec()));
+		assert!(!ext.set_child_storage(b"not_a_child".to_vec(), b"dog".to_vec(), b"puppy".to_vec()));
+		assert_eq!(ext.child_storage(&child, b"dog"), Some(b"puppy".to_vec()));
+		assert!(ext.child_storage_root(&child).unwrap() != empty_root);
+
+		ext.kill_child_storage(&child);
+		assert_eq!(ext.child_storage(&child, b"dog"), None);
+		assert_eq!(ext.child_storage_root(&child).unwrap(), empty_root);
+	}
 }
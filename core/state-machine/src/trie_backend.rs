@@ -77,6 +77,14 @@ impl<S: TrieBackendStorage<H>, H: Hasher> Backend<H> for TrieBackend<S, H> where
 		self.essence.for_keys_with_prefix(prefix, f)
 	}
 
+	fn keys(&self, prefix: &[u8]) -> Result<Vec<Vec<u8>>, Self::Error> {
+		self.essence.keys_paged(prefix, None, usize::max_value())
+	}
+
+	fn keys_paged(&self, prefix: &[u8], start_key: Option<&[u8]>, count: usize) -> Result<Vec<Vec<u8>>, Self::Error> {
+		self.essence.keys_paged(prefix, start_key, count)
+	}
+
 	fn for_keys_in_child_storage<F: FnMut(&[u8])>(&self, storage_key: &[u8], f: F) {
 		self.essence.for_keys_in_child_storage(storage_key, f)
 	}
@@ -232,6 +240,39 @@ pub mod tests {
 		assert!(new_root != test_trie().storage_root(::std::iter::empty()).0);
 	}
 
+	#[test]
+	fn keys_are_listed_in_order() {
+		let trie = test_trie();
+
+		assert_eq!(trie.keys(b"value").unwrap(), vec![b"value1".to_vec(), b"value2".to_vec()]);
+		assert_eq!(trie.keys(b"missing").unwrap(), Vec::<Vec<u8>>::new());
+	}
+
+	#[test]
+	fn keys_paging_works() {
+		let trie = test_trie();
+
+		assert_eq!(trie.keys_paged(b"value", None, 1).unwrap(), vec![b"value1".to_vec()]);
+		assert_eq!(trie.keys_paged(b"value", Some(b"value1"), 10).unwrap(), vec![b"value2".to_vec()]);
+		assert_eq!(trie.keys_paged(b"value", Some(b"value2"), 10).unwrap(), Vec::<Vec<u8>>::new());
+		// the start key doesn't have to exist or to start with the prefix
+		assert_eq!(trie.keys_paged(b"value", Some(b"key"), 10).unwrap(), vec![b"value1".to_vec(), b"value2".to_vec()]);
+		assert_eq!(trie.keys_paged(b"", Some(b"value1"), 1).unwrap(), vec![b"value2".to_vec()]);
+	}
+
+	#[test]
+	fn keys_fail_on_missing_nodes() {
+		use hash_db::HashDB;
+
+		let (mdb, root) = test_db();
+		let mut partial_mdb = MemoryDB::<Blake2Hasher>::default();
+		partial_mdb.insert(&mdb.get(&root).unwrap()[..]);
+		let partial_trie = TrieBackend::new(partial_mdb, root);
+
+		assert!(partial_trie.keys(b"value").is_err());
+		assert!(partial_trie.keys_paged(b"value", None, 10).is_err());
+	}
+
 	#[test]
 	fn prefix_walking_works() {
 		let trie = test_trie();
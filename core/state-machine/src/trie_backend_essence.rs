@@ -22,7 +22,7 @@ use std::ops::Deref;
 use std::sync::Arc;
 use hash_db::{self, Hasher};
 use heapsize::HeapSizeOf;
-use trie::{TrieDB, Trie, MemoryDB, DBValue, TrieError, default_child_trie_root, read_trie_value, read_child_trie_value, for_keys_in_child_trie};
+use trie::{TrieDB, Trie, MemoryDB, DBValue, TrieError, default_child_trie_root, read_trie_value, read_child_trie_value, for_keys_in_child_trie, prefixed_nodes};
 use changes_trie::Storage as ChangesTrieStorage;
 
 /// Patricia trie-based storage trait.
@@ -141,6 +141,68 @@ impl<S: TrieBackendStorage<H>, H: Hasher> TrieBackendEssence<S, H> where H::Out:
 			debug!(target: "trie", "Error while iterating by prefix: {}", e);
 		}
 	}
+
+	/// Get at most `count` keys that start with `prefix` and follow `start_key`, in lexicographic
+	/// order. The keys from the beginning are returned if `start_key` is `None`.
+	///
+	/// Fails rather than returning a partial list if a trie node can't be read.
+	pub fn keys_paged(&self, prefix: &[u8], start_key: Option<&[u8]>, count: usize) -> Result<Vec<Vec<u8>>, String> {
+		let mut read_overlay = MemoryDB::default();
+		let eph = Ephemeral {
+			storage: &self.storage,
+			overlay: &mut read_overlay,
+		};
+
+		let mut keys = Vec::new();
+		{
+			let mut iter = || -> Result<(), Box<TrieError<H::Out>>> {
+				let trie = TrieDB::<H>::new(&eph, &self.root)?;
+				let mut iter = trie.iter()?;
+
+				let seek_key = match start_key {
+					Some(start_key) if start_key > prefix => start_key,
+					_ => prefix,
+				};
+				iter.seek(seek_key)?;
+
+				for x in iter {
+					if keys.len() >= count {
+						break;
+					}
+
+					let (key, _) = x?;
+
+					if !key.starts_with(prefix) {
+						break;
+					}
+
+					if start_key.map_or(true, |start_key| &key[..] > start_key) {
+						keys.push(key);
+					}
+				}
+
+				Ok(())
+			};
+
+			iter().map_err(|e| format!("Trie lookup error: {}", e))?;
+		}
+
+		Ok(keys)
+	}
+
+	/// Get the hashes of the trie nodes that lie on the paths to any of the given key prefixes or
+	/// under them, descending only into the nodes for which `filter` returns `true`.
+	pub fn prefixed_nodes<F: Fn(&H::Out) -> bool>(&self, prefixes: &[Vec<u8>], filter: F) -> Result<Vec<H::Out>, String> {
+		let mut read_overlay = MemoryDB::default();
+		let eph = Ephemeral {
+			storage: &self.storage,
+			overlay: &mut read_overlay,
+		};
+
+		let map_e = |e| format!("Trie lookup error: {}", e);
+
+		prefixed_nodes::<H, F>(&eph, &self.root, prefixes, filter).map_err(map_e)
+	}
 }
 
 pub(crate) struct Ephemeral<'a, S: 'a + TrieBackendStorage<H>, H: 'a + Hasher> {
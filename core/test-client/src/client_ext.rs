@@ -58,7 +58,7 @@ impl<B, E, RA> TestClient for Client<B, E, runtime::Block, RA>
 	}
 
 	fn finalize_block(&self, id: BlockId<runtime::Block>) -> client::error::Result<()> {
-		self.finalize_block(id, true)
+		self.finalize_block(id, None, true)
 	}
 
 	fn genesis_hash(&self) -> runtime::Hash {
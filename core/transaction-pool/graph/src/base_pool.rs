@@ -90,6 +90,8 @@ pub struct Transaction<Hash, Extrinsic> {
 	pub priority: Priority,
 	/// At which block the transaction becomes invalid?
 	pub valid_till: Longevity,
+	/// Size of the encoded transaction.
+	pub bytes: usize,
 	/// Tags required by the transaction.
 	pub requires: Vec<Tag>,
 	/// Tags that this transaction provides.
@@ -226,6 +228,26 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: ::std::fmt::Debug> BasePool<Hash
 		self.future.all()
 	}
 
+	/// Returns hashes of future transactions along with the tags they are waiting for.
+	///
+	/// The tags are the requirements that are not provided by any transaction in the ready queue.
+	pub fn futures_missing_tags(&self) -> Vec<(Hash, Vec<Tag>)> {
+		self.future.all_waiting()
+			.map(|tx| (tx.transaction.hash.clone(), tx.missing_tags.iter().cloned().collect()))
+			.collect()
+	}
+
+	/// Returns hashes of all transactions (both ready and future) that provide any of given tags.
+	pub fn hashes_providing(&self, tags: &[Tag]) -> Vec<Hash> {
+		let provides_any = |tx: &Transaction<Hash, Ex>| tx.provides.iter().any(|tag| tags.contains(tag));
+		let mut hashes = self.ready()
+			.filter(|tx| provides_any(tx))
+			.map(|tx| tx.hash.clone())
+			.collect::<Vec<_>>();
+		hashes.extend(self.futures().filter(|tx| provides_any(tx)).map(|tx| tx.hash.clone()));
+		hashes
+	}
+
 	/// Removes all transactions represented by the hashes and all other transactions
 	/// that depend on them.
 	///
@@ -277,11 +299,44 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: ::std::fmt::Debug> BasePool<Hash
 		}
 	}
 
+	/// Removes the worst transactions until both queues are within given limits.
+	///
+	/// The transactions with the lowest priority are removed first. To respect the dependency
+	/// chains, only ready transactions that no other ready transaction depends on are considered,
+	/// so that a valuable transaction is never removed because a cheap one it depends on.
+	///
+	/// Returns a list of removed transactions.
+	pub fn enforce_limits(&mut self, ready: &Limit, future: &Limit) -> Vec<Arc<Transaction<Hash, Ex>>> {
+		let mut removed = vec![];
+
+		while ready.is_exceeded(self.ready.len(), self.ready.bytes()) {
+			let worst = match self.ready.worst_leaf() {
+				Some(worst) => worst,
+				None => break,
+			};
+			debug!(target: "txpool", "[{:?}] Dropping from ready queue: limit reached", worst);
+			removed.append(&mut self.ready.remove_invalid(&[worst]));
+		}
+
+		while future.is_exceeded(self.future.len(), self.future.bytes()) {
+			let worst = match self.future.worst() {
+				Some(worst) => worst,
+				None => break,
+			};
+			debug!(target: "txpool", "[{:?}] Dropping from future queue: limit reached", worst);
+			removed.extend(self.future.remove(&[worst]).into_iter().map(Arc::new));
+		}
+
+		removed
+	}
+
 	/// Get pool status.
 	pub fn status(&self) -> Status {
 		Status {
 			ready: self.ready.len(),
+			ready_bytes: self.ready.bytes(),
 			future: self.future.len(),
+			future_bytes: self.future.bytes(),
 		}
 	}
 }
@@ -290,8 +345,28 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: ::std::fmt::Debug> BasePool<Hash
 pub struct Status {
 	/// Number of transactions in the ready queue.
 	pub ready: usize,
+	/// Sum of encoded sizes of all transactions in the ready queue.
+	pub ready_bytes: usize,
 	/// Number of transactions in the future queue.
 	pub future: usize,
+	/// Sum of encoded sizes of all transactions in the future queue.
+	pub future_bytes: usize,
+}
+
+/// Queue limits
+#[derive(Debug, Clone)]
+pub struct Limit {
+	/// Maximal number of transactions in the queue.
+	pub count: usize,
+	/// Maximal size of encodings of all transactions in the queue.
+	pub total_bytes: usize,
+}
+
+impl Limit {
+	/// Returns true if any of the provided values exceeds the limit.
+	pub fn is_exceeded(&self, count: usize, bytes: usize) -> bool {
+		self.count < count || self.total_bytes < bytes
+	}
 }
 
 #[cfg(test)]
@@ -304,6 +379,129 @@ mod tests {
 		BasePool::default()
 	}
 
+	#[test]
+	fn should_report_missing_tags_and_find_providers() {
+		// given
+		let mut pool = pool();
+		pool.import(Transaction {
+			data: vec![1u8],
+			hash: 1,
+			priority: 5u64,
+			valid_till: 64u64,
+			bytes: 1,
+			requires: vec![],
+			provides: vec![vec![1]],
+		}).unwrap();
+		pool.import(Transaction {
+			data: vec![3u8],
+			hash: 3,
+			priority: 5u64,
+			valid_till: 64u64,
+			bytes: 1,
+			requires: vec![vec![1], vec![2]],
+			provides: vec![vec![3]],
+		}).unwrap();
+
+		// then
+		assert_eq!(pool.futures_missing_tags(), vec![(3, vec![vec![2]])]);
+		assert_eq!(pool.hashes_providing(&[vec![1]]), vec![1]);
+		assert_eq!(pool.hashes_providing(&[vec![3], vec![4]]), vec![3]);
+		assert_eq!(pool.hashes_providing(&[vec![2]]), Vec::<Hash>::new());
+	}
+
+	#[test]
+	fn should_enforce_limits_respecting_dependencies() {
+		// given
+		let mut pool = pool();
+		let limit = |count| Limit { count, total_bytes: 1024 };
+		pool.import(Transaction {
+			data: vec![1u8],
+			hash: 1,
+			priority: 1u64,
+			valid_till: 64u64,
+			bytes: 1,
+			requires: vec![],
+			provides: vec![vec![1]],
+		}).unwrap();
+		pool.import(Transaction {
+			data: vec![2u8],
+			hash: 2,
+			priority: 10u64,
+			valid_till: 64u64,
+			bytes: 1,
+			requires: vec![vec![1]],
+			provides: vec![vec![2]],
+		}).unwrap();
+		pool.import(Transaction {
+			data: vec![3u8],
+			hash: 3,
+			priority: 5u64,
+			valid_till: 64u64,
+			bytes: 1,
+			requires: vec![],
+			provides: vec![vec![3]],
+		}).unwrap();
+		pool.import(Transaction {
+			data: vec![4u8],
+			hash: 4,
+			priority: 7u64,
+			valid_till: 64u64,
+			bytes: 1,
+			requires: vec![vec![0]],
+			provides: vec![vec![4]],
+		}).unwrap();
+		pool.import(Transaction {
+			data: vec![5u8],
+			hash: 5,
+			priority: 6u64,
+			valid_till: 64u64,
+			bytes: 1,
+			requires: vec![vec![0]],
+			provides: vec![vec![5]],
+		}).unwrap();
+		assert_eq!(pool.status().ready, 3);
+		assert_eq!(pool.status().future, 2);
+		assert_eq!(pool.status().ready_bytes, 3);
+
+		// when
+		let removed = pool.enforce_limits(&limit(2), &limit(1));
+
+		// then
+		// the transaction with the lowest priority stays, since the best one depends on it
+		assert_eq!(removed.iter().map(|tx| tx.hash).collect::<Vec<_>>(), vec![3, 5]);
+		assert_eq!(pool.ready().map(|tx| tx.hash).collect::<Vec<_>>(), vec![1, 2]);
+		assert_eq!(pool.futures().map(|tx| tx.hash).collect::<Vec<_>>(), vec![4]);
+		assert_eq!(pool.status().ready_bytes, 2);
+		assert_eq!(pool.status().future_bytes, 1);
+	}
+
+	#[test]
+	fn should_enforce_bytes_limit() {
+		// given
+		let mut pool = pool();
+		for i in 1..4u64 {
+			pool.import(Transaction {
+				data: vec![i as u8],
+				hash: i,
+				priority: i,
+				valid_till: 64u64,
+				bytes: 10,
+				requires: vec![],
+				provides: vec![vec![i as u8]],
+			}).unwrap();
+		}
+
+		// when
+		let removed = pool.enforce_limits(
+			&Limit { count: 10, total_bytes: 25 },
+			&Limit { count: 10, total_bytes: 25 },
+		);
+
+		// then
+		assert_eq!(removed.iter().map(|tx| tx.hash).collect::<Vec<_>>(), vec![1]);
+		assert_eq!(pool.status().ready_bytes, 20);
+	}
+
 	#[test]
 	fn should_import_transaction_to_ready() {
 		// given
@@ -315,6 +513,7 @@ mod tests {
 			hash: 1u64,
 			priority: 5u64,
 			valid_till: 64u64,
+			bytes: 1,
 			requires: vec![],
 			provides: vec![vec![1]],
 		}).unwrap();
@@ -335,6 +534,7 @@ mod tests {
 			hash: 1,
 			priority: 5u64,
 			valid_till: 64u64,
+			bytes: 1,
 			requires: vec![],
 			provides: vec![vec![1]],
 		}).unwrap();
@@ -343,6 +543,7 @@ mod tests {
 			hash: 1,
 			priority: 5u64,
 			valid_till: 64u64,
+			bytes: 1,
 			requires: vec![],
 			provides: vec![vec![1]],
 		}).unwrap_err();
@@ -364,6 +565,7 @@ mod tests {
 			hash: 1,
 			priority: 5u64,
 			valid_till: 64u64,
+			bytes: 1,
 			requires: vec![vec![0]],
 			provides: vec![vec![1]],
 		}).unwrap();
@@ -374,6 +576,7 @@ mod tests {
 			hash: 2,
 			priority: 5u64,
 			valid_till: 64u64,
+			bytes: 1,
 			requires: vec![],
 			provides: vec![vec![0]],
 		}).unwrap();
@@ -394,6 +597,7 @@ mod tests {
 			hash: 1,
 			priority: 5u64,
 			valid_till: 64u64,
+			bytes: 1,
 			requires: vec![vec![0]],
 			provides: vec![vec![1]],
 		}).unwrap();
@@ -402,6 +606,7 @@ mod tests {
 			hash: 3,
 			priority: 5u64,
 			valid_till: 64u64,
+			bytes: 1,
 			requires: vec![vec![2]],
 			provides: vec![],
 		}).unwrap();
@@ -410,6 +615,7 @@ mod tests {
 			hash: 2,
 			priority: 5u64,
 			valid_till: 64u64,
+			bytes: 1,
 			requires: vec![vec![1]],
 			provides: vec![vec![3], vec![2]],
 		}).unwrap();
@@ -418,6 +624,7 @@ mod tests {
 			hash: 4,
 			priority: 1_000u64,
 			valid_till: 64u64,
+			bytes: 1,
 			requires: vec![vec![3], vec![4]],
 			provides: vec![],
 		}).unwrap();
@@ -429,6 +636,7 @@ mod tests {
 			hash: 5,
 			priority: 5u64,
 			valid_till: 64u64,
+			bytes: 1,
 			requires: vec![],
 			provides: vec![vec![0], vec![4]],
 		}).unwrap();
@@ -459,6 +667,7 @@ mod tests {
 			hash: 1,
 			priority: 5u64,
 			valid_till: 64u64,
+			bytes: 1,
 			requires: vec![vec![0]],
 			provides: vec![vec![1]],
 		}).unwrap();
@@ -467,6 +676,7 @@ mod tests {
 			hash: 3,
 			priority: 5u64,
 			valid_till: 64u64,
+			bytes: 1,
 			requires: vec![vec![1]],
 			provides: vec![vec![2]],
 		}).unwrap();
@@ -479,6 +689,7 @@ mod tests {
 			hash: 2,
 			priority: 5u64,
 			valid_till: 64u64,
+			bytes: 1,
 			requires: vec![vec![2]],
 			provides: vec![vec![0]],
 		}).unwrap();
@@ -497,6 +708,7 @@ mod tests {
 			hash: 4,
 			priority: 50u64,
 			valid_till: 64u64,
+			bytes: 1,
 			requires: vec![],
 			provides: vec![vec![0]],
 		}).unwrap();
@@ -524,6 +736,7 @@ mod tests {
 			hash: 1,
 			priority: 5u64,
 			valid_till: 64u64,
+			bytes: 1,
 			requires: vec![vec![0]],
 			provides: vec![vec![1]],
 		}).unwrap();
@@ -532,6 +745,7 @@ mod tests {
 			hash: 3,
 			priority: 5u64,
 			valid_till: 64u64,
+			bytes: 1,
 			requires: vec![vec![1]],
 			provides: vec![vec![2]],
 		}).unwrap();
@@ -544,6 +758,7 @@ mod tests {
 			hash: 2,
 			priority: 5u64,
 			valid_till: 64u64,
+			bytes: 1,
 			requires: vec![vec![2]],
 			provides: vec![vec![0]],
 		}).unwrap();
@@ -562,6 +777,7 @@ mod tests {
 			hash: 4,
 			priority: 1u64, // lower priority than Tx(2)
 			valid_till: 64u64,
+			bytes: 1,
 			requires: vec![],
 			provides: vec![vec![0]],
 		}).unwrap_err();
@@ -584,6 +800,7 @@ mod tests {
 			hash: 5,
 			priority: 5u64,
 			valid_till: 64u64,
+			bytes: 1,
 			requires: vec![],
 			provides: vec![vec![0], vec![4]],
 		}).unwrap();
@@ -592,6 +809,7 @@ mod tests {
 			hash: 1,
 			priority: 5u64,
 			valid_till: 64u64,
+			bytes: 1,
 			requires: vec![vec![0]],
 			provides: vec![vec![1]],
 		}).unwrap();
@@ -600,6 +818,7 @@ mod tests {
 			hash: 3,
 			priority: 5u64,
 			valid_till: 64u64,
+			bytes: 1,
 			requires: vec![vec![2]],
 			provides: vec![],
 		}).unwrap();
@@ -608,6 +827,7 @@ mod tests {
 			hash: 2,
 			priority: 5u64,
 			valid_till: 64u64,
+			bytes: 1,
 			requires: vec![vec![1]],
 			provides: vec![vec![3], vec![2]],
 		}).unwrap();
@@ -616,6 +836,7 @@ mod tests {
 			hash: 4,
 			priority: 1_000u64,
 			valid_till: 64u64,
+			bytes: 1,
 			requires: vec![vec![3], vec![4]],
 			provides: vec![],
 		}).unwrap();
@@ -625,6 +846,7 @@ mod tests {
 			hash: 6,
 			priority: 1_000u64,
 			valid_till: 64u64,
+			bytes: 1,
 			requires: vec![vec![11]],
 			provides: vec![],
 		}).unwrap();
@@ -650,6 +872,7 @@ mod tests {
 			hash: 5,
 			priority: 5u64,
 			valid_till: 64u64,
+			bytes: 1,
 			requires: vec![vec![0]],
 			provides: vec![vec![100]],
 		}).unwrap();
@@ -659,6 +882,7 @@ mod tests {
 			hash: 1,
 			priority: 5u64,
 			valid_till: 64u64,
+			bytes: 1,
 			requires: vec![],
 			provides: vec![vec![1]],
 		}).unwrap();
@@ -667,6 +891,7 @@ mod tests {
 			hash: 2,
 			priority: 5u64,
 			valid_till: 64u64,
+			bytes: 1,
 			requires: vec![vec![2]],
 			provides: vec![vec![3]],
 		}).unwrap();
@@ -675,6 +900,7 @@ mod tests {
 			hash: 3,
 			priority: 5u64,
 			valid_till: 64u64,
+			bytes: 1,
 			requires: vec![vec![1]],
 			provides: vec![vec![2]],
 		}).unwrap();
@@ -683,6 +909,7 @@ mod tests {
 			hash: 4,
 			priority: 1_000u64,
 			valid_till: 64u64,
+			bytes: 1,
 			requires: vec![vec![3], vec![2]],
 			provides: vec![vec![4]],
 		}).unwrap();
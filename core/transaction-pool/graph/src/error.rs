@@ -45,6 +45,11 @@ error_chain! {
 			description("The priority is too low to replace transactions already in the pool."),
 			display("Too low priority ({} > {})", old, new)
 		}
+		/// Transaction was dropped right after import because the pool is full.
+		ImmediatelyDropped {
+			description("Transaction couldn't enter the pool because of the limit."),
+			display("Immediately Dropped"),
+		}
 		/// Deps cycle detected and we couldn't import transaction.
 		CycleDetected {
 			description("Transaction was not imported because of detected cycle."),
@@ -17,6 +17,7 @@
 use std::{
 	collections::{HashMap, HashSet},
 	hash,
+	time,
 };
 
 use sr_primitives::transaction_validity::{
@@ -32,6 +33,8 @@ pub struct WaitingTransaction<Hash, Ex> {
 	pub transaction: Transaction<Hash, Ex>,
 	/// Tags that are required and have not been satisfied yet by other transactions in the pool.
 	pub missing_tags: HashSet<Tag>,
+	/// Time of import to the Future queue.
+	pub imported_at: time::Instant,
 }
 
 impl<Hash, Ex> WaitingTransaction<Hash, Ex> {
@@ -49,6 +52,7 @@ impl<Hash, Ex> WaitingTransaction<Hash, Ex> {
 		WaitingTransaction {
 			transaction,
 			missing_tags,
+			imported_at: time::Instant::now(),
 		}
 	}
 
@@ -73,6 +77,8 @@ pub struct FutureTransactions<Hash: hash::Hash + Eq, Ex> {
 	wanted_tags: HashMap<Tag, HashSet<Hash>>,
 	/// Transactions waiting for a particular other transaction
 	waiting: HashMap<Hash, WaitingTransaction<Hash, Ex>>,
+	/// Sum of encoding lengths of all transactions in `waiting`
+	bytes: usize,
 }
 
 impl<Hash: hash::Hash + Eq, Ex> Default for FutureTransactions<Hash, Ex> {
@@ -80,6 +86,7 @@ impl<Hash: hash::Hash + Eq, Ex> Default for FutureTransactions<Hash, Ex> {
 		FutureTransactions {
 			wanted_tags: Default::default(),
 			waiting: Default::default(),
+			bytes: 0,
 		}
 	}
 }
@@ -109,6 +116,7 @@ impl<Hash: hash::Hash + Eq + Clone, Ex> FutureTransactions<Hash, Ex> {
 		}
 
 		// Add the transaction to a by-hash waiting map
+		self.bytes += tx.transaction.bytes;
 		self.waiting.insert(tx.transaction.hash.clone(), tx);
 	}
 
@@ -136,6 +144,7 @@ impl<Hash: hash::Hash + Eq + Clone, Ex> FutureTransactions<Hash, Ex> {
 
 					if is_ready {
 						let tx = self.waiting.remove(&hash).expect(WAITING_PROOF);
+						self.bytes -= tx.transaction.bytes;
 						became_ready.push(tx);
 					}
 				}
@@ -152,6 +161,7 @@ impl<Hash: hash::Hash + Eq + Clone, Ex> FutureTransactions<Hash, Ex> {
 		let mut removed = vec![];
 		for hash in hashes {
 			if let Some(waiting_tx) = self.waiting.remove(hash) {
+				self.bytes -= waiting_tx.transaction.bytes;
 				// remove from wanted_tags as well
 				for tag in waiting_tx.missing_tags {
 					let remove = if let Some(mut wanted) = self.wanted_tags.get_mut(&tag) {
@@ -169,13 +179,35 @@ impl<Hash: hash::Hash + Eq + Clone, Ex> FutureTransactions<Hash, Ex> {
 		removed
 	}
 
+	/// Returns iterator over all future transactions along with the tags they are still missing.
+	pub fn all_waiting(&self) -> impl Iterator<Item=&WaitingTransaction<Hash, Ex>> {
+		self.waiting.values()
+	}
+
 	/// Returns iterator over all future transactions
 	pub fn all(&self) -> impl Iterator<Item=&Transaction<Hash, Ex>> {
 		self.waiting.values().map(|waiting| &waiting.transaction)
 	}
 
+	/// Returns the hash of the transaction with the lowest priority in the Future queue.
+	///
+	/// Among transactions with the same priority the most recently imported one is returned.
+	pub fn worst(&self) -> Option<Hash> {
+		self.waiting
+			.values()
+			.min_by(|a, b| a.transaction.priority.cmp(&b.transaction.priority)
+				.then(b.imported_at.cmp(&a.imported_at))
+			)
+			.map(|tx| tx.transaction.hash.clone())
+	}
+
 	/// Returns number of transactions in the Future queue.
 	pub fn len(&self) -> usize {
 		self.waiting.len()
 	}
+
+	/// Returns sum of encoding lengths of all transactions in the Future queue.
+	pub fn bytes(&self) -> usize {
+		self.bytes
+	}
 }
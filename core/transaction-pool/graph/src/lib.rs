@@ -53,5 +53,5 @@ pub mod error;
 pub mod watcher;
 
 pub use self::error::IntoPoolError;
-pub use self::base_pool::{Transaction, Status};
+pub use self::base_pool::{Transaction, Status, Limit};
 pub use self::pool::{Pool, Options, ChainApi, EventStream, ExtrinsicFor, BlockHash, ExHash, NumberFor, TransactionFor};
@@ -15,7 +15,7 @@
 // along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::{
-	collections::HashMap,
+	collections::{HashMap, HashSet},
 	hash,
 	sync::Arc,
 	time,
@@ -31,6 +31,7 @@ use serde::Serialize;
 use futures::sync::mpsc;
 use parking_lot::{Mutex, RwLock};
 use sr_primitives::{
+	codec::Encode,
 	generic::BlockId,
 	traits::{self, As},
 	transaction_validity::{TransactionValidity, TransactionTag as Tag},
@@ -73,12 +74,33 @@ pub trait ChainApi: Send + Sync {
 }
 
 /// Pool configuration options.
-#[derive(Debug, Clone, Default)]
-pub struct Options;
+#[derive(Debug, Clone)]
+pub struct Options {
+	/// Ready queue limits.
+	pub ready: base::Limit,
+	/// Future queue limits.
+	pub future: base::Limit,
+}
+
+impl Default for Options {
+	fn default() -> Self {
+		Options {
+			ready: base::Limit {
+				count: 512,
+				total_bytes: 10 * 1024 * 1024,
+			},
+			future: base::Limit {
+				count: 128,
+				total_bytes: 1 * 1024 * 1024,
+			},
+		}
+	}
+}
 
 /// Extrinsics pool.
 pub struct Pool<B: ChainApi> {
 	api: B,
+	options: Options,
 	listener: RwLock<Listener<ExHash<B>, BlockHash<B>>>,
 	pool: RwLock<base::BasePool<
 		ExHash<B>,
@@ -97,9 +119,10 @@ impl<B: ChainApi> Pool<B> {
 		let block_number = self.api.block_id_to_number(at)?
 			.ok_or_else(|| error::ErrorKind::Msg(format!("Invalid block id: {:?}", at)).into())?;
 
-		Ok(xts
+		let results = xts
 			.into_iter()
 			.map(|xt| -> Result<_, B::Error> {
+				let bytes = xt.encode().len();
 				let hash = self.api.hash(&xt);
 				if self.rotator.is_banned(&hash) {
 					bail!(error::Error::from(error::ErrorKind::TemporarilyBanned))
@@ -114,6 +137,7 @@ impl<B: ChainApi> Pool<B> {
 							requires,
 							provides,
 							valid_till: block_number.as_().saturating_add(longevity),
+							bytes,
 						})
 					},
 					TransactionValidity::Invalid => {
@@ -136,7 +160,36 @@ impl<B: ChainApi> Pool<B> {
 				fire_events(&mut *listener, &imported);
 				Ok(imported.hash().clone())
 			})
-			.collect())
+			.collect::<Vec<_>>();
+
+		let removed = self.enforce_limits();
+
+		Ok(results.into_iter().map(|res| match res {
+			Ok(ref hash) if removed.contains(hash) => Err(error::Error::from(error::ErrorKind::ImmediatelyDropped).into()),
+			other => other,
+		}).collect())
+	}
+
+	/// Drops the worst transactions if the pool exceeds the limits.
+	///
+	/// Returns hashes of the dropped transactions.
+	fn enforce_limits(&self) -> HashSet<ExHash<B>> {
+		let status = self.pool.read().status();
+		let ready_limit = &self.options.ready;
+		let future_limit = &self.options.future;
+
+		if !ready_limit.is_exceeded(status.ready, status.ready_bytes)
+			&& !future_limit.is_exceeded(status.future, status.future_bytes) {
+			return Default::default();
+		}
+
+		let removed = self.pool.write().enforce_limits(ready_limit, future_limit);
+		let mut listener = self.listener.write();
+		for tx in &removed {
+			listener.dropped(&tx.hash, None);
+		}
+
+		removed.into_iter().map(|tx| tx.hash.clone()).collect()
 	}
 
 	/// Imports one unverified extrinsic to the pool
@@ -188,6 +241,79 @@ impl<B: ChainApi> Pool<B> {
 		Ok(())
 	}
 
+	/// Re-validates all ready transactions against the state at given block.
+	///
+	/// Should be called whenever the best chain switches to another fork, since transactions
+	/// validated against the retracted blocks might now be invalid or have different requirements.
+	/// Invalid transactions are removed and temporarily banned. Transactions that provide or
+	/// require different tags now, as well as all transactions depending on removed ones, are
+	/// removed and re-imported. Transactions that fail to be validated are kept as they are.
+	pub fn revalidate_ready(&self, at: &BlockId<B::Block>) -> Result<(), B::Error> {
+		let mut invalid = Vec::new();
+		let mut outdated = Vec::new();
+		for tx in self.ready() {
+			let validity = match self.api.validate_transaction(at, &tx.data) {
+				Ok(validity) => validity,
+				Err(e) => {
+					warn!(target: "txpool", "[{:?}] Error re-validating transaction: {:?}", tx.hash, e);
+					continue;
+				},
+			};
+			match validity {
+				TransactionValidity::Valid { ref requires, ref provides, .. }
+					if *requires == tx.requires && *provides == tx.provides => {},
+				TransactionValidity::Valid { .. } => outdated.push(tx.hash.clone()),
+				TransactionValidity::Invalid => invalid.push(tx.hash.clone()),
+				// keep the transaction until we know more about it.
+				TransactionValidity::Unknown => {},
+			}
+		}
+
+		if invalid.is_empty() && outdated.is_empty() {
+			return Ok(());
+		}
+
+		debug!(target: "txpool", "Re-validated ready transactions. Invalid: {:?}, outdated: {:?}", invalid, outdated);
+		self.rotator.ban(&time::Instant::now(), &invalid);
+
+		let mut to_remove = invalid.clone();
+		to_remove.extend(outdated);
+		let removed = self.pool.write().remove_invalid(&to_remove);
+
+		{
+			let mut listener = self.listener.write();
+			for hash in &invalid {
+				listener.invalid(hash);
+			}
+		}
+
+		// re-import all removed transactions that were not found invalid, including the dependent ones.
+		let to_import = removed.into_iter()
+			.filter(|tx| !invalid.contains(&tx.hash))
+			.collect::<Vec<_>>();
+		let hashes = to_import.iter().map(|tx| tx.hash.clone()).collect::<Vec<_>>();
+		let results = self.submit_at(at, to_import.into_iter().map(|tx| tx.data.clone()))?;
+
+		let mut listener = self.listener.write();
+		for (hash, result) in hashes.into_iter().zip(results) {
+			match result.map_err(error::IntoPoolError::into_pool_error) {
+				Ok(_) => {},
+				Err(Ok(err)) => match err.kind() {
+					error::ErrorKind::InvalidTransaction => {
+						self.rotator.ban(&time::Instant::now(), &[hash.clone()]);
+						listener.invalid(&hash);
+					},
+					// already reported by `submit_at`.
+					error::ErrorKind::UnknownTransactionValidity | error::ErrorKind::ImmediatelyDropped => {},
+					_ => listener.dropped(&hash, None),
+				},
+				Err(Err(_)) => listener.dropped(&hash, None),
+			}
+		}
+
+		Ok(())
+	}
+
 	/// Removes stale transactions from the pool.
 	///
 	/// Stale transactions are transaction beyond their longevity period.
@@ -226,10 +352,10 @@ impl<B: ChainApi> Pool<B> {
 
 impl<B: ChainApi> Pool<B> {
 	/// Create a new transaction pool.
-	/// TODO [ToDr] Options
-	pub fn new(_options: Options, api: B) -> Self {
+	pub fn new(options: Options, api: B) -> Self {
 		Pool {
 			api,
+			options,
 			listener: Default::default(),
 			pool: Default::default(),
 			import_notification_sinks: Default::default(),
@@ -268,11 +394,52 @@ impl<B: ChainApi> Pool<B> {
 		invalid
 	}
 
+	/// Remove transactions from the pool on request, e.g. of the node operator.
+	///
+	/// All transactions depending on the removed ones are removed as well. Removed transactions
+	/// are reported as dropped and the requested ones are temporarily banned, so that they are
+	/// not re-imported right away when propagated back to us.
+	pub fn remove(&self, hashes: &[ExHash<B>]) -> Vec<TransactionFor<B>> {
+		debug!(target: "txpool", "Removing transactions: {:?}", hashes);
+		self.rotator.ban(&time::Instant::now(), hashes);
+
+		let removed = self.pool.write().remove_invalid(hashes);
+
+		let mut listener = self.listener.write();
+		for tx in &removed {
+			listener.dropped(&tx.hash, None);
+		}
+
+		removed
+	}
+
+	/// Returns hashes of all transactions in the pool that provide any of given tags.
+	pub fn hashes_providing(&self, tags: &[Tag]) -> Vec<ExHash<B>> {
+		self.pool.read().hashes_providing(tags)
+	}
+
+	/// Returns hashes of transactions in the future queue along with the tags they are waiting for.
+	pub fn futures_missing_tags(&self) -> Vec<(ExHash<B>, Vec<Tag>)> {
+		self.pool.read().futures_missing_tags()
+	}
+
 	/// Get an iterator for ready transactions ordered by priority
 	pub fn ready(&self) -> impl Iterator<Item=TransactionFor<B>> {
 		self.pool.read().ready()
 	}
 
+	/// Returns all extrinsics in the pool.
+	///
+	/// Ready extrinsics come first, in the order they can be included in a block,
+	/// followed by extrinsics from the future queue.
+	pub fn extrinsics(&self) -> Vec<ExtrinsicFor<B>> {
+		let pool = self.pool.read();
+		pool.ready()
+			.map(|tx| tx.data.clone())
+			.chain(pool.futures().map(|tx| tx.data.clone()))
+			.collect()
+	}
+
 	/// Returns pool status.
 	pub fn status(&self) -> base::Status {
 		self.pool.read().status()
@@ -326,7 +493,8 @@ mod tests {
 
 		/// Verify extrinsic at given block.
 		fn validate_transaction(&self, at: &BlockId<Self::Block>, uxt: &ExtrinsicFor<Self>) -> Result<TransactionValidity, Self::Error> {
-			let block_number = self.block_id_to_number(at)?.unwrap();
+			let block_number = self.block_id_to_number(at)?
+				.ok_or_else(|| error::Error::from(error::ErrorKind::Msg(format!("Unknown block: {:?}", at))))?;
 			let nonce = uxt.transfer.nonce;
 
 			if nonce < block_number {
@@ -413,6 +581,37 @@ mod tests {
 		assert_matches!(res.unwrap_err().kind(), error::ErrorKind::TemporarilyBanned);
 	}
 
+	#[test]
+	fn should_drop_transactions_over_the_limit() {
+		// given
+		let pool = Pool::new(Options {
+			ready: base::Limit { count: 2, total_bytes: 1024 * 1024 },
+			future: base::Limit { count: 1, total_bytes: 1024 * 1024 },
+		}, TestApi::default());
+		let xt = |nonce| uxt(Transfer {
+			from: 1.into(),
+			to: 2.into(),
+			amount: 5,
+			nonce,
+		});
+		pool.submit_one(&BlockId::Number(0), xt(0)).unwrap();
+		pool.submit_one(&BlockId::Number(0), xt(1)).unwrap();
+		pool.submit_one(&BlockId::Number(0), xt(5)).unwrap();
+		assert_eq!(pool.status().ready, 2);
+		assert_eq!(pool.status().future, 1);
+
+		// when
+		let hash = pool.hash_of(&xt(2));
+		let res = pool.submit_one(&BlockId::Number(0), xt(2));
+
+		// then
+		assert_matches!(res.unwrap_err().kind(), error::ErrorKind::ImmediatelyDropped);
+		assert_eq!(pool.status().ready, 2);
+		assert_eq!(pool.status().future, 1);
+		// dropped transactions are not banned
+		assert!(!pool.rotator.is_banned(&hash));
+	}
+
 	#[test]
 	fn should_notify_about_pool_events() {
 		let stream = {
@@ -489,6 +688,76 @@ mod tests {
 		assert!(pool.rotator.is_banned(&hash3));
 	}
 
+	#[test]
+	fn should_revalidate_ready_transactions() {
+		// given
+		let pool = pool();
+		let xt = |nonce| uxt(Transfer {
+			from: 1.into(),
+			to: 2.into(),
+			amount: 5,
+			nonce,
+		});
+		let hash0 = pool.submit_one(&BlockId::Number(0), xt(0)).unwrap();
+		let hash1 = pool.submit_one(&BlockId::Number(0), xt(1)).unwrap();
+		let hash2 = pool.submit_one(&BlockId::Number(0), xt(2)).unwrap();
+		assert_eq!(pool.status().ready, 3);
+
+		// when
+		pool.revalidate_ready(&BlockId::Number(1)).unwrap();
+
+		// then
+		// nonce 0 became invalid and nonce 1 doesn't require any tags any more.
+		assert_eq!(pool.ready().map(|tx| tx.hash).collect::<Vec<_>>(), vec![hash1, hash2]);
+		assert_eq!(pool.ready().next().unwrap().requires, Vec::<Tag>::new());
+		assert_eq!(pool.status().future, 0);
+		assert!(pool.rotator.is_banned(&hash0));
+		assert!(!pool.rotator.is_banned(&hash1));
+		assert!(!pool.rotator.is_banned(&hash2));
+	}
+
+	#[test]
+	fn should_keep_transactions_that_fail_to_revalidate() {
+		// given
+		let pool = pool();
+		let xt = |nonce| uxt(Transfer {
+			from: 1.into(),
+			to: 2.into(),
+			amount: 5,
+			nonce,
+		});
+		let hash0 = pool.submit_one(&BlockId::Number(0), xt(0)).unwrap();
+		let hash1 = pool.submit_one(&BlockId::Number(0), xt(1)).unwrap();
+
+		// when
+		pool.revalidate_ready(&BlockId::Hash(Default::default())).unwrap();
+
+		// then
+		assert_eq!(pool.ready().map(|tx| tx.hash).collect::<Vec<_>>(), vec![hash0, hash1]);
+		assert!(!pool.rotator.is_banned(&hash0));
+	}
+
+	#[test]
+	fn should_return_all_extrinsics() {
+		// given
+		let pool = pool();
+		let xt = |nonce| uxt(Transfer {
+			from: 1.into(),
+			to: 2.into(),
+			amount: 5,
+			nonce,
+		});
+		pool.submit_one(&BlockId::Number(0), xt(3)).unwrap();
+		pool.submit_one(&BlockId::Number(0), xt(1)).unwrap();
+		pool.submit_one(&BlockId::Number(0), xt(0)).unwrap();
+
+		// when
+		let extrinsics = pool.extrinsics();
+
+		// then
+		assert_eq!(extrinsics, vec![xt(0), xt(1), xt(3)]);
+	}
+
 	mod listener {
 		use super::*;
 
@@ -569,6 +838,99 @@ mod tests {
 			assert_eq!(stream.next(), None);
 		}
 
+		#[test]
+		fn should_trigger_invalid_when_revalidated() {
+			// given
+			let pool = pool();
+			let watcher = pool.submit_and_watch(&BlockId::Number(0), uxt(Transfer {
+				from: 1.into(),
+				to: 2.into(),
+				amount: 5,
+				nonce: 0,
+			})).unwrap();
+			assert_eq!(pool.status().ready, 1);
+
+			// when
+			pool.revalidate_ready(&BlockId::Number(1)).unwrap();
+
+			// then
+			assert_eq!(pool.status().ready, 0);
+			let mut stream = watcher.into_stream().wait();
+			assert_eq!(stream.next(), Some(Ok(::watcher::Status::Ready)));
+			assert_eq!(stream.next(), Some(Ok(::watcher::Status::Invalid)));
+			assert_eq!(stream.next(), None);
+		}
+
+		#[test]
+		fn should_trigger_dropped_and_ban_when_removed() {
+			// given
+			let pool = pool();
+			let watcher = pool.submit_and_watch(&BlockId::Number(0), uxt(Transfer {
+				from: 1.into(),
+				to: 2.into(),
+				amount: 5,
+				nonce: 0,
+			})).unwrap();
+			let dependent = pool.submit_and_watch(&BlockId::Number(0), uxt(Transfer {
+				from: 1.into(),
+				to: 2.into(),
+				amount: 5,
+				nonce: 1,
+			})).unwrap();
+			assert_eq!(pool.status().ready, 2);
+
+			// when
+			let hashes = pool.hashes_providing(&[vec![0u8]]);
+			assert_eq!(hashes, vec![*watcher.hash()]);
+			assert_eq!(pool.remove(&hashes).len(), 2);
+
+			// then
+			assert_eq!(pool.status().ready, 0);
+			assert!(pool.rotator.is_banned(watcher.hash()));
+			assert!(!pool.rotator.is_banned(dependent.hash()));
+			let mut stream = watcher.into_stream().wait();
+			assert_eq!(stream.next(), Some(Ok(::watcher::Status::Ready)));
+			assert_eq!(stream.next(), Some(Ok(::watcher::Status::Dropped)));
+			assert_eq!(stream.next(), None);
+			let mut stream = dependent.into_stream().wait();
+			assert_eq!(stream.next(), Some(Ok(::watcher::Status::Ready)));
+			assert_eq!(stream.next(), Some(Ok(::watcher::Status::Dropped)));
+			assert_eq!(stream.next(), None);
+		}
+
+		#[test]
+		fn should_trigger_dropped_when_limit_is_reached() {
+			// given
+			let pool = Pool::new(Options {
+				ready: base::Limit { count: 1, total_bytes: 1024 * 1024 },
+				future: base::Limit { count: 1, total_bytes: 1024 * 1024 },
+			}, TestApi::default());
+			let watcher = pool.submit_and_watch(&BlockId::Number(0), uxt(Transfer {
+				from: 1.into(),
+				to: 2.into(),
+				amount: 5,
+				nonce: 1,
+			})).unwrap();
+			assert_eq!(pool.status().future, 1);
+
+			// when
+			pool.submit_one(&BlockId::Number(0), uxt(Transfer {
+				from: 1.into(),
+				to: 2.into(),
+				amount: 5,
+				nonce: 0,
+			})).unwrap();
+
+			// then
+			assert_eq!(pool.status().ready, 1);
+			assert_eq!(pool.status().future, 0);
+			let mut stream = watcher.into_stream().wait();
+			assert_eq!(stream.next(), Some(Ok(::watcher::Status::Future)));
+			assert_eq!(stream.next(), Some(Ok(::watcher::Status::Ready)));
+			assert_eq!(stream.next(), Some(Ok(::watcher::Status::Dropped)));
+			assert_eq!(stream.next(), None);
+		}
+
 		#[test]
 		fn should_trigger_broadcasted() {
 			// given
@@ -108,6 +108,8 @@ pub struct ReadyTransactions<Hash: hash::Hash + Eq, Ex> {
 	ready: Arc<RwLock<HashMap<Hash, ReadyTx<Hash, Ex>>>>,
 	/// Best transactions that are ready to be included to the block without any other previous transaction.
 	best: BTreeSet<TransactionRef<Hash, Ex>>,
+	/// Sum of encoding lengths of all transactions in `ready`
+	bytes: usize,
 }
 
 impl<Hash: hash::Hash + Eq, Ex> Default for ReadyTransactions<Hash, Ex> {
@@ -117,6 +119,7 @@ impl<Hash: hash::Hash + Eq, Ex> Default for ReadyTransactions<Hash, Ex> {
 			provided_tags: Default::default(),
 			ready: Default::default(),
 			best: Default::default(),
+			bytes: 0,
 		}
 	}
 }
@@ -193,6 +196,7 @@ impl<Hash: hash::Hash + Member + Serialize, Ex> ReadyTransactions<Hash, Ex> {
 		}
 
 		// insert to Ready
+		self.bytes += transaction.transaction.bytes;
 		ready.insert(hash, ReadyTx {
 			transaction,
 			unlocks: vec![],
@@ -224,6 +228,7 @@ impl<Hash: hash::Hash + Member + Serialize, Ex> ReadyTransactions<Hash, Ex> {
 			};
 
 			if let Some(mut tx) = ready.remove(&hash) {
+				self.bytes -= tx.transaction.transaction.bytes;
 				// remove entries from provided_tags
 				for tag in &tx.transaction.transaction.provides {
 					self.provided_tags.remove(tag);
@@ -271,6 +276,7 @@ impl<Hash: hash::Hash + Member + Serialize, Ex> ReadyTransactions<Hash, Ex> {
 			if let Some(tx) = res {
 				let unlocks = tx.unlocks;
 				let tx = tx.transaction.transaction;
+				self.bytes -= tx.bytes;
 
 				// prune previous transactions as well
 				{
@@ -369,6 +375,7 @@ impl<Hash: hash::Hash + Member + Serialize, Ex> ReadyTransactions<Hash, Ex> {
 			let tx = self.ready.write().remove(&hash).expect(HASH_READY);
 			// check if this transaction provides stuff that is not provided by the new one.
 			let (mut unlocks, tx) = (tx.unlocks, tx.transaction.transaction);
+			self.bytes -= tx.bytes;
 			{
 				let invalidated = tx.provides
 					.iter()
@@ -386,11 +393,29 @@ impl<Hash: hash::Hash + Member + Serialize, Ex> ReadyTransactions<Hash, Ex> {
 		}
 	}
 
+	/// Returns the hash of the worst transaction that no other transaction in this queue depends on.
+	///
+	/// Transactions are compared the same way as in `get`: the one with the lowest priority,
+	/// then the longest remaining longevity and then the latest insertion is the worst.
+	pub fn worst_leaf(&self) -> Option<Hash> {
+		self.ready.read()
+			.values()
+			.filter(|tx| tx.unlocks.is_empty())
+			.map(|tx| &tx.transaction)
+			.min()
+			.map(|tx| tx.transaction.hash.clone())
+	}
+
 	/// Returns number of transactions in this queue.
 	pub fn len(&self) -> usize {
 		self.ready.read().len()
 	}
 
+	/// Returns sum of encoding lengths of all transactions in this queue.
+	pub fn bytes(&self) -> usize {
+		self.bytes
+	}
+
 }
 
 pub struct BestIterator<Hash, Ex> {
@@ -469,6 +494,7 @@ mod tests {
 			hash: id as u64,
 			priority: 1,
 			valid_till: 2,
+			bytes: 1,
 			requires: vec![vec![1], vec![2]],
 			provides: vec![vec![3], vec![4]],
 		}
@@ -527,6 +553,7 @@ mod tests {
 			hash: 5,
 			priority: 1,
 			valid_till: u64::max_value(),	// use the max_value() here for testing.
+			bytes: 1,
 			requires: vec![tx1.provides[0].clone()],
 			provides: vec![],
 		};
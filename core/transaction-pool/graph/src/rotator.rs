@@ -116,6 +116,7 @@ mod tests {
 			hash: hash.clone(),
 			priority: 5,
 			valid_till: 1,
+			bytes: 1,
 			requires: vec![],
 			provides: vec![],
 		};
@@ -180,6 +181,7 @@ mod tests {
 				hash,
 				priority: 5,
 				valid_till,
+				bytes: 1,
 				requires: vec![],
 				provides: vec![],
 			}
@@ -35,7 +35,7 @@ pub enum Status<H, H2> {
 	Usurped(H),
 	/// The extrinsic has been broadcast to the given peers.
 	Broadcast(Vec<String>),
-	/// Extrinsic has been dropped from the pool because of the limit.
+	/// Extrinsic has been dropped from the pool because of the limit or on request.
 	Dropped,
 	/// Extrinsic was detected as invalid.
 	Invalid,
@@ -120,9 +120,11 @@ impl<H: Clone, H2: Clone> Sender<H, H2> {
 		self.finalised = true;
 	}
 
-	/// Transaction has been dropped from the pool because of the limit.
+	/// Transaction has been dropped from the pool because of the limit or on request.
 	pub fn dropped(&mut self) {
 		self.send(Status::Dropped);
+		// we mark as finalised as there are no more notifications
+		self.finalised = true;
 	}
 
 	/// The extrinsic has been broadcast to the given peers.
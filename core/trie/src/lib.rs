@@ -221,8 +221,107 @@ pub fn read_child_trie_value_with<H: Hasher, Q: Query<H, Item=DBValue>>(_storage
 	Ok(TrieDB::<H>::new(db, &root)?.get_with(key, query).map(|x| x.map(|val| val.to_vec()))?)
 }
 
+/// Collect the hashes of the nodes of the trie that lie on the paths to any of the given key
+/// `prefixes` or under them.
+///
+/// Only the nodes for which `filter` returns `true` are collected and descended into. This allows
+/// to restrict the traversal to a small part of the trie, e.g. to the nodes that have been
+/// removed by a set of changes, since they always form a subtree starting at the root.
+pub fn prefixed_nodes<H: Hasher, F: Fn(&H::Out) -> bool>(
+	db: &HashDB<H>,
+	root: &H::Out,
+	prefixes: &[Vec<u8>],
+	filter: F,
+) -> Result<Vec<H::Out>, Box<TrieError<H::Out>>> {
+	let prefixes: Vec<Vec<u8>> = prefixes.iter().map(|prefix| key_to_nibbles(prefix)).collect();
+	let mut nodes = Vec::new();
+	if !prefixes.is_empty() && filter(root) {
+		let data = db.get(root).ok_or_else(|| Box::new(TrieError::IncompleteDatabase(root.clone())))?;
+		nodes.push(root.clone());
+		collect_prefixed_nodes::<H, F>(db, root, &data, &mut Vec::new(), &prefixes, &filter, &mut nodes)?;
+	}
+	Ok(nodes)
+}
+
 // Utilities (not exported):
 
+fn key_to_nibbles(key: &[u8]) -> Vec<u8> {
+	key.iter().flat_map(|byte| vec![byte >> 4, byte & 0x0f]).collect()
+}
+
+/// Visit the children of the node encoded in `data`, which is located at the nibble `path`.
+/// `hash` is the hash of the node itself, or of its closest hashed ancestor for inline nodes.
+fn collect_prefixed_nodes<H: Hasher, F: Fn(&H::Out) -> bool>(
+	db: &HashDB<H>,
+	hash: &H::Out,
+	data: &[u8],
+	path: &mut Vec<u8>,
+	prefixes: &[Vec<u8>],
+	filter: &F,
+	nodes: &mut Vec<H::Out>,
+) -> Result<(), Box<TrieError<H::Out>>> {
+	use trie_db::NodeCodec as NodeCodecT;
+	use trie_db::node::Node;
+
+	let node = <NodeCodec<H> as NodeCodecT<H>>::decode(data)
+		.map_err(|e| Box::new(TrieError::DecoderError(hash.clone(), e)))?;
+	match node {
+		Node::Empty | Node::Leaf(..) => Ok(()),
+		Node::Extension(partial, child) => {
+			let len = path.len();
+			path.extend((0..partial.len()).map(|i| partial.at(i)));
+			let result = collect_prefixed_child::<H, F>(db, hash, child, path, prefixes, filter, nodes);
+			path.truncate(len);
+			result
+		},
+		Node::Branch(children, _) => {
+			for (nibble, child) in children.iter().enumerate() {
+				if let Some(child) = child {
+					path.push(nibble as u8);
+					let result = collect_prefixed_child::<H, F>(db, hash, child, path, prefixes, filter, nodes);
+					path.pop();
+					result?;
+				}
+			}
+			Ok(())
+		},
+	}
+}
+
+/// Visit the child node referenced by `child` and located at the nibble `path`, if the path leads
+/// to or lies under one of the `prefixes`.
+fn collect_prefixed_child<H: Hasher, F: Fn(&H::Out) -> bool>(
+	db: &HashDB<H>,
+	parent_hash: &H::Out,
+	child: &[u8],
+	path: &mut Vec<u8>,
+	prefixes: &[Vec<u8>],
+	filter: &F,
+	nodes: &mut Vec<H::Out>,
+) -> Result<(), Box<TrieError<H::Out>>> {
+	use trie_db::NodeCodec as NodeCodecT;
+
+	let on_prefix_path = prefixes.iter()
+		.any(|prefix| prefix.starts_with(&path[..]) || path.starts_with(&prefix[..]));
+	if !on_prefix_path {
+		return Ok(());
+	}
+
+	match <NodeCodec<H> as NodeCodecT<H>>::try_decode_hash(child) {
+		Some(child_hash) => {
+			if !filter(&child_hash) {
+				return Ok(());
+			}
+			let data = db.get(&child_hash)
+				.ok_or_else(|| Box::new(TrieError::IncompleteDatabase(child_hash.clone())))?;
+			nodes.push(child_hash.clone());
+			collect_prefixed_nodes::<H, F>(db, &child_hash, &data, path, prefixes, filter, nodes)
+		},
+		// inline nodes are stored within their parent
+		None => collect_prefixed_nodes::<H, F>(db, parent_hash, child, path, prefixes, filter, nodes),
+	}
+}
+
 const EMPTY_TRIE: u8 = 0;
 const LEAF_NODE_OFFSET: u8 = 1;
 const LEAF_NODE_BIG: u8 = 127;
@@ -522,6 +621,42 @@ mod tests {
 		assert_eq!(trie, ex);
 	}
 
+	#[test]
+	fn prefixed_nodes_works() {
+		let value = vec![42u8; 64];
+		let pairs = vec![
+			(b"aaa1".to_vec(), value.clone()),
+			(b"aaa2".to_vec(), value.clone()),
+			(b"bbb1".to_vec(), value.clone()),
+			(b"bbb2".to_vec(), value.clone()),
+		];
+
+		let mut mdb = MemoryDB::default();
+		let mut root = Default::default();
+		let _ = populate_trie(&mut mdb, &mut root, &pairs);
+
+		let recorded_nodes = |key: &[u8]| {
+			let mut recorder = Recorder::new();
+			TrieDB::<Blake2Hasher>::new(&mdb, &root).unwrap().get_with(key, &mut recorder).unwrap();
+			recorder.drain().into_iter().map(|record| record.hash).collect::<Vec<_>>()
+		};
+
+		let nodes = prefixed_nodes::<Blake2Hasher, _>(&mdb, &root, &[b"aaa".to_vec()], |_| true).unwrap();
+		let aaa1_nodes = recorded_nodes(b"aaa1");
+		assert!(aaa1_nodes.iter().all(|hash| nodes.contains(hash)));
+		assert!(recorded_nodes(b"aaa2").iter().all(|hash| nodes.contains(hash)));
+		// the nodes that are only needed to reach `bbb1` are not collected
+		let bbb1_only_nodes: Vec<_> = recorded_nodes(b"bbb1").into_iter()
+			.filter(|hash| !aaa1_nodes.contains(hash))
+			.collect();
+		assert!(!bbb1_only_nodes.is_empty());
+		assert!(bbb1_only_nodes.iter().all(|hash| !nodes.contains(hash)));
+
+		// nothing is collected beyond the nodes rejected by the filter
+		let nodes = prefixed_nodes::<Blake2Hasher, _>(&mdb, &root, &[b"aaa".to_vec()], |hash| *hash == root).unwrap();
+		assert_eq!(nodes, vec![root]);
+	}
+
 	#[test]
 	fn iterator_works() {
 		let pairs = vec![
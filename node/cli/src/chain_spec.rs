@@ -20,7 +20,7 @@ use primitives::{AuthorityId, ed25519};
 use node_primitives::AccountId;
 use node_runtime::{ConsensusConfig, CouncilSeatsConfig, CouncilVotingConfig, DemocracyConfig,
 	SessionConfig, StakingConfig, TimestampConfig, BalancesConfig, TreasuryConfig,
-	UpgradeKeyConfig, ContractConfig, GrandpaConfig, Permill, Perbill};
+	MultisigConfig, ProxyConfig, UpgradeKeyConfig, ContractConfig, GrandpaConfig, Permill, Perbill};
 pub use node_runtime::GenesisConfig;
 use substrate_service;
 
@@ -88,6 +88,7 @@ fn staging_testnet_config_genesis() -> GenesisConfig {
 			sessions_per_era: 12,
 			bonding_duration: 1 * DAYS,
 			offline_slash_grace: 4,
+			equivocation_slash: Perbill::from_percent(10),
 			minimum_validator_count: 4,
 			_genesis_phantom_data: Default::default(),
 		}),
@@ -126,6 +127,17 @@ fn staging_testnet_config_genesis() -> GenesisConfig {
 			burn: Permill::from_percent(50),
 			_genesis_phantom_data: Default::default(),
 		}),
+		multisig: Some(MultisigConfig {
+			deposit_base: 1 * DOLLARS,
+			deposit_byte: 1 * CENTS,
+			max_signatories: 16,
+			_genesis_phantom_data: Default::default(),
+		}),
+		proxy: Some(ProxyConfig {
+			max_proxies: 32,
+			proxy_deposit: 1 * DOLLARS,
+			_genesis_phantom_data: Default::default(),
+		}),
 		contract: Some(ContractConfig {
 			contract_fee: 1 * CENTS,
 			call_base_fee: 1000,
@@ -134,6 +146,9 @@ fn staging_testnet_config_genesis() -> GenesisConfig {
 			max_depth: 1024,
 			block_gas_limit: 10_000_000,
 			current_schedule: Default::default(),
+			code_byte_deposit: 1 * MILLICENTS,
+			rent_byte_price: 1 * MILLICENTS,
+			surcharge_reward: 150 * MILLICENTS,
 			_genesis_phantom_data: Default::default(),
 		}),
 		upgrade_key: Some(UpgradeKeyConfig {
@@ -220,6 +235,7 @@ pub fn testnet_genesis(
 			current_offline_slash: 0,
 			current_session_reward: 0,
 			offline_slash_grace: 0,
+			equivocation_slash: Perbill::zero(),
 			_genesis_phantom_data: Default::default(),
 		}),
 		democracy: Some(DemocracyConfig {
@@ -259,6 +275,17 @@ pub fn testnet_genesis(
 			burn: Permill::from_percent(50),
 			_genesis_phantom_data: Default::default(),
 		}),
+		multisig: Some(MultisigConfig {
+			deposit_base: 1_000_000,
+			deposit_byte: 10_000,
+			max_signatories: 16,
+			_genesis_phantom_data: Default::default(),
+		}),
+		proxy: Some(ProxyConfig {
+			max_proxies: 32,
+			proxy_deposit: 1_000_000,
+			_genesis_phantom_data: Default::default(),
+		}),
 		contract: Some(ContractConfig {
 			contract_fee: 21,
 			call_base_fee: 135,
@@ -267,6 +294,9 @@ pub fn testnet_genesis(
 			max_depth: 1024,
 			block_gas_limit: 10_000_000,
 			current_schedule: Default::default(),
+			code_byte_deposit: 1,
+			rent_byte_price: 1,
+			surcharge_reward: 150,
 			_genesis_phantom_data: Default::default(),
 		}),
 		upgrade_key: Some(UpgradeKeyConfig {
@@ -20,6 +20,7 @@
 #![warn(unused_extern_crates)]
 
 extern crate tokio;
+extern crate futures;
 
 extern crate substrate_cli as cli;
 extern crate substrate_primitives as primitives;
@@ -36,6 +37,8 @@ extern crate substrate_consensus_aura as consensus;
 extern crate substrate_client as client;
 extern crate substrate_finality_grandpa as grandpa;
 extern crate node_primitives;
+extern crate sr_primitives as runtime_primitives;
+extern crate parity_codec as codec;
 #[macro_use]
 extern crate substrate_service;
 extern crate node_executor;
@@ -56,6 +59,7 @@ use substrate_service::{ServiceFactory, Roles as ServiceRoles};
 use params::{Params as NodeParams};
 use structopt::StructOpt;
 use std::ops::Deref;
+use std::time::Duration;
 
 /// The chain specification option.
 #[derive(Clone, Debug)]
@@ -133,6 +137,11 @@ pub fn run<I, T, E>(args: I, exit: E, version: cli::VersionInfo) -> error::Resul
 		config.roles = ServiceRoles::AUTHORITY;
 	}
 
+	if let Some(drift) = matches.value_of("aura_max_drift") {
+		let drift = drift.parse().map_err(|_| "Invalid aura-max-drift value specified.")?;
+		config.custom.aura_max_drift = Duration::from_secs(drift);
+	}
+
 	match cli::execute_default::<service::Factory, _>(spec, exit, &matches)? {
 		cli::Action::ExecutedInternally => (),
 		cli::Action::RunService(exit) => {
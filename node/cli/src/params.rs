@@ -28,6 +28,10 @@ pub struct Params {
 	#[structopt(long = "grandpa-authority-only", help = "Run Node as a GRANDPA authority only, don't as a usual validator, implies --grandpa-authority")]
 	grandpa_authority_only: bool,
 
+	/// How far in the future blocks may be to be held back until they can be verified
+	#[structopt(long = "aura-max-drift", value_name = "SECONDS", help = "Hold back blocks from up to this many seconds in the future until their slot starts, reject blocks further ahead (default 30)")]
+	aura_max_drift: Option<u64>,
+
 	#[structopt(flatten)]
 	core: CoreParams
 }
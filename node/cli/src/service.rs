@@ -19,22 +19,27 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
 use std::sync::Arc;
-use transaction_pool::{self, txpool::{Pool as TransactionPool}};
-use node_runtime::{GenesisConfig, ClientWithApi};
+use futures::Stream;
+use codec::{Decode, Encode};
+use transaction_pool::{self, txpool::{self, Pool as TransactionPool}};
+use node_runtime::{GenesisConfig, ClientWithApi, Call, AuraCall, AuraEquivocationProof, GrandpaCall, UncheckedExtrinsic};
+use node_runtime::AURA_SLOT_DURATION;
 use node_primitives::Block;
+use runtime_primitives::generic::BlockId;
+use runtime_primitives::traits::Block as BlockT;
 use substrate_service::{
 	FactoryFullConfiguration, LightComponents, FullComponents, FullBackend,
 	FullClient, LightClient, LightBackend, FullExecutor, LightExecutor, TaskExecutor
 };
 use node_executor;
 use consensus::{import_queue, start_aura, Config as AuraConfig, AuraImportQueue, NothingExtra};
+use consensus::{EquivocationProof as AuraEquivocation, EquivocationStream as AuraEquivocationStream};
+use network::import_queue::DEFAULT_MAX_DRIFT;
 use primitives::ed25519::Pair;
 use client;
 use std::time::Duration;
 use grandpa;
 
-const AURA_SLOT_DURATION: u64 = 6;
-
 construct_simple_protocol! {
 	/// Demo protocol attachment for substrate.
 	pub struct NodeProtocol where Block = Block { }
@@ -51,6 +56,10 @@ pub struct NodeConfig<F: substrate_service::ServiceFactory> {
 	// FIXME: rather than putting this on the config, let's have an actual intermediate setup state
 	// https://github.com/paritytech/substrate/issues/1134
 	pub grandpa_import_setup: Option<(Arc<grandpa::BlockImportForService<F>>, grandpa::LinkHalfForService<F>)>,
+	/// equivocations of Aura authors found by the import queue
+	pub aura_equivocations: Option<AuraEquivocationStream<<F::Block as BlockT>::Header>>,
+	/// how far in the future blocks may be to be held back until their slot starts
+	pub aura_max_drift: Duration,
 }
 
 impl<F> Default for NodeConfig<F> where F: substrate_service::ServiceFactory {
@@ -59,10 +68,85 @@ impl<F> Default for NodeConfig<F> where F: substrate_service::ServiceFactory {
 			grandpa_authority: false,
 			grandpa_authority_only: false,
 			grandpa_import_setup: None,
+			aura_equivocations: None,
+			aura_max_drift: DEFAULT_MAX_DRIFT,
 		}
 	}
 }
 
+/// Submits equivocations found by the GRANDPA voter to the transaction pool as
+/// unsigned extrinsics, which the runtime checks before slashing the offender.
+struct GrandpaEquivocationReporter<A: txpool::ChainApi> {
+	client: Arc<FullClient<Factory>>,
+	transaction_pool: Arc<TransactionPool<A>>,
+}
+
+impl<A> grandpa::EquivocationReporter<Block> for GrandpaEquivocationReporter<A> where
+	A: txpool::ChainApi<Block=Block> + 'static,
+{
+	fn report_equivocation(&self, proof: grandpa::EquivocationProof<Block>) {
+		// the runtime's signature type wraps the same ed25519 signature.
+		let proof = Decode::decode(&mut &proof.encode()[..])
+			.expect("Equivocation proof encoding is the same in the runtime; qed");
+		submit_unsigned(
+			&self.client,
+			&self.transaction_pool,
+			Call::Grandpa(GrandpaCall::report_equivocation(proof)),
+			"GRANDPA equivocation",
+		);
+	}
+}
+
+/// Submits an equivocation found by the Aura import queue to the transaction pool as
+/// an unsigned extrinsic, which the runtime checks before slashing the offender.
+fn report_aura_equivocation<A>(
+	client: &FullClient<Factory>,
+	transaction_pool: &TransactionPool<A>,
+	proof: AuraEquivocation<<Block as BlockT>::Header>,
+) where
+	A: txpool::ChainApi<Block=Block> + 'static,
+{
+	let (first, second) = proof.seals()
+		.expect("The import queue only reports headers it checked the seals of; qed");
+	let proof = AuraEquivocationProof {
+		slot: proof.slot,
+		author: proof.author,
+		first: (first.0, first.1.into()),
+		second: (second.0, second.1.into()),
+	};
+	submit_unsigned(
+		client,
+		transaction_pool,
+		Call::Aura(AuraCall::report_equivocation(proof)),
+		"Aura equivocation",
+	);
+}
+
+/// Submits `call` to the transaction pool as an unsigned extrinsic on top of the best block.
+fn submit_unsigned<A>(
+	client: &FullClient<Factory>,
+	transaction_pool: &TransactionPool<A>,
+	call: Call,
+	what: &str,
+) where
+	A: txpool::ChainApi<Block=Block> + 'static,
+{
+	let best_hash = match client.info() {
+		Ok(info) => info.chain.best_hash,
+		Err(e) => {
+			warn!("Error reporting {}: {:?}", what, e);
+			return;
+		}
+	};
+
+	let extrinsic = UncheckedExtrinsic::new_unsigned(call);
+	let uxt: <Block as BlockT>::Extrinsic = Decode::decode(&mut extrinsic.encode().as_slice())
+		.expect("Encoded extrinsic is valid");
+	if let Err(e) = transaction_pool.submit_one(&BlockId::hash(best_hash), uxt) {
+		warn!("Error importing {} report: {:?}", what, e);
+	}
+}
+
 construct_service_factory! {
 	struct Factory {
 		Block = Block,
@@ -76,23 +160,43 @@ construct_service_factory! {
 		Genesis = GenesisConfig,
 		Configuration = NodeConfig<Self>,
 		FullService = FullComponents<Self>
-			{ |config: FactoryFullConfiguration<Self>, executor: TaskExecutor|
-				FullComponents::<Factory>::new(config, executor) },
+			{ |config: FactoryFullConfiguration<Self>, executor: TaskExecutor| {
+				let mut service = FullComponents::<Factory>::new(config, executor.clone())?;
+
+				// every full node reports the equivocations its import queue finds.
+				if let Some(equivocations) = service.config.custom.aura_equivocations.take() {
+					let client = service.client();
+					let transaction_pool = service.transaction_pool();
+					executor.spawn(equivocations.for_each(move |proof| {
+						report_aura_equivocation(&client, &transaction_pool, proof);
+						Ok(())
+					}));
+				}
+
+				Ok(service)
+			}},
 		AuthoritySetup = {
 			|mut service: Self::FullService, executor: TaskExecutor, key: Arc<Pair>| {
 				let (block_import, link_half) = service.config.custom.grandpa_import_setup.take()
 					.expect("Link Half and Block Import are present for Full Services or setup failed before. qed");
 
 				if service.config.custom.grandpa_authority {
-					info!("Running Grandpa session as Authority {}", key.public());
+					// the authority key doubles as the voter key unless there is a dedicated one.
+					let grandpa_public = service.grandpa_key().map_or_else(|| key.public(), |k| k.public());
+					info!("Running Grandpa session as Authority {}", grandpa_public);
 					let grandpa_fut = grandpa::run_grandpa(
 						grandpa::Config {
 							gossip_duration: Duration::new(4, 0), // FIXME: make this available through chainspec?
-							local_key: Some(key.clone()),
+							// follows key rotation, the voter signs with the current key every round.
+							local_key: service.grandpa_key_handle(),
 							name: Some(service.config.name.clone())
 						},
 						link_half,
-						grandpa::NetworkBridge::new(service.network())
+						grandpa::NetworkBridge::new(service.network()),
+						Arc::new(GrandpaEquivocationReporter {
+							client: service.client(),
+							transaction_pool: service.transaction_pool(),
+						}),
 					)?;
 
 					executor.spawn(grandpa_fut);
@@ -101,7 +205,8 @@ construct_service_factory! {
 					info!("Using authority key {}", key.public());
 					executor.spawn(start_aura(
 						AuraConfig {
-							local_key: Some(key),
+							// follows key rotation, blocks are sealed with the current key every slot.
+							local_key: service.authority_key_handle(),
 							slot_duration: AURA_SLOT_DURATION,
 						},
 						service.client(),
@@ -122,14 +227,18 @@ construct_service_factory! {
 
 				config.custom.grandpa_import_setup = Some((block_import.clone(), link_half));
 
-				Ok(import_queue(
+				let queue = import_queue(
 					AuraConfig {
 						local_key: None,
 						slot_duration: 5
 					},
 					block_import,
 					NothingExtra,
-				))
+					config.custom.aura_max_drift,
+				);
+				config.custom.aura_equivocations = Some(queue.verifier().equivocation_stream());
+
+				Ok(queue)
 			}},
 		LightImportQueue = AuraImportQueue<Self::Block, LightClient<Self>, NothingExtra>
 			{ |ref mut config, client| Ok(
@@ -139,6 +248,7 @@ construct_service_factory! {
 				},
 				client,
 				NothingExtra,
+				config.custom.aura_max_drift,
 			))
 			},
 	}
@@ -60,7 +60,7 @@ mod tests {
 	use primitives::{twox_128, Blake2Hasher, ChangesTrieConfiguration,
 		ed25519::{Public, Pair}};
 	use node_primitives::{Hash, BlockNumber, AccountId};
-	use runtime_primitives::traits::{Header as HeaderT, Digest as DigestT};
+	use runtime_primitives::traits::{Header as HeaderT, Digest as DigestT, Hash as HashT};
 	use runtime_primitives::{generic, generic::Era, ApplyOutcome, ApplyError, ApplyResult, Perbill};
 	use {balances, staking, session, system, consensus, timestamp, treasury, contract};
 	use contract::ContractAddressFor;
@@ -254,6 +254,7 @@ mod tests {
 				current_offline_slash: 0,
 				current_session_reward: 0,
 				offline_slash_grace: 0,
+				equivocation_slash: Perbill::zero(),
 				_genesis_phantom_data: Default::default(),
 			}),
 			democracy: Some(Default::default()),
@@ -261,6 +262,8 @@ mod tests {
 			council_voting: Some(Default::default()),
 			timestamp: Some(Default::default()),
 			treasury: Some(Default::default()),
+			multisig: Some(Default::default()),
+			proxy: Some(Default::default()),
 			contract: Some(Default::default()),
 			upgrade_key: Some(Default::default()),
 			grandpa: Some(GrandpaConfig {
@@ -539,6 +542,8 @@ mod tests {
 	(import "env" "ext_input_size" (func $ext_input_size (result i32)))
 	(import "env" "ext_input_copy" (func $ext_input_copy (param i32 i32 i32)))
 	(import "env" "memory" (memory 1 1))
+	(func (export "deploy")
+	)
 	(func (export "call")
 		(block $fail
 			;; fail if ext_input_size != 4
@@ -606,55 +611,15 @@ mod tests {
 )
 "#;
 
-	/// Convert a byte slice to a string with hex values.
-	/// Convert a byte slice to a string with hex values.
-	///
-	/// Each value is preceeded with a `\` character.
-	fn escaped_bytestring(bytes: &[u8]) -> String {
-		use std::fmt::Write;
-		let mut result = String::new();
-		for b in bytes {
-			write!(result, "\\{:02x}", b).unwrap();
-		}
-		result
-	}
-
-	/// Create a constructor for the specified code.
-	///
-	/// When constructor is executed, it will call `ext_return` with code that
-	/// specified in `child_bytecode`.
-	fn code_ctor(child_bytecode: &[u8]) -> String {
-		format!(
-			r#"
-	(module
-		;; ext_return(data_ptr: u32, data_len: u32) -> !
-		(import "env" "ext_return" (func $ext_return (param i32 i32)))
-		(import "env" "memory" (memory 1 1))
-		(func (export "call")
-			(call $ext_return
-				(i32.const 4)
-				(i32.const {code_len})
-			)
-			;; ext_return is diverging, i.e. doesn't return.
-			unreachable
-		)
-		(data (i32.const 4) "{escaped_bytecode}")
-	)
-	"#,
-			escaped_bytecode = escaped_bytestring(child_bytecode),
-			code_len = child_bytecode.len(),
-		)
-	}
-
 	#[test]
 	fn deploying_wasm_contract_should_work() {
 		let mut t = new_test_ext(false);
 
-		let code_transfer = wabt::wat2wasm(CODE_TRANSFER).unwrap();
-		let code_ctor_transfer = wabt::wat2wasm(&code_ctor(&code_transfer)).unwrap();
+		let transfer_code = wabt::wat2wasm(CODE_TRANSFER).unwrap();
+		let transfer_ch = <Runtime as system::Trait>::Hashing::hash(&transfer_code);
 
 		let addr = <Runtime as contract::Trait>::DetermineContractAddress::contract_address_for(
-			&code_ctor_transfer,
+			&transfer_ch,
 			&[],
 			&charlie(),
 		);
@@ -672,11 +637,17 @@ mod tests {
 				CheckedExtrinsic {
 					signed: Some((charlie(), 0)),
 					function: Call::Contract(
-						contract::Call::create::<Runtime>(10.into(), 10_000.into(), code_ctor_transfer, Vec::new())
+						contract::Call::put_code::<Runtime>(10_000.into(), transfer_code)
 					),
 				},
 				CheckedExtrinsic {
 					signed: Some((charlie(), 1)),
+					function: Call::Contract(
+						contract::Call::create::<Runtime>(10.into(), 10_000.into(), transfer_ch, Vec::new())
+					),
+				},
+				CheckedExtrinsic {
+					signed: Some((charlie(), 2)),
 					function: Call::Contract(
 						contract::Call::call::<Runtime>(addr, 10.into(), 10_000.into(), vec![0x00, 0x01, 0x02, 0x03])
 					),
@@ -688,7 +659,12 @@ mod tests {
 
 		runtime_io::with_externalities(&mut t, || {
 			// Verify that the contract constructor worked well and code of TRANSFER contract is actually deployed.
-			assert_eq!(&contract::CodeOf::<Runtime>::get(addr), &code_transfer);
+			assert_eq!(
+				contract::ContractInfoOf::<Runtime>::get(addr)
+					.and_then(|info| info.get_alive())
+					.map(|info| info.code_hash),
+				Some(transfer_ch)
+			);
 		});
 	}
 
@@ -63,6 +63,10 @@ pub type Index = u64;
 pub type Hash = primitives::H256;
 
 /// Alias to 512-bit hash when used in the context of a signature on the chain.
+///
+/// The node stays on ed25519 for now. Moving to `runtime_primitives::MultiSignature` (and
+/// `MultiSigner` accounts) changes the encoding of every account id, so it needs a storage
+/// migration of its own and is left for a separate change.
 pub type Signature = runtime_primitives::Ed25519Signature;
 
 /// A timestamp: seconds since the unix epoch.
@@ -41,6 +41,7 @@ extern crate parity_codec_derive;
 extern crate parity_codec as codec;
 
 extern crate sr_std as rstd;
+extern crate srml_aura as aura;
 extern crate srml_balances as balances;
 extern crate srml_consensus as consensus;
 extern crate srml_contract as contract;
@@ -48,6 +49,8 @@ extern crate srml_council as council;
 extern crate srml_democracy as democracy;
 extern crate srml_executive as executive;
 extern crate srml_grandpa as grandpa;
+extern crate srml_multisig as multisig;
+extern crate srml_proxy as proxy;
 extern crate srml_session as session;
 extern crate srml_staking as staking;
 extern crate srml_system as system;
@@ -58,8 +61,9 @@ extern crate srml_upgrade_key as upgrade_key;
 extern crate sr_version as version;
 extern crate node_primitives;
 
+use codec::Encode;
 #[cfg(feature = "std")]
-use codec::{Encode, Decode};
+use codec::Decode;
 use rstd::prelude::*;
 use substrate_primitives::u32_trait::{_2, _4};
 use node_primitives::{
@@ -72,7 +76,8 @@ use client::{block_builder::api::runtime::*, runtime_api::{runtime::*, id::*}};
 #[cfg(feature = "std")]
 use client::runtime_api::ApiExt;
 use runtime_primitives::ApplyResult;
-use runtime_primitives::transaction_validity::TransactionValidity;
+use runtime_primitives::transaction_validity::{TransactionValidity, TransactionPriority, TransactionLongevity};
+use client::runtime_api::ContractExecResult;
 use runtime_primitives::generic;
 use runtime_primitives::traits::{Convert, BlakeTwo256, Block as BlockT, DigestFor, NumberFor};
 #[cfg(feature = "std")]
@@ -90,8 +95,11 @@ use substrate_primitives::OpaqueMetadata;
 #[cfg(any(feature = "std", test))]
 pub use runtime_primitives::BuildStorage;
 pub use consensus::Call as ConsensusCall;
+pub use aura::Call as AuraCall;
+pub use aura::RawEquivocationProof as AuraEquivocationProof;
 pub use timestamp::Call as TimestampCall;
 pub use balances::Call as BalancesCall;
+pub use grandpa::Call as GrandpaCall;
 pub use runtime_primitives::{Permill, Perbill};
 pub use timestamp::BlockPeriod;
 pub use srml_support::{StorageValue, RuntimeMetadata};
@@ -99,18 +107,22 @@ pub use srml_support::{StorageValue, RuntimeMetadata};
 const TIMESTAMP_SET_POSITION: u32 = 0;
 const NOTE_OFFLINE_POSITION: u32 = 1;
 
+/// The duration of the Aura slots the node authors blocks in, in seconds.
+pub const AURA_SLOT_DURATION: u64 = 6;
+
 /// Runtime version.
 pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: ver_str!("node"),
 	impl_name: ver_str!("substrate-node"),
 	authoring_version: 1,
-	spec_version: 1,
+	spec_version: 2,
 	impl_version: 0,
 	apis: apis_vec!([
 		(BLOCK_BUILDER, 1),
 		(TAGGED_TRANSACTION_QUEUE, 1),
 		(METADATA, 1),
 		(GRANDPA_API, 1),
+		(CONTRACTS, 1),
 	]),
 };
 
@@ -151,6 +163,14 @@ impl consensus::Trait for Runtime {
 	type OnOfflineValidator = Staking;
 }
 
+impl aura::Trait for Runtime {
+	const SLOT_DURATION: u64 = AURA_SLOT_DURATION;
+	// seals are ed25519 signatures by the session key, like GRANDPA votes.
+	type Signature = grandpa::AuthoritySignature;
+	type OnEquivocation = Staking;
+	type Event = Event;
+}
+
 impl timestamp::Trait for Runtime {
 	const TIMESTAMP_SET_POSITION: u32 = TIMESTAMP_SET_POSITION;
 	type Moment = u64;
@@ -200,6 +220,44 @@ impl treasury::Trait for Runtime {
 	type Event = Event;
 }
 
+impl multisig::Trait for Runtime {
+	type Proposal = Call;
+	type DetermineMultisigAddress = multisig::SimpleAddressDeterminator<Runtime>;
+	type Event = Event;
+}
+
+/// The kinds of proxies an account can register, restricting the calls they may make.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum ProxyType {
+	/// May make any call.
+	Any,
+	/// May only make staking calls.
+	Staking,
+	/// May only make democracy and council calls.
+	Governance,
+}
+
+impl proxy::ProxyFilter<Call> for ProxyType {
+	fn allows(&self, call: &Call) -> bool {
+		match (*self, call) {
+			(ProxyType::Any, _) => true,
+			(ProxyType::Staking, &Call::Staking(..)) => true,
+			(ProxyType::Governance, &Call::Democracy(..))
+				| (ProxyType::Governance, &Call::Council(..))
+				| (ProxyType::Governance, &Call::CouncilVoting(..))
+				| (ProxyType::Governance, &Call::CouncilMotions(..)) => true,
+			_ => false,
+		}
+	}
+}
+
+impl proxy::Trait for Runtime {
+	type Proposal = Call;
+	type ProxyType = ProxyType;
+	type Event = Event;
+}
+
 impl contract::Trait for Runtime {
 	type Gas = u64;
 	type DetermineContractAddress = contract::SimpleAddressDeterminator<Runtime>;
@@ -212,6 +270,8 @@ impl upgrade_key::Trait for Runtime {
 
 impl grandpa::Trait for Runtime {
 	type SessionKey = SessionKey;
+	type Signature = grandpa::AuthoritySignature;
+	type OnEquivocation = Staking;
 	type Log = Log;
 	type Event = Event;
 }
@@ -236,6 +296,9 @@ construct_runtime!(
 		Treasury: treasury,
 		Contract: contract::{Module, Call, Config<T>, Event<T>},
 		UpgradeKey: upgrade_key,
+		Multisig: multisig,
+		Proxy: proxy,
+		Aura: aura::{Module, Call, Storage, Event<T>},
 	}
 );
 
@@ -405,6 +468,21 @@ impl client::runtime_api::Metadata<GBlock> for ClientWithApi {
 	}
 }
 
+#[cfg(feature = "std")]
+impl client::runtime_api::ContractsApi<GBlock> for ClientWithApi {
+	fn contracts_call<AccountId: Encode + Decode, Balance: Encode + Decode>(
+		&self,
+		at: &GBlockId,
+		origin: &AccountId,
+		dest: &AccountId,
+		value: &Balance,
+		gas_limit: &u64,
+		input_data: &Vec<u8>
+	) -> Result<ContractExecResult, client::error::Error> {
+		self.call_api_at(at, "contracts_call", &(origin, dest, value, gas_limit, input_data))
+	}
+}
+
 #[cfg(feature = "std")]
 impl substrate_finality_grandpa_primitives::GrandpaApi<GBlock> for ClientWithApi {
 	fn grandpa_pending_change(&self, at: &GBlockId, digest: &DigestFor<GBlock>)
@@ -467,10 +545,61 @@ impl_runtime_apis! {
 
 	impl TaggedTransactionQueue<Block> for Runtime {
 		fn validate_transaction(tx: <Block as BlockT>::Extrinsic) -> TransactionValidity {
+			// equivocation reports are unsigned; they are valid as long as the proof is.
+			if tx.signature.is_none() {
+				let checked = match tx.function {
+					// one report per voter and round.
+					Call::Grandpa(GrandpaCall::report_equivocation(ref proof)) => Some((
+						(proof.set_id, proof.round, &proof.identity).encode(),
+						Grandpa::check_equivocation_proof(proof),
+					)),
+					// one report per author and slot.
+					Call::Aura(AuraCall::report_equivocation(ref proof)) => Some((
+						(proof.slot, &proof.author).encode(),
+						Aura::check_equivocation_proof(proof),
+					)),
+					_ => None,
+				};
+				if let Some((tag, result)) = checked {
+					return match result {
+						Ok(()) => TransactionValidity::Valid {
+							priority: TransactionPriority::max_value(),
+							requires: Vec::new(),
+							provides: [tag].to_vec(),
+							longevity: TransactionLongevity::max_value(),
+						},
+						Err(_) => TransactionValidity::Invalid,
+					};
+				}
+			}
+
 			Executive::validate_transaction(tx)
 		}
 	}
 
+	impl ContractsApi<AccountId, Balance> for Runtime {
+		fn contracts_call(
+			origin: AccountId,
+			dest: AccountId,
+			value: Balance,
+			gas_limit: u64,
+			input_data: Vec<u8>
+		) -> ContractExecResult {
+			let outcome = Contract::bare_call(origin, dest, value, gas_limit, input_data);
+			match outcome.result {
+				Ok(output) => ContractExecResult::Success {
+					output,
+					gas_consumed: outcome.gas_consumed,
+					events: outcome.events.into_iter().map(|e| Event::from(e).encode()).collect(),
+				},
+				Err(reason) => ContractExecResult::Error {
+					reason: reason.as_bytes().to_vec(),
+					gas_consumed: outcome.gas_consumed,
+				},
+			}
+		}
+	}
+
 	impl GrandpaApi<Block> for Runtime {
 		fn grandpa_pending_change(digest: DigestFor<Block>)
 			-> Option<ScheduledChange<NumberFor<Block>>>
@@ -0,0 +1,334 @@
+// Copyright 2018 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Aura: Handles misbehaviour of Aura block authors.
+//!
+//! An authority seals each header it authors with a signature on the slot number and the
+//! hash of the unsealed header. Sealing two different headers in the same slot is an
+//! equivocation, which can be reported with an unsigned extrinsic carrying both seals. The
+//! author must be one of the current authorities of the consensus module, and is passed on to
+//! `Trait::OnEquivocation`, e.g. the staking module for slashing.
+//!
+//! Past authority sets are not kept, so an equivocation can't be reported anymore once its
+//! author has left the authority set, even if the slot is recent enough.
+//!
+//! Equivocations can only be reported for the last `MAX_PAST_SLOTS` slots, the current slot
+//! being derived from the timestamp module. Older reports are pruned.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[macro_use]
+extern crate parity_codec_derive;
+
+#[macro_use]
+extern crate srml_support as runtime_support;
+
+extern crate sr_std as rstd;
+#[cfg(test)]
+extern crate sr_io as runtime_io;
+extern crate parity_codec as codec;
+extern crate sr_primitives as primitives;
+extern crate srml_system as system;
+extern crate srml_consensus as consensus;
+extern crate srml_timestamp as timestamp;
+
+#[cfg(test)]
+extern crate substrate_primitives;
+
+use rstd::prelude::*;
+use codec::Encode;
+use runtime_support::{Parameter, StorageMap, StorageValue};
+use runtime_support::dispatch::Result;
+use primitives::traits::{As, Verify};
+use system::ensure_inherent;
+
+/// The number of slots before the current one for which equivocations can be reported.
+const MAX_PAST_SLOTS: u64 = 4096;
+
+/// A proof that an authority sealed two different headers in the same slot.
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub struct RawEquivocationProof<Hash, SessionKey, Signature> {
+	/// The slot both headers were sealed in.
+	pub slot: u64,
+	/// The authority who sealed both headers.
+	pub author: SessionKey,
+	/// The hash of the first header without its seal, along with the seal's signature.
+	pub first: (Hash, Signature),
+	/// The hash of the second header without its seal, along with the seal's signature.
+	pub second: (Hash, Signature),
+}
+
+/// A proof of equivocation, projected from module trait types.
+pub type EquivocationProof<T> = RawEquivocationProof<
+	<T as system::Trait>::Hash,
+	<T as consensus::Trait>::SessionKey,
+	<T as Trait>::Signature,
+>;
+
+/// Handler for a proven Aura equivocation.
+pub trait OnEquivocation<SessionKey> {
+	/// The authority with the given session key sealed two headers in one slot.
+	fn on_equivocation(who: &SessionKey);
+}
+
+impl<SessionKey> OnEquivocation<SessionKey> for () {
+	fn on_equivocation(_who: &SessionKey) {}
+}
+
+pub trait Trait: consensus::Trait + timestamp::Trait {
+	/// The duration of a slot, in the unit of the timestamp module's moments.
+	const SLOT_DURATION: u64;
+
+	/// The signature type of seals, verifiable against the session key of the author.
+	type Signature: Parameter + Verify<Signer=<Self as consensus::Trait>::SessionKey>;
+
+	/// Handler for authors found equivocating.
+	type OnEquivocation: OnEquivocation<<Self as consensus::Trait>::SessionKey>;
+
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+}
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		fn deposit_event() = default;
+
+		/// Report an equivocation of an author. The proof is checked against the current
+		/// authorities, so this is submitted as an unsigned extrinsic.
+		fn report_equivocation(origin, proof: EquivocationProof<T>) -> Result {
+			ensure_inherent(origin)?;
+			Self::check_equivocation_proof(&proof)?;
+
+			let mut reported = Self::reported(proof.slot);
+			if reported.is_empty() {
+				<ReportedSlots<T>>::mutate(|slots| slots.push(proof.slot));
+			}
+			reported.push(proof.author.clone());
+			<Reported<T>>::insert(proof.slot, reported);
+			Self::prune_reported();
+
+			T::OnEquivocation::on_equivocation(&proof.author);
+			Self::deposit_event(RawEvent::Equivocation(proof.author, proof.slot));
+			Ok(())
+		}
+	}
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as Aura {
+		/// The authors reported for equivocating in a slot.
+		pub Reported get(reported): map u64 => Vec<T::SessionKey>;
+		/// The slots with reported equivocations, which are pruned once they are too old.
+		ReportedSlots get(reported_slots): Vec<u64>;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where <T as consensus::Trait>::SessionKey {
+		/// An author was proven to have sealed two headers in the given slot.
+		Equivocation(SessionKey, u64),
+	}
+);
+
+impl<T: Trait> Module<T> {
+	/// The slot of the current block, according to its timestamp.
+	pub fn current_slot() -> u64 {
+		<timestamp::Module<T>>::now().as_() / T::SLOT_DURATION
+	}
+
+	/// Check that an equivocation proof is valid and has not been reported before.
+	///
+	/// The author is checked against the current authorities only: proofs for authors that
+	/// were rotated out since are rejected with "not an authority".
+	pub fn check_equivocation_proof(proof: &EquivocationProof<T>) -> Result {
+		ensure!(
+			proof.slot >= Self::current_slot().saturating_sub(MAX_PAST_SLOTS),
+			"equivocation too old"
+		);
+		ensure!(
+			<consensus::Module<T>>::authorities().contains(&proof.author),
+			"not an authority"
+		);
+		ensure!(!Self::reported(proof.slot).contains(&proof.author), "equivocation already reported");
+		ensure!(proof.first.0 != proof.second.0, "headers are not conflicting");
+		ensure!(
+			Self::check_seal(proof, &proof.first) && Self::check_seal(proof, &proof.second),
+			"bad seal signature"
+		);
+		Ok(())
+	}
+
+	/// Forget the equivocations in slots which can't be reported anymore.
+	fn prune_reported() {
+		let oldest = Self::current_slot().saturating_sub(MAX_PAST_SLOTS);
+		let (expired, slots): (Vec<_>, Vec<_>) = Self::reported_slots().into_iter()
+			.partition(|slot| *slot < oldest);
+		if !expired.is_empty() {
+			for slot in expired {
+				<Reported<T>>::remove(slot);
+			}
+			<ReportedSlots<T>>::put(slots);
+		}
+	}
+
+	fn check_seal(proof: &EquivocationProof<T>, seal: &(T::Hash, T::Signature)) -> bool {
+		let payload = (proof.slot, &seal.0).encode();
+		seal.1.verify(&payload[..], &proof.author)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use runtime_io::with_externalities;
+	use substrate_primitives::{H256, Blake2Hasher};
+	use primitives::BuildStorage;
+	use primitives::traits::{BlakeTwo256, Lazy};
+	use primitives::testing::{Digest, DigestItem, Header};
+
+	impl_outer_origin! {
+		pub enum Origin for Test {}
+	}
+
+	/// A signature which is valid for the given signer and message.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+	pub struct TestSignature(pub u64, pub Vec<u8>);
+
+	impl Verify for TestSignature {
+		type Signer = u64;
+		fn verify<L: Lazy<[u8]>>(&self, mut msg: L, signer: &u64) -> bool {
+			*signer == self.0 && msg.get() == &self.1[..]
+		}
+	}
+
+	#[derive(Clone, Eq, PartialEq, Debug)]
+	pub struct Test;
+	impl system::Trait for Test {
+		type Origin = Origin;
+		type Index = u64;
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type Digest = Digest;
+		type AccountId = u64;
+		type Header = Header;
+		type Event = ();
+		type Log = DigestItem;
+	}
+	impl consensus::Trait for Test {
+		const NOTE_OFFLINE_POSITION: u32 = 1;
+		type Log = DigestItem;
+		type SessionKey = u64;
+		type OnOfflineValidator = ();
+	}
+	impl timestamp::Trait for Test {
+		const TIMESTAMP_SET_POSITION: u32 = 0;
+		type Moment = u64;
+	}
+	impl Trait for Test {
+		const SLOT_DURATION: u64 = 5;
+		type Signature = TestSignature;
+		type OnEquivocation = ();
+		type Event = ();
+	}
+	type Aura = Module<Test>;
+	type Timestamp = timestamp::Module<Test>;
+
+	fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+		let mut t = system::GenesisConfig::<Test>::default().build_storage().unwrap().0;
+		t.extend(consensus::GenesisConfig::<Test>{
+			code: vec![],
+			authorities: vec![1, 2, 3],
+			_genesis_phantom_data: Default::default(),
+		}.build_storage().unwrap().0);
+		t.into()
+	}
+
+	fn seal(slot: u64, author: u64, hash: H256) -> (H256, TestSignature) {
+		(hash, TestSignature(author, (slot, hash).encode()))
+	}
+
+	fn proof(slot: u64, author: u64) -> EquivocationProof<Test> {
+		RawEquivocationProof {
+			slot,
+			author,
+			first: seal(slot, author, H256::repeat_byte(1)),
+			second: seal(slot, author, H256::repeat_byte(2)),
+		}
+	}
+
+	#[test]
+	fn equivocation_is_reported_once() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_noop!(Aura::report_equivocation(Origin::signed(1), proof(5, 2)), "bad origin: expected to be an inherent origin");
+
+			assert_ok!(Aura::report_equivocation(Origin::INHERENT, proof(5, 2)));
+			assert_eq!(Aura::reported(5), vec![2]);
+			assert_noop!(Aura::report_equivocation(Origin::INHERENT, proof(5, 2)), "equivocation already reported");
+
+			assert_ok!(Aura::report_equivocation(Origin::INHERENT, proof(6, 2)));
+			assert_eq!(Aura::reported(6), vec![2]);
+		});
+	}
+
+	#[test]
+	fn old_equivocations_are_rejected_and_pruned() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_ok!(Aura::report_equivocation(Origin::INHERENT, proof(5, 2)));
+			assert_eq!(Aura::reported_slots(), vec![5]);
+
+			Timestamp::set_timestamp((MAX_PAST_SLOTS + 6) * 5);
+			assert_eq!(Aura::current_slot(), MAX_PAST_SLOTS + 6);
+			assert_noop!(Aura::check_equivocation_proof(&proof(5, 3)), "equivocation too old");
+
+			assert_ok!(Aura::report_equivocation(Origin::INHERENT, proof(6, 3)));
+			assert_eq!(Aura::reported_slots(), vec![6]);
+			assert!(Aura::reported(5).is_empty());
+			assert_eq!(Aura::reported(6), vec![3]);
+		});
+	}
+
+	#[test]
+	fn invalid_equivocation_proofs_are_rejected() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_noop!(Aura::check_equivocation_proof(&proof(5, 4)), "not an authority");
+
+			let mut same_header = proof(5, 2);
+			same_header.second = same_header.first.clone();
+			assert_noop!(Aura::check_equivocation_proof(&same_header), "headers are not conflicting");
+
+			let mut other_slot = proof(5, 2);
+			other_slot.second = seal(4, 2, H256::repeat_byte(2));
+			assert_noop!(Aura::check_equivocation_proof(&other_slot), "bad seal signature");
+
+			let mut other_author = proof(5, 2);
+			other_author.first = seal(5, 3, H256::repeat_byte(1));
+			assert_noop!(Aura::check_equivocation_proof(&other_author), "bad seal signature");
+		});
+	}
+
+	#[test]
+	fn equivocations_of_rotated_out_authorities_are_rejected() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_ok!(Aura::check_equivocation_proof(&proof(5, 3)));
+
+			<consensus::Module<Test>>::set_authorities(&[1, 2]);
+			assert_noop!(Aura::check_equivocation_proof(&proof(5, 3)), "not an authority");
+		});
+	}
+}
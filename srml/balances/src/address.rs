@@ -18,6 +18,8 @@
 
 #[cfg(feature = "std")]
 use std::fmt;
+#[cfg(feature = "std")]
+use substrate_primitives::crypto::Ss58Codec;
 use super::{Member, Decode, Encode, As, Input, Output};
 
 /// A vetted and verified extrinsic from the external world.
@@ -43,6 +45,34 @@ impl<AccountId, AccountIndex> fmt::Display for Address<AccountId, AccountIndex>
 	}
 }
 
+#[cfg(feature = "std")]
+impl<AccountId, AccountIndex> Address<AccountId, AccountIndex> where
+	AccountId: Member + Ss58Codec,
+	AccountIndex: Member,
+{
+	/// Display the address with the account ID as an SS58 address of the default network.
+	pub fn ss58(&self) -> Ss58Address<AccountId, AccountIndex> {
+		Ss58Address(self)
+	}
+}
+
+/// An address displayed with the account ID as an SS58 address, see `Address::ss58`.
+#[cfg(feature = "std")]
+pub struct Ss58Address<'a, AccountId: 'a + Member, AccountIndex: 'a + Member>(&'a Address<AccountId, AccountIndex>);
+
+#[cfg(feature = "std")]
+impl<'a, AccountId, AccountIndex> fmt::Display for Ss58Address<'a, AccountId, AccountIndex> where
+	AccountId: Member + Ss58Codec,
+	AccountIndex: Member,
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self.0 {
+			Address::Id(ref id) => write!(f, "{}", id.to_ss58check()),
+			Address::Index(ref index) => write!(f, "{:?}", index),
+		}
+	}
+}
+
 impl<AccountId, AccountIndex> From<AccountId> for Address<AccountId, AccountIndex> where
 	AccountId: Member,
 	AccountIndex: Member,
@@ -38,7 +38,7 @@ extern crate srml_system as system;
 
 #[cfg(test)]
 extern crate sr_io as runtime_io;
-#[cfg(test)]
+#[cfg(any(feature = "std", test))]
 extern crate substrate_primitives;
 
 use rstd::prelude::*;
@@ -498,3 +498,17 @@ fn transfer_overflow_isnt_exploitable() {
 		}
 	);
 }
+
+#[test]
+fn addresses_display_account_ids_as_ss58_on_request() {
+	use substrate_primitives::AuthorityId;
+	use substrate_primitives::crypto::Ss58Codec;
+
+	let id = AuthorityId([1; 32]);
+	let address = address::Address::<AuthorityId, u32>::Id(id);
+	assert_eq!(address.ss58().to_string(), id.to_ss58check());
+	assert_eq!(address::Address::<AuthorityId, u32>::Index(5).ss58().to_string(), "5");
+
+	// account IDs that aren't SS58 encodable are displayed too.
+	assert_eq!(address::Address::<u64, u32>::Id(1).to_string(), "Id(1)");
+}
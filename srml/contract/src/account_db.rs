@@ -16,17 +16,18 @@
 
 //! Auxilliaries to help with managing partial changes to accounts state.
 
-use super::{CodeOf, StorageOf, Trait};
-use double_map::StorageDoubleMap;
+use super::{CodeHash, ContractInfoOf, Module, RawAliveContractInfo, RawContractInfo, Trait};
+use super::vm::code_cache;
 use rstd::cell::RefCell;
 use rstd::collections::btree_map::{BTreeMap, Entry};
 use rstd::prelude::*;
+use runtime_io;
 use runtime_support::StorageMap;
 use {balances, system};
 
 pub struct ChangeEntry<T: Trait> {
 	balance: Option<T::Balance>,
-	code: Option<Vec<u8>>,
+	code_hash: Option<CodeHash<T>>,
 	storage: BTreeMap<Vec<u8>, Option<Vec<u8>>>,
 }
 
@@ -35,7 +36,7 @@ impl<T: Trait> Default for ChangeEntry<T> {
 	fn default() -> Self {
 		ChangeEntry {
 			balance: Default::default(),
-			code: Default::default(),
+			code_hash: Default::default(),
 			storage: Default::default(),
 		}
 	}
@@ -45,7 +46,7 @@ pub type ChangeSet<T> = BTreeMap<<T as system::Trait>::AccountId, ChangeEntry<T>
 
 pub trait AccountDb<T: Trait> {
 	fn get_storage(&self, account: &T::AccountId, location: &[u8]) -> Option<Vec<u8>>;
-	fn get_code(&self, account: &T::AccountId) -> Vec<u8>;
+	fn get_code(&self, account: &T::AccountId) -> Option<CodeHash<T>>;
 	fn get_balance(&self, account: &T::AccountId) -> T::Balance;
 
 	fn commit(&mut self, change_set: ChangeSet<T>);
@@ -54,10 +55,14 @@ pub trait AccountDb<T: Trait> {
 pub struct DirectAccountDb;
 impl<T: Trait> AccountDb<T> for DirectAccountDb {
 	fn get_storage(&self, account: &T::AccountId, location: &[u8]) -> Option<Vec<u8>> {
-		<StorageOf<T>>::get(account.clone(), location.to_vec())
+		<ContractInfoOf<T>>::get(account)
+			.and_then(|info| info.get_alive())
+			.and_then(|info| runtime_io::child_storage(&info.trie_id, location))
 	}
-	fn get_code(&self, account: &T::AccountId) -> Vec<u8> {
-		<CodeOf<T>>::get(account)
+	fn get_code(&self, account: &T::AccountId) -> Option<CodeHash<T>> {
+		<ContractInfoOf<T>>::get(account)
+			.and_then(|info| info.get_alive())
+			.map(|info| info.code_hash)
 	}
 	fn get_balance(&self, account: &T::AccountId) -> T::Balance {
 		balances::Module::<T>::free_balance(account)
@@ -69,21 +74,48 @@ impl<T: Trait> AccountDb<T> for DirectAccountDb {
 					balances::Module::<T>::set_free_balance_creating(&address, balance)
 				{
 					// Account killed. This will ultimately lead to calling `OnFreeBalanceZero` callback
-					// which will make removal of CodeOf and StorageOf for this account.
+					// which will make removal of ContractInfoOf and the storage for this account.
 					// In order to avoid writing over the deleted properties we `continue` here.
 					continue;
 				}
 			}
-			if let Some(code) = changed.code {
-				<CodeOf<T>>::insert(&address, &code);
+
+			if changed.code_hash.is_none() && changed.storage.is_empty() {
+				continue;
 			}
+
+			let info = match changed.code_hash {
+				// A contract has just been created at this address.
+				Some(code_hash) => {
+					code_cache::increment_refcount::<T>(&code_hash);
+					Some(RawAliveContractInfo {
+						trie_id: <Module<T>>::new_trie_id(&address),
+						storage_size: 0,
+						code_hash,
+						deduct_block: <system::Module<T>>::block_number(),
+					})
+				},
+				None => <ContractInfoOf<T>>::get(&address).and_then(|info| info.get_alive()),
+			};
+			let mut info = match info {
+				Some(info) => info,
+				// Only live contracts can have storage.
+				None => continue,
+			};
+
 			for (k, v) in changed.storage.into_iter() {
+				if let Some(old_value) = runtime_io::child_storage(&info.trie_id, &k) {
+					info.storage_size = info.storage_size.saturating_sub((k.len() + old_value.len()) as u64);
+				}
 				if let Some(value) = v {
-					<StorageOf<T>>::insert(address.clone(), k, value);
+					info.storage_size += (k.len() + value.len()) as u64;
+					runtime_io::set_child_storage(&info.trie_id, &k, &value);
 				} else {
-					<StorageOf<T>>::remove(address.clone(), k);
+					runtime_io::clear_child_storage(&info.trie_id, &k);
 				}
 			}
+
+			<ContractInfoOf<T>>::insert(&address, RawContractInfo::Alive(info));
 		}
 	}
 }
@@ -117,12 +149,12 @@ impl<'a, T: Trait> OverlayAccountDb<'a, T> {
 			.storage
 			.insert(location, value);
 	}
-	pub fn set_code(&mut self, account: &T::AccountId, code: Vec<u8>) {
+	pub fn set_code(&mut self, account: &T::AccountId, code_hash: CodeHash<T>) {
 		self.local
 			.borrow_mut()
 			.entry(account.clone())
 			.or_insert(Default::default())
-			.code = Some(code);
+			.code_hash = Some(code_hash);
 	}
 	pub fn set_balance(&mut self, account: &T::AccountId, balance: T::Balance) {
 		self.local
@@ -142,12 +174,12 @@ impl<'a, T: Trait> AccountDb<T> for OverlayAccountDb<'a, T> {
 			.cloned()
 			.unwrap_or_else(|| self.underlying.get_storage(account, location))
 	}
-	fn get_code(&self, account: &T::AccountId) -> Vec<u8> {
+	fn get_code(&self, account: &T::AccountId) -> Option<CodeHash<T>> {
 		self.local
 			.borrow()
 			.get(account)
-			.and_then(|a| a.code.clone())
-			.unwrap_or_else(|| self.underlying.get_code(account))
+			.and_then(|a| a.code_hash)
+			.or_else(|| self.underlying.get_code(account))
 	}
 	fn get_balance(&self, account: &T::AccountId) -> T::Balance {
 		self.local
@@ -166,8 +198,8 @@ impl<'a, T: Trait> AccountDb<T> for OverlayAccountDb<'a, T> {
 					if changed.balance.is_some() {
 						value.balance = changed.balance;
 					}
-					if changed.code.is_some() {
-						value.code = changed.code;
+					if changed.code_hash.is_some() {
+						value.code_hash = changed.code_hash;
 					}
 					value.storage.extend(changed.storage.into_iter());
 				}
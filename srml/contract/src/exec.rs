@@ -14,14 +14,16 @@
 // You should have received a copy of the GNU General Public License
 // along with Substrate. If not, see <http://www.gnu.org/licenses/>.
 
-use super::{ContractAddressFor, Trait, Event, RawEvent, Config};
+use super::{CodeHash, ContractAddressFor, ContractInfoOf, Trait, Event, RawEvent, Config};
 use account_db::{AccountDb, OverlayAccountDb};
 use gas::GasMeter;
-use vm;
+use vm::{self, code_cache};
 
 use rstd::prelude::*;
 use runtime_primitives::traits::{Zero, CheckedAdd, CheckedSub};
+use runtime_support::StorageMap;
 use balances::{self, EnsureAccountLiquid};
+use {system, timestamp};
 
 // TODO: Add logs
 pub struct CreateReceipt<T: Trait> {
@@ -59,7 +61,13 @@ impl<'a, T: Trait> ExecutionContext<'a, T> {
 			return Err("not enough gas to pay base call fee");
 		}
 
-		let dest_code = self.overlay.get_code(&dest);
+		let dest_code_hash = self.overlay.get_code(&dest);
+
+		// Tombstones are created and removed only outside of contract execution,
+		// so it is fine to look them up directly in the storage.
+		if dest_code_hash.is_none() && <ContractInfoOf<T>>::exists(&dest) {
+			return Err("contract has been evicted");
+		}
 
 		let (change_set, events) = {
 			let mut overlay = OverlayAccountDb::new(&self.overlay);
@@ -83,14 +91,17 @@ impl<'a, T: Trait> ExecutionContext<'a, T> {
 				)?;
 			}
 
-			if !dest_code.is_empty() {
+			if let Some(dest_code_hash) = dest_code_hash {
+				let dest_module = code_cache::load::<T>(&dest_code_hash, &self.config.schedule)?;
 				vm::execute(
-					&dest_code,
+					&dest_module,
+					b"call",
 					data,
 					output_data,
 					&mut CallContext {
 						ctx: &mut nested,
-						_caller: caller,
+						caller,
+						value_transferred: value,
 					},
 					&self.config.schedule,
 					gas_meter,
@@ -106,12 +117,15 @@ impl<'a, T: Trait> ExecutionContext<'a, T> {
 		Ok(CallReceipt)
 	}
 
+	/// Create a contract from the code identified by `code_hash`.
+	///
+	/// The `deploy` function of the code is executed in the context of the newly created account.
 	pub fn create(
 		&mut self,
 		caller: T::AccountId,
 		endowment: T::Balance,
 		gas_meter: &mut GasMeter<T>,
-		init_code: &[u8],
+		code_hash: &CodeHash<T>,
 		data: &[u8],
 	) -> Result<CreateReceipt<T>, &'static str> {
 		if self.depth == self.config.max_depth as usize {
@@ -122,13 +136,15 @@ impl<'a, T: Trait> ExecutionContext<'a, T> {
 			return Err("not enough gas to pay base create fee");
 		}
 
-		let dest = T::DetermineContractAddress::contract_address_for(init_code, data, &self.self_account);
+		let dest = T::DetermineContractAddress::contract_address_for(code_hash, data, &self.self_account);
 
-		if !self.overlay.get_code(&dest).is_empty() {
-			// It should be enough to check only the code.
+		if self.overlay.get_code(&dest).is_some() || <ContractInfoOf<T>>::exists(&dest) {
+			// It should be enough to check only the code and, for evicted contracts, the tombstone.
 			return Err("contract already exists");
 		}
 
+		let module = code_cache::load::<T>(code_hash, &self.config.schedule)?;
+
 		let (change_set, events) = {
 			let mut overlay = OverlayAccountDb::new(&self.overlay);
 
@@ -151,20 +167,21 @@ impl<'a, T: Trait> ExecutionContext<'a, T> {
 				)?;
 			}
 
-			let mut contract_code = Vec::new();
 			vm::execute(
-				init_code,
+				&module,
+				b"deploy",
 				data,
-				&mut contract_code,
+				&mut Vec::new(),
 				&mut CallContext {
 					ctx: &mut nested,
-					_caller: caller,
+					caller,
+					value_transferred: endowment,
 				},
 				&self.config.schedule,
 				gas_meter,
 			).map_err(|_| "vm execute returned error while create")?;
 
-			nested.overlay.set_code(&dest, contract_code);
+			nested.overlay.set_code(&dest, code_hash.clone());
 			(nested.overlay.into_change_set(), nested.events)
 		};
 
@@ -255,7 +272,8 @@ fn transfer<'a, T: Trait>(
 
 struct CallContext<'a, 'b: 'a, T: Trait + 'b> {
 	ctx: &'a mut ExecutionContext<'b, T>,
-	_caller: T::AccountId,
+	caller: T::AccountId,
+	value_transferred: T::Balance,
 }
 
 impl<'a, 'b: 'a, T: Trait + 'b> vm::Ext for CallContext<'a, 'b, T> {
@@ -273,14 +291,14 @@ impl<'a, 'b: 'a, T: Trait + 'b> vm::Ext for CallContext<'a, 'b, T> {
 
 	fn create(
 		&mut self,
-		code: &[u8],
+		code_hash: &CodeHash<T>,
 		endowment: T::Balance,
 		gas_meter: &mut GasMeter<T>,
 		data: &[u8],
 	) -> Result<CreateReceipt<T>, ()> {
 		let caller = self.ctx.self_account.clone();
 		self.ctx
-			.create(caller, endowment, gas_meter, code, &data)
+			.create(caller, endowment, gas_meter, code_hash, &data)
 			.map_err(|_| ())
 	}
 
@@ -298,4 +316,37 @@ impl<'a, 'b: 'a, T: Trait + 'b> vm::Ext for CallContext<'a, 'b, T> {
 			.map_err(|_| ())
 			.map(|_| ())
 	}
+
+	fn caller(&self) -> &T::AccountId {
+		&self.caller
+	}
+
+	fn address(&self) -> &T::AccountId {
+		&self.ctx.self_account
+	}
+
+	fn balance(&self) -> T::Balance {
+		self.ctx.overlay.get_balance(&self.ctx.self_account)
+	}
+
+	fn value_transferred(&self) -> T::Balance {
+		self.value_transferred
+	}
+
+	fn now(&self) -> T::Moment {
+		<timestamp::Module<T>>::now()
+	}
+
+	fn random_seed(&self) -> T::Hash {
+		<system::Module<T>>::random_seed()
+	}
+
+	fn block_number(&self) -> T::BlockNumber {
+		<system::Module<T>>::block_number()
+	}
+
+	fn deposit_event(&mut self, data: Vec<u8>) {
+		let address = self.ctx.self_account.clone();
+		self.ctx.events.push(RawEvent::Contract(address, data));
+	}
 }
@@ -42,7 +42,9 @@ pub struct GasMeter<T: Trait> {
 	gas_price: T::Balance,
 }
 impl<T: Trait> GasMeter<T> {
-	#[cfg(test)]
+	/// Create a gas meter with the given limit, without paying for the gas.
+	///
+	/// Use `buy_gas` to create a meter for gas that is actually paid for.
 	pub fn with_limit(gas_limit: T::Gas, gas_price: T::Balance) -> GasMeter<T> {
 		GasMeter {
 			limit: gas_limit,
@@ -124,7 +126,7 @@ impl<T: Trait> GasMeter<T> {
 	}
 
 	/// Returns how much gas was spent.
-	fn spent(&self) -> T::Gas {
+	pub fn spent(&self) -> T::Gas {
 		self.limit - self.gas_left
 	}
 }
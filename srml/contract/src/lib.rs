@@ -21,6 +21,9 @@
 //! A smart-contract is an account with associated code and storage. When such an account receives a message,
 //! the code associated with that account gets executed.
 //!
+//! The code is uploaded once with `put_code` and is stored under its hash. Any number of contracts can be
+//! then instantiated from the stored code by referring to it with the code hash.
+//!
 //! The code is allowed to alter the storage entries of the associated account,
 //! create smart-contracts or send messages to existing smart-contracts.
 //!
@@ -46,7 +49,18 @@
 //!
 //! When `staking` module determines that account is dead (e.g. account's balance fell below
 //! exsistential deposit) then it reaps the account. That will lead to deletion of the associated
-//! code and storage of the account.
+//! code hash and storage of the account. The code itself stays in the storage.
+//!
+//! ## Storage rent
+//!
+//! Each contract keeps its storage in a child trie of its own. For every block the contract
+//! is charged a rent proportional to the size of its storage. The rent is collected lazily: when
+//! the contract is called by a transaction or when anyone calls `claim_surcharge` for it.
+//!
+//! A contract that cannot pay the rent while staying above the existential deposit is evicted:
+//! its storage is removed and a tombstone is left in its place. The tombstone records the hash of
+//! the storage root and the code hash of the contract. The contract can be brought back with
+//! `restore_to` by anyone who provides the code hash and the storage that match the tombstone.
 //!
 //! [`Module::execute`]: struct.Module.html#impl-OnFinalise
 
@@ -67,15 +81,18 @@ extern crate sr_std as rstd;
 
 extern crate srml_balances as balances;
 extern crate srml_system as system;
+extern crate srml_timestamp as timestamp;
 
 #[macro_use]
 extern crate srml_support as runtime_support;
 
 extern crate sr_primitives as runtime_primitives;
 
-#[cfg(test)]
 extern crate substrate_primitives;
 
+#[cfg(test)]
+extern crate srml_consensus as consensus;
+
 #[cfg(test)]
 #[macro_use]
 extern crate assert_matches;
@@ -84,29 +101,90 @@ extern crate assert_matches;
 extern crate wabt;
 
 mod account_db;
-mod double_map;
 mod exec;
 mod vm;
 mod gas;
+mod rent;
 
 #[cfg(test)]
 mod tests;
 
 use exec::ExecutionContext;
 use account_db::{AccountDb, OverlayAccountDb};
-use double_map::StorageDoubleMap;
+use gas::GasMeter;
+use rent::RentOutcome;
+use vm::code_cache;
 
 use rstd::prelude::*;
 use rstd::marker::PhantomData;
-use codec::{Codec, HasCompact};
+use codec::{Codec, Encode, HasCompact};
 use runtime_primitives::traits::{Hash, As, SimpleArithmetic};
 use runtime_support::dispatch::Result;
 use runtime_support::{Parameter, StorageMap, StorageValue};
+use substrate_primitives::storage::well_known_keys;
 use system::ensure_signed;
 
-pub trait Trait: balances::Trait {
+pub type CodeHash<T> = <T as system::Trait>::Hash;
+
+/// The key of the child trie that holds the storage of a contract.
+pub type TrieId = Vec<u8>;
+
+/// Information about a contract that is alive.
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct RawAliveContractInfo<CodeHash, BlockNumber> {
+	/// Unique key of the child trie that holds the storage of the contract.
+	pub trie_id: TrieId,
+	/// The total size of the keys and values in the storage of the contract, in bytes.
+	pub storage_size: u64,
+	/// The code associated with the contract.
+	pub code_hash: CodeHash,
+	/// The last block at which the rent was paid.
+	pub deduct_block: BlockNumber,
+}
+
+pub type AliveContractInfo<T> = RawAliveContractInfo<CodeHash<T>, <T as system::Trait>::BlockNumber>;
+
+/// Information about a contract account.
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum RawContractInfo<CodeHash, BlockNumber, Hash> {
+	/// The contract is alive and can be called.
+	Alive(RawAliveContractInfo<CodeHash, BlockNumber>),
+	/// The contract has been evicted for failing to pay the rent.
+	///
+	/// Holds the hash of the storage root of the contract at the moment of eviction
+	/// concatenated with its code hash. See `tombstone_hash`.
+	Tombstone(Hash),
+}
+
+impl<CodeHash, BlockNumber, Hash> RawContractInfo<CodeHash, BlockNumber, Hash> {
+	/// Returns the information about the contract if it is alive.
+	pub fn get_alive(self) -> Option<RawAliveContractInfo<CodeHash, BlockNumber>> {
+		match self {
+			RawContractInfo::Alive(alive) => Some(alive),
+			RawContractInfo::Tombstone(_) => None,
+		}
+	}
+}
+
+pub type ContractInfo<T> = RawContractInfo<
+	CodeHash<T>,
+	<T as system::Trait>::BlockNumber,
+	<T as system::Trait>::Hash,
+>;
+
+/// Computes the hash a tombstone is made of: `hash(storage_root + code_hash)`.
+pub fn tombstone_hash<T: Trait>(storage_root: &[u8], code_hash: &CodeHash<T>) -> T::Hash {
+	let mut buf = Vec::new();
+	buf.extend_from_slice(storage_root);
+	buf.extend_from_slice(code_hash.as_ref());
+	T::Hashing::hash(&buf[..])
+}
+
+pub trait Trait: balances::Trait + timestamp::Trait {
 	/// Function type to get the contract address given the creator.
-	type DetermineContractAddress: ContractAddressFor<Self::AccountId>;
+	type DetermineContractAddress: ContractAddressFor<CodeHash<Self>, Self::AccountId>;
 
 	// As<u32> is needed for wasm-utils
 	type Gas: Parameter + Default + Codec + SimpleArithmetic + Copy + As<Self::Balance> + As<u64> + As<u32>;
@@ -115,24 +193,23 @@ pub trait Trait: balances::Trait {
 	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
 }
 
-pub trait ContractAddressFor<AccountId: Sized> {
-	fn contract_address_for(code: &[u8], data: &[u8], origin: &AccountId) -> AccountId;
+pub trait ContractAddressFor<CodeHash, AccountId: Sized> {
+	fn contract_address_for(code_hash: &CodeHash, data: &[u8], origin: &AccountId) -> AccountId;
 }
 
 /// Simple contract address determintator.
 ///
-/// Address calculated from the code (of the constructor), input data to the constructor
+/// Address calculated from the code hash, input data to the `deploy` function
 /// and account id which requested the account creation.
 ///
-/// Formula: `blake2_256(blake2_256(code) + blake2_256(data) + origin)`
+/// Formula: `blake2_256(code_hash + blake2_256(data) + origin)`
 pub struct SimpleAddressDeterminator<T: Trait>(PhantomData<T>);
 
-impl<T: Trait> ContractAddressFor<T::AccountId> for SimpleAddressDeterminator<T>
+impl<T: Trait> ContractAddressFor<CodeHash<T>, T::AccountId> for SimpleAddressDeterminator<T>
 where
 	T::AccountId: From<T::Hash> + AsRef<[u8]>
 {
-	fn contract_address_for(code: &[u8], data: &[u8], origin: &T::AccountId) -> T::AccountId {
-		let code_hash = T::Hashing::hash(code);
+	fn contract_address_for(code_hash: &CodeHash<T>, data: &[u8], origin: &T::AccountId) -> T::AccountId {
 		let data_hash = T::Hashing::hash(data);
 
 		let mut buf = Vec::new();
@@ -148,9 +225,45 @@ decl_module! {
 	/// Contracts module.
 	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
 		fn deposit_event() = default;
+
+		/// Stores the given binary Wasm code into the chain's storage and returns its `code_hash`
+		/// in the `CodeStored` event.
+		///
+		/// The code is validated and instrumented before it is stored. Storing the code is paid
+		/// for with gas proportional to the size of the code, and a deposit of `CodeByteDeposit`
+		/// per byte is reserved from the sender for as long as the code is stored. Contracts can
+		/// only be instantiated from stored code.
+		///
+		/// The code is removed, and the deposit unreserved, along with the last contract
+		/// instantiated from it, or with `remove_code` if no contract uses it.
+		fn put_code(
+			origin,
+			gas_limit: <T::Gas as HasCompact>::Type,
+			code: Vec<u8>
+		) -> Result {
+			let origin = ensure_signed(origin)?;
+			let gas_limit = gas_limit.into();
+			let schedule = <Module<T>>::current_schedule();
+
+			// Pay for the gas upfront.
+			//
+			// NOTE: it is very important to avoid any state changes before
+			// paying for the gas.
+			let mut gas_meter = gas::buy_gas::<T>(&origin, gas_limit)?;
+
+			let result = code_cache::save::<T>(&origin, code, &mut gas_meter, &schedule);
+			if let Ok(code_hash) = result {
+				Self::deposit_event(RawEvent::CodeStored(code_hash));
+			}
+
+			// Refund cost of the unused gas.
+			gas::refund_unused_gas::<T>(&origin, gas_meter);
+
+			result.map(|_| ())
+		}
+
 		// TODO: Change AccountId to staking::Address
 		/// Make a call to a specified account, optionally transferring some balance.
-		/// Make a call to a specified account, optionally transferring some balance.
 		fn call(
 			origin,
 			dest: T::AccountId,
@@ -168,6 +281,15 @@ decl_module! {
 			// paying for the gas.
 			let mut gas_meter = gas::buy_gas::<T>(&origin, gas_limit)?;
 
+			// Collect the rent due from the destination contract. A contract that fails to pay
+			// is evicted and the call below fails.
+			//
+			// NOTE: the rent is collected only for the contract called by the transaction, as
+			// it is the only one that can't have pending changes in the overlay at this point.
+			if rent::pay_rent::<T>(&dest) == RentOutcome::Evicted {
+				Self::deposit_event(RawEvent::Evicted(dest.clone()));
+			}
+
 			let cfg = Config::preload();
 			let mut ctx = ExecutionContext {
 				self_account: origin.clone(),
@@ -201,16 +323,16 @@ decl_module! {
 		///
 		/// Creation is executed as follows:
 		///
-		/// - the destination address is computed based on the sender and hash of the code.
+		/// - the destination address is computed based on the sender, the code hash and the input data.
 		/// - account is created at the computed address.
-		/// - the `ctor_code` is executed in the context of the newly created account. Buffer returned
-		///   after the execution is saved as the `code` of the account. That code will be invoked
-		///   upon any message received by this account.
+		/// - the `deploy` function of the code identified by `code_hash` is executed in the context
+		///   of the newly created account. The `code_hash` is then associated with the account and
+		///   the `call` function of the code will be invoked upon any message received by this account.
 		fn create(
 			origin,
 			endowment: <T::Balance as HasCompact>::Type,
 			gas_limit: <T::Gas as HasCompact>::Type,
-			ctor_code: Vec<u8>,
+			code_hash: CodeHash<T>,
 			data: Vec<u8>
 		) -> Result {
 			let origin = ensure_signed(origin)?;
@@ -231,7 +353,7 @@ decl_module! {
 				events: Vec::new(),
 				config: &cfg,
 			};
-			let result = ctx.create(origin.clone(), endowment, &mut gas_meter, &ctor_code, &data);
+			let result = ctx.create(origin.clone(), endowment, &mut gas_meter, &code_hash, &data);
 
 			if let Ok(ref r) = result {
 				// Commit all changes that made it thus far into the persistant storage.
@@ -252,6 +374,70 @@ decl_module! {
 			result.map(|_| ())
 		}
 
+		/// Collects the rent due from the contract at `dest`.
+		///
+		/// If the contract can't afford the rent, it is evicted and the sender receives
+		/// a reward of up to `SurchargeReward` out of the remaining balance of the contract.
+		fn claim_surcharge(origin, dest: T::AccountId) -> Result {
+			let origin = ensure_signed(origin)?;
+
+			match rent::try_evict::<T>(&dest, &origin) {
+				RentOutcome::NotAlive => Err("destination is not a live contract"),
+				RentOutcome::Paid => Ok(()),
+				RentOutcome::Evicted => {
+					Self::deposit_event(RawEvent::Evicted(dest));
+					Ok(())
+				}
+			}
+		}
+
+		/// Restores the evicted contract at `dest` from its tombstone.
+		///
+		/// The sender provides the code hash and the full storage of the contract as it was at the
+		/// moment of eviction, as a list of key-value pairs sorted by key. They are accepted only
+		/// if they match the hash recorded in the tombstone. The code has to be stored, it is
+		/// removed on eviction unless another contract still uses it.
+		///
+		/// Writing the storage is paid for with gas proportional to its size.
+		///
+		/// The tombstone keeps only the existential deposit, so the account should be topped up
+		/// beforehand, otherwise the restored contract is likely to be evicted again right away.
+		fn restore_to(
+			origin,
+			dest: T::AccountId,
+			code_hash: CodeHash<T>,
+			gas_limit: <T::Gas as HasCompact>::Type,
+			storage: Vec<(Vec<u8>, Vec<u8>)>
+		) -> Result {
+			let origin = ensure_signed(origin)?;
+			let gas_limit = gas_limit.into();
+			let schedule = <Module<T>>::current_schedule();
+
+			// Pay for the gas upfront.
+			//
+			// NOTE: it is very important to avoid any state changes before
+			// paying for the gas.
+			let mut gas_meter = gas::buy_gas::<T>(&origin, gas_limit)?;
+
+			let result = rent::restore_to::<T>(&dest, code_hash, storage, &mut gas_meter, &schedule);
+			if result.is_ok() {
+				Self::deposit_event(RawEvent::Restored(dest));
+			}
+
+			// Refund cost of the unused gas.
+			gas::refund_unused_gas::<T>(&origin, gas_meter);
+
+			result
+		}
+
+		/// Removes the code with the given hash, stored by the sender, and unreserves its deposit.
+		///
+		/// Fails if a contract uses the code.
+		fn remove_code(origin, code_hash: CodeHash<T>) -> Result {
+			let origin = ensure_signed(origin)?;
+			code_cache::remove::<T>(&origin, &code_hash)
+		}
+
 		fn on_finalise() {
 			<GasSpent<T>>::kill();
 		}
@@ -262,13 +448,26 @@ decl_event! {
 	pub enum Event<T>
 	where
 		<T as balances::Trait>::Balance,
-		<T as system::Trait>::AccountId
+		<T as system::Trait>::AccountId,
+		<T as system::Trait>::Hash
 	{
 		/// Transfer happened `from` -> `to` with given `value` as part of a `message-call` or `create`.
 		Transfer(AccountId, AccountId, Balance),
 
 		/// Contract deployed by address at the specified address.
 		Created(AccountId, AccountId),
+
+		/// Code with the specified hash has been stored.
+		CodeStored(Hash),
+
+		/// An event deposited by contract with code at the specified address.
+		Contract(AccountId, Vec<u8>),
+
+		/// The contract at the specified address has been evicted for failing to pay the rent.
+		Evicted(AccountId),
+
+		/// The contract at the specified address has been restored from its tombstone.
+		Restored(AccountId),
 	}
 }
 
@@ -290,29 +489,103 @@ decl_storage! {
 		GasSpent get(gas_spent): T::Gas;
 		/// Current cost schedule for contracts.
 		CurrentSchedule get(current_schedule) config(): Schedule<T::Gas> = Schedule::default();
-		/// The code associated with an account.
-		pub CodeOf: map T::AccountId => Vec<u8>;	// TODO Vec<u8> values should be optimised to not do a length prefix.
+		/// The deposit reserved per byte of code stored with `put_code`.
+		CodeByteDeposit get(code_byte_deposit) config(): T::Balance = T::Balance::sa(1);
+		/// The price of storing one byte in the storage of a contract for one block.
+		RentByteFee get(rent_byte_price) config(): T::Balance = T::Balance::sa(1);
+		/// The maximum reward for evicting a contract that failed to pay the rent.
+		SurchargeReward get(surcharge_reward) config(): T::Balance = T::Balance::sa(150);
+		/// The number of contract tries created so far. Used to generate unique trie ids.
+		AccountCounter get(account_counter): u64 = 0;
+		/// The contract information associated with an account.
+		pub ContractInfoOf: map T::AccountId => Option<ContractInfo<T>>;
+		/// A mapping from an original code hash to the original code, untouched by instrumentation.
+		pub PristineCode: map CodeHash<T> => Option<Vec<u8>>;
+		/// A mapping between an original code hash and instrumented wasm code, ready for the execution.
+		pub CodeStorage: map CodeHash<T> => Option<vm::PrefabWasmModule>;
+		/// The account that stored the code with the given hash, and the deposit reserved from it.
+		pub CodeDeposit: map CodeHash<T> => Option<(T::AccountId, T::Balance)>;
+		/// The number of alive contracts using the code with the given hash.
+		pub CodeRefCount: map CodeHash<T> => u32;
 	}
 }
 
 // TODO: consider storing upper-bound for contract's gas limit in fixed-length runtime
 // code in contract itself and use that.
 
-/// The storage items associated with an account/key.
-///
-/// TODO: keys should also be able to take AsRef<KeyType> to ensure Vec<u8>s can be passed as &[u8]
-pub(crate) struct StorageOf<T>(::rstd::marker::PhantomData<T>);
-impl<T: Trait> double_map::StorageDoubleMap for StorageOf<T> {
-	const PREFIX: &'static [u8] = b"con:sto:";
-	type Key1 = T::AccountId;
-	type Key2 = Vec<u8>;
-	type Value = Vec<u8>;
+/// The outcome of a contract call performed with `Module::bare_call`.
+pub struct BareCallOutcome<T: Trait> {
+	/// The output data returned by the contract, or the reason of the failure.
+	pub result: rstd::result::Result<Vec<u8>, &'static str>,
+	/// The amount of gas consumed by the call.
+	pub gas_consumed: T::Gas,
+	/// The events produced by the call. Empty if the call failed.
+	pub events: Vec<Event<T>>,
+}
+
+impl<T: Trait> Module<T> {
+	/// Perform a call to the contract at `dest` the same way the `call` dispatchable would, but
+	/// without committing any changes.
+	///
+	/// The gas is not paid for and the rent is not collected. This is meant to be used by
+	/// runtime APIs, e.g. to estimate the gas required by a call or to query a contract.
+	pub fn bare_call(
+		origin: T::AccountId,
+		dest: T::AccountId,
+		value: T::Balance,
+		gas_limit: T::Gas,
+		input_data: Vec<u8>,
+	) -> BareCallOutcome<T> {
+		let mut gas_meter = GasMeter::<T>::with_limit(gas_limit, Self::gas_price());
+
+		let cfg = Config::preload();
+		let mut ctx = ExecutionContext {
+			self_account: origin.clone(),
+			depth: 0,
+			overlay: OverlayAccountDb::<T>::new(&account_db::DirectAccountDb),
+			events: Vec::new(),
+			config: &cfg,
+		};
+
+		let mut output_data = Vec::new();
+		let result = ctx.call(origin, dest, value, &mut gas_meter, &input_data, &mut output_data);
+
+		BareCallOutcome {
+			gas_consumed: gas_meter.spent(),
+			events: if result.is_ok() { ctx.events } else { Vec::new() },
+			result: result.map(|_| output_data),
+		}
+	}
+
+	/// Generates a new unique trie id for a contract at the given address.
+	fn new_trie_id(account: &T::AccountId) -> TrieId {
+		let counter = Self::account_counter().wrapping_add(1);
+		<AccountCounter<T>>::put(counter);
+		Self::trie_id(account, counter)
+	}
+
+	/// The trie id of a contract at the given address, given the value of `AccountCounter`
+	/// it was created at.
+	fn trie_id(account: &T::AccountId, counter: u64) -> TrieId {
+		let mut buf = Vec::new();
+		buf.extend_from_slice(&account.encode());
+		buf.extend_from_slice(&counter.encode());
+		let hash = T::Hashing::hash(&buf[..]);
+
+		let mut trie_id = well_known_keys::CHILD_STORAGE_KEY_PREFIX.to_vec();
+		trie_id.extend_from_slice(b"default:");
+		trie_id.extend_from_slice(hash.as_ref());
+		trie_id
+	}
 }
 
 impl<T: Trait> balances::OnFreeBalanceZero<T::AccountId> for Module<T> {
 	fn on_free_balance_zero(who: &T::AccountId) {
-		<CodeOf<T>>::remove(who);
-		<StorageOf<T>>::remove_prefix(who.clone());
+		if let Some(RawContractInfo::Alive(info)) = <ContractInfoOf<T>>::get(who) {
+			runtime_io::kill_child_storage(&info.trie_id);
+			code_cache::decrement_refcount::<T>(&info.code_hash);
+		}
+		<ContractInfoOf<T>>::remove(who);
 	}
 }
 
@@ -350,6 +623,18 @@ impl<T: Trait> Config<T> {
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize, Debug))]
 #[derive(Clone, Encode, Decode)]
 pub struct Schedule<Gas> {
+	/// Version of the schedule.
+	///
+	/// Stored code instrumented with a lower version is re-instrumented on the next use, so
+	/// the version should be bumped whenever a change requires re-instrumentation.
+	pub version: u32,
+
+	/// Cost of putting a byte of code into the storage.
+	pub put_code_per_byte_cost: Gas,
+
+	/// Cost of writing a byte of the storage of a contract restored from its tombstone.
+	pub restore_per_byte_cost: Gas,
+
 	/// Gas cost of a growing memory by single page.
 	pub grow_mem_cost: Gas,
 
@@ -365,6 +650,16 @@ pub struct Schedule<Gas> {
 	/// Gas cost per one byte written to the sandbox memory.
 	sandbox_data_write_cost: Gas,
 
+	/// Gas cost of querying a value of the execution environment, such as the caller's address
+	/// or the current block number.
+	pub env_query_cost: Gas,
+
+	/// Gas cost to deposit an event; the per-byte portion.
+	pub event_data_per_byte_cost: Gas,
+
+	/// Gas cost to deposit an event; the base.
+	pub event_base_cost: Gas,
+
 	/// How tall the stack is allowed to grow?
 	///
 	/// See https://wiki.parity.io/WebAssembly-StackHeight to find out
@@ -379,11 +674,17 @@ pub struct Schedule<Gas> {
 impl<Gas: As<u64>> Default for Schedule<Gas> {
 	fn default() -> Schedule<Gas> {
 		Schedule {
+			version: 0,
+			put_code_per_byte_cost: Gas::sa(1),
+			restore_per_byte_cost: Gas::sa(1),
 			grow_mem_cost: Gas::sa(1),
 			regular_op_cost: Gas::sa(1),
 			return_data_per_byte_cost: Gas::sa(1),
 			sandbox_data_read_cost: Gas::sa(1),
 			sandbox_data_write_cost: Gas::sa(1),
+			env_query_cost: Gas::sa(1),
+			event_data_per_byte_cost: Gas::sa(1),
+			event_base_cost: Gas::sa(1),
 			max_stack_height: 64 * 1024,
 			max_memory_pages: 16,
 		}
@@ -0,0 +1,193 @@
+// Copyright 2018 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate. If not, see <http://www.gnu.org/licenses/>.
+
+//! Storage rent collection, eviction of contracts that can't pay it and their restoration.
+
+use {AccountCounter, AliveContractInfo, CodeHash, CodeStorage, ContractInfoOf, Module, RawContractInfo, Schedule, Trait};
+use gas::GasMeter;
+use tombstone_hash;
+use vm::code_cache;
+use rstd::cmp;
+use rstd::prelude::*;
+use runtime_io;
+use runtime_primitives::traits::{As, CheckedAdd, CheckedMul, CheckedSub, Zero};
+use runtime_support::{StorageMap, StorageValue};
+use {balances, system};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RentOutcome {
+	/// The account doesn't hold a live contract, so there is nothing to collect.
+	NotAlive,
+	/// The rent due was paid, if any.
+	Paid,
+	/// The contract couldn't afford the rent and has been replaced by a tombstone.
+	Evicted,
+}
+
+/// Collect the rent due from the contract at `account` since the last time it was paid.
+///
+/// The contract is evicted if it can't pay.
+pub fn pay_rent<T: Trait>(account: &T::AccountId) -> RentOutcome {
+	collect_rent::<T>(account, None)
+}
+
+/// Same as `pay_rent`, but if the contract gets evicted, `rewarded` receives
+/// the surcharge reward out of the remaining balance of the contract.
+pub fn try_evict<T: Trait>(account: &T::AccountId, rewarded: &T::AccountId) -> RentOutcome {
+	collect_rent::<T>(account, Some(rewarded))
+}
+
+fn collect_rent<T: Trait>(account: &T::AccountId, rewarded: Option<&T::AccountId>) -> RentOutcome {
+	let mut contract = match <ContractInfoOf<T>>::get(account) {
+		Some(RawContractInfo::Alive(contract)) => contract,
+		_ => return RentOutcome::NotAlive,
+	};
+
+	let current_block = <system::Module<T>>::block_number();
+	if contract.deduct_block >= current_block {
+		// The rent has already been paid for this block.
+		return RentOutcome::Paid;
+	}
+	let blocks_passed = current_block - contract.deduct_block;
+
+	// `None` means that the rent is too high to be represented, and thus can't be paid.
+	let rent = <Module<T>>::rent_byte_price()
+		.checked_mul(&<T::Balance as As<u64>>::sa(contract.storage_size))
+		.and_then(|rent_per_block| {
+			rent_per_block.checked_mul(&<T::Balance as As<u64>>::sa(blocks_passed.as_()))
+		});
+
+	let balance = <balances::Module<T>>::free_balance(account);
+	let subsistence_threshold = <balances::Module<T>>::existential_deposit();
+
+	// The contract must keep at least the existential deposit after paying the rent, otherwise
+	// the account would be reaped along with its tombstone.
+	let new_balance = rent
+		.and_then(|rent| balance.checked_sub(&rent))
+		.filter(|new_balance| *new_balance >= subsistence_threshold);
+
+	match new_balance {
+		Some(new_balance) => {
+			<balances::Module<T>>::set_free_balance(account, new_balance);
+			<balances::Module<T>>::decrease_total_stake_by(balance - new_balance);
+
+			contract.deduct_block = current_block;
+			<ContractInfoOf<T>>::insert(account, RawContractInfo::Alive(contract));
+
+			RentOutcome::Paid
+		}
+		None => {
+			// Everything above the existential deposit goes towards the rent, except
+			// for the reward of the one who claimed the eviction. The reward is forfeited
+			// if it can't be credited without overflowing.
+			let surplus = balance.checked_sub(&subsistence_threshold).unwrap_or_else(Zero::zero);
+			let reward = rewarded.and_then(|rewarded| {
+				let reward = cmp::min(<Module<T>>::surcharge_reward(), surplus);
+				<balances::Module<T>>::free_balance(rewarded)
+					.checked_add(&reward)
+					.map(|new_balance| (rewarded, reward, new_balance))
+			});
+
+			<balances::Module<T>>::set_free_balance(account, balance - surplus);
+			match reward {
+				Some((rewarded, reward, new_balance)) => {
+					<balances::Module<T>>::decrease_total_stake_by(surplus - reward);
+					<balances::Module<T>>::set_free_balance_creating(rewarded, new_balance);
+				}
+				None => <balances::Module<T>>::decrease_total_stake_by(surplus),
+			}
+
+			evict::<T>(account, contract);
+
+			RentOutcome::Evicted
+		}
+	}
+}
+
+/// Remove the storage of the contract and replace it with a tombstone.
+fn evict<T: Trait>(account: &T::AccountId, contract: AliveContractInfo<T>) {
+	let storage_root = runtime_io::child_storage_root(&contract.trie_id)
+		.expect("trie ids are always valid child storage keys; qed");
+	let tombstone = tombstone_hash::<T>(&storage_root, &contract.code_hash);
+
+	runtime_io::kill_child_storage(&contract.trie_id);
+	<ContractInfoOf<T>>::insert(account, RawContractInfo::Tombstone(tombstone));
+	code_cache::decrement_refcount::<T>(&contract.code_hash);
+}
+
+/// Restore the contract at `dest` from its tombstone.
+///
+/// `storage` must be sorted by key without duplicates. Together with `code_hash`, it must
+/// produce the hash recorded in the tombstone. Writing the storage is charged from `gas_meter`
+/// per byte of the keys and values.
+///
+/// The storage has to be written to compute its root, so it is removed again if it doesn't
+/// match the tombstone. Nothing else is changed unless the restoration succeeds.
+pub fn restore_to<T: Trait>(
+	dest: &T::AccountId,
+	code_hash: CodeHash<T>,
+	storage: Vec<(Vec<u8>, Vec<u8>)>,
+	gas_meter: &mut GasMeter<T>,
+	schedule: &Schedule<T::Gas>,
+) -> Result<(), &'static str> {
+	let tombstone = match <ContractInfoOf<T>>::get(dest) {
+		Some(RawContractInfo::Tombstone(tombstone)) => tombstone,
+		_ => return Err("cannot restore to an account without a tombstone"),
+	};
+	if !<CodeStorage<T>>::exists(&code_hash) {
+		return Err("code is not found");
+	}
+	if storage.windows(2).any(|pair| pair[0].0 >= pair[1].0) {
+		return Err("storage keys must be sorted and unique");
+	}
+
+	let storage_size = storage.iter()
+		.try_fold(0u64, |size, &(ref key, ref value)| {
+			size.checked_add((key.len() as u64).checked_add(value.len() as u64)?)
+		})
+		.ok_or("overflow occured when calculating the storage size")?;
+	let price = schedule
+		.restore_per_byte_cost
+		.checked_mul(&<T::Gas as As<u64>>::sa(storage_size))
+		.ok_or("overflow occured when calculating restore_to price")?;
+	if gas_meter.charge(price).is_out_of_gas() {
+		return Err("there is not enough gas for restoring the storage");
+	}
+
+	let counter = <Module<T>>::account_counter().wrapping_add(1);
+	let trie_id = <Module<T>>::trie_id(dest, counter);
+	for (key, value) in storage {
+		runtime_io::set_child_storage(&trie_id, &key, &value);
+	}
+
+	let storage_root = runtime_io::child_storage_root(&trie_id)
+		.expect("trie ids are always valid child storage keys; qed");
+	if tombstone_hash::<T>(&storage_root, &code_hash) != tombstone {
+		runtime_io::kill_child_storage(&trie_id);
+		return Err("storage and code hash don't match the tombstone");
+	}
+
+	<AccountCounter<T>>::put(counter);
+	code_cache::increment_refcount::<T>(&code_hash);
+	<ContractInfoOf<T>>::insert(dest, RawContractInfo::Alive(AliveContractInfo::<T> {
+		trie_id,
+		storage_size,
+		code_hash,
+		deduct_block: <system::Module<T>>::block_number(),
+	}));
+
+	Ok(())
+}
@@ -14,18 +14,20 @@
 // You should have received a copy of the GNU General Public License
 // along with Substrate. If not, see <http://www.gnu.org/licenses/>.
 
-use double_map::StorageDoubleMap;
+use account_db::{AccountDb, DirectAccountDb, OverlayAccountDb};
+use gas::GasMeter;
 use runtime_io::with_externalities;
 use runtime_primitives::testing::{Digest, DigestItem, H256, Header};
-use runtime_primitives::traits::{BlakeTwo256};
+use runtime_primitives::traits::{BlakeTwo256, Hash};
 use runtime_primitives::BuildStorage;
 use runtime_support::StorageMap;
 use substrate_primitives::{Blake2Hasher};
 use system::{Phase, EventRecord};
+use vm::code_cache;
 use wabt;
 use {
-	runtime_io, balances, system, CodeOf, ContractAddressFor,
-	GenesisConfig, Module, StorageOf, Trait, RawEvent,
+	runtime_io, balances, consensus, system, timestamp, CodeStorage, ContractAddressFor, ContractInfoOf,
+	GenesisConfig, Module, PristineCode, RawContractInfo, Schedule, Trait, RawEvent,
 };
 
 impl_outer_origin! {
@@ -62,6 +64,16 @@ impl balances::Trait for Test {
 	type EnsureAccountLiquid = ();
 	type Event = MetaEvent;
 }
+impl consensus::Trait for Test {
+	const NOTE_OFFLINE_POSITION: u32 = 1;
+	type Log = DigestItem;
+	type SessionKey = u64;
+	type OnOfflineValidator = ();
+}
+impl timestamp::Trait for Test {
+	const TIMESTAMP_SET_POSITION: u32 = 0;
+	type Moment = u64;
+}
 impl Trait for Test {
 	type Gas = u64;
 	type DetermineContractAddress = DummyContractAddressFor;
@@ -73,18 +85,54 @@ type Contract = Module<Test>;
 type System = system::Module<Test>;
 
 pub struct DummyContractAddressFor;
-impl ContractAddressFor<u64> for DummyContractAddressFor {
-	fn contract_address_for(_code: &[u8], _data: &[u8], origin: &u64) -> u64 {
+impl ContractAddressFor<H256, u64> for DummyContractAddressFor {
+	fn contract_address_for(_code_hash: &H256, _data: &[u8], origin: &u64) -> u64 {
 		origin + 1
 	}
 }
 
+/// Instrument and store the given wasm code without charging anyone for it.
+///
+/// Returns the hash of the stored code.
+fn store_code(wasm: &[u8]) -> H256 {
+	code_cache::save::<Test>(
+		&0,
+		wasm.to_vec(),
+		&mut GasMeter::with_limit(100_000_000, 1),
+		&Schedule::default(),
+	).unwrap()
+}
+
+/// Store the given wasm code and associate it with the specified account.
+fn set_code(account: u64, wasm: &[u8]) {
+	let mut overlay = OverlayAccountDb::<Test>::new(&DirectAccountDb);
+	overlay.set_code(&account, store_code(wasm));
+	DirectAccountDb.commit(overlay.into_change_set());
+}
+
+/// Set a storage entry of the contract at the specified account.
+fn set_storage(account: u64, key: &[u8], value: &[u8]) {
+	let mut overlay = OverlayAccountDb::<Test>::new(&DirectAccountDb);
+	overlay.set_storage(&account, key.to_vec(), Some(value.to_vec()));
+	DirectAccountDb.commit(overlay.into_change_set());
+}
+
+fn get_storage(account: u64, key: &[u8]) -> Option<Vec<u8>> {
+	AccountDb::<Test>::get_storage(&DirectAccountDb, &account, key)
+}
+
+fn get_code(account: u64) -> Option<H256> {
+	AccountDb::<Test>::get_code(&DirectAccountDb, &account)
+}
+
 struct ExtBuilder {
 	existential_deposit: u64,
 	gas_price: u64,
 	block_gas_limit: u64,
 	transfer_fee: u64,
 	creation_fee: u64,
+	rent_byte_price: u64,
+	code_byte_deposit: u64,
 }
 impl Default for ExtBuilder {
 	fn default() -> Self {
@@ -94,6 +142,8 @@ impl Default for ExtBuilder {
 			block_gas_limit: 100_000_000,
 			transfer_fee: 0,
 			creation_fee: 0,
+			rent_byte_price: 0,
+			code_byte_deposit: 0,
 		}
 	}
 }
@@ -118,6 +168,14 @@ impl ExtBuilder {
 		self.creation_fee = creation_fee;
 		self
 	}
+	fn rent_byte_price(mut self, rent_byte_price: u64) -> Self {
+		self.rent_byte_price = rent_byte_price;
+		self
+	}
+	fn code_byte_deposit(mut self, code_byte_deposit: u64) -> Self {
+		self.code_byte_deposit = code_byte_deposit;
+		self
+	}
 	fn build(self) -> runtime_io::TestExternalities<Blake2Hasher> {
 		let mut t = system::GenesisConfig::<Test>::default()
 			.build_storage()
@@ -144,6 +202,9 @@ impl ExtBuilder {
 				max_depth: 100,
 				block_gas_limit: self.block_gas_limit,
 				current_schedule: Default::default(),
+				code_byte_deposit: self.code_byte_deposit,
+				rent_byte_price: self.rent_byte_price,
+				surcharge_reward: 150,
 				_genesis_phantom_data: Default::default(),
 			}.build_storage()
 			.unwrap().0,
@@ -165,6 +226,8 @@ const CODE_TRANSFER: &str = r#"
 	;; ) -> u32
 	(import "env" "ext_call" (func $ext_call (param i32 i32 i64 i32 i32 i32 i32) (result i32)))
 	(import "env" "memory" (memory 1 1))
+	(func (export "deploy")
+	)
 	(func (export "call")
 		(drop
 			(call $ext_call
@@ -195,7 +258,7 @@ fn contract_transfer() {
 	let code_transfer = wabt::wat2wasm(CODE_TRANSFER).unwrap();
 
 	with_externalities(&mut ExtBuilder::default().build(), || {
-		<CodeOf<Test>>::insert(1, code_transfer.to_vec());
+		set_code(1, &code_transfer);
 
 		Balances::set_free_balance(&0, 100_000_000);
 		Balances::increase_total_stake_by(100_000_000);
@@ -251,14 +314,14 @@ fn contract_transfer_to_death() {
 	let code_transfer = wabt::wat2wasm(CODE_TRANSFER).unwrap();
 
 	with_externalities(&mut ExtBuilder::default().existential_deposit(5).build(), || {
-		<CodeOf<Test>>::insert(1, code_transfer.to_vec());
+		set_code(1, &code_transfer);
 
 		Balances::set_free_balance(&0, 100_000_000);
 		Balances::increase_total_stake_by(100_000_000);
 
 		Balances::set_free_balance(&1, 6);
 		Balances::increase_total_stake_by(6);
-		<StorageOf<Test>>::insert(1, b"foo".to_vec(), b"1".to_vec());
+		set_storage(1, b"foo", b"1");
 
 		assert_ok!(Contract::call(Origin::signed(0), 1, 0.into(), 100_000.into(), Vec::new()));
 
@@ -270,8 +333,8 @@ fn contract_transfer_to_death() {
 			100_000_000 - (2 * 26) - (2 * 135) - (2 * 135),
 		);
 
-		assert!(!<CodeOf<Test>>::exists(1));
-		assert!(!<StorageOf<Test>>::exists(1, b"foo".to_vec()));
+		assert!(!<ContractInfoOf<Test>>::exists(1));
+		assert_eq!(get_storage(1, b"foo"), None);
 		assert_eq!(Balances::free_balance(&1), 0);
 
 		assert_eq!(Balances::free_balance(&9), CONTRACT_SHOULD_TRANSFER_VALUE);
@@ -286,7 +349,7 @@ fn contract_transfer_takes_creation_fee() {
 	let code_transfer = wabt::wat2wasm(CODE_TRANSFER).unwrap();
 
 	with_externalities(&mut ExtBuilder::default().creation_fee(105).build(), || {
-		<CodeOf<Test>>::insert(1, code_transfer.to_vec());
+		set_code(1, &code_transfer);
 
 		Balances::set_free_balance(&0, 100_000_000);
 		Balances::increase_total_stake_by(100_000_000);
@@ -323,7 +386,7 @@ fn contract_transfer_takes_transfer_fee() {
 	let code_transfer = wabt::wat2wasm(CODE_TRANSFER).unwrap();
 
 	with_externalities(&mut ExtBuilder::default().creation_fee(105).transfer_fee(45).build(), || {
-		<CodeOf<Test>>::insert(1, code_transfer.to_vec());
+		set_code(1, &code_transfer);
 
 		Balances::set_free_balance(&0, 100_000_000);
 		Balances::increase_total_stake_by(100_000_000);
@@ -364,7 +427,7 @@ fn contract_transfer_oog() {
 	let code_transfer = wabt::wat2wasm(CODE_TRANSFER).unwrap();
 
 	with_externalities(&mut ExtBuilder::default().build(), || {
-		<CodeOf<Test>>::insert(1, code_transfer.to_vec());
+		set_code(1, &code_transfer);
 
 		Balances::set_free_balance(&0, 100_000_000);
 		Balances::increase_total_stake_by(100_000_000);
@@ -403,7 +466,7 @@ fn contract_transfer_max_depth() {
 	let code_transfer = wabt::wat2wasm(CODE_TRANSFER).unwrap();
 
 	with_externalities(&mut ExtBuilder::default().build(), || {
-		<CodeOf<Test>>::insert(CONTRACT_SHOULD_TRANSFER_TO, code_transfer.to_vec());
+		set_code(CONTRACT_SHOULD_TRANSFER_TO, &code_transfer);
 
 		Balances::set_free_balance(&0, 100_000_000);
 		Balances::increase_total_stake_by(100_000_000);
@@ -436,43 +499,16 @@ fn escaped_bytestring(bytes: &[u8]) -> String {
 	result
 }
 
-/// Create a constructor for the specified code.
-///
-/// When constructor is executed, it will call `ext_return` with code that
-/// specified in `child_bytecode`.
-fn code_ctor(child_bytecode: &[u8]) -> String {
-	format!(
-		r#"
-(module
-	;; ext_return(data_ptr: u32, data_len: u32) -> !
-	(import "env" "ext_return" (func $ext_return (param i32 i32)))
-	(import "env" "memory" (memory 1 1))
-	(func (export "call")
-		(call $ext_return
-			(i32.const 4)
-			(i32.const {code_len})
-		)
-		;; ext_return is diverging, i.e. doesn't return.
-		unreachable
-	)
-	(data (i32.const 4) "{escaped_bytecode}")
-)
-"#,
-		escaped_bytecode = escaped_bytestring(child_bytecode),
-		code_len = child_bytecode.len(),
-	)
-}
-
 /// Returns code that uses `ext_create` runtime call.
 ///
-/// Takes bytecode of the contract that needs to be deployed.
-fn code_create(constructor: &[u8]) -> String {
+/// Takes the hash of the code of the contract that needs to be deployed.
+fn code_create(code_hash: &[u8]) -> String {
 	format!(
 		r#"
 (module
 	;; ext_create(
-	;;     code_ptr: u32,
-	;;     code_len: u32,
+	;;     code_hash_ptr: u32,
+	;;     code_hash_len: u32,
 	;;     gas: u64,
 	;;     value_ptr: u32,
 	;;     value_len: u32,
@@ -484,8 +520,8 @@ fn code_create(constructor: &[u8]) -> String {
 	(func (export "call")
 		(drop
 			(call $ext_create
-				(i32.const 12)   ;; Pointer to `code`
-				(i32.const {code_len}) ;; Length of `code`
+				(i32.const 12)   ;; Pointer to `code_hash`
+				(i32.const {code_hash_len}) ;; Length of `code_hash`
 				(i64.const 0)   ;; How much gas to devote for the execution. 0 = all.
 				(i32.const 4)   ;; Pointer to the buffer with value to transfer
 				(i32.const 8)   ;; Length of the buffer with value to transfer
@@ -497,20 +533,18 @@ fn code_create(constructor: &[u8]) -> String {
 	;; Amount of value to transfer.
 	;; Represented by u64 (8 bytes long) in little endian.
 	(data (i32.const 4) "\03\00\00\00\00\00\00\00")
-	;; Embedded wasm code.
-	(data (i32.const 12) "{escaped_constructor}")
+	;; Hash of the code to deploy.
+	(data (i32.const 12) "{escaped_code_hash}")
 )
 "#,
-		escaped_constructor = escaped_bytestring(constructor),
-		code_len = constructor.len(),
+		escaped_code_hash = escaped_bytestring(code_hash),
+		code_hash_len = code_hash.len(),
 	)
 }
 
 #[test]
 fn contract_create() {
 	let code_transfer = wabt::wat2wasm(CODE_TRANSFER).unwrap();
-	let code_ctor_transfer = wabt::wat2wasm(&code_ctor(&code_transfer)).unwrap();
-	let code_create = wabt::wat2wasm(&code_create(&code_ctor_transfer)).unwrap();
 
 	with_externalities(&mut ExtBuilder::default().build(), || {
 		Balances::set_free_balance(&0, 100_000_000);
@@ -519,27 +553,30 @@ fn contract_create() {
 		Balances::set_free_balance(&9, 30);
 		Balances::increase_total_stake_by(30);
 
-		<CodeOf<Test>>::insert(1, code_create.to_vec());
+		let code_hash = store_code(&code_transfer);
+		let code_create = wabt::wat2wasm(&code_create(code_hash.as_ref())).unwrap();
+		set_code(1, &code_create);
 
 		// When invoked, the contract at address `1` must create a contract with 'transfer' code.
 		assert_ok!(Contract::call(Origin::signed(0), 1, 11.into(), 100_000.into(), Vec::new()));
 
 		let derived_address = <Test as Trait>::DetermineContractAddress::contract_address_for(
-			&code_ctor_transfer,
+			&code_hash,
 			&[],
 			&1,
 		);
 
 		// 11 - value sent with the transaction
-		// 2 * 362 - gas spent by the deployer contract (362) multiplied by gas price (2)
+		// 2 * 51 - gas spent by the deployer contract (51) multiplied by gas price (2)
 		// 2 * 135 - base gas fee for call (top level)
 		// 2 * 175 - base gas fee for create (by contract)
 		// ((21 / 2) * 2) - price per account creation
 		let expected_gas_after_create =
-			100_000_000 - 11 - (2 * 362) - (2 * 135) - (2 * 175) - ((21 / 2) * 2);
+			100_000_000 - 11 - (2 * 51) - (2 * 135) - (2 * 175) - ((21 / 2) * 2);
 		assert_eq!(Balances::free_balance(&0), expected_gas_after_create);
 		assert_eq!(Balances::free_balance(&1), 8);
 		assert_eq!(Balances::free_balance(&derived_address), 3);
+		assert_eq!(get_code(derived_address), Some(code_hash));
 
 		assert_eq!(System::events(), vec![
 			EventRecord {
@@ -581,17 +618,20 @@ fn contract_create() {
 #[test]
 fn top_level_create() {
 	let code_transfer = wabt::wat2wasm(CODE_TRANSFER).unwrap();
-	let code_ctor_transfer = wabt::wat2wasm(&code_ctor(&code_transfer)).unwrap();
 
 	with_externalities(&mut ExtBuilder::default().gas_price(3).build(), || {
+		Balances::set_free_balance(&0, 100_000_000);
+		Balances::increase_total_stake_by(100_000_000);
+
+		assert_ok!(Contract::put_code(Origin::signed(0), 100_000.into(), code_transfer.clone()));
+		let code_hash = <Test as system::Trait>::Hashing::hash(&code_transfer);
+
 		let derived_address = <Test as Trait>::DetermineContractAddress::contract_address_for(
-			&code_ctor_transfer,
+			&code_hash,
 			&[],
 			&0,
 		);
 
-		Balances::set_free_balance(&0, 100_000_000);
-		Balances::increase_total_stake_by(100_000_000);
 		Balances::set_free_balance(&derived_address, 30);
 		Balances::increase_total_stake_by(30);
 
@@ -599,23 +639,28 @@ fn top_level_create() {
 			Origin::signed(0),
 			11.into(),
 			100_000.into(),
-			code_ctor_transfer.clone(),
+			code_hash,
 			Vec::new(),
 		));
 
+		// (3 * code_len) - gas spent for storing the code (1 per byte) multiplied by gas price (3)
 		// 11 - value sent with the transaction
-		// (3 * 129) - gas spent by the init_code.
+		// (3 * 1) - gas spent by the `deploy` function.
 		// (3 * 175) - base gas fee for create (175) (top level) multipled by gas price (3)
 		// ((21 / 3) * 3) - price for contract creation
 		assert_eq!(
 			Balances::free_balance(&0),
-			100_000_000 - 11 - (3 * 129) - (3 * 175) - ((21 / 3) * 3)
+			100_000_000 - (3 * code_transfer.len() as u64) - 11 - (3 * 1) - (3 * 175) - ((21 / 3) * 3)
 		);
 		assert_eq!(Balances::free_balance(&derived_address), 30 + 11);
 
-		assert_eq!(<CodeOf<Test>>::get(&derived_address), code_transfer);
+		assert_eq!(get_code(derived_address), Some(code_hash));
 
 		assert_eq!(System::events(), vec![
+			EventRecord {
+				phase: Phase::ApplyExtrinsic(0),
+				event: MetaEvent::contract(RawEvent::CodeStored(code_hash)),
+			},
 			EventRecord {
 				phase: Phase::ApplyExtrinsic(0),
 				event: MetaEvent::contract(RawEvent::Transfer(0, derived_address, 11)),
@@ -628,20 +673,144 @@ fn top_level_create() {
 	});
 }
 
+#[test]
+fn create_with_unknown_code_hash_fails() {
+	with_externalities(&mut ExtBuilder::default().build(), || {
+		Balances::set_free_balance(&0, 100_000_000);
+		Balances::increase_total_stake_by(100_000_000);
+
+		assert_err!(
+			Contract::create(Origin::signed(0), 0.into(), 100_000.into(), [0x55; 32].into(), Vec::new()),
+			"code is not found"
+		);
+
+		assert!(!<ContractInfoOf<Test>>::exists(1));
+		assert_eq!(Balances::free_balance(&0), 100_000_000 - (2 * 175));
+	});
+}
+
+#[test]
+fn put_code_rejects_invalid_code() {
+	with_externalities(&mut ExtBuilder::default().build(), || {
+		Balances::set_free_balance(&0, 100_000_000);
+		Balances::increase_total_stake_by(100_000_000);
+
+		assert_err!(
+			Contract::put_code(Origin::signed(0), 100_000.into(), vec![0x00, 0x01, 0x02, 0x03]),
+			"code validation failed"
+		);
+
+		assert_eq!(System::events(), vec![]);
+	});
+}
+
+#[test]
+fn put_code_reserves_deposit() {
+	let code_transfer = wabt::wat2wasm(CODE_TRANSFER).unwrap();
+
+	with_externalities(&mut ExtBuilder::default().code_byte_deposit(2).build(), || {
+		Balances::set_free_balance(&0, 100_000_000);
+		Balances::increase_total_stake_by(100_000_000);
+
+		assert_ok!(Contract::put_code(Origin::signed(0), 100_000.into(), code_transfer.clone()));
+		let deposit = 2 * code_transfer.len() as u64;
+		assert_eq!(Balances::reserved_balance(&0), deposit);
+		assert_eq!(
+			Balances::free_balance(&0),
+			100_000_000 - (2 * code_transfer.len() as u64) - deposit,
+		);
+
+		// storing the same code again is only paid for with gas.
+		assert_ok!(Contract::put_code(Origin::signed(0), 100_000.into(), code_transfer.clone()));
+		assert_eq!(Balances::reserved_balance(&0), deposit);
+
+		// the code is not stored if the sender can't afford the deposit after buying the gas.
+		Balances::set_free_balance(&1, 2 * 10_000);
+		Balances::increase_total_stake_by(2 * 10_000);
+		let code_nop = wabt::wat2wasm(CODE_NOP).unwrap();
+		assert_err!(
+			Contract::put_code(Origin::signed(1), 10_000.into(), code_nop.clone()),
+			"not enough free funds for the code deposit"
+		);
+		assert!(!<PristineCode<Test>>::exists(<Test as system::Trait>::Hashing::hash(&code_nop)));
+	});
+}
+
 const CODE_NOP: &'static str = r#"
 (module
+	(func (export "deploy")
+	)
 	(func (export "call")
 		nop
 	)
 )
 "#;
 
+#[test]
+fn code_is_removed_with_the_last_contract_using_it() {
+	let code_nop = wabt::wat2wasm(CODE_NOP).unwrap();
+
+	with_externalities(&mut ExtBuilder::default().existential_deposit(100).code_byte_deposit(2).build(), || {
+		Balances::set_free_balance(&0, 100_000_000);
+		Balances::increase_total_stake_by(100_000_000);
+		assert_ok!(Contract::put_code(Origin::signed(0), 100_000.into(), code_nop.clone()));
+		let code_hash = <Test as system::Trait>::Hashing::hash(&code_nop);
+		let deposit = 2 * code_nop.len() as u64;
+		assert_eq!(Balances::reserved_balance(&0), deposit);
+
+		Balances::set_free_balance(&1, 110);
+		Balances::increase_total_stake_by(110);
+		set_code(1, &code_nop);
+		Balances::set_free_balance(&2, 110);
+		Balances::increase_total_stake_by(110);
+		set_code(2, &code_nop);
+
+		// only the account that stored the code can remove it, and only while no contract uses it.
+		assert_err!(Contract::remove_code(Origin::signed(1), code_hash), "code was stored by another account");
+		assert_err!(Contract::remove_code(Origin::signed(0), code_hash), "code is used by a contract");
+
+		// killing the first contract leaves the code to the second one.
+		assert_ok!(Balances::transfer(Origin::signed(1), 2.into(), 20.into()));
+		assert!(!<ContractInfoOf<Test>>::exists(1));
+		assert!(<CodeStorage<Test>>::exists(code_hash));
+		assert_eq!(Balances::reserved_balance(&0), deposit);
+
+		// the code goes with the last contract, and the deposit is unreserved.
+		assert_ok!(Balances::transfer(Origin::signed(2), 0.into(), 50.into()));
+		assert!(!<ContractInfoOf<Test>>::exists(2));
+		assert!(!<CodeStorage<Test>>::exists(code_hash));
+		assert!(!<PristineCode<Test>>::exists(code_hash));
+		assert_eq!(Balances::reserved_balance(&0), 0);
+	});
+}
+
+#[test]
+fn unused_code_can_be_removed_by_the_account_that_stored_it() {
+	let code_nop = wabt::wat2wasm(CODE_NOP).unwrap();
+
+	with_externalities(&mut ExtBuilder::default().code_byte_deposit(2).build(), || {
+		Balances::set_free_balance(&0, 100_000_000);
+		Balances::increase_total_stake_by(100_000_000);
+		assert_ok!(Contract::put_code(Origin::signed(0), 100_000.into(), code_nop.clone()));
+		let code_hash = <Test as system::Trait>::Hashing::hash(&code_nop);
+		let free_balance = Balances::free_balance(&0);
+
+		assert_ok!(Contract::remove_code(Origin::signed(0), code_hash));
+		assert!(!<CodeStorage<Test>>::exists(code_hash));
+		assert!(!<PristineCode<Test>>::exists(code_hash));
+		assert_eq!(Balances::reserved_balance(&0), 0);
+		assert_eq!(Balances::free_balance(&0), free_balance + 2 * code_nop.len() as u64);
+
+		assert_err!(Contract::remove_code(Origin::signed(0), code_hash), "code is not found");
+	});
+}
+
 #[test]
 fn refunds_unused_gas() {
 	let code_nop = wabt::wat2wasm(CODE_NOP).unwrap();
 
 	with_externalities(&mut ExtBuilder::default().build(), || {
-		<CodeOf<Test>>::insert(1, code_nop.to_vec());
+		set_code(1, &code_nop);
 
 		Balances::set_free_balance(&0, 100_000_000);
 		Balances::increase_total_stake_by(100_000_000);
@@ -655,8 +824,6 @@ fn refunds_unused_gas() {
 #[test]
 fn call_with_zero_value() {
 	with_externalities(&mut ExtBuilder::default().build(), || {
-		<CodeOf<Test>>::insert(1, vec![]);
-
 		Balances::set_free_balance(&0, 100_000_000);
 		Balances::increase_total_stake_by(100_000_000);
 
@@ -674,33 +841,39 @@ fn create_with_zero_endowment() {
 		Balances::set_free_balance(&0, 100_000_000);
 		Balances::increase_total_stake_by(100_000_000);
 
-		assert_ok!(Contract::create(Origin::signed(0), 0.into(), 100_000.into(), code_nop, Vec::new()));
+		let code_hash = store_code(&code_nop);
+
+		assert_ok!(Contract::create(Origin::signed(0), 0.into(), 100_000.into(), code_hash, Vec::new()));
 
 		assert_eq!(
 			Balances::free_balance(&0),
-			// 4 - for the gas spent by the constructor
+			// 2 - for the gas spent by the `deploy` function
 			// 2 * 175 - base gas fee for create (175) multiplied by gas price (2) (top level)
-			100_000_000 - 4 - (2 * 175),
+			100_000_000 - 2 - (2 * 175),
 		);
 	});
 }
 
 #[test]
 fn account_removal_removes_storage() {
+	let code_nop = wabt::wat2wasm(CODE_NOP).unwrap();
+
 	with_externalities(
 		&mut ExtBuilder::default().existential_deposit(100).build(),
 		|| {
-			// Setup two accounts with free balance above than exsistential threshold.
+			// Setup two contracts with free balance above than exsistential threshold.
 			{
 				Balances::set_free_balance(&1, 110);
 				Balances::increase_total_stake_by(110);
-				<StorageOf<Test>>::insert(1, b"foo".to_vec(), b"1".to_vec());
-				<StorageOf<Test>>::insert(1, b"bar".to_vec(), b"2".to_vec());
+				set_code(1, &code_nop);
+				set_storage(1, b"foo", b"1");
+				set_storage(1, b"bar", b"2");
 
 				Balances::set_free_balance(&2, 110);
 				Balances::increase_total_stake_by(110);
-				<StorageOf<Test>>::insert(2, b"hello".to_vec(), b"3".to_vec());
-				<StorageOf<Test>>::insert(2, b"world".to_vec(), b"4".to_vec());
+				set_code(2, &code_nop);
+				set_storage(2, b"hello", b"3");
+				set_storage(2, b"world", b"4");
 			}
 
 			// Transfer funds from account 1 of such amount that after this transfer
@@ -712,17 +885,12 @@ fn account_removal_removes_storage() {
 			// Verify that all entries from account 1 is removed, while
 			// entries from account 2 is in place.
 			{
-				assert_eq!(<StorageOf<Test>>::get(1, b"foo".to_vec()), None);
-				assert_eq!(<StorageOf<Test>>::get(1, b"bar".to_vec()), None);
-
-				assert_eq!(
-					<StorageOf<Test>>::get(2, b"hello".to_vec()),
-					Some(b"3".to_vec())
-				);
-				assert_eq!(
-					<StorageOf<Test>>::get(2, b"world".to_vec()),
-					Some(b"4".to_vec())
-				);
+				assert!(!<ContractInfoOf<Test>>::exists(1));
+				assert_eq!(get_storage(1, b"foo"), None);
+				assert_eq!(get_storage(1, b"bar"), None);
+
+				assert_eq!(get_storage(2, b"hello"), Some(b"3".to_vec()));
+				assert_eq!(get_storage(2, b"world"), Some(b"4".to_vec()));
 			}
 		},
 	);
@@ -741,7 +909,7 @@ const CODE_UNREACHABLE: &'static str = r#"
 fn top_level_call_refunds_even_if_fails() {
 	let code_unreachable = wabt::wat2wasm(CODE_UNREACHABLE).unwrap();
 	with_externalities(&mut ExtBuilder::default().gas_price(4).build(), || {
-		<CodeOf<Test>>::insert(1, code_unreachable.to_vec());
+		set_code(1, &code_unreachable);
 
 		Balances::set_free_balance(&0, 100_000_000);
 		Balances::increase_total_stake_by(100_000_000);
@@ -757,6 +925,109 @@ fn top_level_call_refunds_even_if_fails() {
 	});
 }
 
+const CODE_DEPOSIT_EVENT: &'static str = r#"
+(module
+	;; ext_deposit_event(data_ptr: u32, data_len: u32)
+	(import "env" "ext_deposit_event" (func $ext_deposit_event (param i32 i32)))
+	(import "env" "memory" (memory 1 1))
+	(func (export "call")
+		(call $ext_deposit_event
+			(i32.const 8) ;; Pointer to the start of the event data
+			(i32.const 4) ;; Length of the event data
+		)
+	)
+	(data (i32.const 8) "\01\02\03\04")
+)
+"#;
+
+#[test]
+fn deposit_event() {
+	let code_deposit_event = wabt::wat2wasm(CODE_DEPOSIT_EVENT).unwrap();
+	with_externalities(&mut ExtBuilder::default().build(), || {
+		set_code(1, &code_deposit_event);
+
+		Balances::set_free_balance(&0, 100_000_000);
+		Balances::increase_total_stake_by(100_000_000);
+
+		assert_ok!(Contract::call(Origin::signed(0), 1, 0.into(), 100_000.into(), Vec::new()));
+
+		assert_eq!(System::events(), vec![
+			EventRecord {
+				phase: Phase::ApplyExtrinsic(0),
+				event: MetaEvent::contract(RawEvent::Contract(1, vec![1, 2, 3, 4])),
+			},
+		]);
+	});
+}
+
+#[test]
+fn bare_call_does_not_commit() {
+	let code_deposit_event = wabt::wat2wasm(CODE_DEPOSIT_EVENT).unwrap();
+	with_externalities(&mut ExtBuilder::default().build(), || {
+		set_code(1, &code_deposit_event);
+
+		Balances::set_free_balance(&0, 100_000_000);
+		Balances::increase_total_stake_by(100_000_000);
+
+		let outcome = Contract::bare_call(0, 1, 0, 100_000, Vec::new());
+
+		assert_eq!(outcome.result, Ok(Vec::new()));
+		// 135 - base call fee
+		// 4 - instructions of the `call` function
+		// 1 + 4 * 1 - event base cost and the per-byte cost of the event data
+		// 4 - reading the event data from the sandbox memory
+		assert_eq!(outcome.gas_consumed, 135 + 4 + (1 + 4 * 1) + 4);
+		assert_eq!(outcome.events, vec![RawEvent::Contract(1, vec![1, 2, 3, 4])]);
+
+		// Neither the gas was paid for nor the event was deposited.
+		assert_eq!(Balances::free_balance(&0), 100_000_000);
+		assert_eq!(System::events(), vec![]);
+	});
+}
+
+const CODE_DEPOSIT_EVENT_AND_TRAP: &'static str = r#"
+(module
+	(import "env" "ext_deposit_event" (func $ext_deposit_event (param i32 i32)))
+	(import "env" "memory" (memory 1 1))
+	(func (export "call")
+		(call $ext_deposit_event
+			(i32.const 8) ;; Pointer to the start of the event data
+			(i32.const 4) ;; Length of the event data
+		)
+		unreachable
+	)
+	(data (i32.const 8) "\01\02\03\04")
+)
+"#;
+
+#[test]
+fn events_of_reverted_nested_call_are_dropped() {
+	let code_transfer = wabt::wat2wasm(CODE_TRANSFER).unwrap();
+	let code_deposit_event_and_trap = wabt::wat2wasm(CODE_DEPOSIT_EVENT_AND_TRAP).unwrap();
+
+	with_externalities(&mut ExtBuilder::default().build(), || {
+		// The contract at 1 calls 9, which deposits an event and then traps.
+		set_code(1, &code_transfer);
+		set_code(9, &code_deposit_event_and_trap);
+
+		Balances::set_free_balance(&0, 100_000_000);
+		Balances::increase_total_stake_by(100_000_000);
+		Balances::set_free_balance(&1, 11);
+		Balances::increase_total_stake_by(11);
+
+		assert_ok!(Contract::call(Origin::signed(0), 1, 3.into(), 100_000.into(), Vec::new()));
+
+		// Neither the transfer to 9 nor the event deposited by 9 should be recorded.
+		assert_eq!(Balances::free_balance(&9), 0);
+		assert_eq!(System::events(), vec![
+			EventRecord {
+				phase: Phase::ApplyExtrinsic(0),
+				event: MetaEvent::contract(RawEvent::Transfer(0, 1, 3)),
+			},
+		]);
+	});
+}
+
 const CODE_LOOP: &'static str = r#"
 (module
 	(func (export "call")
@@ -773,7 +1044,7 @@ fn block_gas_limit() {
 	with_externalities(
 		&mut ExtBuilder::default().block_gas_limit(100_000).build(),
 		|| {
-			<CodeOf<Test>>::insert(1, code_loop.to_vec());
+			set_code(1, &code_loop);
 
 			Balances::set_free_balance(&0, 100_000_000);
 			Balances::increase_total_stake_by(100_000_000);
@@ -860,7 +1131,7 @@ fn input_data() {
 	with_externalities(
 		&mut ExtBuilder::default().build(),
 		|| {
-			<CodeOf<Test>>::insert(1, code_input_data.to_vec());
+			set_code(1, &code_input_data);
 
 			Balances::set_free_balance(&0, 100_000_000);
 			Balances::increase_total_stake_by(100_000_000);
@@ -871,3 +1142,114 @@ fn input_data() {
 		},
 	);
 }
+
+#[test]
+fn rent_is_charged_per_byte_per_block() {
+	let code_nop = wabt::wat2wasm(CODE_NOP).unwrap();
+
+	with_externalities(&mut ExtBuilder::default().existential_deposit(5).rent_byte_price(2).build(), || {
+		System::set_block_number(1);
+		Balances::set_free_balance(&1, 1_000);
+		Balances::increase_total_stake_by(1_000);
+		set_code(1, &code_nop);
+		// 3 bytes of the key and 1 byte of the value.
+		set_storage(1, b"foo", b"1");
+
+		// The rent is not due within the block the contract was created at.
+		assert_ok!(Contract::claim_surcharge(Origin::signed(0), 1));
+		assert_eq!(Balances::free_balance(&1), 1_000);
+
+		System::set_block_number(11);
+		assert_ok!(Contract::claim_surcharge(Origin::signed(0), 1));
+
+		// 10 blocks * 4 bytes * 2 per byte.
+		assert_eq!(Balances::free_balance(&1), 1_000 - 10 * 4 * 2);
+		assert_eq!(Balances::total_issuance(), 1_000 - 10 * 4 * 2);
+		assert_eq!(get_storage(1, b"foo"), Some(b"1".to_vec()));
+
+		// The rent is paid up to the current block, so claiming again doesn't charge anything.
+		assert_ok!(Contract::claim_surcharge(Origin::signed(0), 1));
+		assert_eq!(Balances::free_balance(&1), 1_000 - 10 * 4 * 2);
+	});
+}
+
+#[test]
+fn contract_that_cannot_pay_rent_is_evicted_and_can_be_restored() {
+	let code_nop = wabt::wat2wasm(CODE_NOP).unwrap();
+
+	with_externalities(&mut ExtBuilder::default().existential_deposit(5).rent_byte_price(1).build(), || {
+		System::set_block_number(1);
+		Balances::set_free_balance(&0, 1_000);
+		Balances::increase_total_stake_by(1_000);
+		Balances::set_free_balance(&1, 30);
+		Balances::increase_total_stake_by(30);
+		set_code(1, &code_nop);
+		set_storage(1, b"foo", b"1");
+		let code_hash = get_code(1).unwrap();
+
+		// 10 blocks * 4 bytes is more than the contract can afford.
+		System::set_block_number(11);
+		assert_ok!(Contract::claim_surcharge(Origin::signed(0), 1));
+
+		// The contract keeps the existential deposit and the claimer gets the rest as a reward,
+		// since it's less than the surcharge reward.
+		assert_eq!(Balances::free_balance(&1), 5);
+		assert_eq!(Balances::free_balance(&0), 1_000 + 25);
+		assert_eq!(get_code(1), None);
+		assert_eq!(get_storage(1, b"foo"), None);
+		assert_matches!(<ContractInfoOf<Test>>::get(1), Some(RawContractInfo::Tombstone(_)));
+		assert_eq!(System::events(), vec![
+			EventRecord {
+				phase: Phase::ApplyExtrinsic(0),
+				event: MetaEvent::contract(RawEvent::Evicted(1)),
+			},
+		]);
+
+		// Evicted contracts can't be called or claimed.
+		assert_err!(
+			Contract::call(Origin::signed(0), 1, 0.into(), 100_000.into(), Vec::new()),
+			"contract has been evicted"
+		);
+		assert_err!(
+			Contract::claim_surcharge(Origin::signed(0), 1),
+			"destination is not a live contract"
+		);
+
+		// The code is removed along with the last contract using it, so it has to be stored
+		// again before the contract can be restored.
+		assert!(!<PristineCode<Test>>::exists(code_hash));
+		assert_err!(
+			Contract::restore_to(Origin::signed(0), 1, code_hash, 100.into(), vec![(b"foo".to_vec(), b"1".to_vec())]),
+			"code is not found"
+		);
+		assert_eq!(store_code(&code_nop), code_hash);
+
+		// The storage must match the one at the moment of eviction. A failed restoration
+		// leaves nothing behind but the gas spent.
+		let account_counter = Contract::account_counter();
+		assert_err!(
+			Contract::restore_to(Origin::signed(0), 1, code_hash, 100.into(), vec![(b"foo".to_vec(), b"2".to_vec())]),
+			"storage and code hash don't match the tombstone"
+		);
+		assert_eq!(Contract::account_counter(), account_counter);
+		assert_eq!(Balances::free_balance(&0), 1_000 + 25 - 2 * 4);
+
+		// Writing the storage is paid for per byte.
+		assert_err!(
+			Contract::restore_to(Origin::signed(0), 1, code_hash, 3.into(), vec![(b"foo".to_vec(), b"1".to_vec())]),
+			"there is not enough gas for restoring the storage"
+		);
+		assert_eq!(Balances::free_balance(&0), 1_000 + 25 - 2 * 4 - 2 * 3);
+
+		assert_ok!(Contract::restore_to(Origin::signed(0), 1, code_hash, 100.into(), vec![(b"foo".to_vec(), b"1".to_vec())]));
+		assert_eq!(Contract::account_counter(), account_counter + 1);
+		assert_eq!(Balances::free_balance(&0), 1_000 + 25 - 2 * 4 - 2 * 3 - 2 * 4);
+
+		assert_eq!(get_code(1), Some(code_hash));
+		assert_eq!(get_storage(1, b"foo"), Some(b"1".to_vec()));
+		assert_matches!(
+			<ContractInfoOf<Test>>::get(1),
+			Some(RawContractInfo::Alive(ref info)) if info.storage_size == 4 && info.deduct_block == 11
+		);
+	});
+}
@@ -0,0 +1,156 @@
+// Copyright 2018 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate. If not, see <http://www.gnu.org/licenses/>.
+
+//! A module that implements instrumented code cache.
+//!
+//! - In order to run contract code we need to instrument it with gas counters and stack height limiter.
+//! - The instrumentation depends on the schedule, so the instrumented code should be updated
+//!   whenever the schedule changes.
+//!
+//! On `save`, the original code is stored in `PristineCode` and the instrumented code is stored in
+//! `CodeStorage` along with the version of the schedule it was instrumented with. The account that
+//! stores the code has a deposit proportional to the size of the code reserved. On `load`,
+//! the code is re-instrumented and the cache is updated if the schedule version has been bumped
+//! since then.
+//!
+//! `CodeRefCount` counts the alive contracts using the code. The code is removed, and the deposit
+//! unreserved, along with the last of them. Code no contract uses can be removed by the account
+//! that stored it.
+
+use gas::GasMeter;
+use rstd::prelude::*;
+use runtime_primitives::traits::{As, CheckedMul, Hash, Zero};
+use runtime_support::StorageMap;
+use super::prepare;
+use super::runtime::Env;
+use super::PrefabWasmModule;
+use {balances, CodeDeposit, CodeHash, CodeRefCount, CodeStorage, Module, PristineCode, Schedule, Trait};
+
+/// Put code in the storage. The hash of code is used as a key and is returned
+/// as a result of this function.
+///
+/// This function instruments the given code and caches it in the storage. The caller
+/// pays for the storing of the code by gas proportional to the size of the code. On top of
+/// that, `CodeByteDeposit` per byte of the code is reserved from `origin` for as long as the
+/// code is stored. Storing code that is already stored is only paid for with gas.
+///
+/// The code stays stored until `remove` is called or the last contract using it is gone.
+pub fn save<T: Trait>(
+	origin: &T::AccountId,
+	original_code: Vec<u8>,
+	gas_meter: &mut GasMeter<T>,
+	schedule: &Schedule<T::Gas>,
+) -> Result<CodeHash<T>, &'static str> {
+	let price = schedule
+		.put_code_per_byte_cost
+		.checked_mul(&<T::Gas as As<u64>>::sa(original_code.len() as u64))
+		.ok_or("overflow occured when calculating put_code price")?;
+	if gas_meter.charge(price).is_out_of_gas() {
+		return Err("there is not enough gas for storing the code");
+	}
+
+	let code_hash = T::Hashing::hash(&original_code);
+	if <PristineCode<T>>::exists(&code_hash) {
+		return Ok(code_hash);
+	}
+
+	let prefab_module = prepare::prepare_contract::<Env, _>(&original_code, schedule)
+		.map_err(|_| "code validation failed")?;
+
+	let deposit = <Module<T>>::code_byte_deposit()
+		.checked_mul(&<T::Balance as As<u64>>::sa(original_code.len() as u64))
+		.ok_or("overflow occured when calculating put_code deposit")?;
+	if !deposit.is_zero() {
+		<balances::Module<T>>::reserve(origin, deposit)
+			.map_err(|_| "not enough free funds for the code deposit")?;
+	}
+
+	<CodeStorage<T>>::insert(code_hash, prefab_module);
+	<PristineCode<T>>::insert(code_hash, original_code);
+	<CodeDeposit<T>>::insert(code_hash, (origin.clone(), deposit));
+
+	Ok(code_hash)
+}
+
+/// Remove the code with the given hash on behalf of `origin`, unreserving its deposit.
+///
+/// Only the account that stored the code can remove it, and only while no contract uses it.
+pub fn remove<T: Trait>(origin: &T::AccountId, code_hash: &CodeHash<T>) -> Result<(), &'static str> {
+	match <CodeDeposit<T>>::get(code_hash) {
+		Some((ref depositor, _)) if depositor == origin => {},
+		Some(_) => return Err("code was stored by another account"),
+		None => return Err("code is not found"),
+	}
+	if <CodeRefCount<T>>::get(code_hash) > 0 {
+		return Err("code is used by a contract");
+	}
+
+	purge::<T>(code_hash);
+	Ok(())
+}
+
+/// Note that a contract using the code with the given hash has come alive.
+pub fn increment_refcount<T: Trait>(code_hash: &CodeHash<T>) {
+	<CodeRefCount<T>>::mutate(code_hash, |count| *count = count.saturating_add(1));
+}
+
+/// Note that a contract using the code with the given hash is gone, i.e. it has been killed or
+/// evicted.
+///
+/// The code is removed, and its deposit unreserved, along with the last contract using it.
+pub fn decrement_refcount<T: Trait>(code_hash: &CodeHash<T>) {
+	let count = <CodeRefCount<T>>::get(code_hash).saturating_sub(1);
+	if count > 0 {
+		<CodeRefCount<T>>::insert(code_hash, count);
+	} else {
+		purge::<T>(code_hash);
+	}
+}
+
+fn purge<T: Trait>(code_hash: &CodeHash<T>) {
+	<CodeRefCount<T>>::remove(code_hash);
+	<CodeStorage<T>>::remove(code_hash);
+	<PristineCode<T>>::remove(code_hash);
+	if let Some((depositor, deposit)) = <CodeDeposit<T>>::take(code_hash) {
+		<balances::Module<T>>::unreserve(&depositor, deposit);
+	}
+}
+
+/// Load code with the given code hash.
+///
+/// If the module was instrumented with a lower version of schedule than
+/// the current one given as an argument, then this function will perform
+/// re-instrumentation and update the cache in the storage.
+pub fn load<T: Trait>(
+	code_hash: &CodeHash<T>,
+	schedule: &Schedule<T::Gas>,
+) -> Result<PrefabWasmModule, &'static str> {
+	let mut prefab_module =
+		<CodeStorage<T>>::get(code_hash).ok_or_else(|| "code is not found")?;
+
+	if prefab_module.schedule_version < schedule.version {
+		// The current schedule version is greater than the version of the one cached
+		// in the storage.
+		//
+		// We need to re-instrument the code with the latest schedule here.
+		let original_code =
+			<PristineCode<T>>::get(code_hash).ok_or_else(|| "pristine code is not found")?;
+		prefab_module = prepare::prepare_contract::<Env, _>(&original_code, schedule)
+			.map_err(|_| "code re-instrumentation failed")?;
+		<CodeStorage<T>>::insert(code_hash, &prefab_module);
+	}
+	Ok(prefab_module)
+}
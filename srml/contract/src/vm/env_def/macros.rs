@@ -122,35 +122,53 @@ macro_rules! define_func {
 
 /// Define a function set that can be imported by executing wasm code.
 ///
+/// The macro defines a unit struct with the given name that implements `ImportSatisfyCheck`
+/// and exposes `init` which builds the `HostFunctionSet` for the given `Ext`.
+///
 /// **NB**: Be advised that all functions defined by this macro
 /// will panic if called with unexpected arguments.
 ///
 /// It's up to the user of this macro to check signatures of wasm code to be executed
 /// and reject the code if any imported function has a mismached signature.
 macro_rules! define_env {
-	( $init_name:ident , < E: $ext_ty:tt > ,
+	( $env_name:ident , < E: $ext_ty:tt > ,
 		$( $name:ident ( $ctx:ident $( , $names:ident : $params:ty )* )
 			$( -> $returns:ty )* => $body:tt , )*
 	) => {
-		pub(crate) fn $init_name<E: Ext>() -> $crate::vm::env_def::HostFunctionSet<E> {
-			let mut env = $crate::vm::env_def::HostFunctionSet::new();
-
-			$(
-				env.funcs.insert(
-					stringify!( $name ).into(),
-					$crate::vm::env_def::HostFunction::new(
-						gen_signature!( ( $( $params ),* ) $( -> $returns )* ),
-						{
-							define_func!(
-								< E: $ext_ty > $name ( $ctx $(, $names : $params )* ) $( -> $returns )* => $body
-							);
-							$name::<E>
-						},
-					),
-				);
-			)*
-
-			env
+		pub(crate) struct $env_name;
+
+		impl $crate::vm::env_def::ImportSatisfyCheck for $env_name {
+			fn can_satisfy(name: &[u8], func_type: &$crate::parity_wasm::elements::FunctionType) -> bool {
+				$(
+					if name == stringify!( $name ).as_bytes() {
+						let signature = gen_signature!( ( $( $params ),* ) $( -> $returns )* );
+						return &signature == func_type;
+					}
+				)*
+				false
+			}
+		}
+
+		impl $env_name {
+			pub(crate) fn init<E: Ext>() -> $crate::vm::env_def::HostFunctionSet<E> {
+				let mut env = $crate::vm::env_def::HostFunctionSet::new();
+
+				$(
+					env.funcs.insert(
+						stringify!( $name ).into(),
+						$crate::vm::env_def::HostFunction::new(
+							{
+								define_func!(
+									< E: $ext_ty > $name ( $ctx $(, $names : $params )* ) $( -> $returns )* => $body
+								);
+								$name::<E>
+							},
+						),
+					);
+				)*
+
+				env
+			}
 		}
 	};
 }
@@ -161,6 +179,7 @@ mod tests {
 	use parity_wasm::elements::ValueType;
 	use runtime_primitives::traits::{As, Zero};
 	use sandbox::{self, ReturnValue, TypedValue};
+	use vm::env_def::ImportSatisfyCheck;
 	use vm::tests::MockExt;
 	use vm::{Ext, Runtime};
 	use Trait;
@@ -267,7 +286,7 @@ mod tests {
 
 	#[test]
 	fn macro_define_env() {
-		define_env!(init_env, <E: Ext>,
+		define_env!(Env, <E: Ext>,
 			ext_gas( _ctx, amount: u32 ) => {
 				let amount = <<E::T as Trait>::Gas as As<u32>>::sa(amount);
 				if !amount.is_zero() {
@@ -278,7 +297,11 @@ mod tests {
 			},
 		);
 
-		let env = init_env::<MockExt>();
+		let env = Env::init::<MockExt>();
 		assert!(env.funcs.get(&b"ext_gas"[..]).is_some());
+
+		assert!(Env::can_satisfy(b"ext_gas", &FunctionType::new(vec![ValueType::I32], None)));
+		assert!(!Env::can_satisfy(b"ext_gas", &FunctionType::new(vec![ValueType::I64], None)));
+		assert!(!Env::can_satisfy(b"ext_unknown", &FunctionType::new(vec![], None)));
 	}
 }
@@ -83,16 +83,14 @@ impl<E: Ext> HostFunctionSet<E> {
 pub(crate) struct HostFunction<E: Ext> {
 	pub(crate) f: fn(&mut Runtime<E>, &[sandbox::TypedValue])
 		-> Result<sandbox::ReturnValue, sandbox::HostError>,
-	func_type: FunctionType,
 }
 impl<E: Ext> HostFunction<E> {
 	/// Create a new instance of a host function.
 	pub fn new(
-		func_type: FunctionType,
 		f: fn(&mut Runtime<E>, &[sandbox::TypedValue])
 			-> Result<sandbox::ReturnValue, sandbox::HostError>,
 	) -> Self {
-		HostFunction { func_type, f }
+		HostFunction { f }
 	}
 
 	/// Returns a function pointer of this host function.
@@ -102,9 +100,14 @@ impl<E: Ext> HostFunction<E> {
 		-> Result<sandbox::ReturnValue, sandbox::HostError> {
 		self.f
 	}
+}
 
-	/// Check if the this function could be invoked with the given function signature.
-	pub fn func_type_matches(&self, func_type: &FunctionType) -> bool {
-		&self.func_type == func_type
-	}
+/// Checks whether the environment is able to satisfy imports of a wasm module.
+///
+/// This doesn't require an instance of `Ext`, so it can be used for validating
+/// the code before any execution takes place (e.g. when the code is put into the storage).
+pub(crate) trait ImportSatisfyCheck {
+	/// Returns `true` if a host function with the given `name` and signature `func_type`
+	/// is defined in the environment.
+	fn can_satisfy(name: &[u8], func_type: &FunctionType) -> bool;
 }
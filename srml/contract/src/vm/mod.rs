@@ -20,19 +20,36 @@
 use exec::CreateReceipt;
 use gas::GasMeter;
 use rstd::prelude::*;
-use {Trait, Schedule};
-use {balances, sandbox, system};
+use {CodeHash, Trait, Schedule};
+use {balances, sandbox, system, timestamp};
 
 type BalanceOf<T> = <T as balances::Trait>::Balance;
 type AccountIdOf<T> = <T as system::Trait>::AccountId;
+type MomentOf<T> = <T as timestamp::Trait>::Moment;
+type SeedOf<T> = <T as system::Trait>::Hash;
+type BlockNumberOf<T> = <T as system::Trait>::BlockNumber;
 
 mod prepare;
 #[macro_use]
 mod env_def;
 mod runtime;
-
-use self::prepare::{prepare_contract, PreparedContract};
-use self::runtime::{to_execution_result, Runtime};
+pub mod code_cache;
+
+use self::runtime::{to_execution_result, Env, Runtime};
+
+/// A prepared wasm module ready for execution.
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Clone, Encode, Decode)]
+pub struct PrefabWasmModule {
+	/// Version of the schedule with which the code was instrumented.
+	schedule_version: u32,
+	/// Initial memory size of a contract's sandbox, in pages.
+	initial: u32,
+	/// The maximum memory size of a contract's sandbox, in pages.
+	maximum: u32,
+	/// Code instrumented with the schedule of `schedule_version`.
+	code: Vec<u8>,
+}
 
 /// An interface that provides an access to the external environment in which the
 /// smart-contract is executed.
@@ -50,11 +67,11 @@ pub trait Ext {
 
 	/// Create a new account for a contract.
 	///
-	/// The newly created account will be associated with the `code`. `value` specifies the amount of value
-	/// transfered from this to the newly created account.
+	/// The newly created account will be associated with the code identified by `code_hash`.
+	/// `value` specifies the amount of value transfered from this to the newly created account.
 	fn create(
 		&mut self,
-		code: &[u8],
+		code_hash: &CodeHash<Self::T>,
 		value: BalanceOf<Self::T>,
 		gas_meter: &mut GasMeter<Self::T>,
 		data: &[u8],
@@ -69,6 +86,34 @@ pub trait Ext {
 		data: &[u8],
 		output_data: &mut Vec<u8>,
 	) -> Result<(), ()>;
+
+	/// Returns a reference to the account id of the caller.
+	fn caller(&self) -> &AccountIdOf<Self::T>;
+
+	/// Returns a reference to the account id of the current contract.
+	fn address(&self) -> &AccountIdOf<Self::T>;
+
+	/// Returns the balance of the current contract.
+	///
+	/// The `value_transferred` is already added.
+	fn balance(&self) -> BalanceOf<Self::T>;
+
+	/// Returns the value transfered along with this call or as endowment.
+	fn value_transferred(&self) -> BalanceOf<Self::T>;
+
+	/// Returns the timestamp of the current block.
+	fn now(&self) -> MomentOf<Self::T>;
+
+	/// Returns the random seed for the current block.
+	fn random_seed(&self) -> SeedOf<Self::T>;
+
+	/// Returns the current block number.
+	fn block_number(&self) -> BlockNumberOf<Self::T>;
+
+	/// Deposit an event with the given data on behalf of the current contract.
+	///
+	/// The event is dropped if the current call or any of its callers is reverted.
+	fn deposit_event(&mut self, data: Vec<u8>);
 }
 
 /// Error that can occur while preparing or executing wasm smart-contract.
@@ -111,21 +156,23 @@ pub enum Error {
 	Memory,
 }
 
-/// Execute the given code as a contract.
+/// Execute the given prepared module as a contract.
+///
+/// `func_name` is the name of the exported function to invoke, i.e. `deploy` for
+/// a freshly created contract or `call` for a message sent to an existing one.
 pub fn execute<'a, E: Ext>(
-	code: &[u8],
+	module: &PrefabWasmModule,
+	func_name: &[u8],
 	input_data: &[u8],
 	output_data: &mut Vec<u8>,
 	ext: &'a mut E,
 	schedule: &Schedule<<E::T as Trait>::Gas>,
 	gas_meter: &mut GasMeter<E::T>,
 ) -> Result<(), Error> {
-	let env = runtime::init_env();
+	let env = Env::init::<E>();
 
-	let PreparedContract {
-		instrumented_code,
-		memory,
-	} = prepare_contract(code, &schedule, &env)?;
+	let memory = sandbox::Memory::new(module.initial, Some(module.maximum))
+		.map_err(|_| Error::Memory)?;
 
 	let mut imports = sandbox::EnvironmentDefinitionBuilder::new();
 	for (func_name, ext_func) in &env.funcs {
@@ -136,11 +183,11 @@ pub fn execute<'a, E: Ext>(
 	let mut runtime = Runtime::new(ext, input_data, output_data, &schedule, memory, gas_meter);
 
 	// Instantiate the instance from the instrumented module code.
-	match sandbox::Instance::new(&instrumented_code, &imports, &mut runtime) {
+	match sandbox::Instance::new(&module.code, &imports, &mut runtime) {
 		// No errors or traps were generated on instantiation! That
 		// means we can now invoke the contract entrypoint.
 		Ok(mut instance) => {
-			let err = instance.invoke(b"call", &[], &mut runtime).err();
+			let err = instance.invoke(func_name, &[], &mut runtime).err();
 			to_execution_result(runtime, err)
 		}
 		// `start` function trapped. Treat it in the same manner as an execution error.
@@ -154,14 +201,16 @@ pub fn execute<'a, E: Ext>(
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use codec::Encode;
 	use gas::GasMeter;
 	use std::collections::HashMap;
+	use substrate_primitives::H256;
 	use tests::Test;
 	use wabt;
 
 	#[derive(Debug, PartialEq, Eq)]
 	struct CreateEntry {
-		code: Vec<u8>,
+		code_hash: H256,
 		endowment: u64,
 		data: Vec<u8>,
 		gas_left: u64,
@@ -179,6 +228,14 @@ mod tests {
 		creates: Vec<CreateEntry>,
 		transfers: Vec<TransferEntry>,
 		next_account_id: u64,
+		caller: u64,
+		address: u64,
+		balance: u64,
+		value_transferred: u64,
+		now: u64,
+		random_seed: H256,
+		block_number: u64,
+		events: Vec<Vec<u8>>,
 	}
 	impl Ext for MockExt {
 		type T = Test;
@@ -191,13 +248,13 @@ mod tests {
 		}
 		fn create(
 			&mut self,
-			code: &[u8],
+			code_hash: &H256,
 			endowment: u64,
 			gas_meter: &mut GasMeter<Test>,
 			data: &[u8],
 		) -> Result<CreateReceipt<Test>, ()> {
 			self.creates.push(CreateEntry {
-				code: code.to_vec(),
+				code_hash: code_hash.clone(),
 				endowment,
 				data: data.to_vec(),
 				gas_left: gas_meter.gas_left(),
@@ -225,6 +282,43 @@ mod tests {
 			// TODO: Add tests for different call outcomes.
 			Ok(())
 		}
+		fn caller(&self) -> &u64 {
+			&self.caller
+		}
+		fn address(&self) -> &u64 {
+			&self.address
+		}
+		fn balance(&self) -> u64 {
+			self.balance
+		}
+		fn value_transferred(&self) -> u64 {
+			self.value_transferred
+		}
+		fn now(&self) -> u64 {
+			self.now
+		}
+		fn random_seed(&self) -> H256 {
+			self.random_seed
+		}
+		fn block_number(&self) -> u64 {
+			self.block_number
+		}
+		fn deposit_event(&mut self, data: Vec<u8>) {
+			self.events.push(data);
+		}
+	}
+
+	/// Prepare the given wasm code and invoke its `call` export.
+	fn execute<E: Ext>(
+		code: &[u8],
+		input_data: &[u8],
+		output_data: &mut Vec<u8>,
+		ext: &mut E,
+		schedule: &Schedule<<E::T as Trait>::Gas>,
+		gas_meter: &mut GasMeter<E::T>,
+	) -> Result<(), Error> {
+		let module = prepare::prepare_contract::<Env, _>(code, schedule)?;
+		super::execute(&module, b"call", input_data, output_data, ext, schedule, gas_meter)
 	}
 
 	const CODE_TRANSFER: &str = r#"
@@ -294,8 +388,8 @@ mod tests {
 	const CODE_CREATE: &str = r#"
 (module
 	;; ext_create(
-	;;     code_ptr: u32,
-	;;     code_len: u32,
+	;;     code_hash_ptr: u32,
+	;;     code_hash_len: u32,
 	;;     gas: u64,
 	;;     value_ptr: u32,
 	;;     value_len: u32,
@@ -307,12 +401,12 @@ mod tests {
 	(func (export "call")
 		(drop
 			(call $ext_create
-				(i32.const 12)   ;; Pointer to `code`
-				(i32.const 8)    ;; Length of `code`
+				(i32.const 16)   ;; Pointer to `code_hash`
+				(i32.const 32)   ;; Length of `code_hash`
 				(i64.const 0)    ;; How much gas to devote for the execution. 0 = all.
 				(i32.const 4)    ;; Pointer to the buffer with value to transfer
 				(i32.const 8)    ;; Length of the buffer with value to transfer
-				(i32.const 20)   ;; Pointer to input data buffer address
+				(i32.const 12)   ;; Pointer to input data buffer address
 				(i32.const 4)    ;; Length of input data buffer
 			)
 		)
@@ -320,10 +414,10 @@ mod tests {
 	;; Amount of value to transfer.
 	;; Represented by u64 (8 bytes long) in little endian.
 	(data (i32.const 4) "\03\00\00\00\00\00\00\00")
-	;; Embedded wasm code.
-	(data (i32.const 12) "\00\61\73\6d\01\00\00\00")
 	;; Input data to pass to the contract being created.
-	(data (i32.const 20) "\01\02\03\04")
+	(data (i32.const 12) "\01\02\03\04")
+	;; Hash of code.
+	(data (i32.const 16) "\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11")
 )
 "#;
 
@@ -344,12 +438,12 @@ mod tests {
 		assert_eq!(
 			&mock_ext.creates,
 			&[CreateEntry {
-				code: vec![0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00],
+				code_hash: [0x11; 32].into(),
 				endowment: 3,
 				data: vec![
 					1, 2, 3, 4,
 				],
-				gas_left: 49970,
+				gas_left: 49946,
 			}]
 		);
 	}
@@ -533,4 +627,126 @@ mod tests {
 			[0x22; 32].to_vec(),
 		);
 	}
+
+	/// Returns code that invokes the given environment query function and then
+	/// returns the contents of the scratch buffer.
+	fn code_env_query(func_name: &str) -> String {
+		format!(
+			r#"
+(module
+	(import "env" "{func_name}" (func $query))
+	(import "env" "ext_scratch_size" (func $ext_scratch_size (result i32)))
+	(import "env" "ext_scratch_copy" (func $ext_scratch_copy (param i32 i32 i32)))
+	(import "env" "ext_return" (func $ext_return (param i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(func (export "call")
+		(local $buf_size i32)
+
+		;; Put the queried value into the scratch buffer.
+		(call $query)
+
+		;; Find out the size of the scratch buffer
+		(set_local $buf_size
+			(call $ext_scratch_size)
+		)
+
+		;; Copy scratch buffer into this contract memory.
+		(call $ext_scratch_copy
+			(i32.const 8)		;; The pointer where to store the scratch buffer contents.
+			(i32.const 0)		;; Offset from the start of the scratch buffer.
+			(get_local $buf_size)	;; Count of bytes to copy.
+		)
+
+		;; Return the contents of the buffer
+		(call $ext_return
+			(i32.const 8)
+			(get_local $buf_size)
+		)
+
+		;; env:ext_return doesn't return, so this is effectively unreachable.
+		(unreachable)
+	)
+)
+"#,
+			func_name = func_name,
+		)
+	}
+
+	/// Execute the code produced by `code_env_query` and return the output data.
+	fn env_query(func_name: &str, mock_ext: &mut MockExt) -> Vec<u8> {
+		let code = wabt::wat2wasm(&code_env_query(func_name)).unwrap();
+
+		let mut return_buf = Vec::new();
+		execute(
+			&code,
+			&[],
+			&mut return_buf,
+			mock_ext,
+			&Schedule::default(),
+			&mut GasMeter::with_limit(50_000, 1),
+		).unwrap();
+		return_buf
+	}
+
+	#[test]
+	fn env_query_functions_put_values_into_scratch_buf() {
+		let mut mock_ext = MockExt {
+			caller: 42,
+			address: 69,
+			balance: 228,
+			value_transferred: 1337,
+			now: 1111,
+			random_seed: [0x33; 32].into(),
+			block_number: 121,
+			..Default::default()
+		};
+
+		assert_eq!(env_query("ext_caller", &mut mock_ext), 42u64.encode());
+		assert_eq!(env_query("ext_address", &mut mock_ext), 69u64.encode());
+		assert_eq!(env_query("ext_balance", &mut mock_ext), 228u64.encode());
+		assert_eq!(env_query("ext_value_transferred", &mut mock_ext), 1337u64.encode());
+		assert_eq!(env_query("ext_now", &mut mock_ext), 1111u64.encode());
+		assert_eq!(env_query("ext_random_seed", &mut mock_ext), H256::from([0x33; 32]).encode());
+		assert_eq!(env_query("ext_block_number", &mut mock_ext), 121u64.encode());
+	}
+
+	const CODE_DEPOSIT_EVENT: &str = r#"
+(module
+	;; ext_deposit_event(data_ptr: u32, data_len: u32)
+	(import "env" "ext_deposit_event" (func $ext_deposit_event (param i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(func (export "call")
+		(call $ext_deposit_event
+			(i32.const 8) ;; Pointer to the start of the event data
+			(i32.const 4) ;; Length of the event data
+		)
+	)
+	(data (i32.const 8) "\01\02\03\04")
+)
+"#;
+
+	#[test]
+	fn deposit_event() {
+		let code = wabt::wat2wasm(CODE_DEPOSIT_EVENT).unwrap();
+
+		let mut mock_ext = MockExt::default();
+		let mut gas_meter = GasMeter::with_limit(50_000, 1);
+		execute(
+			&code,
+			&[],
+			&mut Vec::new(),
+			&mut mock_ext,
+			&Schedule::default(),
+			&mut gas_meter,
+		).unwrap();
+
+		assert_eq!(mock_ext.events, vec![vec![1, 2, 3, 4]]);
+
+		// 4 - instructions of the `call` function
+		// 1 + 4 * 1 - event base cost and the per-byte cost of the event data
+		// 4 - reading the event data from the sandbox memory
+		assert_eq!(gas_meter.gas_left(), 50_000 - 4 - (1 + 4 * 1) - 4);
+	}
 }
@@ -17,15 +17,14 @@
 //! Module that takes care of loading, checking and preprocessing of a
 //! wasm module before execution.
 
-use super::env_def::HostFunctionSet;
-use super::{Error, Ext};
+use super::env_def::ImportSatisfyCheck;
+use super::{Error, PrefabWasmModule};
 use rstd::prelude::*;
 use parity_wasm::elements::{self, External, MemoryType, Type};
 use pwasm_utils;
 use pwasm_utils::rules;
 use runtime_primitives::traits::As;
-use sandbox;
-use {Trait, Schedule};
+use Schedule;
 
 struct ContractModule<'a, Gas: 'a> {
 	// An `Option` is used here for loaning (`take()`-ing) the module.
@@ -102,7 +101,7 @@ impl<'a, Gas: 'a + As<u32> + Clone> ContractModule<'a, Gas> {
 	/// - checks any imported function against defined host functions set, incl.
 	///   their signatures.
 	/// - if there is a memory import, returns it's descriptor
-	fn scan_imports<E: Ext>(&self, env: &HostFunctionSet<E>) -> Result<Option<&MemoryType>, Error> {
+	fn scan_imports<C: ImportSatisfyCheck>(&self) -> Result<Option<&MemoryType>, Error> {
 		let module = self
 			.module
 			.as_ref()
@@ -136,11 +135,7 @@ impl<'a, Gas: 'a + As<u32> + Clone> ContractModule<'a, Gas> {
 				.get(*type_idx as usize)
 				.ok_or_else(|| Error::Instantiate)?;
 
-			let ext_func = env
-				.funcs
-				.get(import.field().as_bytes())
-				.ok_or_else(|| Error::Instantiate)?;
-			if !ext_func.func_type_matches(func_ty) {
+			if !C::can_satisfy(import.field().as_bytes(), func_ty) {
 				return Err(Error::Instantiate);
 			}
 		}
@@ -156,11 +151,6 @@ impl<'a, Gas: 'a + As<u32> + Clone> ContractModule<'a, Gas> {
 	}
 }
 
-pub(super) struct PreparedContract {
-	pub instrumented_code: Vec<u8>,
-	pub memory: sandbox::Memory,
-}
-
 /// Loads the given module given in `original_code`, performs some checks on it and
 /// does some preprocessing.
 ///
@@ -171,17 +161,16 @@ pub(super) struct PreparedContract {
 /// - all imported functions from the external environment matches defined by `env` module,
 ///
 /// The preprocessing includes injecting code for gas metering and metering the height of stack.
-pub(super) fn prepare_contract<E: Ext>(
+pub(super) fn prepare_contract<C: ImportSatisfyCheck, Gas: As<u32> + Clone>(
 	original_code: &[u8],
-	schedule: &Schedule<<E::T as Trait>::Gas>,
-	env: &HostFunctionSet<E>,
-) -> Result<PreparedContract, Error> {
+	schedule: &Schedule<Gas>,
+) -> Result<PrefabWasmModule, Error> {
 	let mut contract_module = ContractModule::new(original_code, schedule)?;
 	contract_module.ensure_no_internal_memory()?;
 	contract_module.inject_gas_metering()?;
 	contract_module.inject_stack_height_metering()?;
 
-	let memory = if let Some(memory_type) = contract_module.scan_imports(env)? {
+	let (initial, maximum) = if let Some(memory_type) = contract_module.scan_imports::<C>()? {
 		// Inspect the module to extract the initial and maximum page count.
 		let limits = memory_type.limits();
 		match (limits.initial(), limits.maximum()) {
@@ -199,39 +188,32 @@ pub(super) fn prepare_contract<E: Ext>(
 				// to configured maximum.
 				return Err(Error::Memory);
 			}
-			(initial, maximum) => sandbox::Memory::new(initial, maximum),
+			(initial, Some(maximum)) => (initial, maximum),
 		}
 	} else {
 		// If none memory imported then just crate an empty placeholder.
 		// Any access to it will lead to out of bounds trap.
-		sandbox::Memory::new(0, Some(0))
+		(0, 0)
 	};
-	let memory = memory.map_err(|_| Error::Memory)?;
 
-	Ok(PreparedContract {
-		instrumented_code: contract_module.into_wasm_code()?,
-		memory,
+	Ok(PrefabWasmModule {
+		schedule_version: schedule.version,
+		initial,
+		maximum,
+		code: contract_module.into_wasm_code()?,
 	})
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use std::fmt;
-	use vm::tests::MockExt;
+	use vm::runtime::Env;
 	use wabt;
 
-	impl fmt::Debug for PreparedContract {
-		fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-			write!(f, "PreparedContract {{ .. }}")
-		}
-	}
-
-	fn parse_and_prepare_wat(wat: &str) -> Result<PreparedContract, Error> {
+	fn parse_and_prepare_wat(wat: &str) -> Result<PrefabWasmModule, Error> {
 		let wasm = wabt::Wat2Wasm::new().validate(false).convert(wat).unwrap();
 		let schedule = Schedule::<u64>::default();
-		let env = ::vm::runtime::init_env();
-		prepare_contract::<MockExt>(wasm.as_ref(), &schedule, &env)
+		prepare_contract::<Env, _>(wasm.as_ref(), &schedule)
 	}
 
 	#[test]
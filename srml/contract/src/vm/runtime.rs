@@ -16,11 +16,11 @@
 
 //! Environment definition of the wasm smart-contract runtime.
 
-use super::{BalanceOf, Schedule, CreateReceipt, Error, Ext};
+use super::{BalanceOf, CodeHash, Schedule, CreateReceipt, Error, Ext};
 use rstd::prelude::*;
 use codec::{Decode, Encode};
 use gas::{GasMeter, GasMeterResult};
-use runtime_primitives::traits::{As, CheckedMul};
+use runtime_primitives::traits::{As, CheckedAdd, CheckedMul};
 use sandbox;
 use system;
 use Trait;
@@ -159,11 +159,9 @@ fn write_sandbox_memory<T: Trait>(
 // * AFTER MAKING A CHANGE MAKE SURE TO UPDATE COMPLEXITY.MD *
 // ***********************************************************
 
-// TODO: ext_balance, ext_address, ext_callvalue, etc.
-
-// Define a function `fn init_env<E: Ext>() -> HostFunctionSet<E>` that returns
+// Define a struct `Env` with a function `fn init<E: Ext>() -> HostFunctionSet<E>` that returns
 // a function set which can be imported by an executed contract.
-define_env!(init_env, <E: Ext>,
+define_env!(Env, <E: Ext>,
 
 	// Account for used gas. Traps if gas used is greater than gas limit.
 	//
@@ -275,34 +273,39 @@ define_env!(init_env, <E: Ext>,
 		}
 	},
 
-	// Create a contract with code returned by the specified initializer code.
+	// Create a contract with the code identified by the specified code hash.
 	//
-	// This function creates an account and executes initializer code. After the execution,
-	// the returned buffer is saved as the code of the created account.
+	// This function creates an account and executes the `deploy` function of the code.
+	// After the execution, the code hash is associated with the created account.
 	//
 	// Returns 0 on the successful contract creation and puts the address
 	// of the created contract into the scratch buffer.
 	// Otherwise, returns 1 and clears the scratch buffer.
 	//
-	// - init_code_ptr: a pointer to the buffer that contains the initializer code.
-	// - init_code_len: length of the initializer code buffer.
-	// - gas: how much gas to devote to the execution of the initializer code.
+	// - code_hash_ptr: a pointer to the buffer that contains the hash of the code.
+	//   Should be decodable as a `T::Hash`. Traps otherwise.
+	// - code_hash_len: length of the code hash buffer.
+	// - gas: how much gas to devote to the execution of the `deploy` function.
 	// - value_ptr: a pointer to the buffer with value, how much value to send.
 	//   Should be decodable as a `T::Balance`. Traps otherwise.
 	// - value_len: length of the value buffer.
-	// - input_data_ptr: a pointer to a buffer to be used as input data to the initializer code.
+	// - input_data_ptr: a pointer to a buffer to be used as input data to the `deploy` function.
 	// - input_data_len: length of the input data buffer.
 	ext_create(
 		ctx,
-		init_code_ptr: u32,
-		init_code_len: u32,
+		code_hash_ptr: u32,
+		code_hash_len: u32,
 		gas: u64,
 		value_ptr: u32,
 		value_len: u32,
 		input_data_ptr: u32,
 		input_data_len: u32
 	) -> u32 => {
-		let init_code = read_sandbox_memory(ctx, init_code_ptr, init_code_len)?;
+		let code_hash = {
+			let code_hash_buf = read_sandbox_memory(ctx, code_hash_ptr, code_hash_len)?;
+			CodeHash::<<E as Ext>::T>::decode(&mut &code_hash_buf[..])
+				.ok_or_else(|| sandbox::HostError)?
+		};
 		let value = {
 			let value_buf = read_sandbox_memory(ctx, value_ptr, value_len)?;
 			BalanceOf::<<E as Ext>::T>::decode(&mut &value_buf[..])
@@ -321,7 +324,7 @@ define_env!(init_env, <E: Ext>,
 		let ext = &mut ctx.ext;
 		let create_outcome = ctx.gas_meter.with_nested(nested_gas_limit, |nested_meter| {
 			match nested_meter {
-				Some(nested_meter) => ext.create(&init_code, value, nested_meter, &input_data),
+				Some(nested_meter) => ext.create(&code_hash, value, nested_meter, &input_data),
 				// there is not enough gas to allocate for the nested call.
 				None => Err(()),
 			}
@@ -423,4 +426,85 @@ define_env!(init_env, <E: Ext>,
 
 		Ok(())
 	},
+
+	// Stores the address of the caller into the scratch buffer.
+	//
+	// If this is a top-level call (i.e. initiated by an extrinsic) the origin address of the extrinsic
+	// will be returned. Otherwise, if this call is initiated by another contract then the address
+	// of the contract will be returned.
+	ext_caller(ctx) => {
+		charge_gas(ctx.gas_meter, ctx.schedule.env_query_cost)?;
+		ctx.scratch_buf = ctx.ext.caller().encode();
+		Ok(())
+	},
+
+	// Stores the address of the current contract into the scratch buffer.
+	ext_address(ctx) => {
+		charge_gas(ctx.gas_meter, ctx.schedule.env_query_cost)?;
+		ctx.scratch_buf = ctx.ext.address().encode();
+		Ok(())
+	},
+
+	// Stores the balance of the current account into the scratch buffer.
+	//
+	// The value is stored to the scratch buffer encoded as `T::Balance`.
+	ext_balance(ctx) => {
+		charge_gas(ctx.gas_meter, ctx.schedule.env_query_cost)?;
+		ctx.scratch_buf = ctx.ext.balance().encode();
+		Ok(())
+	},
+
+	// Stores the value transferred along with this call or as endowment into the scratch buffer.
+	//
+	// The value is stored to the scratch buffer encoded as `T::Balance`.
+	ext_value_transferred(ctx) => {
+		charge_gas(ctx.gas_meter, ctx.schedule.env_query_cost)?;
+		ctx.scratch_buf = ctx.ext.value_transferred().encode();
+		Ok(())
+	},
+
+	// Load the latest block timestamp into the scratch buffer.
+	//
+	// The value is stored to the scratch buffer encoded as `T::Moment`.
+	ext_now(ctx) => {
+		charge_gas(ctx.gas_meter, ctx.schedule.env_query_cost)?;
+		ctx.scratch_buf = ctx.ext.now().encode();
+		Ok(())
+	},
+
+	// Load the latest block random seed into the scratch buffer.
+	//
+	// The value is stored to the scratch buffer encoded as `T::Hash`.
+	ext_random_seed(ctx) => {
+		charge_gas(ctx.gas_meter, ctx.schedule.env_query_cost)?;
+		ctx.scratch_buf = ctx.ext.random_seed().encode();
+		Ok(())
+	},
+
+	// Stores the current block number into the scratch buffer.
+	//
+	// The value is stored to the scratch buffer encoded as `T::BlockNumber`.
+	ext_block_number(ctx) => {
+		charge_gas(ctx.gas_meter, ctx.schedule.env_query_cost)?;
+		ctx.scratch_buf = ctx.ext.block_number().encode();
+		Ok(())
+	},
+
+	// Deposit a contract event with the data buffer.
+	//
+	// - data_ptr: a pointer to a buffer with the data of the event.
+	// - data_len: length of the event data buffer.
+	ext_deposit_event(ctx, data_ptr: u32, data_len: u32) => {
+		let data_len_in_gas = <<E::T as Trait>::Gas as As<u64>>::sa(data_len as u64);
+		let price = (ctx.schedule.event_data_per_byte_cost)
+			.checked_mul(&data_len_in_gas)
+			.and_then(|data_cost| data_cost.checked_add(&ctx.schedule.event_base_cost))
+			.ok_or(sandbox::HostError)?;
+		charge_gas(ctx.gas_meter, price)?;
+
+		let event_data = read_sandbox_memory(ctx, data_ptr, data_len)?;
+		ctx.ext.deposit_event(event_data);
+
+		Ok(())
+	},
 );
@@ -19,8 +19,12 @@
 //! This manages the GRANDPA authority set ready for the native code.
 //! These authorities are only for GRANDPA finality, not for consensus overall.
 //!
-//! In the future, it will also handle misbehavior reports, and on-chain
-//! finality notifications.
+//! Equivocations of GRANDPA voters can be reported with an unsigned extrinsic
+//! carrying an `EquivocationProof`. The proof is checked against the authority
+//! set it was made in and the offender is passed on to `Trait::OnEquivocation`,
+//! e.g. the staking module for slashing.
+//!
+//! In the future, it will also handle on-chain finality notifications.
 //!
 //! For full integration with GRANDPA, the `GrandpaApi` should be implemented.
 //! The necessary items are re-exported via the `fg_primitives` crate.
@@ -48,21 +52,20 @@ extern crate srml_system as system;
 extern crate srml_session as session;
 extern crate substrate_primitives;
 
-#[cfg(test)]
 extern crate sr_io as runtime_io;
 
 // re-export since this is necessary for `impl_apis` in runtime.
 pub extern crate substrate_finality_grandpa_primitives as fg_primitives;
 
 use rstd::prelude::*;
-use fg_primitives::ScheduledChange;
+use fg_primitives::{ScheduledChange, SignedVote, localized_vote_payload};
 use runtime_support::Parameter;
 use runtime_support::dispatch::Result;
-use runtime_support::storage::StorageValue;
+use runtime_support::storage::{StorageValue, StorageMap};
 use runtime_support::storage::unhashed::StorageVec;
-use primitives::traits::{CurrentHeight, Convert};
-use substrate_primitives::AuthorityId;
-use system::ensure_signed;
+use primitives::traits::{CurrentHeight, Convert, Lazy, Verify};
+use substrate_primitives::{AuthorityId, H512};
+use system::ensure_inherent;
 
 #[cfg(feature = "std")]
 use primitives::traits::MaybeSerializeDebug;
@@ -73,6 +76,9 @@ use primitives::traits::MaybeSerializeDebugButNotDeserialize;
 mod mock;
 mod tests;
 
+/// The number of authority sets before the current one for which equivocations can be reported.
+const MAX_PAST_SETS: u64 = 16;
+
 struct AuthorityStorageVec<S: codec::Codec + Default>(rstd::marker::PhantomData<S>);
 impl<S: codec::Codec + Default> StorageVec for AuthorityStorageVec<S> {
 	type Item = (S, u64);
@@ -123,6 +129,42 @@ impl<N, SessionKey> GrandpaChangeSignal<N> for RawLog<N, SessionKey>
 	}
 }
 
+/// A proof of equivocation, projected from module trait types.
+pub type EquivocationProof<T> = fg_primitives::EquivocationProof<
+	<T as system::Trait>::Hash,
+	<T as system::Trait>::BlockNumber,
+	<T as Trait>::SessionKey,
+	<T as Trait>::Signature,
+>;
+
+/// Handler for a proven GRANDPA equivocation.
+pub trait OnEquivocation<SessionKey> {
+	/// The voter with the given session key equivocated.
+	fn on_equivocation(who: &SessionKey);
+}
+
+impl<SessionKey> OnEquivocation<SessionKey> for () {
+	fn on_equivocation(_who: &SessionKey) {}
+}
+
+/// An ed25519 signature of a GRANDPA voter, verifiable against its `AuthorityId`.
+#[derive(Eq, PartialEq, Clone, Default, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct AuthoritySignature(pub H512);
+
+impl Verify for AuthoritySignature {
+	type Signer = AuthorityId;
+	fn verify<L: Lazy<[u8]>>(&self, mut msg: L, signer: &Self::Signer) -> bool {
+		runtime_io::ed25519_verify((self.0).as_fixed_bytes(), msg.get(), &signer.0)
+	}
+}
+
+impl From<H512> for AuthoritySignature {
+	fn from(h: H512) -> AuthoritySignature {
+		AuthoritySignature(h)
+	}
+}
+
 pub trait Trait: system::Trait {
 	/// Type for all log entries of this module.
 	type Log: From<Log<Self>> + Into<system::DigestItemOf<Self>>;
@@ -135,6 +177,12 @@ pub trait Trait: system::Trait {
 	#[cfg(feature = "std")]
 	type SessionKey: Parameter + Default + MaybeSerializeDebug;
 
+	/// The signature type of votes, verifiable against the session key of the voter.
+	type Signature: Parameter + Verify<Signer=Self::SessionKey>;
+
+	/// Handler for voters found equivocating.
+	type OnEquivocation: OnEquivocation<Self::SessionKey>;
+
 	/// The event type of this module.
 	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
 }
@@ -155,6 +203,8 @@ decl_event!(
 	pub enum Event<T> where <T as Trait>::SessionKey {
 		/// New authority set has been applied.
 		NewAuthorities(Vec<(SessionKey, u64)>),
+		/// A voter was proven to have equivocated in the given authority set and round.
+		Equivocation(SessionKey, u64, u64),
 	}
 );
 
@@ -162,6 +212,12 @@ decl_storage! {
 	trait Store for Module<T: Trait> as GrandpaFinality {
 		// Pending change: (signalled at, scheduled change).
 		PendingChange get(pending_change): Option<StoredPendingChange<T::BlockNumber, T::SessionKey>>;
+		/// The id of the current authority set. Incremented with every applied change.
+		CurrentSetId get(current_set_id): u64;
+		/// The authorities of the most recent past authority sets, by set id.
+		PastAuthorities get(past_authorities): map u64 => Vec<(T::SessionKey, u64)>;
+		/// The voters reported for equivocating in an authority set, along with the round.
+		Reported get(reported): map u64 => Vec<(u64, T::SessionKey)>;
 	}
 	add_extra_genesis {
 		config(authorities): Vec<(T::SessionKey, u64)>;
@@ -188,10 +244,18 @@ decl_module! {
 	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
 		fn deposit_event() = default;
 
-		/// Report some misbehaviour.
-		fn report_misbehavior(origin, _report: Vec<u8>) -> Result {
-			ensure_signed(origin)?;
-			// TODO: https://github.com/paritytech/substrate/issues/1112
+		/// Report an equivocation of a voter. The proof is checked against the authorities of
+		/// the set it was made in, so this is submitted as an unsigned extrinsic.
+		fn report_equivocation(origin, proof: EquivocationProof<T>) -> Result {
+			ensure_inherent(origin)?;
+			Self::check_equivocation_proof(&proof)?;
+
+			let mut reported = Self::reported(proof.set_id);
+			reported.push((proof.round, proof.identity.clone()));
+			<Reported<T>>::insert(proof.set_id, reported);
+
+			T::OnEquivocation::on_equivocation(&proof.identity);
+			Self::deposit_event(RawEvent::Equivocation(proof.identity, proof.set_id, proof.round));
 			Ok(())
 		}
 
@@ -208,6 +272,7 @@ decl_module! {
 					Self::deposit_event(
 						RawEvent::NewAuthorities(pending_change.next_authorities.clone())
 					);
+					Self::note_new_set();
 					<AuthorityStorageVec<T::SessionKey>>::set_items(pending_change.next_authorities);
 					<PendingChange<T>>::kill();
 				}
@@ -248,6 +313,65 @@ impl<T: Trait> Module<T> {
 		}
 	}
 
+	/// Get the authorities of the given set, if it is the current one or one of the
+	/// `MAX_PAST_SETS` before.
+	pub fn set_authorities(set_id: u64) -> Option<Vec<(T::SessionKey, u64)>> {
+		let current = Self::current_set_id();
+		if set_id == current {
+			Some(Self::grandpa_authorities())
+		} else if set_id < current && current - set_id <= MAX_PAST_SETS {
+			Some(Self::past_authorities(set_id))
+		} else {
+			None
+		}
+	}
+
+	/// Check that an equivocation proof is valid and has not been reported before.
+	pub fn check_equivocation_proof(proof: &EquivocationProof<T>) -> Result {
+		let authorities = Self::set_authorities(proof.set_id).ok_or("unknown authority set")?;
+		ensure!(authorities.iter().any(|&(ref a, _)| a == &proof.identity), "not a voter of the set");
+		ensure!(
+			!Self::reported(proof.set_id).iter().any(|&(r, ref a)| r == proof.round && a == &proof.identity),
+			"equivocation already reported"
+		);
+		ensure!(
+			(&proof.first.target_hash, &proof.first.target_number)
+				!= (&proof.second.target_hash, &proof.second.target_number),
+			"votes are not conflicting"
+		);
+		ensure!(
+			Self::check_vote(proof, &proof.first) && Self::check_vote(proof, &proof.second),
+			"bad vote signature"
+		);
+		Ok(())
+	}
+
+	fn check_vote(
+		proof: &EquivocationProof<T>,
+		vote: &SignedVote<T::Hash, T::BlockNumber, T::Signature>,
+	) -> bool {
+		let payload = localized_vote_payload(
+			proof.stage,
+			&vote.target_hash,
+			&vote.target_number,
+			proof.round,
+			proof.set_id,
+		);
+		vote.signature.verify(&payload[..], &proof.identity)
+	}
+
+	/// Archive the current authority set before a new one is applied, and forget the
+	/// oldest one kept.
+	fn note_new_set() {
+		let set_id = Self::current_set_id();
+		<PastAuthorities<T>>::insert(set_id, Self::grandpa_authorities());
+		if set_id >= MAX_PAST_SETS {
+			<PastAuthorities<T>>::remove(set_id - MAX_PAST_SETS);
+			<Reported<T>>::remove(set_id - MAX_PAST_SETS);
+		}
+		<CurrentSetId<T>>::put(set_id + 1);
+	}
+
 	/// Deposit one of this module's logs.
 	fn deposit_log(log: Log<T>) {
 		<system::Module<T>>::deposit_log(<T as Trait>::Log::from(log).into());
@@ -20,6 +20,7 @@
 
 use primitives::{BuildStorage, testing::{Digest, DigestItem, Header}};
 use primitives::generic::DigestItem as GenDigestItem;
+use primitives::traits::{Lazy, Verify};
 use runtime_io;
 use substrate_primitives::{H256, Blake2Hasher};
 use parity_codec::Encode;
@@ -35,12 +36,25 @@ impl From<RawLog<u64, u64>> for DigestItem {
 	}
 }
 
+/// A signature of a voter on a message, valid if both match.
+#[derive(Clone, PartialEq, Eq, Debug, Decode, Encode)]
+pub struct TestSignature(pub u64, pub Vec<u8>);
+
+impl Verify for TestSignature {
+	type Signer = u64;
+	fn verify<L: Lazy<[u8]>>(&self, mut msg: L, signer: &u64) -> bool {
+		*signer == self.0 && msg.get() == &self.1[..]
+	}
+}
+
 // Workaround for https://github.com/rust-lang/rust/issues/26925 . Remove when sorted.
 #[derive(Clone, PartialEq, Eq, Debug, Decode, Encode)]
 pub struct Test;
 impl Trait for Test {
 	type Log = DigestItem;
 	type SessionKey = u64;
+	type Signature = TestSignature;
+	type OnEquivocation = ();
 	type Event = TestEvent;
 }
 impl system::Trait for Test {
@@ -21,9 +21,11 @@
 use primitives::{testing, traits::OnFinalise};
 use primitives::traits::Header;
 use runtime_io::with_externalities;
-use mock::{Grandpa, System, new_test_ext};
+use substrate_primitives::H256;
+use fg_primitives::{SignedVote, VoteStage, localized_vote_payload};
+use mock::{Grandpa, System, Origin, Test, TestSignature, new_test_ext};
 use system::{EventRecord, Phase};
-use {RawLog, RawEvent};
+use {RawLog, RawEvent, EquivocationProof};
 
 #[test]
 fn authorities_change_logged() {
@@ -106,3 +108,88 @@ fn cannot_schedule_change_when_one_pending() {
 		let _header = System::finalise();
 	});
 }
+
+fn signed_vote(voter: u64, target: (H256, u64), round: u64, set_id: u64) -> SignedVote<H256, u64, TestSignature> {
+	let payload = localized_vote_payload(VoteStage::Prevote, &target.0, &target.1, round, set_id);
+	SignedVote {
+		target_hash: target.0,
+		target_number: target.1,
+		signature: TestSignature(voter, payload),
+	}
+}
+
+fn equivocation_proof(voter: u64, round: u64, set_id: u64) -> EquivocationProof<Test> {
+	fg_primitives::EquivocationProof {
+		set_id,
+		round,
+		stage: VoteStage::Prevote,
+		identity: voter,
+		first: signed_vote(voter, (H256::from_low_u64_be(1), 1), round, set_id),
+		second: signed_vote(voter, (H256::from_low_u64_be(2), 1), round, set_id),
+	}
+}
+
+#[test]
+fn equivocation_is_reported_once() {
+	with_externalities(&mut new_test_ext(vec![(1, 1), (2, 1), (3, 1)]), || {
+		System::initialise(&1, &Default::default(), &Default::default());
+		assert_noop!(
+			Grandpa::report_equivocation(Origin::signed(1), equivocation_proof(2, 5, 0)),
+			"bad origin: expected to be an inherent origin"
+		);
+		assert_ok!(Grandpa::report_equivocation(Origin::INHERENT, equivocation_proof(2, 5, 0)));
+		assert_eq!(Grandpa::reported(0), vec![(5, 2)]);
+		assert_noop!(
+			Grandpa::report_equivocation(Origin::INHERENT, equivocation_proof(2, 5, 0)),
+			"equivocation already reported"
+		);
+		assert_eq!(System::events(), vec![
+			EventRecord {
+				phase: Phase::ApplyExtrinsic(0),
+				event: RawEvent::Equivocation(2, 0, 5).into(),
+			},
+		]);
+	});
+}
+
+#[test]
+fn invalid_equivocation_proofs_are_rejected() {
+	with_externalities(&mut new_test_ext(vec![(1, 1), (2, 1), (3, 1)]), || {
+		System::initialise(&1, &Default::default(), &Default::default());
+		assert_noop!(
+			Grandpa::report_equivocation(Origin::INHERENT, equivocation_proof(4, 5, 0)),
+			"not a voter of the set"
+		);
+		assert_noop!(
+			Grandpa::report_equivocation(Origin::INHERENT, equivocation_proof(2, 5, 1)),
+			"unknown authority set"
+		);
+
+		let mut proof = equivocation_proof(2, 5, 0);
+		proof.second = proof.first.clone();
+		assert_noop!(Grandpa::report_equivocation(Origin::INHERENT, proof), "votes are not conflicting");
+
+		let mut proof = equivocation_proof(2, 5, 0);
+		proof.round = 6;
+		assert_noop!(Grandpa::report_equivocation(Origin::INHERENT, proof), "bad vote signature");
+	});
+}
+
+#[test]
+fn equivocations_in_past_sets_can_be_reported() {
+	with_externalities(&mut new_test_ext(vec![(1, 1), (2, 1), (3, 1)]), || {
+		System::initialise(&1, &Default::default(), &Default::default());
+		Grandpa::schedule_change(vec![(4, 1), (5, 1), (6, 1)], 0).unwrap();
+		Grandpa::on_finalise(1);
+		let _header = System::finalise();
+
+		assert_eq!(Grandpa::current_set_id(), 1);
+		assert_eq!(Grandpa::set_authorities(0), Some(vec![(1, 1), (2, 1), (3, 1)]));
+		assert_noop!(
+			Grandpa::report_equivocation(Origin::INHERENT, equivocation_proof(2, 5, 1)),
+			"not a voter of the set"
+		);
+		assert_ok!(Grandpa::report_equivocation(Origin::INHERENT, equivocation_proof(2, 5, 0)));
+		assert_ok!(Grandpa::report_equivocation(Origin::INHERENT, equivocation_proof(5, 5, 1)));
+	});
+}
@@ -0,0 +1,477 @@
+// Copyright 2018 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Multisig: Accounts controlled by M-of-N signatories.
+//!
+//! The account ID of a multisig account is derived from its sorted signatories and its
+//! threshold, so it doesn't need to be created before use. A signatory proposes a call to be
+//! dispatched from the multisig account, reserving a deposit. The other signatories approve it by
+//! its hash and once `threshold` signatories did, the call is dispatched with the multisig account
+//! as signed origin and the deposit is returned. The proposer may cancel the call before that.
+//!
+//! The deposit is a base amount plus an amount per byte of the encoded call, as the call is kept
+//! in storage until it is dispatched or cancelled.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate sr_std as rstd;
+
+#[macro_use]
+extern crate srml_support as runtime_support;
+
+#[cfg(test)]
+extern crate sr_io as runtime_io;
+
+#[macro_use]
+extern crate parity_codec_derive;
+
+extern crate parity_codec as codec;
+#[cfg(test)]
+extern crate substrate_primitives;
+extern crate sr_primitives as runtime_primitives;
+extern crate srml_system as system;
+extern crate srml_balances as balances;
+
+use rstd::prelude::*;
+use rstd::{marker::PhantomData, result};
+use codec::{Compact, Encode};
+use runtime_support::{StorageValue, StorageMap};
+use runtime_support::dispatch::{Result, Dispatchable, Parameter};
+use runtime_primitives::traits::{As, Hash};
+use system::ensure_signed;
+
+/// A function that returns the account ID of the multisig account of given sorted signatories
+/// and threshold.
+pub trait MultisigAddressFor<AccountId: Sized> {
+	fn multisig_address_for(signatories: &[AccountId], threshold: u32) -> AccountId;
+}
+
+/// Simple multisig address determination function that determines an address based on the
+/// sorted signatories and the threshold.
+///
+/// Formula: `blake2_256(b"multisig" + (signatories, threshold))`
+pub struct SimpleAddressDeterminator<T: Trait>(PhantomData<T>);
+
+impl<T: Trait> MultisigAddressFor<T::AccountId> for SimpleAddressDeterminator<T>
+where
+	T::AccountId: From<T::Hash>
+{
+	fn multisig_address_for(signatories: &[T::AccountId], threshold: u32) -> T::AccountId {
+		let mut buf = b"multisig".to_vec();
+		(signatories, threshold).encode_to(&mut buf);
+
+		T::Hashing::hash(&buf[..]).into()
+	}
+}
+
+pub trait Trait: balances::Trait {
+	/// The call that can be dispatched from a multisig account.
+	type Proposal: Parameter + Dispatchable<Origin=Self::Origin>;
+
+	/// A function that determines the account ID of a multisig account.
+	type DetermineMultisigAddress: MultisigAddressFor<Self::AccountId>;
+
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+}
+
+/// A call pending approval of the signatories of a multisig account.
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub struct Multisig<AccountId, Balance, Proposal> {
+	/// The signatory that proposed the call and reserved the deposit.
+	depositor: AccountId,
+	/// The reserved deposit.
+	deposit: Balance,
+	/// The signatories that approved the call so far.
+	approvals: Vec<AccountId>,
+	/// The call itself.
+	proposal: Proposal,
+}
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		fn deposit_event() = default;
+
+		/// Propose a call to be dispatched from the multisig account of the sender and
+		/// `other_signatories` once `threshold` of them approved it. This counts as the approval
+		/// of the sender, who has to reserve the deposit until the call is dispatched or cancelled.
+		///
+		/// With a threshold of one, the call is dispatched immediately.
+		fn propose(
+			origin,
+			threshold: Compact<u32>,
+			other_signatories: Vec<T::AccountId>,
+			proposal: Box<T::Proposal>
+		) -> Result {
+			let who = ensure_signed(origin)?;
+			let threshold = threshold.into();
+			let signatories = Self::signatories(&who, other_signatories, threshold)?;
+			let id = T::DetermineMultisigAddress::multisig_address_for(&signatories, threshold);
+			let proposal_hash = T::Hashing::hash_of(&proposal);
+
+			if threshold < 2 {
+				Self::dispatch(id, proposal_hash, *proposal);
+				return Ok(());
+			}
+
+			ensure!(!<Multisigs<T>>::exists(&(id.clone(), proposal_hash)), "call already proposed");
+
+			let deposit = Self::deposit_for(&proposal);
+			<balances::Module<T>>::reserve(&who, deposit)
+				.map_err(|_| "balance too low to reserve the deposit")?;
+
+			<Multisigs<T>>::insert(&(id.clone(), proposal_hash), Multisig {
+				depositor: who.clone(),
+				deposit,
+				approvals: vec![who.clone()],
+				proposal: *proposal,
+			});
+
+			Self::deposit_event(RawEvent::NewMultisig(who, id, proposal_hash));
+			Ok(())
+		}
+
+		/// Approve the call with hash `proposal_hash` of the multisig account of the sender and
+		/// `other_signatories`. Once `threshold` signatories approved it, the call is dispatched.
+		fn approve(
+			origin,
+			threshold: Compact<u32>,
+			other_signatories: Vec<T::AccountId>,
+			proposal_hash: T::Hash
+		) -> Result {
+			let who = ensure_signed(origin)?;
+			let threshold = threshold.into();
+			let signatories = Self::signatories(&who, other_signatories, threshold)?;
+			let id = T::DetermineMultisigAddress::multisig_address_for(&signatories, threshold);
+			let key = (id.clone(), proposal_hash);
+
+			let mut multisig = Self::multisigs(&key).ok_or("no such call pending")?;
+			ensure!(!multisig.approvals.contains(&who), "duplicate approval");
+			multisig.approvals.push(who.clone());
+
+			Self::deposit_event(RawEvent::Approval(who, id.clone(), proposal_hash));
+
+			if multisig.approvals.len() as u32 >= threshold {
+				<Multisigs<T>>::remove(&key);
+				let _ = <balances::Module<T>>::unreserve(&multisig.depositor, multisig.deposit);
+				Self::dispatch(id, proposal_hash, multisig.proposal);
+			} else {
+				<Multisigs<T>>::insert(&key, multisig);
+			}
+			Ok(())
+		}
+
+		/// Cancel the call with hash `proposal_hash` of the multisig account of the sender and
+		/// `other_signatories`, returning the deposit. Only the proposer of the call may do this.
+		fn cancel(
+			origin,
+			threshold: Compact<u32>,
+			other_signatories: Vec<T::AccountId>,
+			proposal_hash: T::Hash
+		) -> Result {
+			let who = ensure_signed(origin)?;
+			let threshold = threshold.into();
+			let signatories = Self::signatories(&who, other_signatories, threshold)?;
+			let id = T::DetermineMultisigAddress::multisig_address_for(&signatories, threshold);
+			let key = (id.clone(), proposal_hash);
+
+			let multisig = Self::multisigs(&key).ok_or("no such call pending")?;
+			ensure!(multisig.depositor == who, "only the proposer can cancel a call");
+
+			<Multisigs<T>>::remove(&key);
+			let _ = <balances::Module<T>>::unreserve(&multisig.depositor, multisig.deposit);
+
+			Self::deposit_event(RawEvent::Cancelled(who, id, proposal_hash));
+			Ok(())
+		}
+	}
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as Multisig {
+		/// The base deposit reserved by the proposer of a call until it is dispatched or cancelled.
+		pub DepositBase get(deposit_base) config(): T::Balance;
+		/// The deposit reserved in addition per byte of the encoded call.
+		pub DepositByte get(deposit_byte) config(): T::Balance;
+
+		/// The maximum number of signatories of a multisig account.
+		pub MaxSignatories get(max_signatories) config(): u32 = 16;
+
+		/// The calls pending approval, by multisig account and call hash.
+		pub Multisigs get(multisigs): map (T::AccountId, T::Hash) => Option<Multisig<T::AccountId, T::Balance, T::Proposal>>;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where <T as system::Trait>::AccountId, <T as system::Trait>::Hash {
+		/// A call (given hash) was proposed by an account for a multisig account.
+		NewMultisig(AccountId, AccountId, Hash),
+		/// A call (given hash) of a multisig account was approved by an account.
+		Approval(AccountId, AccountId, Hash),
+		/// A call (given hash) of a multisig account was dispatched; `bool` is true if it returned
+		/// without error.
+		Executed(AccountId, Hash, bool),
+		/// A call (given hash) of a multisig account was cancelled by its proposer.
+		Cancelled(AccountId, AccountId, Hash),
+	}
+);
+
+impl<T: Trait> Module<T> {
+	/// The account ID of the multisig account of given signatories and threshold.
+	pub fn multisig_account_id(signatories: &[T::AccountId], threshold: u32) -> T::AccountId {
+		let mut signatories = signatories.to_vec();
+		signatories.sort();
+		T::DetermineMultisigAddress::multisig_address_for(&signatories, threshold)
+	}
+
+	/// The deposit to reserve for keeping `proposal` in storage.
+	pub fn deposit_for(proposal: &T::Proposal) -> T::Balance {
+		Self::deposit_base() + Self::deposit_byte() * <T::Balance as As<u64>>::sa(proposal.encode().len() as u64)
+	}
+
+	/// The sorted signatories of a multisig account, checking that they are valid for `threshold`.
+	fn signatories(
+		who: &T::AccountId,
+		mut signatories: Vec<T::AccountId>,
+		threshold: u32,
+	) -> result::Result<Vec<T::AccountId>, &'static str> {
+		signatories.push(who.clone());
+		signatories.sort();
+		let count = signatories.len();
+		signatories.dedup();
+
+		ensure!(signatories.len() == count, "signatories must be unique");
+		ensure!(count >= 2, "a multisig account needs at least two signatories");
+		ensure!(count as u32 <= Self::max_signatories(), "too many signatories");
+		ensure!(threshold >= 1 && threshold as usize <= count, "threshold must be between one and the number of signatories");
+		Ok(signatories)
+	}
+
+	fn dispatch(id: T::AccountId, proposal_hash: T::Hash, proposal: T::Proposal) {
+		let ok = proposal.dispatch(system::RawOrigin::Signed(id.clone()).into()).is_ok();
+		Self::deposit_event(RawEvent::Executed(id, proposal_hash, ok));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use runtime_io::with_externalities;
+	use substrate_primitives::{H256, Blake2Hasher};
+	use runtime_primitives::BuildStorage;
+	use runtime_primitives::traits::BlakeTwo256;
+	use runtime_primitives::testing::{Digest, DigestItem, Header};
+	use balances::address::Address;
+
+	impl_outer_origin! {
+		pub enum Origin for Test {}
+	}
+
+	impl_outer_dispatch! {
+		pub enum Call for Test where origin: Origin {
+			balances::Balances,
+		}
+	}
+
+	#[derive(Clone, Eq, PartialEq, Debug)]
+	pub struct Test;
+	impl system::Trait for Test {
+		type Origin = Origin;
+		type Index = u64;
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type Digest = Digest;
+		type AccountId = u64;
+		type Header = Header;
+		type Event = ();
+		type Log = DigestItem;
+	}
+	impl balances::Trait for Test {
+		type Balance = u64;
+		type AccountIndex = u64;
+		type OnFreeBalanceZero = ();
+		type EnsureAccountLiquid = ();
+		type Event = ();
+	}
+
+	pub struct DummyMultisigAddressFor;
+	impl MultisigAddressFor<u64> for DummyMultisigAddressFor {
+		fn multisig_address_for(signatories: &[u64], threshold: u32) -> u64 {
+			signatories.iter().fold(threshold as u64, |acc, s| acc * 10 + s) + 1000
+		}
+	}
+
+	impl Trait for Test {
+		type Proposal = Call;
+		type DetermineMultisigAddress = DummyMultisigAddressFor;
+		type Event = ();
+	}
+	type Balances = balances::Module<Test>;
+	type Multisig = Module<Test>;
+
+	// The 2-of-3 multisig account of accounts 1, 2 and 3.
+	const MULTISIG: u64 = 3123;
+
+	fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+		let mut t = system::GenesisConfig::<Test>::default().build_storage().unwrap().0;
+		t.extend(balances::GenesisConfig::<Test>{
+			balances: vec![(1, 100), (2, 100), (3, 100), (MULTISIG, 100)],
+			transaction_base_fee: 0,
+			transaction_byte_fee: 0,
+			transfer_fee: 0,
+			creation_fee: 0,
+			existential_deposit: 0,
+			reclaim_rebate: 0,
+			_genesis_phantom_data: Default::default(),
+		}.build_storage().unwrap().0);
+		t.extend(GenesisConfig::<Test>{
+			deposit_base: 2,
+			deposit_byte: 1,
+			max_signatories: 3,
+			_genesis_phantom_data: Default::default(),
+		}.build_storage().unwrap().0);
+		t.into()
+	}
+
+	fn transfer(to: u64, value: u64) -> Call {
+		Call::Balances(balances::Call::transfer(Address::Id(to), value.into()))
+	}
+
+	#[test]
+	fn multisig_account_id_is_independent_of_signatory_order() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_eq!(Multisig::multisig_account_id(&[3, 1, 2], 2), MULTISIG);
+			assert_eq!(Multisig::multisig_account_id(&[1, 2, 3], 2), MULTISIG);
+		});
+	}
+
+	#[test]
+	fn call_is_dispatched_once_threshold_is_met() {
+		with_externalities(&mut new_test_ext(), || {
+			let call = transfer(4, 50);
+			let hash = BlakeTwo256::hash_of(&call);
+
+			assert_ok!(Multisig::propose(Origin::signed(1), 2.into(), vec![2, 3], Box::new(call)));
+			assert_eq!(Balances::reserved_balance(&1), 14);
+			assert_eq!(Balances::free_balance(&MULTISIG), 100);
+
+			assert_ok!(Multisig::approve(Origin::signed(3), 2.into(), vec![1, 2], hash));
+			assert_eq!(Balances::free_balance(&MULTISIG), 50);
+			assert_eq!(Balances::free_balance(&4), 50);
+			assert_eq!(Balances::reserved_balance(&1), 0);
+			assert_eq!(Multisig::multisigs(&(MULTISIG, hash)), None);
+		});
+	}
+
+	#[test]
+	fn proposer_can_cancel() {
+		with_externalities(&mut new_test_ext(), || {
+			let call = transfer(4, 50);
+			let hash = BlakeTwo256::hash_of(&call);
+
+			assert_ok!(Multisig::propose(Origin::signed(1), 2.into(), vec![2, 3], Box::new(call)));
+			assert_noop!(
+				Multisig::cancel(Origin::signed(2), 2.into(), vec![1, 3], hash),
+				"only the proposer can cancel a call"
+			);
+			assert_ok!(Multisig::cancel(Origin::signed(1), 2.into(), vec![2, 3], hash));
+			assert_eq!(Balances::reserved_balance(&1), 0);
+			assert_noop!(
+				Multisig::approve(Origin::signed(2), 2.into(), vec![1, 3], hash),
+				"no such call pending"
+			);
+		});
+	}
+
+	#[test]
+	fn duplicate_approvals_are_rejected() {
+		with_externalities(&mut new_test_ext(), || {
+			let call = transfer(4, 50);
+			let hash = BlakeTwo256::hash_of(&call);
+
+			assert_ok!(Multisig::propose(Origin::signed(1), 2.into(), vec![2, 3], Box::new(call.clone())));
+			assert_noop!(
+				Multisig::approve(Origin::signed(1), 2.into(), vec![2, 3], hash),
+				"duplicate approval"
+			);
+			assert_noop!(
+				Multisig::propose(Origin::signed(2), 2.into(), vec![1, 3], Box::new(call)),
+				"call already proposed"
+			);
+		});
+	}
+
+	#[test]
+	fn invalid_signatories_are_rejected() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_noop!(
+				Multisig::propose(Origin::signed(1), 2.into(), vec![1, 2], Box::new(transfer(4, 50))),
+				"signatories must be unique"
+			);
+			assert_noop!(
+				Multisig::propose(Origin::signed(1), 1.into(), vec![], Box::new(transfer(4, 50))),
+				"a multisig account needs at least two signatories"
+			);
+			assert_noop!(
+				Multisig::propose(Origin::signed(1), 2.into(), vec![2, 3, 4], Box::new(transfer(4, 50))),
+				"too many signatories"
+			);
+			assert_noop!(
+				Multisig::propose(Origin::signed(1), 4.into(), vec![2, 3], Box::new(transfer(4, 50))),
+				"threshold must be between one and the number of signatories"
+			);
+		});
+	}
+
+	#[test]
+	fn threshold_of_one_dispatches_immediately() {
+		with_externalities(&mut new_test_ext(), || {
+			let multisig = Multisig::multisig_account_id(&[1, 2], 1);
+			Balances::set_free_balance(&multisig, 20);
+
+			assert_ok!(Multisig::propose(Origin::signed(1), 1.into(), vec![2], Box::new(transfer(4, 20))));
+			assert_eq!(Balances::free_balance(&4), 20);
+			assert_eq!(Balances::reserved_balance(&1), 0);
+		});
+	}
+
+	#[test]
+	fn deposit_scales_with_the_proposal_length() {
+		with_externalities(&mut new_test_ext(), || {
+			// Module and call index, the address with its prefix and the compact value.
+			assert_eq!(Multisig::deposit_for(&transfer(4, 50)), 2 + 12);
+			assert_eq!(Multisig::deposit_for(&transfer(4, 1 << 20)), 2 + 15);
+
+			assert_ok!(Multisig::propose(Origin::signed(1), 2.into(), vec![2, 3], Box::new(transfer(4, 50))));
+			assert_ok!(Multisig::propose(Origin::signed(1), 2.into(), vec![2, 3], Box::new(transfer(4, 1 << 20))));
+			assert_eq!(Balances::reserved_balance(&1), 14 + 17);
+		});
+	}
+
+	#[test]
+	fn proposing_requires_the_deposit() {
+		with_externalities(&mut new_test_ext(), || {
+			Balances::set_free_balance(&1, 13);
+			assert_noop!(
+				Multisig::propose(Origin::signed(1), 2.into(), vec![2, 3], Box::new(transfer(4, 50))),
+				"balance too low to reserve the deposit"
+			);
+		});
+	}
+}
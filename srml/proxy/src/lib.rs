@@ -0,0 +1,339 @@
+// Copyright 2018 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Proxy: Lets accounts dispatch calls on behalf of other accounts.
+//!
+//! An account registers other accounts as its proxies, each with a proxy type that restricts
+//! the calls the proxy may make, e.g. to staking calls only. A proxy wraps a call in
+//! `proxy(real, call)` to dispatch it with the real account as signed origin, so that a hot key
+//! can operate on behalf of a cold one. Registering a proxy reserves a deposit from the account,
+//! which is returned when the proxy is unregistered.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+#[macro_use]
+extern crate parity_codec_derive;
+
+#[macro_use]
+extern crate srml_support as runtime_support;
+
+extern crate sr_std as rstd;
+#[cfg(test)]
+extern crate sr_io as runtime_io;
+extern crate parity_codec as codec;
+extern crate sr_primitives as primitives;
+extern crate srml_system as system;
+
+extern crate srml_balances as balances;
+
+#[cfg(test)]
+extern crate substrate_primitives;
+
+use rstd::prelude::*;
+use runtime_support::StorageMap;
+use runtime_support::dispatch::{Result, Dispatchable, Parameter};
+use system::ensure_signed;
+
+/// A filter on the calls a proxy may make on behalf of the account it represents.
+pub trait ProxyFilter<Call> {
+	/// Returns true if a proxy of this type may dispatch `call`.
+	fn allows(&self, call: &Call) -> bool;
+}
+
+/// Proxies without restrictions.
+impl<Call> ProxyFilter<Call> for () {
+	fn allows(&self, _: &Call) -> bool { true }
+}
+
+pub trait Trait: balances::Trait {
+	/// The call that can be dispatched by a proxy.
+	type Proposal: Parameter + Dispatchable<Origin=Self::Origin>;
+
+	/// The kinds of proxies, determining the calls they may dispatch.
+	type ProxyType: Parameter + ProxyFilter<Self::Proposal>;
+
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+}
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		fn deposit_event() = default;
+
+		/// Register `proxy` as a proxy of the sender, restricted to the calls allowed by
+		/// `proxy_type`. Registering an existing proxy again replaces its proxy type.
+		///
+		/// Registering a new proxy reserves the proxy deposit from the sender until the proxy is
+		/// unregistered.
+		fn add_proxy(origin, proxy: T::AccountId, proxy_type: T::ProxyType) -> Result {
+			let who = ensure_signed(origin)?;
+			ensure!(proxy != who, "an account can't be its own proxy");
+
+			let mut proxies = Self::proxies(&who);
+			if let Some(pos) = proxies.iter().position(|&(ref p, _)| p == &proxy) {
+				proxies[pos].1 = proxy_type.clone();
+			} else {
+				ensure!((proxies.len() as u32) < Self::max_proxies(), "too many proxies");
+
+				let deposit = Self::proxy_deposit();
+				<balances::Module<T>>::reserve(&who, deposit)
+					.map_err(|_| "balance too low to reserve the deposit")?;
+				<ProxyDeposits<T>>::insert(&(who.clone(), proxy.clone()), deposit);
+
+				proxies.push((proxy.clone(), proxy_type.clone()));
+			}
+			<Proxies<T>>::insert(&who, proxies);
+
+			Self::deposit_event(RawEvent::ProxyAdded(who, proxy, proxy_type));
+			Ok(())
+		}
+
+		/// Unregister `proxy` as a proxy of the sender, returning the deposit reserved for it.
+		fn remove_proxy(origin, proxy: T::AccountId) -> Result {
+			let who = ensure_signed(origin)?;
+
+			let mut proxies = Self::proxies(&who);
+			let pos = proxies.iter().position(|&(ref p, _)| p == &proxy).ok_or("not a proxy")?;
+			proxies.swap_remove(pos);
+			if proxies.is_empty() {
+				<Proxies<T>>::remove(&who);
+			} else {
+				<Proxies<T>>::insert(&who, proxies);
+			}
+
+			let deposit = <ProxyDeposits<T>>::take(&(who.clone(), proxy.clone()));
+			let _ = <balances::Module<T>>::unreserve(&who, deposit);
+
+			Self::deposit_event(RawEvent::ProxyRemoved(who, proxy));
+			Ok(())
+		}
+
+		/// Dispatch `call` with `real` as signed origin. The sender must be a proxy of `real`
+		/// whose proxy type allows the call.
+		fn proxy(origin, real: T::AccountId, call: Box<T::Proposal>) -> Result {
+			let who = ensure_signed(origin)?;
+
+			let proxy_type = Self::proxy_type(&real, &who).ok_or("not a proxy")?;
+			ensure!(proxy_type.allows(&call), "call not allowed for proxy type");
+
+			let ok = call.dispatch(system::RawOrigin::Signed(real.clone()).into()).is_ok();
+			Self::deposit_event(RawEvent::Proxied(real, who, ok));
+			Ok(())
+		}
+	}
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as Proxy {
+		/// The maximum number of proxies of an account.
+		pub MaxProxies get(max_proxies) config(): u32 = 32;
+
+		/// The deposit reserved by an account for each of its proxies.
+		pub ProxyDeposit get(proxy_deposit) config(): T::Balance;
+
+		/// The proxies of an account along with their proxy types.
+		pub Proxies get(proxies): map T::AccountId => Vec<(T::AccountId, T::ProxyType)>;
+
+		/// The deposit reserved by an account (first) for one of its proxies (second).
+		pub ProxyDeposits get(proxy_deposits): map (T::AccountId, T::AccountId) => T::Balance;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where <T as system::Trait>::AccountId, <T as Trait>::ProxyType {
+		/// An account (first) registered a proxy (second) with the given proxy type.
+		ProxyAdded(AccountId, AccountId, ProxyType),
+		/// An account (first) unregistered a proxy (second).
+		ProxyRemoved(AccountId, AccountId),
+		/// A proxy (second) dispatched a call on behalf of an account (first); `bool` is true if
+		/// it returned without error.
+		Proxied(AccountId, AccountId, bool),
+	}
+);
+
+impl<T: Trait> Module<T> {
+	/// The proxy type of `proxy` for `real`, if it is a proxy of `real` at all.
+	pub fn proxy_type(real: &T::AccountId, proxy: &T::AccountId) -> Option<T::ProxyType> {
+		Self::proxies(real).into_iter()
+			.find(|&(ref p, _)| p == proxy)
+			.map(|(_, proxy_type)| proxy_type)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use runtime_io::with_externalities;
+	use substrate_primitives::{H256, Blake2Hasher};
+	use primitives::BuildStorage;
+	use primitives::traits::BlakeTwo256;
+	use primitives::testing::{Digest, DigestItem, Header};
+	use balances::address::Address;
+
+	impl_outer_origin! {
+		pub enum Origin for Test {}
+	}
+
+	impl_outer_dispatch! {
+		pub enum Call for Test where origin: Origin {
+			balances::Balances,
+		}
+	}
+
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+	pub enum ProxyType {
+		Any,
+		TransferOnly,
+	}
+
+	impl ProxyFilter<Call> for ProxyType {
+		fn allows(&self, call: &Call) -> bool {
+			match (self, call) {
+				(&ProxyType::Any, _) => true,
+				(&ProxyType::TransferOnly, &Call::Balances(balances::Call::transfer(..))) => true,
+				_ => false,
+			}
+		}
+	}
+
+	#[derive(Clone, Eq, PartialEq, Debug)]
+	pub struct Test;
+	impl system::Trait for Test {
+		type Origin = Origin;
+		type Index = u64;
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type Digest = Digest;
+		type AccountId = u64;
+		type Header = Header;
+		type Event = ();
+		type Log = DigestItem;
+	}
+	impl balances::Trait for Test {
+		type Balance = u64;
+		type AccountIndex = u64;
+		type OnFreeBalanceZero = ();
+		type EnsureAccountLiquid = ();
+		type Event = ();
+	}
+	impl Trait for Test {
+		type Proposal = Call;
+		type ProxyType = ProxyType;
+		type Event = ();
+	}
+	type Balances = balances::Module<Test>;
+	type Proxy = Module<Test>;
+
+	fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+		let mut t = system::GenesisConfig::<Test>::default().build_storage().unwrap().0;
+		t.extend(balances::GenesisConfig::<Test>{
+			balances: vec![(1, 10), (2, 10)],
+			transaction_base_fee: 0,
+			transaction_byte_fee: 0,
+			transfer_fee: 0,
+			creation_fee: 0,
+			existential_deposit: 0,
+			reclaim_rebate: 0,
+			_genesis_phantom_data: Default::default(),
+		}.build_storage().unwrap().0);
+		t.extend(GenesisConfig::<Test>{
+			max_proxies: 2,
+			proxy_deposit: 2,
+			_genesis_phantom_data: Default::default(),
+		}.build_storage().unwrap().0);
+		t.into()
+	}
+
+	fn transfer(to: u64, value: u64) -> Box<Call> {
+		Box::new(Call::Balances(balances::Call::transfer(Address::Id(to), value.into())))
+	}
+
+	#[test]
+	fn proxy_dispatches_on_behalf_of_real_account() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_noop!(Proxy::proxy(Origin::signed(2), 1, transfer(3, 5)), "not a proxy");
+
+			assert_ok!(Proxy::add_proxy(Origin::signed(1), 2, ProxyType::Any));
+			assert_eq!(Proxy::proxy_type(&1, &2), Some(ProxyType::Any));
+			assert_ok!(Proxy::proxy(Origin::signed(2), 1, transfer(3, 5)));
+			assert_eq!(Balances::free_balance(&1), 3);
+			assert_eq!(Balances::free_balance(&2), 10);
+			assert_eq!(Balances::free_balance(&3), 5);
+
+			assert_ok!(Proxy::remove_proxy(Origin::signed(1), 2));
+			assert_noop!(Proxy::proxy(Origin::signed(2), 1, transfer(3, 5)), "not a proxy");
+		});
+	}
+
+	#[test]
+	fn proxy_type_restricts_calls() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_ok!(Proxy::add_proxy(Origin::signed(1), 2, ProxyType::TransferOnly));
+			let set_balance = Box::new(Call::Balances(balances::Call::set_balance(Address::Id(2), 100.into(), 0.into())));
+			assert_noop!(Proxy::proxy(Origin::signed(2), 1, set_balance), "call not allowed for proxy type");
+			assert_ok!(Proxy::proxy(Origin::signed(2), 1, transfer(3, 5)));
+			assert_eq!(Balances::free_balance(&3), 5);
+		});
+	}
+
+	#[test]
+	fn adding_proxies_is_bounded() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_noop!(Proxy::add_proxy(Origin::signed(1), 1, ProxyType::Any), "an account can't be its own proxy");
+			assert_ok!(Proxy::add_proxy(Origin::signed(1), 2, ProxyType::Any));
+			assert_ok!(Proxy::add_proxy(Origin::signed(1), 3, ProxyType::Any));
+			assert_noop!(Proxy::add_proxy(Origin::signed(1), 4, ProxyType::Any), "too many proxies");
+
+			// Updating the type of an existing proxy is always possible.
+			assert_ok!(Proxy::add_proxy(Origin::signed(1), 3, ProxyType::TransferOnly));
+			assert_eq!(Proxy::proxies(&1), vec![(2, ProxyType::Any), (3, ProxyType::TransferOnly)]);
+			assert_noop!(Proxy::remove_proxy(Origin::signed(1), 4), "not a proxy");
+		});
+	}
+
+	#[test]
+	fn proxy_deposit_is_reserved_until_proxy_is_removed() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_ok!(Proxy::add_proxy(Origin::signed(1), 2, ProxyType::Any));
+			assert_eq!(Balances::reserved_balance(&1), 2);
+			assert_eq!(Balances::free_balance(&1), 8);
+
+			// Updating the type of an existing proxy doesn't reserve again.
+			assert_ok!(Proxy::add_proxy(Origin::signed(1), 2, ProxyType::TransferOnly));
+			assert_eq!(Balances::reserved_balance(&1), 2);
+
+			assert_ok!(Proxy::add_proxy(Origin::signed(1), 3, ProxyType::Any));
+			assert_eq!(Balances::reserved_balance(&1), 4);
+
+			assert_ok!(Proxy::remove_proxy(Origin::signed(1), 2));
+			assert_eq!(Balances::reserved_balance(&1), 2);
+			assert_ok!(Proxy::remove_proxy(Origin::signed(1), 3));
+			assert_eq!(Balances::reserved_balance(&1), 0);
+			assert_eq!(Balances::free_balance(&1), 10);
+		});
+	}
+
+	#[test]
+	fn adding_a_proxy_requires_the_deposit() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_noop!(Proxy::add_proxy(Origin::signed(4), 1, ProxyType::Any), "balance too low to reserve the deposit");
+			assert_eq!(Proxy::proxy_type(&4, &1), None);
+		});
+	}
+}
@@ -33,9 +33,11 @@ extern crate parity_codec_derive;
 
 extern crate parity_codec as codec;
 extern crate sr_primitives as primitives;
+extern crate srml_aura as aura;
 extern crate srml_balances as balances;
 extern crate srml_consensus as consensus;
 extern crate srml_session as session;
+extern crate srml_grandpa as grandpa;
 extern crate srml_system as system;
 
 #[cfg(test)]
@@ -51,7 +53,7 @@ use codec::{HasCompact, Compact};
 use runtime_support::{Parameter, StorageValue, StorageMap};
 use runtime_support::dispatch::Result;
 use session::OnSessionChange;
-use primitives::{Perbill, traits::{Zero, One, Bounded, As}};
+use primitives::{Perbill, traits::{Zero, One, Bounded, As, Convert}};
 use balances::{address::Address, OnDilution};
 use system::ensure_signed;
 
@@ -247,6 +249,9 @@ decl_event!(
 		OfflineWarning(AccountId, u32),
 		/// One validator (and their nominators) has been slashed by the given amount.
 		OfflineSlash(AccountId, Balance),
+		/// One validator (and their nominators) has been slashed by the given amount for
+		/// equivocating in finality voting.
+		EquivocationSlash(AccountId, Balance),
 	}
 );
 
@@ -267,6 +272,8 @@ decl_storage! {
 		pub OfflineSlash get(offline_slash) config(): Perbill = Perbill::from_millionths(1000); // Perbill::from_fraction() is only for std, so use from_millionths().
 		/// Number of instances of offline reports before slashing begins for validators.
 		pub OfflineSlashGrace get(offline_slash_grace) config(): u32;
+		/// Slash, per validator that is proven to have equivocated, as a fraction of their slashable balance.
+		pub EquivocationSlash get(equivocation_slash) config(): Perbill = Perbill::from_millionths(100_000);
 		/// The length of the bonding duration in blocks.
 		pub BondingDuration get(bonding_duration) config(): T::BlockNumber = T::BlockNumber::sa(1000);
 
@@ -403,6 +410,29 @@ impl<T: Trait> Module<T> {
 		Ok(())
 	}
 
+	/// Slash and unstake the validator with the given session key for equivocating.
+	fn slash_equivocation(key: &T::SessionKey) {
+		// the equivocator may have left the validator set since, but not the intentions.
+		let v = match <session::Module<T>>::validators().into_iter()
+			.chain(Self::intentions().into_iter())
+			.find(|v| &T::ConvertAccountIdToSessionKey::convert(v.clone()) == key)
+		{
+			Some(v) => v,
+			None => return,
+		};
+
+		let slash = Self::equivocation_slash().times(Self::slashable_balance(&v));
+		Self::slash_validator(&v, slash);
+		if let Some(pos) = Self::intentions().into_iter().position(|x| &x == &v) {
+			Self::apply_unstake(&v, pos)
+				.expect("pos derived correctly from Self::intentions(); \
+					apply_unstake can only fail if pos wrong; \
+					Self::intentions() doesn't change; qed");
+			let _ = Self::apply_force_new_era(false);
+		}
+		Self::deposit_event(RawEvent::EquivocationSlash(v, slash));
+	}
+
 	/// Get the reward for the session, assuming it ends with this block.
 	fn this_session_reward(actual_elapsed: T::Moment) -> T::Balance {
 		let ideal_elapsed = <session::Module<T>>::ideal_session_duration();
@@ -554,3 +584,15 @@ impl<T: Trait> consensus::OnOfflineValidator for Module<T> {
 		Self::deposit_event(event);
 	}
 }
+
+impl<T: Trait> grandpa::OnEquivocation<T::SessionKey> for Module<T> {
+	fn on_equivocation(key: &T::SessionKey) {
+		Self::slash_equivocation(key)
+	}
+}
+
+impl<T: Trait> aura::OnEquivocation<T::SessionKey> for Module<T> {
+	fn on_equivocation(key: &T::SessionKey) {
+		Self::slash_equivocation(key)
+	}
+}
@@ -125,6 +125,7 @@ pub fn new_test_ext(
 		current_session_reward: reward,
 		current_offline_slash: 20,
 		offline_slash_grace: 0,
+		equivocation_slash: Perbill::from_percent(50),
 		_genesis_phantom_data: Default::default(),
 	}.build_storage().unwrap().0);
 	t.extend(timestamp::GenesisConfig::<Test>{
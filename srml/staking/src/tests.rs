@@ -20,6 +20,7 @@
 
 use super::*;
 use consensus::OnOfflineValidator;
+use grandpa::OnEquivocation;
 use runtime_io::with_externalities;
 use mock::{Balances, Session, Staking, System, Timestamp, Test, new_test_ext, Origin};
 
@@ -51,6 +52,36 @@ fn note_offline_should_work() {
 	});
 }
 
+#[test]
+fn equivocation_should_slash_and_unstake() {
+	with_externalities(&mut new_test_ext(0, 3, 3, 0, true, 10), || {
+		Balances::set_free_balance(&10, 70);
+		assert_eq!(Staking::intentions(), vec![10, 20]);
+		System::set_extrinsic_index(1);
+		Staking::on_equivocation(&10);
+		assert_eq!(Balances::free_balance(&10), 35);
+		assert_eq!(Staking::intentions(), vec![20]);
+		assert!(Staking::forcing_new_era().is_some());
+
+		// unknown session keys are ignored.
+		Staking::on_equivocation(&30);
+		assert_eq!(Balances::free_balance(&20), 20);
+	});
+}
+
+#[test]
+fn aura_equivocation_should_slash_and_unstake() {
+	with_externalities(&mut new_test_ext(0, 3, 3, 0, true, 10), || {
+		Balances::set_free_balance(&10, 70);
+		assert_eq!(Staking::intentions(), vec![10, 20]);
+		System::set_extrinsic_index(1);
+		<Staking as aura::OnEquivocation<u64>>::on_equivocation(&10);
+		assert_eq!(Balances::free_balance(&10), 35);
+		assert_eq!(Staking::intentions(), vec![20]);
+		assert!(Staking::forcing_new_era().is_some());
+	});
+}
+
 #[test]
 fn note_offline_exponent_should_work() {
 	with_externalities(&mut new_test_ext(0, 3, 3, 0, true, 10), || {
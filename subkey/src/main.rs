@@ -18,12 +18,26 @@
 #[cfg(feature = "bench")]
 extern crate test;
 extern crate substrate_primitives;
+extern crate sr_primitives;
+extern crate node_primitives;
+extern crate node_runtime;
+extern crate parity_codec as codec;
 extern crate rand;
+extern crate rustc_hex;
 
 #[macro_use]
 extern crate clap;
 
-use substrate_primitives::{ed25519::Pair, hexdisplay::HexDisplay};
+use std::io::{self, Read};
+use std::process;
+
+use codec::{Decode, Encode};
+use node_primitives::{Balance, Hash, Index};
+use node_runtime::{Call, BalancesCall, RawAddress, UncheckedExtrinsic};
+use rustc_hex::FromHex;
+use sr_primitives::generic::Era;
+use substrate_primitives::{ed25519::{self, Pair, Public}, hexdisplay::HexDisplay};
+use substrate_primitives::crypto::{KeyPair, Ss58AddressFormat, Ss58Codec, set_default_ss58_version};
 
 mod vanity;
 
@@ -31,7 +45,87 @@ fn main() {
 	let yaml = load_yaml!("cli.yml");
 	let matches = clap::App::from_yaml(yaml).get_matches();
 
+	let network = matches.value_of("network")
+		.or_else(|| matches.subcommand().1.and_then(|m| m.value_of("network")));
+	if let Some(network) = network {
+		let format = network.parse::<Ss58AddressFormat>()
+			.unwrap_or_else(|_| fail(&format!("Invalid network: {}", network)));
+		set_default_ss58_version(format);
+	}
+
 	match matches.subcommand() {
+		("generate", Some(matches)) => {
+			let password = matches.value_of("password");
+			let (pair, phrase) = Pair::generate_with_phrase(password);
+			println!("Secret phrase `{}` is account:\n    Public key (hex): 0x{}\n    Address (SS58): {}",
+				phrase,
+				HexDisplay::from(&pair.public().0),
+				pair.public().to_ss58check()
+			);
+		}
+		("inspect", Some(matches)) => {
+			let uri = matches.value_of("uri")
+				.expect("URI parameter is required; thus it can't be None; qed");
+			let pair = pair_from_suri(uri, matches.value_of("password"));
+			println!("Secret URI `{}` is account:\n    Public key (hex): 0x{}\n    Address (SS58): {}",
+				uri,
+				HexDisplay::from(&pair.public().0),
+				pair.public().to_ss58check()
+			);
+		}
+		("sign", Some(matches)) => {
+			let suri = matches.value_of("suri")
+				.expect("secret URI parameter is required; thus it can't be None; qed");
+			let pair = pair_from_suri(suri, matches.value_of("password"));
+			let message = read_message(matches.is_present("hex"));
+			println!("0x{}", HexDisplay::from(&pair.sign(&message).as_bytes()));
+		}
+		("verify", Some(matches)) => {
+			let sig = matches.value_of("sig")
+				.expect("signature parameter is required; thus it can't be None; qed");
+			let sig = decode_hex(sig).unwrap_or_else(|e| fail(&format!("Invalid signature: {}", e)));
+			if sig.len() != 64 {
+				fail("Invalid signature: expected 64 bytes");
+			}
+			let public = matches.value_of("public")
+				.expect("public key parameter is required; thus it can't be None; qed");
+			let public = parse_public(public).unwrap_or_else(|e| fail(&format!("Invalid public key: {}", e)));
+			let message = read_message(matches.is_present("hex"));
+			if ed25519::verify(&sig, &message, &public) {
+				println!("Signature verifies correctly.");
+			} else {
+				fail("Signature invalid.");
+			}
+		}
+		("transfer", Some(matches)) => {
+			let from = matches.value_of("from")
+				.expect("sender parameter is required; thus it can't be None; qed");
+			let signer = pair_from_suri(from, matches.value_of("password"));
+			let to = matches.value_of("to")
+				.expect("recipient parameter is required; thus it can't be None; qed");
+			let to = parse_public(to).unwrap_or_else(|e| fail(&format!("Invalid recipient: {}", e)));
+			let amount: Balance = parse_arg(matches, "amount");
+			let index: Index = parse_arg(matches, "index");
+			let function = Call::Balances(BalancesCall::transfer(RawAddress::Id(to.0.into()), amount.into()));
+
+			let (era, checkpoint) = era(matches);
+			let extrinsic = create_extrinsic(&signer, index, function, era, checkpoint);
+			println!("0x{}", HexDisplay::from(&extrinsic.encode()));
+		}
+		("sign-transaction", Some(matches)) => {
+			let suri = matches.value_of("suri")
+				.expect("secret URI parameter is required; thus it can't be None; qed");
+			let signer = pair_from_suri(suri, matches.value_of("password"));
+			let call = matches.value_of("call")
+				.expect("call parameter is required; thus it can't be None; qed");
+			let call = decode_hex(call).unwrap_or_else(|e| fail(&format!("Invalid call: {}", e)));
+			let function = Call::decode(&mut &call[..]).unwrap_or_else(|| fail("Invalid call: can't decode"));
+			let index: Index = parse_arg(matches, "index");
+
+			let (era, checkpoint) = era(matches);
+			let extrinsic = create_extrinsic(&signer, index, function, era, checkpoint);
+			println!("0x{}", HexDisplay::from(&extrinsic.encode()));
+		}
 		("vanity", Some(matches)) => {
 			let desired: String = matches.value_of("pattern").map(str::to_string).unwrap_or_default();
 			let key = vanity::generate_key(&desired).expect("Key generation failed");
@@ -68,6 +162,132 @@ fn main() {
 	}
 }
 
+/// Prints the error and exits with a non-zero code.
+fn fail(message: &str) -> ! {
+	eprintln!("{}", message);
+	process::exit(1)
+}
+
+fn pair_from_suri(suri: &str, password: Option<&str>) -> Pair {
+	Pair::from_string(suri, password)
+		.unwrap_or_else(|e| fail(&format!("Invalid secret URI `{}`: {:?}", suri, e)))
+}
+
+/// Builds a signed extrinsic of the node runtime.
+///
+/// `checkpoint` is the hash of the block at the start of the era, i.e. the genesis hash for immortal extrinsics.
+fn create_extrinsic(signer: &Pair, index: Index, function: Call, era: Era, checkpoint: Hash) -> UncheckedExtrinsic {
+	let payload = (index, function, era, checkpoint);
+	let signature = signer.sign(&payload.encode()).into();
+	UncheckedExtrinsic::new_signed(payload.0, payload.1, RawAddress::Id(signer.public().0.into()), signature, era)
+}
+
+fn parse_arg<T: std::str::FromStr>(matches: &clap::ArgMatches, name: &str) -> T {
+	let value = matches.value_of(name)
+		.unwrap_or_else(|| fail(&format!("{} parameter is required", name)));
+	value.parse().unwrap_or_else(|_| fail(&format!("Invalid {}: {}", name, value)))
+}
+
+/// Returns the era of the extrinsic and the hash of the block it starts at.
+///
+/// Immortal extrinsics start at genesis. Mortal ones start exactly at `--era-block`; periods too long
+/// for that block to be the start of an era are rejected rather than silently moved to an earlier block.
+fn era(matches: &clap::ArgMatches) -> (Era, Hash) {
+	if matches.is_present("era-period") {
+		let period: u64 = parse_arg(matches, "era-period");
+		let birth: u64 = parse_arg(matches, "era-block");
+		let era = Era::mortal(period, birth);
+		if era.birth(birth) != birth {
+			fail(&format!("Invalid era-block: an era with period {} can't start at block {}", period, birth));
+		}
+		(era, parse_hash(matches, "era-hash"))
+	} else {
+		(Era::immortal(), parse_hash(matches, "genesis"))
+	}
+}
+
+fn parse_hash(matches: &clap::ArgMatches, name: &str) -> Hash {
+	let hash = matches.value_of(name)
+		.unwrap_or_else(|| fail(&format!("{} parameter is required", name)));
+	let raw = decode_hex(hash).unwrap_or_else(|e| fail(&format!("Invalid {}: {}", name, e)));
+	if raw.len() != 32 {
+		fail(&format!("Invalid {}: expected 32 bytes", name));
+	}
+	Hash::from_slice(&raw)
+}
+
+fn decode_hex(data: &str) -> Result<Vec<u8>, String> {
+	let data = if data.starts_with("0x") { &data[2..] } else { data };
+	data.from_hex().map_err(|e| format!("{}", e))
+}
+
+fn parse_public(public: &str) -> Result<Public, String> {
+	if public.starts_with("0x") {
+		let raw = decode_hex(public)?;
+		if raw.len() != 32 {
+			return Err("expected 32 bytes".into());
+		}
+		Ok(Public::from_slice(&raw))
+	} else {
+		Public::from_ss58check(public).map_err(|e| format!("{:?}", e))
+	}
+}
+
+/// Reads the message to sign or verify from stdin.
+fn read_message(hex: bool) -> Vec<u8> {
+	let mut message = Vec::new();
+	io::stdin().lock().read_to_end(&mut message)
+		.unwrap_or_else(|e| fail(&format!("Error reading the message from stdin: {}", e)));
+	if hex {
+		let message = String::from_utf8(message).unwrap_or_else(|_| fail("Invalid hex message"));
+		decode_hex(message.trim()).unwrap_or_else(|e| fail(&format!("Invalid hex message: {}", e)))
+	} else {
+		message
+	}
+}
+
 fn print_usage(matches: &clap::ArgMatches) {
 	println!("{}", matches.usage());
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sr_primitives::traits::Verify;
+
+	const PHRASE: &str = "bottom drive obey lake curtain smoke basket hold race lonely fit walk";
+
+	fn transfer(era: Era, checkpoint: Hash) -> (Pair, Vec<u8>) {
+		let signer = Pair::from_string(&format!("{}//Alice", PHRASE), None).unwrap();
+		let to = Pair::from_string(&format!("{}//Bob", PHRASE), None).unwrap().public();
+		let function = Call::Balances(BalancesCall::transfer(RawAddress::Id(to.0.into()), (69 as Balance).into()));
+		let extrinsic = create_extrinsic(&signer, 5, function, era, checkpoint);
+		(signer, extrinsic.encode())
+	}
+
+	fn assert_signed_over(encoded: &[u8], signer: &Pair, era: Era, checkpoint: Hash) {
+		let extrinsic = UncheckedExtrinsic::decode(&mut &encoded[..]).expect("extrinsic decodes");
+		let (address, signature, index, decoded_era) = extrinsic.signature.expect("extrinsic is signed");
+		assert_eq!(address, RawAddress::Id(signer.public().0.into()));
+		assert_eq!(index, 5);
+		assert!(decoded_era == era);
+		let payload = (index, extrinsic.function, decoded_era, checkpoint);
+		assert!(signature.verify(&payload.encode()[..], &signer.public().0.into()));
+	}
+
+	#[test]
+	fn immortal_transfer_is_signed_over_genesis() {
+		let genesis = Hash::from([1; 32]);
+		let (signer, encoded) = transfer(Era::immortal(), genesis);
+		assert_signed_over(&encoded, &signer, Era::immortal(), genesis);
+	}
+
+	#[test]
+	fn mortal_transfer_is_signed_over_era_hash() {
+		let era = Era::mortal(64, 1000);
+		assert_eq!(era.birth(1000), 1000);
+		let era_hash = Hash::from([2; 32]);
+		let (signer, encoded) = transfer(era, era_hash);
+		assert_signed_over(&encoded, &signer, era, era_hash);
+	}
+}
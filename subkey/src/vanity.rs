@@ -16,6 +16,7 @@
 
 use rand::{OsRng, Rng};
 use substrate_primitives::ed25519::Pair;
+use substrate_primitives::crypto::Ss58Codec;
 
 fn good_waypoint(done: u64) -> u64 {
 	match done {